@@ -45,7 +45,7 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 use tui_textarea::TextArea;
 use ubertooth_core::{PentestTool, ToolRegistry};
-use ubertooth_platform::SidecarManager;
+use ubertooth_platform::{CaptureStore, SidecarManager};
 use ubertooth_tools::create_tool_registry;
 
 use super::events::EventHandler;
@@ -151,6 +151,7 @@ pub enum ExportOption {
     ComparisonReport,
     TimelineData,
     AllPackets,
+    AnnotatedCapture,
 }
 
 impl ExportOption {
@@ -162,6 +163,7 @@ impl ExportOption {
             Self::ComparisonReport,
             Self::TimelineData,
             Self::AllPackets,
+            Self::AnnotatedCapture,
         ]
     }
 
@@ -173,6 +175,7 @@ impl ExportOption {
             Self::ComparisonReport => "Export Comparison Report (Markdown)",
             Self::TimelineData => "Export Timeline Data (CSV)",
             Self::AllPackets => "Export All Packets (JSON)",
+            Self::AnnotatedCapture => "Export Capture with Notes (PCAPNG)",
         }
     }
 
@@ -184,6 +187,7 @@ impl ExportOption {
             Self::ComparisonReport => "Export side-by-side comparison of marked packets",
             Self::TimelineData => "Export timeline activity data as CSV",
             Self::AllPackets => "Export all packets in the current capture",
+            Self::AnnotatedCapture => "Export the whole capture with notes as Wireshark packet comments",
         }
     }
 }
@@ -446,6 +450,64 @@ impl PacketListState {
     pub fn has_annotation(&self, index: usize) -> bool {
         self.annotations.contains_key(&index)
     }
+
+    /// Capture packet number of a row of a bt_decode result.
+    ///
+    /// Rows and packet numbers differ once the result is filtered, so notes
+    /// are always stored against the packet's own `index`.
+    pub fn packet_number(output: &serde_json::Value, row: usize) -> usize {
+        output
+            .get("decoded_packets")
+            .and_then(|p| p.get(row))
+            .and_then(|p| p.get("index"))
+            .and_then(|i| i.as_u64())
+            .map(|i| i as usize)
+            .unwrap_or(row)
+    }
+
+    /// Row of a bt_decode result showing capture packet `packet_number`.
+    fn row_of_packet(output: &serde_json::Value, packet_number: usize) -> Option<usize> {
+        let packets = output.get("decoded_packets").and_then(|p| p.as_array())?;
+        packets
+            .iter()
+            .position(|p| p.get("index").and_then(|i| i.as_u64()) == Some(packet_number as u64))
+    }
+
+    /// Restore the notes and bookmarks saved with a capture, from the
+    /// `annotations` of a bt_decode result, onto the rows showing them.
+    pub fn restore_annotations(&mut self, output: &serde_json::Value) {
+        let saved = output.get("annotations").and_then(|a| a.as_array()).into_iter().flatten();
+        for annotation in saved {
+            let Some(packet_number) = annotation.get("packet_index").and_then(|i| i.as_u64()).map(|i| i as usize) else {
+                continue;
+            };
+            // Packets filtered out of this result have no row
+            let Some(index) = Self::row_of_packet(output, packet_number) else {
+                continue;
+            };
+            if let Some(note) = annotation.get("note").and_then(|n| n.as_str()) {
+                self.add_annotation(index, note.to_string());
+            }
+            if annotation.get("bookmarked").and_then(|b| b.as_bool()).unwrap_or(false) {
+                self.bookmarks.insert(index);
+            }
+        }
+    }
+
+    /// Save the note and bookmark of the packet in row `index` with the
+    /// capture in `output`.
+    pub fn persist_annotation(&self, output: &serde_json::Value, index: usize) -> Result<()> {
+        let Some(capture_id) = output.get("capture_id").and_then(|c| c.as_str()) else {
+            return Ok(());
+        };
+        CaptureStore::new()?.annotate(
+            capture_id,
+            Self::packet_number(output, index),
+            self.get_annotation(index).cloned(),
+            self.is_bookmarked(index),
+        )?;
+        Ok(())
+    }
 }
 
 /// Main TUI application
@@ -597,7 +659,9 @@ impl App {
 
                                             // Initialize packet list state for bt_decode
                                             let packet_list_state = if tool_name == "bt_decode" {
-                                                Some(PacketListState::new())
+                                                let mut state = PacketListState::new();
+                                                state.restore_annotations(&output);
+                                                Some(state)
                                             } else {
                                                 None
                                             };
@@ -763,6 +827,25 @@ impl App {
             return Ok(());
         }
 
+        // Esc while a tool runs cancels its ubertooth-* commands; the tool
+        // still returns, with whatever it captured until then
+        if let AppState::Executing { tool_name, .. } = &self.state {
+            if let Event::Key(KeyEvent { code: KeyCode::Esc, .. }) = event {
+                if let Some(cancel) = self.registry.get("cancel_running") {
+                    tokio::spawn(async move {
+                        if let Err(e) = cancel.execute(serde_json::json!({})).await {
+                            tracing::warn!("Failed to cancel running commands: {}", e);
+                        }
+                    });
+                    self.notification = Some(Notification {
+                        message: format!("Cancelling {}...", tool_name),
+                        success: true,
+                    });
+                }
+                return Ok(());
+            }
+        }
+
         // Handle form input specially
         if let AppState::ToolForm { form, error, hotkey_mode } = &mut self.state {
             if let Event::Key(KeyEvent { code, modifiers, .. }) = event {
@@ -1115,6 +1198,12 @@ impl App {
                                 KeyCode::Char('b') | KeyCode::Char('B') => {
                                     // Toggle bookmark
                                     pls.toggle_bookmark(pls.selected_index);
+                                    if let Err(e) = pls.persist_annotation(output, pls.selected_index) {
+                                        self.notification = Some(Notification {
+                                            message: format!("Bookmark not saved: {}", e),
+                                            success: false,
+                                        });
+                                    }
                                     return Ok(());
                                 }
                                 KeyCode::Char('m') | KeyCode::Char('M') => {
@@ -1165,7 +1254,10 @@ impl App {
                                     self.dialog = Some(TextInputDialog {
                                         textarea,
                                         context: DialogContext::Annotation { packet_index },
-                                        title: format!("Annotation for Packet #{}", packet_index),
+                                        title: format!(
+                                            "Annotation for Packet #{}",
+                                            PacketListState::packet_number(output, packet_index)
+                                        ),
                                     });
                                     return Ok(());
                                 }
@@ -1173,6 +1265,12 @@ impl App {
                                     // Remove annotation from selected packet (when in list view and has annotation)
                                     if pls.view_mode == PacketViewMode::List && pls.has_annotation(pls.selected_index) {
                                         pls.remove_annotation(pls.selected_index);
+                                        if let Err(e) = pls.persist_annotation(output, pls.selected_index) {
+                                            self.notification = Some(Notification {
+                                                message: format!("Annotation not saved: {}", e),
+                                                success: false,
+                                            });
+                                        }
                                     }
                                     return Ok(());
                                 }
@@ -1327,6 +1425,7 @@ impl App {
                         let option = options[*selected_index];
                         let packets_clone = packets.clone();
                         let state_clone = packet_list_state.clone();
+                        let capture_id = previous_output.get("capture_id").and_then(|c| c.as_str()).map(str::to_string);
 
                         // Store previous state to restore
                         let prev_tool = previous_tool_name.clone();
@@ -1346,7 +1445,7 @@ impl App {
                         };
 
                         // Perform export
-                        match self.export_packets(option, packets_clone, state_clone) {
+                        match self.export_packets(option, packets_clone, state_clone, capture_id) {
                             Ok(path) => {
                                 self.notification = Some(Notification {
                                     message: format!("Exported to: {}", path),
@@ -2069,7 +2168,7 @@ impl App {
         match dialog.context {
             DialogContext::Annotation { packet_index } => {
                 // Update annotation in packet list state
-                if let AppState::Results { packet_list_state, .. } = &mut self.state {
+                if let AppState::Results { output, packet_list_state, .. } = &mut self.state {
                     if let Some(pls) = packet_list_state {
                         if text.is_empty() {
                             // Remove annotation if text is empty
@@ -2078,6 +2177,14 @@ impl App {
                             // Add or update annotation
                             pls.add_annotation(packet_index, text);
                         }
+
+                        // Keep it with the capture so it is there when the capture is reopened
+                        if let Err(e) = pls.persist_annotation(output, packet_index) {
+                            self.notification = Some(Notification {
+                                message: format!("Annotation not saved: {}", e),
+                                success: false,
+                            });
+                        }
                     }
                 }
             }
@@ -2163,14 +2270,11 @@ impl App {
     }
 
     /// Export packets based on selected option
-    fn export_packets(&self, option: ExportOption, packets: Vec<serde_json::Value>, state: PacketListState) -> Result<String> {
+    fn export_packets(&self, option: ExportOption, packets: Vec<serde_json::Value>, state: PacketListState, capture_id: Option<String>) -> Result<String> {
         use std::fs;
-        use std::path::PathBuf;
 
         // Create export directory
-        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        let export_dir = PathBuf::from(home).join(".ubertooth").join("exports");
-        fs::create_dir_all(&export_dir)?;
+        let export_dir = ubertooth_core::storage::exports_dir()?;
 
         // Generate filename with timestamp
         let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
@@ -2388,6 +2492,26 @@ impl App {
                 fs::write(&path, json)?;
                 Ok(path.to_string_lossy().to_string())
             }
+
+            ExportOption::AnnotatedCapture => {
+                let capture_id = capture_id.ok_or_else(|| anyhow::anyhow!("No capture to export"))?;
+                let store = CaptureStore::new()?;
+                let metadata = store.load_metadata(&capture_id)?;
+
+                let filename = format!("{}_annotated_{}.pcapng", capture_id, timestamp);
+                let path = export_dir.join(&filename);
+                let comments = store.write_pcapng(&metadata, &path)?;
+                store.log_custody(
+                    &capture_id,
+                    "exported",
+                    serde_json::json!({
+                        "format": "pcapng",
+                        "export_path": path.to_string_lossy(),
+                        "comments": comments
+                    }),
+                )?;
+                Ok(path.to_string_lossy().to_string())
+            }
         }
     }
 
@@ -582,6 +582,9 @@ fn render_comparison_results(f: &mut Frame, area: Rect, output: &serde_json::Val
         lines_left.push(Line::from(""));
         lines_right.push(Line::from(""));
 
+        // Mode-specific view (devices, packets or timing)
+        render_comparison_view(comparison, &mut lines_left, &mut lines_right);
+
         // Differences
         if let Some(diffs) = comparison.get("differences").and_then(|d| d.as_array()) {
             if !diffs.is_empty() {
@@ -636,15 +639,17 @@ fn render_comparison_results(f: &mut Frame, area: Rect, output: &serde_json::Val
             }
         }
 
+        let mode = comparison.get("mode").and_then(|m| m.as_str()).unwrap_or("devices");
+
         // Render left panel (Capture A)
         let left_content = Paragraph::new(Text::from(lines_left))
-            .block(Block::default().borders(Borders::ALL).title(" Capture A "))
+            .block(Block::default().borders(Borders::ALL).title(format!(" Capture A ({}) ", mode)))
             .wrap(Wrap { trim: false });
         f.render_widget(left_content, chunks[0]);
 
         // Render right panel (Capture B)
         let right_content = Paragraph::new(Text::from(lines_right))
-            .block(Block::default().borders(Borders::ALL).title(" Capture B "))
+            .block(Block::default().borders(Borders::ALL).title(format!(" Capture B ({}) ", mode)))
             .wrap(Wrap { trim: false });
         f.render_widget(right_content, chunks[1]);
     } else {
@@ -662,6 +667,146 @@ fn render_comparison_results(f: &mut Frame, area: Rect, output: &serde_json::Val
     }
 }
 
+/// Add the lines of the comparison's mode view to the capture A and B panels
+fn render_comparison_view<'a>(
+    comparison: &serde_json::Map<String, serde_json::Value>,
+    lines_left: &mut Vec<Line<'a>>,
+    lines_right: &mut Vec<Line<'a>>,
+) {
+    let heading = |text: &str| {
+        Line::from(Span::styled(
+            text.to_string(),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ))
+    };
+    let entry = |label: String, value: String, color: Color| {
+        Line::from(vec![
+            Span::styled(label, Style::default().fg(Color::Gray)),
+            Span::styled(value, Style::default().fg(color)),
+        ])
+    };
+    let device_label = |device: &serde_json::Value| {
+        let address = device.get("address").and_then(|a| a.as_str()).unwrap_or("?");
+        match device.get("name").and_then(|n| n.as_str()) {
+            Some(name) => format!("{} ({})", address, name),
+            None => address.to_string(),
+        }
+    };
+    let connection_count = |view: &serde_json::Value, key: &str| {
+        view.get(key).and_then(|c| c.as_array()).map(|c| c.len()).unwrap_or(0)
+    };
+    // Rows of a count table: "key: count (percent%)" for each side
+    let count_table = |rows: Option<&Vec<serde_json::Value>>,
+                       prefix: &str,
+                       lines_left: &mut Vec<Line<'a>>,
+                       lines_right: &mut Vec<Line<'a>>| {
+        for row in rows.into_iter().flatten() {
+            let key = match row.get("key") {
+                Some(serde_json::Value::String(key)) => key.clone(),
+                Some(key) => key.to_string(),
+                None => continue,
+            };
+            for (side, lines) in [("a", &mut *lines_left), ("b", &mut *lines_right)] {
+                let count = row.get(format!("count_{}", side)).and_then(|c| c.as_u64()).unwrap_or(0);
+                let percent = row.get(format!("percent_{}", side)).and_then(|p| p.as_f64()).unwrap_or(0.0);
+                let color = if count == 0 { Color::DarkGray } else { Color::White };
+                lines.push(entry(format!("{}{}: ", prefix, key), format!("{} ({:.1}%)", count, percent), color));
+            }
+        }
+    };
+
+    let mode = comparison.get("mode").and_then(|m| m.as_str()).unwrap_or("devices");
+    let Some(view) = comparison.get(mode) else {
+        return;
+    };
+
+    match mode {
+        "devices" => {
+            lines_left.push(heading("Only in A (disappeared)"));
+            lines_right.push(heading("Only in B (appeared)"));
+            for (key, lines, marker, color) in [
+                ("disappeared", &mut *lines_left, "- ", Color::Red),
+                ("appeared", &mut *lines_right, "+ ", Color::Green),
+            ] {
+                let devices = view.get(key).and_then(|d| d.as_array()).cloned().unwrap_or_default();
+                if devices.is_empty() {
+                    lines.push(Line::from(Span::styled("none", Style::default().fg(Color::DarkGray))));
+                }
+                for device in devices.iter().take(10) {
+                    lines.push(Line::from(vec![
+                        Span::styled(marker, Style::default().fg(color)),
+                        Span::styled(device_label(device), Style::default().fg(Color::White)),
+                    ]));
+                }
+                if devices.len() > 10 {
+                    lines.push(Line::from(Span::styled(
+                        format!("... and {} more", devices.len() - 10),
+                        Style::default().fg(Color::Gray).add_modifier(Modifier::ITALIC),
+                    )));
+                }
+            }
+
+            let changed = view.get("changed").and_then(|c| c.as_array()).map(|c| c.len()).unwrap_or(0);
+            let unchanged = view.get("unchanged").and_then(|u| u.as_u64()).unwrap_or(0);
+            for lines in [&mut *lines_left, &mut *lines_right] {
+                lines.push(Line::from(""));
+                lines.push(entry("Changed devices: ".to_string(), changed.to_string(), Color::Yellow));
+                lines.push(entry("Unchanged devices: ".to_string(), unchanged.to_string(), Color::Green));
+            }
+        }
+        "packets" => {
+            lines_left.push(heading("PDU Types"));
+            lines_right.push(heading("PDU Types"));
+            count_table(view.get("pdu_types").and_then(|p| p.as_array()), "", lines_left, lines_right);
+
+            lines_left.push(Line::from(""));
+            lines_right.push(Line::from(""));
+            lines_left.push(heading("Channels"));
+            lines_right.push(heading("Channels"));
+            count_table(view.get("channels").and_then(|c| c.as_array()), "ch ", lines_left, lines_right);
+
+            lines_left.push(Line::from(""));
+            lines_right.push(Line::from(""));
+            lines_left.push(entry("Connections: ".to_string(), connection_count(view, "connections_a").to_string(), Color::White));
+            lines_right.push(entry("Connections: ".to_string(), connection_count(view, "connections_b").to_string(), Color::White));
+        }
+        "timing" => {
+            lines_left.push(heading("Timing"));
+            lines_right.push(heading("Timing"));
+            for (side, lines) in [("a", &mut *lines_left), ("b", &mut *lines_right)] {
+                let duration = view.get(format!("duration_sec_{}", side)).and_then(|d| d.as_f64()).unwrap_or(0.0);
+                let rate = view.get(format!("packet_rate_{}", side)).and_then(|r| r.as_f64()).unwrap_or(0.0);
+                lines.push(entry("Duration: ".to_string(), format!("{:.1} s", duration), Color::White));
+                lines.push(entry("Packet rate: ".to_string(), format!("{:.1} pkt/s", rate), Color::White));
+                lines.push(entry(
+                    "Connections: ".to_string(),
+                    connection_count(view, &format!("connections_{}", side)).to_string(),
+                    Color::White,
+                ));
+            }
+
+            let intervals = view.get("adv_intervals").and_then(|i| i.as_array()).cloned().unwrap_or_default();
+            if !intervals.is_empty() {
+                lines_left.push(Line::from(""));
+                lines_right.push(Line::from(""));
+                lines_left.push(heading("Advertising Intervals"));
+                lines_right.push(heading("Advertising Intervals"));
+                for interval in intervals.iter().take(10) {
+                    let address = interval.get("address").and_then(|a| a.as_str()).unwrap_or("?");
+                    for (side, lines) in [("a", &mut *lines_left), ("b", &mut *lines_right)] {
+                        let ms = interval.get(format!("interval_ms_{}", side)).and_then(|i| i.as_f64()).unwrap_or(0.0);
+                        lines.push(entry(format!("{}: ", address), format!("{:.1} ms", ms), Color::White));
+                    }
+                }
+            }
+        }
+        _ => return,
+    }
+
+    lines_left.push(Line::from(""));
+    lines_right.push(Line::from(""));
+}
+
 /// Render analysis overview (summary of all sections)
 fn render_analysis_overview(f: &mut Frame, area: Rect, output: &serde_json::Value) {
     let mut lines = Vec::new();
@@ -815,6 +960,15 @@ fn render_analysis_devices(f: &mut Frame, area: Rect, output: &serde_json::Value
                                 ]));
                             }
                         }
+                        if let Some(adv) = device.get("advertising_data") {
+                            for (label, value) in advertising_detail_lines(adv) {
+                                lines.push(Line::from(vec![
+                                    Span::raw("  │ "),
+                                    Span::styled(label, Style::default().fg(Color::Gray)),
+                                    Span::styled(value, Style::default().fg(Color::White)),
+                                ]));
+                            }
+                        }
                         lines.push(Line::from("  │"));
                     }
                 }
@@ -844,6 +998,59 @@ fn render_analysis_devices(f: &mut Frame, area: Rect, output: &serde_json::Value
     f.render_widget(content, area);
 }
 
+/// Format `[{id_key, name}]` entries as "Name (id)" strings.
+fn named_entries(entries: Option<&serde_json::Value>, id_key: &str) -> Vec<String> {
+    entries
+        .and_then(|e| e.as_array())
+        .map(|arr| {
+            arr.iter()
+                .map(|entry| {
+                    let id = entry.get(id_key).and_then(|v| v.as_str()).unwrap_or("?");
+                    match entry.get("name").and_then(|n| n.as_str()) {
+                        Some(name) => format!("{} ({})", name, id),
+                        None => id.to_string(),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Label/value lines for a device's decoded advertising data.
+fn advertising_detail_lines(adv: &serde_json::Value) -> Vec<(&'static str, String)> {
+    let mut details = Vec::new();
+
+    let manufacturers = named_entries(adv.get("manufacturer_data"), "company_id");
+    if !manufacturers.is_empty() {
+        details.push(("Manufacturer: ", manufacturers.join(", ")));
+    }
+    let services = named_entries(adv.get("service_uuids"), "uuid");
+    if !services.is_empty() {
+        details.push(("Services: ", services.join(", ")));
+    }
+    let service_data = named_entries(adv.get("service_data"), "uuid");
+    if !service_data.is_empty() {
+        details.push(("Service Data: ", service_data.join(", ")));
+    }
+    if let Some(appearance) = adv.get("appearance").and_then(|a| a.as_str()) {
+        let value = match adv.get("appearance_name").and_then(|n| n.as_str()) {
+            Some(name) => format!("{} ({})", name, appearance),
+            None => appearance.to_string(),
+        };
+        details.push(("Appearance: ", value));
+    }
+    if let Some(tx_power) = adv.get("tx_power_dbm").and_then(|t| t.as_i64()) {
+        details.push(("TX Power: ", format!("{} dBm", tx_power)));
+    }
+    if let Some(beacons) = adv.get("beacons").and_then(|b| b.as_array()) {
+        for summary in beacons.iter().filter_map(|b| b.get("summary").and_then(|s| s.as_str())) {
+            details.push(("Beacon: ", summary.to_string()));
+        }
+    }
+
+    details
+}
+
 /// Render security observations view
 fn render_analysis_security(f: &mut Frame, area: Rect, output: &serde_json::Value, state: &crate::tui::app::AnalysisViewState) {
     let mut lines = Vec::new();
@@ -1300,6 +1507,8 @@ fn render_executing(f: &mut Frame, area: Rect, tool_name: &str, frame_count: u64
         Line::from(Span::styled("Please wait...", Style::default().fg(Color::Gray))),
         Line::from(""),
         Line::from(Span::styled("This may take a few seconds depending on the tool.", Style::default().fg(Color::DarkGray))),
+        Line::from(""),
+        Line::from(Span::styled("Press Esc to cancel; captures keep what was recorded so far.", Style::default().fg(Color::DarkGray))),
     ];
 
     let paragraph = Paragraph::new(text)
@@ -1497,7 +1706,7 @@ fn render_footer(f: &mut Frame, area: Rect, state: &AppState) {
             }
         }
         AppState::Executing { .. } => {
-            "Executing... please wait"
+            "Executing... please wait  [Esc] Cancel"
         }
         AppState::Results { tool_name, .. } => {
             match tool_name.as_str() {
@@ -1924,21 +2133,39 @@ fn render_packet_list(f: &mut Frame, area: Rect, packets: &[serde_json::Value],
                 ]));
 
                 // Show protocol layers if available
-                if let Some(full_packet) = packet.get("full_packet") {
-                    if let Some(layers) = full_packet.get("_source").and_then(|s| s.get("layers")) {
-                        let layer_names: Vec<String> = if let Some(obj) = layers.as_object() {
-                            obj.keys().map(|k| k.to_string()).collect()
-                        } else {
-                            vec![]
-                        };
-
-                        if !layer_names.is_empty() {
-                            lines.push(Line::from(vec![
-                                Span::raw("  │ "),
-                                Span::styled("Layers: ", Style::default().fg(Color::Gray)),
-                                Span::styled(layer_names.join(" → "), Style::default().fg(Color::Cyan)),
-                            ]));
-                        }
+                let mut layer = packet.get("protocol_tree");
+                let mut layer_names = Vec::new();
+                while let Some(node) = layer {
+                    if let Some(name) = node.get("layer").and_then(|l| l.as_str()) {
+                        layer_names.push(name);
+                    }
+                    layer = node.get("children").and_then(|c| c.get(0));
+                }
+                if !layer_names.is_empty() {
+                    lines.push(Line::from(vec![
+                        Span::raw("  │ "),
+                        Span::styled("Layers: ", Style::default().fg(Color::Gray)),
+                        Span::styled(layer_names.join(" → "), Style::default().fg(Color::Cyan)),
+                    ]));
+                }
+
+                if let Some(error) = packet.get("error").and_then(|e| e.as_str()) {
+                    lines.push(Line::from(vec![
+                        Span::raw("  │ "),
+                        Span::styled("Decode error: ", Style::default().fg(Color::Red)),
+                        Span::styled(error, Style::default().fg(Color::White)),
+                    ]));
+                }
+
+                // Show vendors and services resolved from the SIG assigned numbers
+                for (label, key, id_key) in [("Vendor: ", "companies", "company_id"), ("Services: ", "services", "uuid")] {
+                    let entries = named_entries(packet.get(key), id_key);
+                    if !entries.is_empty() {
+                        lines.push(Line::from(vec![
+                            Span::raw("  │ "),
+                            Span::styled(label, Style::default().fg(Color::Gray)),
+                            Span::styled(entries.join(", "), Style::default().fg(Color::White)),
+                        ]));
                     }
                 }
 
@@ -2805,6 +3032,9 @@ fn render_export_menu(f: &mut Frame, area: Rect, selected_index: usize, packet_c
     f.render_widget(list, chunks[0]);
 
     // Help text
+    let exports_dir = ubertooth_core::storage::exports_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|_| "the exports directory of the storage root".to_string());
     let help_text = vec![
         Line::from(vec![
             Span::styled("↑/↓", Style::default().fg(Color::Cyan)),
@@ -2816,7 +3046,7 @@ fn render_export_menu(f: &mut Frame, area: Rect, selected_index: usize, packet_c
         ]),
         Line::from(""),
         Line::from(Span::styled(
-            "Exports are saved to ~/.ubertooth/exports/",
+            format!("Exports are saved to {}", exports_dir),
             Style::default().fg(Color::Gray).add_modifier(Modifier::ITALIC),
         )),
     ];
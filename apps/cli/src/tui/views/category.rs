@@ -55,14 +55,29 @@ impl Category {
                             || name.starts_with("bt_fingerprint")
                             || name.starts_with("bt_compare")
                             || name.starts_with("pcap_merge")
+                            || name.starts_with("bt_tracker_detect")
+                            || name.starts_with("specan_summary")
+                            || name.starts_with("specan_interference")
+                    }
+                    "capture_" => {
+                        // Only show capture_list (other ops are hotkeys), retention, verification, bundles and the spectrum recording tools
+                        name == "capture_list"
+                            || name == "capture_prune"
+                            || name == "capture_verify"
+                            || name == "capture_bundle_export"
+                            || name == "capture_bundle_import"
+                            || name == "specan_list"
+                            || name == "specan_replay"
+                            || name == "specan_slice"
                     }
-                    "capture_" => name == "capture_list",  // Only show capture_list (other ops are hotkeys)
                     "config" => {
                         name.starts_with("configure_")
                             || name.starts_with("bt_save_config")
                             || name.starts_with("bt_load_config")
                             || name.starts_with("config_list")
                             || name.starts_with("config_delete")
+                            || name.starts_with("oui_update")
+                            || name.starts_with("squelch_calibrate")
                     }
                     "attack" => {
                         name.starts_with("btle_inject")
@@ -115,6 +130,13 @@ impl Category {
                         "capture_get" => 2,
                         "capture_export" => 3,
                         "capture_delete" => 4,
+                        "specan_list" => 5,
+                        "specan_replay" => 6,
+                        "specan_slice" => 7,
+                        "capture_prune" => 8,
+                        "capture_verify" => 9,
+                        "capture_bundle_export" => 10,
+                        "capture_bundle_import" => 11,
                         _ => 999,
                     }
                 });
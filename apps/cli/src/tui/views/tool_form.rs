@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tui_textarea::TextArea;
 use ubertooth_core::PentestTool;
+use ubertooth_platform::CaptureStore;
 
 /// Field type extracted from JSON schema
 #[derive(Debug, Clone)]
@@ -194,48 +195,26 @@ impl ToolForm {
         None
     }
 
-    /// Get list of available capture IDs from ~/.ubertooth/captures/
+    /// Get list of available capture IDs from `<storage root>/captures/`
+    ///
+    /// Goes through the capture store so encrypted metadata is decrypted.
     fn get_available_captures() -> Option<Vec<String>> {
-        use std::fs;
-        use std::path::PathBuf;
-
-        // Get captures directory
-        let mut captures_dir = PathBuf::from(std::env::var("HOME").ok()?);
-        captures_dir.push(".ubertooth");
-        captures_dir.push("captures");
-
-        tracing::debug!("Looking for captures in: {:?}", captures_dir);
-
-        if !captures_dir.exists() {
-            tracing::debug!("Captures directory doesn't exist");
-            return None;
-        }
-
-        // Read all .json metadata files
-        let mut captures = Vec::new();
-        if let Ok(entries) = fs::read_dir(&captures_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|e| e.to_str()) == Some("json") {
-                    // Read metadata to get capture_id
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        if let Ok(metadata) = serde_json::from_str::<serde_json::Value>(&content) {
-                            if let Some(id) = metadata.get("capture_id").and_then(|v| v.as_str()) {
-                                captures.push(id.to_string());
-                                tracing::debug!("Found capture: {}", id);
-                            }
-                        }
-                    }
-                }
+        let store = match CaptureStore::new() {
+            Ok(store) => store,
+            Err(e) => {
+                tracing::debug!("Capture store unavailable: {}", e);
+                return None;
             }
-        }
-
-        // Sort by most recent (reverse alphabetical by ID which includes timestamp)
-        captures.sort();
-        captures.reverse();
-
-        // Limit to most recent 20 to keep dropdown manageable
-        captures.truncate(20);
+        };
+
+        // Most recent first; keep the dropdown manageable
+        let captures: Vec<String> = match store.list_captures() {
+            Ok(captures) => captures.into_iter().map(|m| m.capture_id).take(20).collect(),
+            Err(e) => {
+                tracing::debug!("Failed to list captures: {}", e);
+                return None;
+            }
+        };
 
         tracing::debug!("Total captures found: {}", captures.len());
 
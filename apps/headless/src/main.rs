@@ -41,6 +41,14 @@ struct Args {
     /// Backend to use: 'python' (default) or 'rust'
     #[arg(long, default_value = "python")]
     backend: String,
+
+    /// Storage root for captures, configs and exports (default: ~/.ubertooth)
+    #[arg(long)]
+    storage_root: Option<String>,
+
+    /// Encrypt the existing capture store with the configured key and exit
+    #[arg(long)]
+    encrypt_store: bool,
 }
 
 #[tokio::main]
@@ -62,6 +70,13 @@ async fn main() -> anyhow::Result<()> {
     if let Some(token) = &args.auth_token {
         std::env::set_var("AUTH_TOKEN", token);
     }
+    if let Some(root) = &args.storage_root {
+        std::env::set_var(ubertooth_core::storage::STORAGE_ROOT_ENV, root);
+    }
+
+    if args.encrypt_store {
+        return encrypt_store();
+    }
 
     tracing::info!("ubertooth-agent starting (v{})", env!("CARGO_PKG_VERSION"));
 
@@ -113,12 +128,15 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
-    // Create capture store (~/.ubertooth/)
-    let _store = Arc::new(CaptureStore::new()?);
-    tracing::info!("Capture store initialized at ~/.ubertooth/");
+    // Create capture store at the storage root
+    let store = Arc::new(CaptureStore::new()?);
+    tracing::info!("Capture store initialized at {}", store.base_path().display());
+
+    // Apply the capture retention policy before taking new captures
+    ubertooth_platform::retention::enforce(&store, None);
 
     // Create tool registry and connector
-    let tools = create_tool_registry(backend);
+    let tools = create_tool_registry(backend.clone());
     tracing::info!("Registered {} tools:", tools.tools().len());
     for name in tools.names() {
         tracing::info!("  - {}", name);
@@ -126,6 +144,9 @@ async fn main() -> anyhow::Result<()> {
 
     let connector = Arc::new(UbertoothConnector::new(tools));
 
+    // Stream parsed ubertooth-* output as tool progress events
+    backend.set_event_sender(connector.event_sender());
+
     // Verify Strike48 SDK Tool behavior integration
     tracing::info!("Connector behavior: {:?}", connector.behavior());
     let metadata = connector.metadata();
@@ -293,3 +314,39 @@ async fn run_local_smoke_test(connector: Arc<UbertoothConnector>) -> anyhow::Res
     tracing::info!("Local smoke test complete ✓");
     Ok(())
 }
+
+/// Encrypt every plaintext file in the capture store, creating the
+/// configured keyfile first if it does not exist yet.
+fn encrypt_store() -> anyhow::Result<()> {
+    use ubertooth_platform::encryption::{self, KeySource};
+
+    match KeySource::configured()? {
+        Some(KeySource::Keyfile(path)) if !path.exists() => {
+            encryption::generate_keyfile(&path)?;
+            tracing::info!("Generated store key at {}", path.display());
+        }
+        Some(_) => {}
+        None => anyhow::bail!(
+            "No store key configured: set {} or {}, or [encryption] keyfile in the config file",
+            encryption::KEYFILE_ENV,
+            encryption::PASSPHRASE_ENV
+        ),
+    }
+
+    let store = CaptureStore::new()?;
+    let report = store.migrate_encryption()?;
+    tracing::info!(
+        "Encrypted {} files in {} ({} already encrypted)",
+        report.encrypted,
+        store.base_path().display(),
+        report.already_encrypted
+    );
+    for failure in &report.failed {
+        tracing::error!("Not encrypted: {}", failure);
+    }
+    if !report.failed.is_empty() {
+        anyhow::bail!("{} files could not be encrypted", report.failed.len());
+    }
+
+    Ok(())
+}
@@ -19,6 +19,9 @@ pub enum UbertoothError {
     #[error("Firmware too old: {current}, required: {required}")]
     FirmwareTooOld { current: String, required: String },
 
+    #[error("Incompatible ubertooth-tools: {0}")]
+    Incompatible(String),
+
     #[error("Permission denied - check udev rules (run: sudo ubertooth-one-connector/scripts/install-udev-rules.sh)")]
     PermissionDenied,
 
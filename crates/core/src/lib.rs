@@ -4,6 +4,7 @@ pub mod connector;
 pub mod error;
 pub mod events;
 pub mod logging;
+pub mod storage;
 pub mod tools;
 
 pub use connector::UbertoothConnector;
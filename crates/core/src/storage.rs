@@ -0,0 +1,179 @@
+//! Storage root resolution shared by every backend and front end.
+//!
+//! All persistent data (captures, configs, exports, OUI registry,
+//! fingerprint signatures) lives under one storage root, resolved in this
+//! order:
+//!
+//! 1. `UBERTOOTH_STORAGE_ROOT` (the agent's `--storage-root` flag sets it)
+//! 2. `storage.root` in the config file (`UBERTOOTH_CONFIG`, or
+//!    `<config dir>/ubertooth/config.toml`)
+//! 3. `~/.ubertooth`
+//!
+//! ```toml
+//! [storage]
+//! root = "/srv/evidence/ubertooth"
+//! ```
+//!
+//! Other components read their own sections of the same file through
+//! [`config_section`].
+
+use crate::error::{Result, UbertoothError};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the storage root.
+pub const STORAGE_ROOT_ENV: &str = "UBERTOOTH_STORAGE_ROOT";
+
+/// Environment variable pointing at the config file.
+pub const CONFIG_FILE_ENV: &str = "UBERTOOTH_CONFIG";
+
+/// Captures directory name under the storage root.
+pub const CAPTURES_DIR: &str = "captures";
+
+/// Config presets directory name under the storage root.
+pub const CONFIGS_DIR: &str = "configs";
+
+/// Exports directory name under the storage root.
+pub const EXPORTS_DIR: &str = "exports";
+
+/// OUI registry directory name under the storage root.
+pub const OUI_DIR: &str = "oui";
+
+/// Device fingerprint signature directory name under the storage root.
+pub const FINGERPRINTS_DIR: &str = "fingerprints";
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    storage: StorageSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StorageSection {
+    root: Option<String>,
+}
+
+/// Path of the config file, whether or not it exists.
+pub fn config_file_path() -> Option<PathBuf> {
+    match std::env::var_os(CONFIG_FILE_ENV) {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => dirs::config_dir().map(|dir| dir.join("ubertooth").join("config.toml")),
+    }
+}
+
+/// Resolve the storage root (without creating it).
+pub fn storage_root() -> Result<PathBuf> {
+    let env = std::env::var(STORAGE_ROOT_ENV).ok();
+    let config = match config_file_path() {
+        Some(path) if path.exists() => Some(read_config_root(&path)?),
+        _ => None,
+    };
+
+    resolve_root(env.as_deref(), config.flatten().as_deref(), dirs::home_dir().as_deref()).ok_or_else(|| {
+        UbertoothError::BackendError(format!(
+            "Could not determine storage root; set {} or storage.root in the config file",
+            STORAGE_ROOT_ENV
+        ))
+    })
+}
+
+/// Read a section of the config file, e.g. `[retention]`.
+///
+/// A missing config file or section yields `T::default()`.
+pub fn config_section<T: DeserializeOwned + Default>(name: &str) -> Result<T> {
+    let Some(path) = config_file_path().filter(|p| p.exists()) else {
+        return Ok(T::default());
+    };
+
+    let text = fs::read_to_string(&path)?;
+    let table: toml::Table = toml::from_str(&text).map_err(|e| {
+        UbertoothError::ParseError(format!("Invalid config file {}: {}", path.display(), e))
+    })?;
+
+    match table.get(name) {
+        Some(section) => section.clone().try_into().map_err(|e| {
+            UbertoothError::ParseError(format!("Invalid [{}] section in {}: {}", name, path.display(), e))
+        }),
+        None => Ok(T::default()),
+    }
+}
+
+/// Expand a leading `~/` to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Resolve and create a directory under the storage root.
+pub fn storage_dir(name: &str) -> Result<PathBuf> {
+    let dir = storage_root()?.join(name);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Captures directory, created if missing.
+pub fn captures_dir() -> Result<PathBuf> {
+    storage_dir(CAPTURES_DIR)
+}
+
+/// Exports directory, created if missing.
+pub fn exports_dir() -> Result<PathBuf> {
+    storage_dir(EXPORTS_DIR)
+}
+
+fn read_config_root(path: &Path) -> Result<Option<String>> {
+    let text = fs::read_to_string(path)?;
+    let config: ConfigFile = toml::from_str(&text).map_err(|e| {
+        UbertoothError::ParseError(format!("Invalid config file {}: {}", path.display(), e))
+    })?;
+    Ok(config.storage.root)
+}
+
+fn resolve_root(env: Option<&str>, config: Option<&str>, home: Option<&Path>) -> Option<PathBuf> {
+    let configured = env.or(config).map(str::trim).filter(|v| !v.is_empty());
+
+    match configured {
+        Some(path) => match path.strip_prefix("~/") {
+            Some(rest) => home.map(|h| h.join(rest)),
+            None if path == "~" => home.map(Path::to_path_buf),
+            None => Some(PathBuf::from(path)),
+        },
+        None => home.map(|h| h.join(".ubertooth")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_root_precedence() {
+        let home = Path::new("/home/analyst");
+
+        assert_eq!(resolve_root(None, None, Some(home)), Some(home.join(".ubertooth")));
+        assert_eq!(
+            resolve_root(None, Some("/srv/evidence"), Some(home)),
+            Some(PathBuf::from("/srv/evidence"))
+        );
+        assert_eq!(
+            resolve_root(Some("/mnt/case-42"), Some("/srv/evidence"), Some(home)),
+            Some(PathBuf::from("/mnt/case-42"))
+        );
+        assert_eq!(resolve_root(Some("~/cases"), None, Some(home)), Some(home.join("cases")));
+        assert_eq!(resolve_root(Some(" "), None, Some(home)), Some(home.join(".ubertooth")));
+        assert_eq!(resolve_root(None, None, None), None);
+    }
+
+    #[test]
+    fn test_config_file_storage_root() {
+        let config: ConfigFile = toml::from_str("[storage]\nroot = \"/srv/evidence\"\n").unwrap();
+        assert_eq!(config.storage.root.as_deref(), Some("/srv/evidence"));
+
+        let empty: ConfigFile = toml::from_str("").unwrap();
+        assert!(empty.storage.root.is_none());
+    }
+}
@@ -0,0 +1,270 @@
+#!/usr/bin/env python3
+"""Persistent helper process for the ubertooth connector's CLI backend.
+
+Speaks line-delimited JSON-RPC 2.0 on stdin/stdout, one message per line.
+The connector starts it once and runs every ubertooth-* tool through it:
+
+    -> {"jsonrpc": "2.0", "id": 1, "method": "run",
+        "params": {"program": "ubertooth-btle", "args": ["-n"],
+                   "timeout": 135.0, "stop_after": 120.0, "grace": 3.0}}
+    <- {"jsonrpc": "2.0", "method": "output", "params": {"id": 1, "line": "..."}}
+    <- {"jsonrpc": "2.0", "id": 1,
+        "result": {"termination": "stopped", "exit_code": 0, "signal": null, "stderr": ""}}
+
+Methods:
+
+    ping      heartbeat; returns pid, uptime and the number of running commands
+    run       run a program, streaming each stdout line as an "output"
+              notification before the result. "stop_after" ends it as planned,
+              "timeout" ends it as hung; both send SIGINT and kill it after
+              "grace" seconds. "termination" is exited, stopped, timed_out or
+              cancelled.
+    cancel    stop the run with request id "id" (SIGINT, then kill)
+    shutdown  stop every run and exit
+
+Commands run concurrently. When stdin closes the helper stops its commands
+and exits, and on Linux commands are also terminated if the helper is killed.
+Only the standard library is used.
+"""
+
+import json
+import os
+import shutil
+import signal
+import subprocess
+import sys
+import threading
+import time
+
+PROTOCOL_VERSION = 1
+
+# JSON-RPC error codes
+PARSE_ERROR = -32700
+INVALID_PARAMS = -32602
+METHOD_NOT_FOUND = -32601
+EXECUTION_FAILED = -32000
+
+_started = time.monotonic()
+_write_lock = threading.Lock()
+_runs = {}
+_runs_lock = threading.Lock()
+
+# preexec_fn is not safe while the reader threads run, so on Linux each
+# command is started through this wrapper instead: a fresh single-threaded
+# interpreter that asks the kernel to terminate it when the helper dies,
+# then execs the command (the setting survives exec).
+_DEATHSIG_WRAPPER = """
+import ctypes, os, signal, sys
+PR_SET_PDEATHSIG = 1
+ctypes.CDLL(None).prctl(PR_SET_PDEATHSIG, signal.SIGTERM)
+if os.getppid() != int(sys.argv[1]):
+    sys.exit(1)
+os.execv(sys.argv[2], sys.argv[2:])
+"""
+
+
+def _deathsig_supported():
+    if not sys.platform.startswith("linux"):
+        return False
+    try:
+        import ctypes
+
+        ctypes.CDLL(None)
+    except (ImportError, OSError):
+        return False
+    return True
+
+
+_use_deathsig = _deathsig_supported()
+
+
+def _signal_group(proc, sig):
+    try:
+        os.killpg(proc.pid, sig)
+    except OSError:
+        pass
+
+
+def command_line(program, args):
+    """The argv running program, resolved so a missing one fails before starting."""
+    path = shutil.which(program)
+    if path is None:
+        raise OSError("{}: command not found".format(program))
+    if _use_deathsig:
+        return [sys.executable, "-c", _DEATHSIG_WRAPPER, str(os.getpid()), path] + args
+    return [path] + args
+
+
+def send(message):
+    message["jsonrpc"] = "2.0"
+    line = json.dumps(message, separators=(",", ":"))
+    with _write_lock:
+        sys.stdout.write(line + "\n")
+        sys.stdout.flush()
+
+
+def reply(request_id, result):
+    send({"id": request_id, "result": result})
+
+
+def reply_error(request_id, code, message):
+    send({"id": request_id, "error": {"code": code, "message": message}})
+
+
+class Run:
+    """One program started by a "run" request."""
+
+    def __init__(self, request_id, params):
+        self.request_id = request_id
+        self.program = params["program"]
+        self.args = [str(arg) for arg in params.get("args", [])]
+        self.timeout = params.get("timeout")
+        self.stop_after = params.get("stop_after")
+        self.grace = params.get("grace", 3.0)
+        self.cancelled = threading.Event()
+        self.started = None
+        self.thread = threading.Thread(target=self._run, daemon=True)
+
+    def start(self):
+        self.started = time.monotonic()
+        with _runs_lock:
+            _runs[self.request_id] = self
+        self.thread.start()
+
+    def _finish(self):
+        with _runs_lock:
+            _runs.pop(self.request_id, None)
+
+    def _forward_stdout(self, pipe):
+        for raw in iter(pipe.readline, b""):
+            line = raw.decode("utf-8", "replace").rstrip("\r\n")
+            send({"method": "output", "params": {"id": self.request_id, "line": line}})
+
+    def _interrupt(self, proc):
+        # Each command leads its own process group, so its children stop too
+        _signal_group(proc, signal.SIGINT)
+        try:
+            proc.wait(timeout=self.grace)
+        except subprocess.TimeoutExpired:
+            _signal_group(proc, signal.SIGKILL)
+            proc.kill()
+            proc.wait()
+
+    def _run(self):
+        try:
+            proc = subprocess.Popen(
+                command_line(self.program, self.args),
+                stdin=subprocess.DEVNULL,
+                stdout=subprocess.PIPE,
+                stderr=subprocess.PIPE,
+                start_new_session=True,
+            )
+        except OSError as e:
+            self._finish()
+            reply_error(self.request_id, EXECUTION_FAILED, "Failed to execute {}: {}".format(self.program, e))
+            return
+
+        stderr = []
+        readers = [
+            threading.Thread(target=self._forward_stdout, args=(proc.stdout,), daemon=True),
+            threading.Thread(target=lambda: stderr.append(proc.stderr.read()), daemon=True),
+        ]
+        for reader in readers:
+            reader.start()
+
+        termination = None
+        while termination is None:
+            if self.cancelled.wait(0.05):
+                termination = "cancelled"
+            elif proc.poll() is not None:
+                termination = "exited"
+            elif self.stop_after is not None and time.monotonic() - self.started >= self.stop_after:
+                termination = "stopped"
+            elif self.timeout is not None and time.monotonic() - self.started >= self.timeout:
+                termination = "timed_out"
+        if termination != "exited":
+            self._interrupt(proc)
+
+        # Lines printed while exiting are sent before the result, but a
+        # leftover process holding the pipes open is not waited for
+        deadline = time.monotonic() + self.grace
+        for reader in readers:
+            reader.join(max(0.0, deadline - time.monotonic()))
+        self._finish()
+
+        code = proc.returncode
+        reply(
+            self.request_id,
+            {
+                "termination": termination,
+                "exit_code": code if code >= 0 else None,
+                "signal": -code if code < 0 else None,
+                "stderr": b"".join(stderr).decode("utf-8", "replace"),
+            },
+        )
+
+
+def running():
+    with _runs_lock:
+        return list(_runs.values())
+
+
+def handle(message):
+    request_id = message.get("id")
+    method = message.get("method")
+    params = message.get("params") or {}
+
+    if method == "ping":
+        reply(
+            request_id,
+            {
+                "pong": True,
+                "pid": os.getpid(),
+                "protocol": PROTOCOL_VERSION,
+                "uptime_sec": round(time.monotonic() - _started, 3),
+                "running": len(running()),
+            },
+        )
+    elif method == "run":
+        if "program" not in params:
+            reply_error(request_id, INVALID_PARAMS, "Missing 'program'")
+            return
+        Run(request_id, params).start()
+    elif method == "cancel":
+        with _runs_lock:
+            run = _runs.get(params.get("id"))
+        if run is not None:
+            run.cancelled.set()
+        reply(request_id, {"cancelled": run is not None})
+    elif method == "shutdown":
+        stop_all()
+        reply(request_id, {"stopped": True})
+        sys.exit(0)
+    else:
+        reply_error(request_id, METHOD_NOT_FOUND, "Method not found: {}".format(method))
+
+
+def stop_all():
+    runs = running()
+    for run in runs:
+        run.cancelled.set()
+    for run in runs:
+        run.thread.join()
+
+
+def main():
+    for raw in iter(sys.stdin.buffer.readline, b""):
+        raw = raw.strip()
+        if not raw:
+            continue
+        try:
+            message = json.loads(raw)
+        except ValueError as e:
+            reply_error(None, PARSE_ERROR, "Invalid JSON: {}".format(e))
+            continue
+        handle(message)
+    stop_all()
+
+
+if __name__ == "__main__":
+    main()
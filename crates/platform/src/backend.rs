@@ -2,7 +2,9 @@
 
 use async_trait::async_trait;
 use serde_json::Value;
+use tokio::sync::broadcast;
 use ubertooth_core::error::Result;
+use ubertooth_core::events::ToolEvent;
 
 /// Trait for backend providers (Python sidecar or Rust USB).
 ///
@@ -29,4 +31,9 @@ pub trait UbertoothBackendProvider: Send + Sync {
 
     /// Get backend type identifier.
     fn backend_type(&self) -> &str;
+
+    /// Send `ToolEvent::Progress` updates for running tools to `events`.
+    ///
+    /// Backends without live progress ignore this.
+    fn set_event_sender(&self, _events: broadcast::Sender<ToolEvent>) {}
 }
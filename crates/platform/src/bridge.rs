@@ -0,0 +1,510 @@
+//! Persistent sidecar helper speaking JSON-RPC over stdio.
+//!
+//! The CLI backend runs its ubertooth-* tools through one long-lived Python
+//! helper (`sidecar/ubertooth_bridge.py`, embedded in the binary) instead of
+//! forking each one from the connector. Requests and responses are
+//! line-delimited JSON-RPC 2.0 messages matched by request ID; a running
+//! command streams its stdout lines back as `output` notifications before
+//! its result. A run whose caller drops its future is cancelled in the
+//! helper, just as a directly spawned command is killed.
+//!
+//! A heartbeat pings the helper every [`HEARTBEAT_INTERVAL`]. A helper that
+//! exits or stops answering is marked dead, requests waiting on it fail, and
+//! the next request starts a new one.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{mpsc, Mutex};
+use ubertooth_core::error::{Result, UbertoothError};
+
+use crate::process::{ProcessOutput, RunOptions, Termination};
+
+/// The helper script, run with `python3 -u -c`.
+const BRIDGE_SCRIPT: &str = include_str!("../sidecar/ubertooth_bridge.py");
+
+/// Environment variable naming the Python interpreter (default `python3`).
+pub const PYTHON_ENV: &str = "UBERTOOTH_SIDECAR_PYTHON";
+
+/// Time between heartbeats.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Time the helper has to answer a ping before it counts as hung.
+pub const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A message for one request: its output notifications, then its reply.
+#[derive(Debug)]
+enum Message {
+    Output(String),
+    Reply(std::result::Result<Value, String>),
+}
+
+/// One running helper process.
+struct Connection {
+    child: std::sync::Mutex<Child>,
+    stdin: Mutex<ChildStdin>,
+    pending: std::sync::Mutex<HashMap<u64, mpsc::UnboundedSender<Message>>>,
+    next_id: AtomicU64,
+    alive: AtomicBool,
+}
+
+impl Connection {
+    fn spawn(python: &str) -> Result<Arc<Self>> {
+        let mut child = Command::new(python)
+            .args(["-u", "-c", BRIDGE_SCRIPT])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| UbertoothError::BackendError(format!("Failed to start sidecar helper with {}: {}", python, e)))?;
+
+        let stdin = child.stdin.take().ok_or_else(|| UbertoothError::BackendError("Sidecar helper has no stdin".to_string()))?;
+        let stdout = child.stdout.take().ok_or_else(|| UbertoothError::BackendError("Sidecar helper has no stdout".to_string()))?;
+        tracing::info!("Started sidecar helper (pid {:?})", child.id());
+
+        let connection = Arc::new(Self {
+            child: std::sync::Mutex::new(child),
+            stdin: Mutex::new(stdin),
+            pending: std::sync::Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            alive: AtomicBool::new(true),
+        });
+        tokio::spawn(Self::read_messages(Arc::downgrade(&connection), BufReader::new(stdout)));
+        tokio::spawn(Self::heartbeat(Arc::downgrade(&connection)));
+        Ok(connection)
+    }
+
+    fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    /// Mark the helper dead, kill it and fail every request waiting on it.
+    fn fail(&self, reason: &str) {
+        if self.alive.swap(false, Ordering::SeqCst) {
+            tracing::warn!("Sidecar helper {}", reason);
+        }
+        let mut child = self.child.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = child.start_kill();
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        for sender in pending.into_values() {
+            let _ = sender.send(Message::Reply(Err(format!("sidecar helper {}", reason))));
+        }
+    }
+
+    /// Route replies and notifications to the requests they belong to.
+    async fn read_messages(connection: Weak<Self>, mut stdout: BufReader<tokio::process::ChildStdout>) {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = stdout.read_line(&mut line).await;
+            let Some(connection) = connection.upgrade() else { return };
+            if !matches!(read, Ok(n) if n > 0) {
+                let status = connection.child.lock().unwrap_or_else(|e| e.into_inner()).try_wait();
+                connection.fail(&match status {
+                    Ok(Some(status)) => format!("exited ({})", status),
+                    _ => "closed its output".to_string(),
+                });
+                return;
+            }
+
+            let message: Value = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::warn!("Ignoring malformed sidecar message ({}): {}", e, line.trim());
+                    continue;
+                }
+            };
+            let (id, message) = if message.get("method").and_then(Value::as_str) == Some("output") {
+                let params = &message["params"];
+                let line = params["line"].as_str().unwrap_or_default().to_string();
+                (params["id"].as_u64(), Message::Output(line))
+            } else if let Some(error) = message.get("error") {
+                let error = error["message"].as_str().unwrap_or("unknown error").to_string();
+                (message["id"].as_u64(), Message::Reply(Err(error)))
+            } else {
+                (message["id"].as_u64(), Message::Reply(Ok(message["result"].clone())))
+            };
+
+            let Some(id) = id else {
+                tracing::warn!("Sidecar message without a request id: {}", line.trim());
+                continue;
+            };
+            let mut pending = connection.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let sender = match message {
+                Message::Reply(_) => pending.remove(&id),
+                Message::Output(_) => pending.get(&id).cloned(),
+            };
+            if let Some(sender) = sender {
+                let _ = sender.send(message);
+            }
+        }
+    }
+
+    /// Ping the helper until it dies, killing it if it stops answering.
+    async fn heartbeat(connection: Weak<Self>) {
+        loop {
+            tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+            let Some(connection) = connection.upgrade() else { return };
+            if !connection.is_alive() {
+                return;
+            }
+            match tokio::time::timeout(HEARTBEAT_TIMEOUT, connection.request("ping", json!({}))).await {
+                Ok(Ok(_)) => tracing::trace!("Sidecar helper heartbeat ok"),
+                Ok(Err(e)) => connection.fail(&format!("failed its heartbeat: {}", e)),
+                Err(_) => connection.fail(&format!("missed its heartbeat ({:?})", HEARTBEAT_TIMEOUT)),
+            }
+        }
+    }
+
+    /// Send a request, returning its ID and the channel its messages arrive on.
+    async fn send(&self, method: &str, params: Value) -> Result<(u64, mpsc::UnboundedReceiver<Message>)> {
+        if !self.is_alive() {
+            return Err(UbertoothError::BackendError("Sidecar helper is not running".to_string()));
+        }
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).insert(id, sender);
+
+        let mut line = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }).to_string();
+        line.push('\n');
+        let mut stdin = self.stdin.lock().await;
+        if let Err(e) = async {
+            stdin.write_all(line.as_bytes()).await?;
+            stdin.flush().await
+        }
+        .await
+        {
+            drop(stdin);
+            self.fail(&format!("stopped reading requests: {}", e));
+            return Err(UbertoothError::BackendError(format!("Failed to send {} to sidecar helper: {}", method, e)));
+        }
+        Ok((id, receiver))
+    }
+
+    /// Send a request and wait for its result.
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let (_, mut receiver) = self.send(method, params).await?;
+        while let Some(message) = receiver.recv().await {
+            if let Message::Reply(reply) = message {
+                return reply.map_err(|e| UbertoothError::BackendError(format!("Sidecar {} failed: {}", method, e)));
+            }
+        }
+        Err(UbertoothError::BackendError("Sidecar helper exited".to_string()))
+    }
+}
+
+/// Cancels a helper run whose caller stopped waiting for it.
+///
+/// Dropping the future of [`Bridge::run_streaming`] would otherwise leave the
+/// command running in the helper until its timeout, unlike a directly spawned
+/// command, which is killed when its future is dropped.
+struct RunGuard {
+    connection: Arc<Connection>,
+    id: u64,
+    finished: bool,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.connection.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.id);
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let (connection, id) = (self.connection.clone(), self.id);
+        tracing::debug!("Run {} abandoned, cancelling it in the sidecar helper", id);
+        runtime.spawn(async move {
+            if let Err(e) = connection.request("cancel", json!({ "id": id })).await {
+                tracing::warn!("Failed to cancel abandoned run {}: {}", id, e);
+            }
+        });
+    }
+}
+
+/// Build the exit status reported by the helper.
+fn exit_status(reply: &Value) -> ExitStatus {
+    let code = reply["exit_code"].as_i64().unwrap_or(-1) as i32;
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        match reply["signal"].as_i64() {
+            Some(signal) => ExitStatus::from_raw(signal as i32),
+            None => ExitStatus::from_raw((code & 0xff) << 8),
+        }
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatus::from_raw(code as u32)
+    }
+}
+
+fn termination(reply: &Value) -> Result<Termination> {
+    serde_json::from_value(reply["termination"].clone())
+        .map_err(|e| UbertoothError::BackendError(format!("Invalid sidecar termination: {}", e)))
+}
+
+/// The persistent helper, started on first use and restarted after it dies.
+pub struct Bridge {
+    python: String,
+    connection: Mutex<Option<Arc<Connection>>>,
+    restarts: AtomicU32,
+}
+
+impl Default for Bridge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bridge {
+    /// A helper run with the interpreter from [`PYTHON_ENV`].
+    pub fn new() -> Self {
+        Self::with_python(std::env::var(PYTHON_ENV).unwrap_or_else(|_| "python3".to_string()))
+    }
+
+    /// A helper run with `python`.
+    pub fn with_python(python: impl Into<String>) -> Self {
+        Self {
+            python: python.into(),
+            connection: Mutex::new(None),
+            restarts: AtomicU32::new(0),
+        }
+    }
+
+    /// Number of times a dead helper was replaced.
+    pub fn restarts(&self) -> u32 {
+        self.restarts.load(Ordering::SeqCst)
+    }
+
+    /// The running helper, starting one if there is none or it died.
+    async fn connection(&self) -> Result<Arc<Connection>> {
+        let mut connection = self.connection.lock().await;
+        match connection.as_ref() {
+            Some(current) if current.is_alive() => return Ok(current.clone()),
+            Some(_) => {
+                // Forget the dead helper even if no new one can be started
+                *connection = None;
+                self.restarts.fetch_add(1, Ordering::SeqCst);
+                tracing::info!("Restarting sidecar helper");
+            }
+            None => {}
+        }
+        let started = Connection::spawn(&self.python)?;
+        *connection = Some(started.clone());
+        Ok(started)
+    }
+
+    /// Whether a helper can be started (or is running).
+    pub async fn available(&self) -> bool {
+        self.connection().await.is_ok()
+    }
+
+    /// Round-trip a ping, starting the helper if needed.
+    pub async fn ping(&self) -> Result<Value> {
+        let connection = self.connection().await?;
+        Self::ping_on(&connection).await
+    }
+
+    /// Whether the current helper is alive and answers a ping, without
+    /// starting or replacing one.
+    ///
+    /// `None` when there is no helper: none was needed yet, or none could
+    /// be started (no interpreter) and commands run directly.
+    pub async fn check(&self) -> Option<bool> {
+        let current = self.connection.lock().await.clone()?;
+        Some(current.is_alive() && Self::ping_on(&current).await.is_ok())
+    }
+
+    async fn ping_on(connection: &Connection) -> Result<Value> {
+        tokio::time::timeout(HEARTBEAT_TIMEOUT, connection.request("ping", json!({})))
+            .await
+            .map_err(|_| {
+                connection.fail("did not answer a ping");
+                UbertoothError::BackendError("Sidecar helper did not answer".to_string())
+            })?
+    }
+
+    /// Stop the helper (and the commands it runs) and start a new one.
+    pub async fn restart(&self) -> Result<()> {
+        let previous = self.connection.lock().await.take();
+        if let Some(previous) = previous {
+            if previous.is_alive() {
+                // Give running commands their SIGINT before the helper goes away
+                let _ = tokio::time::timeout(HEARTBEAT_TIMEOUT, previous.request("shutdown", json!({}))).await;
+            }
+            previous.fail("was stopped for a restart");
+            self.restarts.fetch_add(1, Ordering::SeqCst);
+        }
+        self.ping().await.map(|_| ())
+    }
+
+    /// Run `program` through the helper, like [`process::run_streaming`](crate::process::run_streaming).
+    pub async fn run_streaming<F>(&self, program: &str, args: &[&str], options: &RunOptions, mut on_line: F) -> Result<ProcessOutput>
+    where
+        F: FnMut(&str) + Send,
+    {
+        let connection = self.connection().await?;
+        let params = json!({
+            "program": program,
+            "args": args,
+            "timeout": options.timeout.map(|d| d.as_secs_f64()),
+            "stop_after": options.stop_after.map(|d| d.as_secs_f64()),
+            "grace": options.grace.as_secs_f64(),
+        });
+        let (id, mut receiver) = connection.send("run", params).await?;
+        let mut guard = RunGuard {
+            connection: connection.clone(),
+            id,
+            finished: false,
+        };
+
+        let cancel = options.cancel.clone();
+        let cancelled = async {
+            match &cancel {
+                Some(token) => token.cancelled().await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(cancelled);
+        let mut cancel_sent = false;
+
+        let mut stdout = String::new();
+        let reply = loop {
+            tokio::select! {
+                message = receiver.recv() => match message {
+                    Some(Message::Output(line)) => {
+                        on_line(&line);
+                        stdout.push_str(&line);
+                        stdout.push('\n');
+                    }
+                    Some(Message::Reply(reply)) => break reply,
+                    None => break Err("exited".to_string()),
+                },
+                _ = &mut cancelled, if !cancel_sent => {
+                    cancel_sent = true;
+                    connection.send("cancel", json!({ "id": id })).await?;
+                }
+            }
+        };
+        guard.finished = true;
+        let reply = reply.map_err(|e| UbertoothError::BackendError(format!("{} via sidecar helper: {}", program, e)))?;
+
+        Ok(ProcessOutput {
+            termination: termination(&reply)?,
+            status: exit_status(&reply),
+            stdout,
+            stderr: reply["stderr"].as_str().unwrap_or_default().to_string(),
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::process::CancelToken;
+
+    /// A bridge, or `None` when python3 is not installed.
+    async fn bridge() -> Option<Bridge> {
+        let bridge = Bridge::with_python("python3");
+        bridge.available().await.then_some(bridge)
+    }
+
+    #[tokio::test]
+    async fn test_run_through_helper() {
+        let Some(bridge) = bridge().await else { return };
+        assert_eq!(bridge.ping().await.unwrap()["pong"], true);
+
+        let mut lines = Vec::new();
+        let output = bridge
+            .run_streaming("sh", &["-c", "echo out; echo err >&2; exit 3"], &RunOptions::default(), |l| lines.push(l.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(output.termination, Termination::Exited);
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!((output.stdout.as_str(), output.stderr.as_str(), lines), ("out\n", "err\n", vec!["out".to_string()]));
+        assert!(bridge.run_streaming("/nonexistent/tool", &[], &RunOptions::default(), |_| {}).await.is_err());
+
+        // Cancelling interrupts the command, which can still flush its output
+        let token = CancelToken::new();
+        let options = RunOptions { cancel: Some(token.clone()), ..Default::default() };
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            canceller.cancel();
+        });
+        let script = "trap 'echo flushed; exit 0' INT; echo started; while :; do sleep 0.05; done";
+        let output = bridge.run_streaming("sh", &["-c", script], &options, |_| {}).await.unwrap();
+        assert_eq!(output.termination, Termination::Cancelled);
+        assert_eq!(output.stdout, "started\nflushed\n");
+
+        let output = bridge
+            .run_streaming("sh", &["-c", script], &RunOptions::stop_after(Duration::from_millis(200)), |_| {})
+            .await
+            .unwrap();
+        assert_eq!(output.termination, Termination::Stopped);
+        assert!(output.success());
+    }
+
+    #[tokio::test]
+    async fn test_dropped_run_is_cancelled() {
+        let Some(bridge) = bridge().await else { return };
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("interrupted");
+
+        // The caller gives up; the helper must still interrupt the command
+        let script = format!("trap 'touch {}; exit 0' INT; while :; do sleep 0.05; done", marker.display());
+        let args = ["-c", script.as_str()];
+        let options = RunOptions::default();
+        let run = bridge.run_streaming("sh", &args, &options, |_| {});
+        assert!(tokio::time::timeout(Duration::from_millis(300), run).await.is_err());
+
+        let connection = bridge.connection.lock().await.clone().unwrap();
+        let settled = || marker.exists() && connection.pending.lock().unwrap().is_empty();
+        for _ in 0..100 {
+            if settled() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(settled());
+    }
+
+    #[tokio::test]
+    async fn test_crash_detection_and_restart() {
+        let Some(bridge) = bridge().await else { return };
+        let pid = bridge.ping().await.unwrap()["pid"].as_i64().unwrap();
+
+        // A crashed helper is detected and replaced by the next request
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGKILL);
+        }
+        for _ in 0..50 {
+            if !bridge.connection.lock().await.as_ref().unwrap().is_alive() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        // Checking reports the crash without starting a replacement
+        assert_eq!(bridge.check().await, Some(false));
+        assert_eq!(bridge.restarts(), 0);
+
+        let restarted = bridge.ping().await.unwrap()["pid"].as_i64().unwrap();
+        assert_eq!(bridge.check().await, Some(true));
+        assert_ne!(restarted, pid);
+        assert_eq!(bridge.restarts(), 1);
+
+        bridge.restart().await.unwrap();
+        assert_ne!(bridge.ping().await.unwrap()["pid"].as_i64().unwrap(), restarted);
+        assert_eq!(bridge.restarts(), 2);
+    }
+}
@@ -0,0 +1,679 @@
+//! Capture bundles for handing captures to another operator.
+//!
+//! A bundle is a gzipped tar archive:
+//!
+//! ```text
+//! manifest.json            BundleManifest: every file with its SHA-256
+//! captures/<id>.json       CaptureMetadata (tags, description, provenance, ...)
+//! captures/<id>.pcap       capture data, decrypted
+//! configs/<name>.json      configuration presets the captures were taken with
+//! ```
+//!
+//! Import verifies every file against the manifest, capture data against
+//! its recorded provenance hash, and that capture IDs and preset names are
+//! plain file names, before anything is written. A
+//! capture already in the store (same data hash) is skipped; one whose ID
+//! is taken by a different capture gets a new ID. Presets that clash with a
+//! different local preset of the same name are imported under a new name.
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use sysinfo::System;
+use ubertooth_core::error::{Result, UbertoothError};
+
+use crate::capture_store::{CaptureMetadata, CaptureStore};
+use crate::config_store::ConfigMetadata;
+use crate::custody::CustodyConfig;
+
+/// Bundle format version written to the manifest.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Manifest path inside the archive.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// File name extension of bundles.
+pub const BUNDLE_EXTENSION: &str = "tar.gz";
+
+/// A file in the bundle and its hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledFile {
+    /// Path inside the archive
+    pub path: String,
+    pub sha256: String,
+    pub size_bytes: u64,
+}
+
+impl BundledFile {
+    fn of(path: String, data: &[u8]) -> Self {
+        Self {
+            path,
+            sha256: sha256_hex(data),
+            size_bytes: data.len() as u64,
+        }
+    }
+}
+
+/// A capture in the bundle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledCapture {
+    pub capture_id: String,
+    pub metadata: BundledFile,
+    /// Absent for captures whose data was pruned
+    pub data: Option<BundledFile>,
+}
+
+/// A configuration preset in the bundle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundledConfig {
+    pub name: String,
+    pub file: BundledFile,
+}
+
+/// Contents of `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub operator: Option<String>,
+    pub host: Option<String>,
+    pub captures: Vec<BundledCapture>,
+    #[serde(default)]
+    pub configs: Vec<BundledConfig>,
+}
+
+impl BundleManifest {
+    /// Total size of the bundled files.
+    pub fn total_bytes(&self) -> u64 {
+        self.files().map(|f| f.size_bytes).sum()
+    }
+
+    fn files(&self) -> impl Iterator<Item = &BundledFile> {
+        self.captures
+            .iter()
+            .flat_map(|c| std::iter::once(&c.metadata).chain(c.data.as_ref()))
+            .chain(self.configs.iter().map(|c| &c.file))
+    }
+}
+
+/// Write the captures `capture_ids` and the presets they used to a bundle
+/// at `output`. `extra_configs` adds presets by name.
+pub fn export_bundle(
+    store: &CaptureStore,
+    capture_ids: &[String],
+    extra_configs: &[String],
+    output: &Path,
+) -> Result<BundleManifest> {
+    if capture_ids.is_empty() {
+        return Err(UbertoothError::InvalidParameter("No captures to bundle".to_string()));
+    }
+
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut captures = Vec::new();
+    let mut preset_names: BTreeSet<String> = extra_configs.iter().cloned().collect();
+
+    for capture_id in capture_ids {
+        let metadata = store.load_metadata(capture_id)?;
+        if let Some(preset) = &metadata.config_preset {
+            preset_names.insert(preset.clone());
+        }
+
+        let data = match metadata.pruned {
+            Some(_) => None,
+            None => {
+                let data = store.read_data(&metadata)?;
+                let ext = Path::new(&metadata.pcap_path)
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "pcap".to_string());
+                let path = format!("captures/{}.{}", capture_id, ext);
+                let file = BundledFile::of(path.clone(), &data);
+                files.push((path, data));
+                Some(file)
+            }
+        };
+
+        let json = serde_json::to_vec_pretty(&metadata)?;
+        let path = format!("captures/{}.json", capture_id);
+        let metadata_file = BundledFile::of(path.clone(), &json);
+        files.push((path, json));
+
+        captures.push(BundledCapture {
+            capture_id: capture_id.clone(),
+            metadata: metadata_file,
+            data,
+        });
+    }
+
+    let mut configs = Vec::new();
+    for name in preset_names {
+        let preset_path = store.configs_dir().join(format!("{}.json", name));
+        let Ok(data) = fs::read(&preset_path) else {
+            tracing::warn!("Preset '{}' not found, not bundled", name);
+            continue;
+        };
+        let path = format!("configs/{}.json", name);
+        configs.push(BundledConfig {
+            name,
+            file: BundledFile::of(path.clone(), &data),
+        });
+        files.push((path, data));
+    }
+
+    let custody = CustodyConfig::load();
+    let manifest = BundleManifest {
+        version: BUNDLE_VERSION,
+        created_at: Utc::now(),
+        operator: custody.operator,
+        host: System::host_name(),
+        captures,
+        configs,
+    };
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut archive = tar::Builder::new(GzEncoder::new(File::create(output)?, Compression::default()));
+    append(&mut archive, MANIFEST_FILE, &serde_json::to_vec_pretty(&manifest)?)?;
+    for (path, data) in &files {
+        append(&mut archive, path, data)?;
+    }
+    archive.into_inner()?.finish()?;
+
+    let bundle_sha256 = sha256_hex(&fs::read(output)?);
+    for capture in &manifest.captures {
+        store.log_custody(
+            &capture.capture_id,
+            "exported",
+            json!({
+                "format": "bundle",
+                "export_path": output.to_string_lossy(),
+                "sha256": bundle_sha256
+            }),
+        )?;
+    }
+
+    Ok(manifest)
+}
+
+fn append<W: std::io::Write>(archive: &mut tar::Builder<W>, path: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// What happened to one capture on import.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportedCapture {
+    /// ID in the bundle
+    pub original_id: String,
+    /// ID in the receiving store
+    pub capture_id: String,
+    /// "imported", "renamed" (ID collision) or "duplicate" (already present)
+    pub status: String,
+}
+
+/// What happened to one preset on import.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportedConfig {
+    pub name: String,
+    pub imported_as: String,
+    /// "imported", "renamed" or "identical"
+    pub status: String,
+}
+
+/// Outcome of [`import_bundle`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub bundle_sha256: String,
+    pub operator: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub captures: Vec<ImportedCapture>,
+    pub configs: Vec<ImportedConfig>,
+}
+
+/// Check a bundle and register its captures and presets in `store`.
+///
+/// Nothing is written unless every file matches the manifest: all files are
+/// checked and every write is prepared before the first one happens.
+pub fn import_bundle(store: &CaptureStore, bundle: &Path) -> Result<ImportReport> {
+    let raw = fs::read(bundle)?;
+    let bundle_sha256 = sha256_hex(&raw);
+    let (manifest, mut files) = read_bundle(&raw)?;
+
+    // Presets first, so renamed ones can be updated in the capture metadata
+    let configs_dir = store.configs_dir();
+    let mut configs = Vec::new();
+    let mut writes: Vec<PendingWrite> = Vec::new();
+    let mut renamed_presets = BTreeMap::new();
+    let mut planned_presets = BTreeMap::new();
+    for config in &manifest.configs {
+        let data = files.remove(&config.file.path).unwrap_or_default();
+        let (imported, write) = plan_config(&configs_dir, &config.name, data, &mut planned_presets)?;
+        if imported.status == "renamed" {
+            renamed_presets.insert(imported.name.clone(), imported.imported_as.clone());
+        }
+        writes.extend(write);
+        configs.push(imported);
+    }
+
+    // Captures already in the store, by data hash
+    let mut known: BTreeMap<String, String> = store
+        .list_captures()?
+        .into_iter()
+        .filter_map(|c| c.provenance.map(|p| (p.sha256, c.capture_id)))
+        .collect();
+
+    let mut captures = Vec::new();
+    let mut new_captures = Vec::new();
+    let mut taken_ids = BTreeSet::new();
+    for bundled in &manifest.captures {
+        let mut metadata: CaptureMetadata =
+            serde_json::from_slice(files.get(&bundled.metadata.path).map(Vec::as_slice).unwrap_or_default())?;
+        let data = bundled.data.as_ref().and_then(|f| files.remove(&f.path));
+
+        let duplicate_of = match &metadata.provenance {
+            Some(provenance) => known.get(&provenance.sha256).cloned(),
+            None => store
+                .load_metadata(&bundled.capture_id)
+                .ok()
+                .filter(|existing| existing.timestamp == metadata.timestamp && existing.capture_type == metadata.capture_type)
+                .map(|existing| existing.capture_id),
+        };
+        if let Some(existing) = duplicate_of {
+            captures.push(ImportedCapture {
+                original_id: bundled.capture_id.clone(),
+                capture_id: existing,
+                status: "duplicate".to_string(),
+            });
+            continue;
+        }
+
+        let id_free = !taken_ids.contains(&bundled.capture_id) && store.load_metadata(&bundled.capture_id).is_err();
+        let (capture_id, status) = if id_free {
+            (bundled.capture_id.clone(), "imported")
+        } else {
+            (CaptureStore::generate_capture_id("imported"), "renamed")
+        };
+        taken_ids.insert(capture_id.clone());
+        if let Some(provenance) = &metadata.provenance {
+            known.insert(provenance.sha256.clone(), capture_id.clone());
+        }
+
+        metadata.capture_id = capture_id.clone();
+        if let Some(preset) = metadata.config_preset.as_ref().and_then(|p| renamed_presets.get(p)) {
+            metadata.config_preset = Some(preset.clone());
+        }
+        match (data, &bundled.data) {
+            (Some(data), Some(file)) => {
+                let ext = Path::new(&file.path)
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_string())
+                    .filter(|ext| is_safe_name(ext))
+                    .unwrap_or_else(|| "pcap".to_string());
+                let path = store.captures_dir().join(format!("{}.{}", capture_id, ext));
+                metadata.pcap_path = path.to_string_lossy().to_string();
+                metadata.file_size_bytes = data.len() as u64;
+                writes.push((path, data));
+            }
+            _ => metadata.pcap_path = String::new(),
+        }
+
+        new_captures.push((bundled.capture_id.clone(), metadata));
+        captures.push(ImportedCapture {
+            original_id: bundled.capture_id.clone(),
+            capture_id,
+            status: status.to_string(),
+        });
+    }
+
+    for (path, data) in &writes {
+        fs::write(path, data)?;
+    }
+    for (original_id, metadata) in &new_captures {
+        // Provenance comes from the bundle, so the capture stays verifiable
+        store.save_metadata(metadata)?;
+        store.log_custody(
+            &metadata.capture_id,
+            "imported",
+            json!({
+                "bundle": bundle.to_string_lossy(),
+                "bundle_sha256": bundle_sha256,
+                "original_id": original_id,
+                "bundled_by": manifest.operator,
+                "bundled_on": manifest.host
+            }),
+        )?;
+    }
+
+    Ok(ImportReport {
+        bundle_sha256,
+        operator: manifest.operator,
+        created_at: manifest.created_at,
+        captures,
+        configs,
+    })
+}
+
+/// Unpack a bundle into memory and check it against its manifest.
+pub fn read_bundle(raw: &[u8]) -> Result<(BundleManifest, BTreeMap<String, Vec<u8>>)> {
+    let mut files = BTreeMap::new();
+    let mut archive = tar::Archive::new(GzDecoder::new(raw));
+    for entry in archive
+        .entries()
+        .map_err(|e| UbertoothError::ParseError(format!("Not a capture bundle: {}", e)))?
+    {
+        let mut entry = entry?;
+        let path = entry.path()?.to_string_lossy().to_string();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        files.insert(path, data);
+    }
+
+    let manifest: BundleManifest = serde_json::from_slice(
+        files
+            .get(MANIFEST_FILE)
+            .ok_or_else(|| UbertoothError::ParseError(format!("Bundle has no {}", MANIFEST_FILE)))?,
+    )?;
+    if manifest.version > BUNDLE_VERSION {
+        return Err(UbertoothError::ParseError(format!(
+            "Bundle version {} is newer than supported ({})",
+            manifest.version, BUNDLE_VERSION
+        )));
+    }
+
+    let mut problems = Vec::new();
+    for capture in &manifest.captures {
+        if !is_safe_name(&capture.capture_id) {
+            problems.push(format!("capture ID {:?} is not a valid file name", capture.capture_id));
+        }
+    }
+    for config in &manifest.configs {
+        if !is_safe_name(&config.name) {
+            problems.push(format!("preset name {:?} is not a valid file name", config.name));
+        }
+    }
+    for file in manifest.files() {
+        match files.get(&file.path) {
+            None => problems.push(format!("{} missing", file.path)),
+            Some(data) if data.len() as u64 != file.size_bytes || sha256_hex(data) != file.sha256 => {
+                problems.push(format!("{} does not match its hash", file.path))
+            }
+            Some(_) => {}
+        }
+    }
+    for capture in &manifest.captures {
+        let metadata: Option<CaptureMetadata> = files
+            .get(&capture.metadata.path)
+            .and_then(|json| serde_json::from_slice(json).ok());
+        let Some(metadata) = metadata else {
+            problems.push(format!("{} is not capture metadata", capture.metadata.path));
+            continue;
+        };
+        if let (Some(provenance), Some(data)) = (&metadata.provenance, &capture.data) {
+            if provenance.sha256 != data.sha256 {
+                problems.push(format!("{} differs from the hash recorded when it was captured", data.path));
+            }
+        }
+    }
+    if !problems.is_empty() {
+        return Err(UbertoothError::BackendError(format!(
+            "Bundle failed integrity check: {}",
+            problems.join("; ")
+        )));
+    }
+
+    Ok((manifest, files))
+}
+
+/// A file [`import_bundle`] writes once every check has passed.
+type PendingWrite = (PathBuf, Vec<u8>);
+
+/// Decide under which name a bundled preset is imported and what to write.
+///
+/// `planned` holds the presets claimed by earlier entries of the same bundle.
+fn plan_config(
+    configs_dir: &Path,
+    name: &str,
+    data: Vec<u8>,
+    planned: &mut BTreeMap<String, Vec<u8>>,
+) -> Result<(ImportedConfig, Option<PendingWrite>)> {
+    let path = preset_path(configs_dir, name);
+
+    let existing = planned.get(name).cloned().or_else(|| fs::read(&path).ok());
+    let (imported_as, status) = match existing {
+        None => (name.to_string(), "imported"),
+        Some(existing) if existing == data => (name.to_string(), "identical"),
+        Some(_) => {
+            let free = (1..)
+                .map(|n| format!("{}-imported{}", name, if n == 1 { String::new() } else { n.to_string() }))
+                .find(|candidate| !planned.contains_key(candidate) && !preset_path(configs_dir, candidate).exists())
+                .unwrap_or_default();
+            (free, "renamed")
+        }
+    };
+
+    let write = match status {
+        "imported" => Some((path, data)),
+        "renamed" => {
+            let mut config: ConfigMetadata = serde_json::from_slice(&data)?;
+            config.name = imported_as.clone();
+            Some((preset_path(configs_dir, &imported_as), serde_json::to_vec_pretty(&config)?))
+        }
+        _ => None,
+    };
+    if let Some((_, contents)) = &write {
+        planned.insert(imported_as.clone(), contents.clone());
+    }
+
+    Ok((
+        ImportedConfig {
+            name: name.to_string(),
+            imported_as,
+            status: status.to_string(),
+        },
+        write,
+    ))
+}
+
+/// Capture IDs and preset names from a bundle become file names; reject
+/// anything that could point outside the store directories.
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.contains("..")
+        && !name.contains(['/', '\\', '\0', std::path::MAIN_SEPARATOR])
+}
+
+fn preset_path(configs_dir: &Path, name: &str) -> PathBuf {
+    configs_dir.join(format!("{}.json", name))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::custody::Provenance;
+
+    fn capture(store: &CaptureStore, id: &str, data: &[u8]) -> CaptureMetadata {
+        let path = store.captures_dir().join(format!("{}.pcap", id));
+        fs::write(&path, data).unwrap();
+        let metadata = CaptureMetadata {
+            capture_id: id.to_string(),
+            timestamp: Utc::now(),
+            capture_type: "btle_sniff".to_string(),
+            packet_count: 1,
+            duration_sec: Some(10),
+            file_size_bytes: data.len() as u64,
+            pcap_path: path.to_string_lossy().to_string(),
+            tags: vec!["lobby".to_string()],
+            description: "front desk".to_string(),
+            channels: vec![37],
+            engagement: Some("ACME-2026-03".to_string()),
+            pruned: None,
+            provenance: Some(Provenance::record(&path, &CustodyConfig::default()).unwrap()),
+            config_preset: Some("stealth".to_string()),
+            annotations: Vec::new(),
+        };
+        store.save_metadata(&metadata).unwrap();
+        metadata
+    }
+
+    fn preset(store: &CaptureStore, name: &str, channel: u8) {
+        let config = json!({
+            "name": name,
+            "description": "",
+            "created": "2026-03-01T00:00:00Z",
+            "settings": { "channel": channel }
+        });
+        fs::write(store.configs_dir().join(format!("{}.json", name)), config.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_bundle_roundtrip_and_collisions() {
+        let sender_dir = tempfile::tempdir().unwrap();
+        let sender = CaptureStore::with_root(sender_dir.path().to_path_buf()).unwrap();
+        capture(&sender, "cap-a", b"first capture");
+        capture(&sender, "cap-b", b"second capture");
+        preset(&sender, "stealth", 37);
+
+        let bundle = sender_dir.path().join("handover.tar.gz");
+        let manifest = export_bundle(&sender, &["cap-a".to_string(), "cap-b".to_string()], &[], &bundle).unwrap();
+        assert_eq!(manifest.captures.len(), 2);
+        assert_eq!(manifest.configs.len(), 1);
+
+        // The receiver already has a different cap-b and another "stealth" preset
+        let receiver_dir = tempfile::tempdir().unwrap();
+        let receiver = CaptureStore::with_root(receiver_dir.path().to_path_buf()).unwrap();
+        capture(&receiver, "cap-b", b"unrelated capture");
+        preset(&receiver, "stealth", 38);
+
+        let report = import_bundle(&receiver, &bundle).unwrap();
+        let status: Vec<&str> = report.captures.iter().map(|c| c.status.as_str()).collect();
+        assert_eq!(status, vec!["imported", "renamed"]);
+        assert_eq!(report.configs[0].imported_as, "stealth-imported");
+
+        let renamed = receiver.load_metadata(&report.captures[1].capture_id).unwrap();
+        assert_eq!(receiver.read_data(&renamed).unwrap(), b"second capture");
+        assert_eq!(renamed.tags, vec!["lobby".to_string()]);
+        assert_eq!(renamed.config_preset.as_deref(), Some("stealth-imported"));
+
+        // Importing again only finds duplicates
+        let again = import_bundle(&receiver, &bundle).unwrap();
+        assert!(again.captures.iter().all(|c| c.status == "duplicate"));
+    }
+
+    #[test]
+    fn test_tampered_bundle_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CaptureStore::with_root(dir.path().to_path_buf()).unwrap();
+        capture(&store, "cap-a", b"original");
+
+        // Capture data changed after it was hashed
+        fs::write(store.captures_dir().join("cap-a.pcap"), b"modified").unwrap();
+        let bundle = dir.path().join("b.tar.gz");
+        export_bundle(&store, &["cap-a".to_string()], &[], &bundle).unwrap();
+        assert!(import_bundle(&store, &bundle).is_err());
+
+        assert!(read_bundle(b"not a bundle").is_err());
+    }
+
+    fn write_bundle(path: &Path, manifest: &BundleManifest, files: &[(&str, &[u8])]) {
+        let mut archive = tar::Builder::new(GzEncoder::new(File::create(path).unwrap(), Compression::default()));
+        append(&mut archive, MANIFEST_FILE, &serde_json::to_vec(manifest).unwrap()).unwrap();
+        for (name, data) in files {
+            append(&mut archive, name, data).unwrap();
+        }
+        archive.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn manifest(captures: Vec<BundledCapture>, configs: Vec<BundledConfig>) -> BundleManifest {
+        BundleManifest {
+            version: BUNDLE_VERSION,
+            created_at: Utc::now(),
+            operator: None,
+            host: None,
+            captures,
+            configs,
+        }
+    }
+
+    #[test]
+    fn test_path_traversal_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CaptureStore::with_root(dir.path().join("store")).unwrap();
+        let metadata = serde_json::to_vec(&capture(&store, "cap-a", b"data")).unwrap();
+        let preset = br#"{"name":"evil","description":"","created":"2026-03-01T00:00:00Z","settings":{}}"#;
+
+        let bundle = dir.path().join("evil.tar.gz");
+        write_bundle(
+            &bundle,
+            &manifest(
+                vec![BundledCapture {
+                    capture_id: "../../escape".to_string(),
+                    metadata: BundledFile::of("captures/cap-a.json".to_string(), &metadata),
+                    data: None,
+                }],
+                vec![BundledConfig {
+                    name: "../evil".to_string(),
+                    file: BundledFile::of("configs/evil.json".to_string(), preset),
+                }],
+            ),
+            &[("captures/cap-a.json", &metadata), ("configs/evil.json", preset)],
+        );
+
+        let err = import_bundle(&store, &bundle).unwrap_err().to_string();
+        assert!(err.contains("../../escape"));
+        assert!(err.contains("../evil"));
+        assert!(!dir.path().join("escape.json").exists());
+        assert!(!dir.path().join("store").join("evil.json").exists());
+        assert!(!is_safe_name("a\\b"));
+        assert!(is_safe_name("btle_20260301_120000"));
+    }
+
+    #[test]
+    fn test_failed_import_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CaptureStore::with_root(dir.path().to_path_buf()).unwrap();
+        preset(&store, "stealth", 38);
+
+        // "stealth" clashes and must be rewritten, but is not a preset
+        let alpha = br#"{"name":"alpha","description":"","created":"2026-03-01T00:00:00Z","settings":{}}"#;
+        let broken = b"not a preset";
+        let bundle = dir.path().join("broken.tar.gz");
+        write_bundle(
+            &bundle,
+            &manifest(
+                Vec::new(),
+                vec![
+                    BundledConfig {
+                        name: "alpha".to_string(),
+                        file: BundledFile::of("configs/alpha.json".to_string(), alpha),
+                    },
+                    BundledConfig {
+                        name: "stealth".to_string(),
+                        file: BundledFile::of("configs/stealth.json".to_string(), broken),
+                    },
+                ],
+            ),
+            &[("configs/alpha.json", alpha), ("configs/stealth.json", broken)],
+        );
+
+        assert!(import_bundle(&store, &bundle).is_err());
+        assert!(!store.configs_dir().join("alpha.json").exists());
+    }
+}
@@ -0,0 +1,319 @@
+//! Noise-floor estimation, squelch recommendation and RSSI calibration.
+//!
+//! Shared by both backends: each one records a short spectrum sweep with
+//! its own radio path, then hands the recording to [`calibrate`], which
+//! estimates the noise floor, recommends a squelch level and stores the
+//! per-dongle calibration in the [`ConfigStore`]. Tool results pass
+//! through [`apply_to_result`] so reported RSSI is on the calibrated scale.
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_protocol::spectrum::SpectrumRecording;
+
+use crate::config_store::{ConfigStore, RssiCalibration};
+
+/// Squelch margin above the noise floor when none is given.
+pub const DEFAULT_SQUELCH_MARGIN_DB: i8 = 10;
+
+/// Sweep duration when none is given.
+pub const DEFAULT_CALIBRATION_SEC: u64 = 5;
+
+/// Percentile of a channel's samples taken as its noise floor.
+pub const NOISE_FLOOR_PERCENTILE: f64 = 20.0;
+
+/// Per-item RSSI fields shifted by [`apply_to_result`].
+const RSSI_FIELDS: &[&str] = &["rssi", "rssi_avg", "rssi_min", "rssi_max"];
+
+/// Calibration parameters from a `squelch_calibrate` call.
+#[derive(Debug, Clone)]
+pub struct CalibrationRequest {
+    pub duration_sec: u64,
+    pub low_freq_mhz: u16,
+    pub high_freq_mhz: u16,
+    pub margin_db: i8,
+    pub apply: bool,
+    /// Align the offset with another dongle's calibration
+    pub reference_serial: Option<String>,
+    /// Align the offset with an absolute noise floor (dBm)
+    pub reference_noise_floor_dbm: Option<i8>,
+}
+
+impl CalibrationRequest {
+    /// Parse tool parameters.
+    pub fn from_params(params: &Value) -> Result<Self> {
+        let low_freq_mhz = params.get("low_freq").and_then(|v| v.as_u64()).unwrap_or(2402) as u16;
+        let high_freq_mhz = params.get("high_freq").and_then(|v| v.as_u64()).unwrap_or(2480) as u16;
+        if low_freq_mhz > high_freq_mhz {
+            return Err(UbertoothError::InvalidParameter(
+                "low_freq must not be above high_freq".to_string(),
+            ));
+        }
+
+        let margin_db = params
+            .get("margin_db")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(DEFAULT_SQUELCH_MARGIN_DB as i64);
+        if !(0..=40).contains(&margin_db) {
+            return Err(UbertoothError::InvalidParameter(
+                "margin_db must be between 0 and 40 dB".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            duration_sec: params
+                .get("duration_sec")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(DEFAULT_CALIBRATION_SEC),
+            low_freq_mhz,
+            high_freq_mhz,
+            margin_db: margin_db as i8,
+            apply: params.get("apply").and_then(|v| v.as_bool()).unwrap_or(true),
+            reference_serial: params
+                .get("reference_serial")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            reference_noise_floor_dbm: params
+                .get("reference_noise_floor_dbm")
+                .and_then(|v| v.as_i64())
+                .map(|v| v.clamp(-128, 0) as i8),
+        })
+    }
+}
+
+/// Noise floor estimate of a recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseFloorEstimate {
+    pub channel_noise_floor_dbm: Vec<Option<i8>>,
+    /// Median of the per-channel floors
+    pub noise_floor_dbm: i8,
+    pub recommended_squelch_dbm: i8,
+}
+
+/// Estimate the noise floor of a recording and the squelch level
+/// `margin_db` above it.
+pub fn estimate(recording: &SpectrumRecording, margin_db: i8) -> Result<NoiseFloorEstimate> {
+    let channel_noise_floor_dbm = recording.noise_floor(NOISE_FLOOR_PERCENTILE);
+
+    let mut floors: Vec<i8> = channel_noise_floor_dbm.iter().flatten().copied().collect();
+    if floors.is_empty() {
+        return Err(UbertoothError::BackendError(
+            "No spectrum samples captured; cannot estimate the noise floor".to_string(),
+        ));
+    }
+    floors.sort_unstable();
+    let noise_floor_dbm = floors[floors.len() / 2];
+
+    Ok(NoiseFloorEstimate {
+        channel_noise_floor_dbm,
+        noise_floor_dbm,
+        recommended_squelch_dbm: (noise_floor_dbm as i16 + margin_db as i16).clamp(-128, 0) as i8,
+    })
+}
+
+/// Estimate the noise floor of `recording`, work out the squelch level and
+/// RSSI offset, and store the calibration for `serial`.
+pub fn calibrate(
+    store: &ConfigStore,
+    serial: &str,
+    recording: &SpectrumRecording,
+    request: &CalibrationRequest,
+) -> Result<RssiCalibration> {
+    if serial.trim().is_empty() {
+        return Err(UbertoothError::BackendError(
+            "Dongle serial number is unknown; calibration is stored per dongle and cannot be saved".to_string(),
+        ));
+    }
+
+    let NoiseFloorEstimate {
+        channel_noise_floor_dbm,
+        noise_floor_dbm,
+        recommended_squelch_dbm,
+    } = estimate(recording, request.margin_db)?;
+
+    let (offset_db, reference) = match (&request.reference_serial, request.reference_noise_floor_dbm) {
+        (Some(reference_serial), _) if reference_serial != serial => {
+            let reference = store.load_calibration(reference_serial)?.ok_or_else(|| {
+                UbertoothError::InvalidParameter(format!(
+                    "Reference dongle '{}' has not been calibrated",
+                    reference_serial
+                ))
+            })?;
+            (
+                reference.corrected_noise_floor() - noise_floor_dbm as i16,
+                format!("serial:{}", reference_serial),
+            )
+        }
+        (_, Some(absolute)) => (absolute as i16 - noise_floor_dbm as i16, "absolute".to_string()),
+        _ => (0, "none".to_string()),
+    };
+
+    let calibration = RssiCalibration {
+        serial: serial.to_string(),
+        calibrated: Utc::now(),
+        low_freq_mhz: recording.header.low_freq_mhz,
+        high_freq_mhz: recording.header.high_freq_mhz,
+        sweep_count: recording.sweeps.len(),
+        noise_floor_dbm,
+        channel_noise_floor_dbm,
+        squelch_margin_db: request.margin_db,
+        recommended_squelch_dbm,
+        offset_db,
+        reference,
+    };
+    store.save_calibration(&calibration)?;
+
+    Ok(calibration)
+}
+
+/// Stored calibration of the dongle with `serial`, if any.
+///
+/// Readings from unidentified or uncalibrated dongles are reported as-is.
+pub fn for_device(serial: Option<&str>) -> Option<RssiCalibration> {
+    let serial = serial.filter(|s| !s.trim().is_empty())?;
+    match ConfigStore::new().and_then(|store| store.load_calibration(serial)) {
+        Ok(calibration) => calibration,
+        Err(e) => {
+            tracing::warn!("Could not load RSSI calibration for {}: {}", serial, e);
+            None
+        }
+    }
+}
+
+/// Shift the RSSI fields of every item under `path` (a JSON pointer to an
+/// array) by the calibration offset, and record the offset in the result.
+pub fn apply_to_result(result: &mut Value, paths: &[&str], calibration: Option<&RssiCalibration>) {
+    if let Some(calibration) = calibration {
+        for path in paths {
+            let Some(items) = result.pointer_mut(path).and_then(Value::as_array_mut) else {
+                continue;
+            };
+            for item in items.iter_mut().filter_map(Value::as_object_mut) {
+                for field in RSSI_FIELDS {
+                    if let Some(rssi) = item.get(*field).and_then(Value::as_i64) {
+                        item.insert(field.to_string(), json!(calibration.corrected(rssi as i16)));
+                    }
+                }
+            }
+        }
+    }
+
+    result["rssi_calibration"] = match calibration {
+        Some(c) => json!({ "serial": c.serial, "offset_db": c.offset_db, "reference": c.reference }),
+        None => Value::Null,
+    };
+}
+
+
+pub fn calibration_result(calibration: &RssiCalibration, applied_squelch_dbm: Option<i64>) -> Value {
+    let channels: Vec<Value> = calibration
+        .channel_noise_floor_dbm
+        .iter()
+        .enumerate()
+        .map(|(idx, floor)| json!({
+            "frequency_mhz": calibration.low_freq_mhz + idx as u16,
+            "noise_floor_dbm": floor
+        }))
+        .collect();
+
+    let message = match applied_squelch_dbm {
+        Some(level) => format!(
+            "Noise floor {} dBm; squelch set to {} dBm ({} dB margin)",
+            calibration.noise_floor_dbm, level, calibration.squelch_margin_db
+        ),
+        None => format!(
+            "Noise floor {} dBm; recommended squelch {} dBm (not applied)",
+            calibration.noise_floor_dbm, calibration.recommended_squelch_dbm
+        ),
+    };
+
+    json!({
+        "success": true,
+        "serial": calibration.serial,
+        "sweep_count": calibration.sweep_count,
+        "noise_floor_dbm": calibration.noise_floor_dbm,
+        "channels": channels,
+        "margin_db": calibration.squelch_margin_db,
+        "recommended_squelch_dbm": calibration.recommended_squelch_dbm,
+        "applied": applied_squelch_dbm.is_some(),
+        "squelch_level": applied_squelch_dbm,
+        "rssi_offset_db": calibration.offset_db,
+        "reference": calibration.reference,
+        "calibrated": calibration.calibrated.to_rfc3339(),
+        "message": message
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_noise_floor() {
+        let mut recording = SpectrumRecording::new(2402, 2404, 0, "test");
+        for i in 0..10 {
+            // Channel 2403 carries traffic in every other sweep
+            let busy = if i % 2 == 0 { -40 } else { -95 };
+            recording.push_sweep(i * 100, vec![-98, busy, -92]).unwrap();
+        }
+
+        let estimate = estimate(&recording, 10).unwrap();
+        assert_eq!(estimate.channel_noise_floor_dbm, vec![Some(-98), Some(-95), Some(-92)]);
+        assert_eq!(estimate.noise_floor_dbm, -95);
+        assert_eq!(estimate.recommended_squelch_dbm, -85);
+
+        let empty = SpectrumRecording::new(2402, 2404, 0, "test");
+        assert!(super::estimate(&empty, 10).is_err());
+    }
+
+    #[test]
+    fn test_calibration_request() {
+        let request = CalibrationRequest::from_params(&json!({
+            "margin_db": 6,
+            "apply": false,
+            "reference_noise_floor_dbm": -100
+        }))
+        .unwrap();
+        assert_eq!(request.margin_db, 6);
+        assert!(!request.apply);
+        assert_eq!(request.duration_sec, DEFAULT_CALIBRATION_SEC);
+        assert_eq!(request.reference_noise_floor_dbm, Some(-100));
+
+        assert!(CalibrationRequest::from_params(&json!({ "margin_db": 80 })).is_err());
+        assert!(CalibrationRequest::from_params(&json!({ "low_freq": 2480, "high_freq": 2402 })).is_err());
+    }
+
+    #[test]
+    fn test_apply_to_result() {
+        let calibration = RssiCalibration {
+            serial: "a1b2".to_string(),
+            calibrated: Utc::now(),
+            low_freq_mhz: 2402,
+            high_freq_mhz: 2480,
+            sweep_count: 10,
+            noise_floor_dbm: -97,
+            channel_noise_floor_dbm: Vec::new(),
+            squelch_margin_db: 10,
+            recommended_squelch_dbm: -87,
+            offset_db: -4,
+            reference: "absolute".to_string(),
+        };
+
+        let mut result = json!({
+            "devices_found": [{ "rssi_avg": -60, "packet_count": 3 }, { "rssi_avg": null }],
+            "analysis": { "devices": [{ "rssi": -70 }] }
+        });
+        apply_to_result(&mut result, &["/devices_found", "/analysis/devices"], Some(&calibration));
+
+        assert_eq!(result["devices_found"][0]["rssi_avg"], -64);
+        assert_eq!(result["devices_found"][0]["packet_count"], 3);
+        assert!(result["devices_found"][1]["rssi_avg"].is_null());
+        assert_eq!(result["analysis"]["devices"][0]["rssi"], -74);
+        assert_eq!(result["rssi_calibration"]["offset_db"], -4);
+
+        let mut uncalibrated = json!({ "devices_found": [{ "rssi_avg": -60 }] });
+        apply_to_result(&mut uncalibrated, &["/devices_found"], None);
+        assert_eq!(uncalibrated["devices_found"][0]["rssi_avg"], -60);
+        assert!(uncalibrated["rssi_calibration"].is_null());
+    }
+}
@@ -0,0 +1,588 @@
+//! Persistent, searchable index of stored captures.
+//!
+//! The catalog lives in `<storage root>/catalog.json` and holds one entry per
+//! capture: type, tags, time range, duration, channels, engagement and the
+//! devices (MAC and name) seen in it. [`CaptureCatalog::refresh`] only
+//! re-reads captures whose metadata or data file changed since the last
+//! refresh, so listing and searching no longer deserialise every metadata
+//! file and re-parse every PCAP on each call.
+//!
+//! Queries can be given as structured fields or as a search string:
+//!
+//! ```text
+//! mac:AA:BB:CC tag:lobby date:2026-03 type:btle_sniff channel:37
+//! engagement:acme name:keyboard after:2026-03-01 before:2026-03-15 beacon
+//! ```
+//!
+//! Bare words match the capture ID, description and tags; bare MAC addresses
+//! (or OUI prefixes) match devices.
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use ubertooth_core::error::{Result, UbertoothError};
+
+use crate::capture_store::{CaptureMetadata, CaptureStore, PruneRecord};
+use crate::encryption::{self, StoreCipher};
+
+/// Catalog file name under the storage root.
+pub const CATALOG_FILE: &str = "catalog.json";
+
+/// Bumped when the entry layout changes; older catalogs are rebuilt.
+pub const CATALOG_VERSION: u32 = 2;
+
+/// A device seen in a capture.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct DeviceSeen {
+    pub mac: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Size and modification time of a file, used to detect changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub modified_ms: i64,
+    pub size: u64,
+}
+
+impl FileStamp {
+    /// Stamp of a file, or `None` if it does not exist.
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        let modified_ms = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        Some(Self { modified_ms, size: meta.len() })
+    }
+}
+
+/// Indexed view of one capture.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub capture_id: String,
+    pub capture_type: String,
+    pub tags: Vec<String>,
+    pub description: String,
+    pub engagement: Option<String>,
+    pub channels: Vec<u8>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration_sec: Option<u64>,
+    pub packet_count: usize,
+    pub file_size_bytes: u64,
+    pub pcap_path: String,
+    pub devices: Vec<DeviceSeen>,
+    /// Set when retention removed the capture data
+    pub pruned: Option<PruneRecord>,
+    pub metadata_stamp: Option<FileStamp>,
+    pub data_stamp: Option<FileStamp>,
+}
+
+impl CatalogEntry {
+    /// Build an entry from capture metadata and the devices found in its data.
+    pub fn new(metadata: &CaptureMetadata, devices: Vec<DeviceSeen>, metadata_path: &Path) -> Self {
+        let end = metadata.timestamp + Duration::seconds(metadata.duration_sec.unwrap_or(0) as i64);
+
+        let mut devices = devices;
+        devices.extend(tag_devices(&metadata.tags));
+        devices.sort();
+        devices.dedup_by(|a, b| {
+            if a.mac != b.mac {
+                return false;
+            }
+            if b.name.is_none() {
+                b.name = a.name.take();
+            }
+            true
+        });
+
+        Self {
+            capture_id: metadata.capture_id.clone(),
+            capture_type: metadata.capture_type.clone(),
+            tags: metadata.tags.clone(),
+            description: metadata.description.clone(),
+            engagement: metadata.engagement.clone(),
+            channels: metadata.channels.clone(),
+            start: metadata.timestamp,
+            end,
+            duration_sec: metadata.duration_sec,
+            packet_count: metadata.packet_count,
+            file_size_bytes: metadata.file_size_bytes,
+            pcap_path: metadata.pcap_path.clone(),
+            devices,
+            pruned: metadata.pruned.clone(),
+            metadata_stamp: FileStamp::of(metadata_path),
+            data_stamp: FileStamp::of(Path::new(&metadata.pcap_path)),
+        }
+    }
+
+    /// Whether the capture files changed since this entry was built.
+    fn is_stale(&self, metadata_path: &Path) -> bool {
+        self.metadata_stamp != FileStamp::of(metadata_path)
+            || self.data_stamp != FileStamp::of(Path::new(&self.pcap_path))
+    }
+}
+
+/// Counts from a catalog refresh.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RefreshStats {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CatalogFile {
+    version: u32,
+    entries: BTreeMap<String, CatalogEntry>,
+}
+
+/// Persistent capture index.
+pub struct CaptureCatalog {
+    path: PathBuf,
+    cipher: Option<StoreCipher>,
+    entries: BTreeMap<String, CatalogEntry>,
+}
+
+impl CaptureCatalog {
+    /// Open the catalog of a capture store.
+    pub fn open(store: &CaptureStore) -> Result<Self> {
+        Self::open_with(store.base_path().join(CATALOG_FILE), store.cipher().cloned())
+    }
+
+    /// Open a catalog file; a missing, unreadable or outdated catalog starts empty.
+    pub fn open_at(path: PathBuf) -> Result<Self> {
+        Self::open_with(path, None)
+    }
+
+    /// Open a catalog file that is encrypted with `cipher`, if given.
+    pub fn open_with(path: PathBuf, cipher: Option<StoreCipher>) -> Result<Self> {
+        let entries = encryption::read_file(cipher.as_ref(), &path)
+            .ok()
+            .and_then(|json| serde_json::from_slice::<CatalogFile>(&json).ok())
+            .filter(|file| file.version == CATALOG_VERSION)
+            .map(|file| file.entries)
+            .unwrap_or_default();
+
+        Ok(Self { path, cipher, entries })
+    }
+
+    /// Number of indexed captures.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the catalog is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up one capture.
+    pub fn get(&self, capture_id: &str) -> Option<&CatalogEntry> {
+        self.entries.get(capture_id)
+    }
+
+    /// Drop every entry so the next refresh re-indexes all captures.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Bring the catalog in line with the metadata files in `captures_dir`.
+    ///
+    /// Only new or changed captures are re-indexed; `devices` is called for
+    /// each of them to extract the devices seen in the capture data. The
+    /// catalog is written back when anything changed.
+    pub fn refresh<F>(&mut self, captures_dir: &Path, devices: F) -> Result<RefreshStats>
+    where
+        F: Fn(&CaptureMetadata) -> Vec<DeviceSeen>,
+    {
+        let mut stats = RefreshStats::default();
+        let mut seen = BTreeSet::new();
+
+        for entry in fs::read_dir(captures_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(capture_id) = path.file_stem().and_then(|s| s.to_str()).map(str::to_string) else {
+                continue;
+            };
+
+            if let Some(existing) = self.entries.get(&capture_id) {
+                if !existing.is_stale(&path) {
+                    stats.unchanged += 1;
+                    seen.insert(capture_id);
+                    continue;
+                }
+            }
+
+            let Some(metadata) = encryption::read_file(self.cipher.as_ref(), &path)
+                .ok()
+                .and_then(|json| serde_json::from_slice::<CaptureMetadata>(&json).ok())
+            else {
+                continue;
+            };
+
+            let entry = CatalogEntry::new(&metadata, devices(&metadata), &path);
+            match self.entries.insert(capture_id.clone(), entry) {
+                Some(_) => stats.updated += 1,
+                None => stats.added += 1,
+            }
+            seen.insert(capture_id);
+        }
+
+        let before = self.entries.len();
+        self.entries.retain(|id, _| seen.contains(id));
+        stats.removed = before - self.entries.len();
+
+        if stats.added + stats.updated + stats.removed > 0 {
+            self.save()?;
+        }
+
+        Ok(stats)
+    }
+
+    /// Write the catalog to disk.
+    pub fn save(&self) -> Result<()> {
+        let file = CatalogFile {
+            version: CATALOG_VERSION,
+            entries: self.entries.clone(),
+        };
+        let json = serde_json::to_string(&file)?;
+        let tmp = self.path.with_extension("json.tmp");
+        match &self.cipher {
+            // Sealed under the catalog's own name, not the temp file's
+            Some(cipher) => fs::write(&tmp, cipher.encrypt(json.as_bytes(), &encryption::aad_name(&self.path))?)?,
+            None => fs::write(&tmp, json)?,
+        }
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Captures matching `query`, newest first.
+    pub fn query(&self, query: &CatalogQuery) -> Vec<&CatalogEntry> {
+        let mut matches: Vec<&CatalogEntry> = self.entries.values().filter(|e| query.matches(e)).collect();
+        matches.sort_by_key(|e| std::cmp::Reverse(e.start));
+        matches
+    }
+}
+
+/// Catalog search criteria; every given criterion must match.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CatalogQuery {
+    pub capture_type: Option<String>,
+    /// Tags that must all be present
+    pub tags: Vec<String>,
+    /// Device MAC address or prefix (e.g. an OUI)
+    pub macs: Vec<String>,
+    /// Substring of a device name
+    pub device_name: Option<String>,
+    pub channel: Option<u8>,
+    pub engagement: Option<String>,
+    /// Capture must overlap `[since, until)`
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub min_duration_sec: Option<u64>,
+    pub max_duration_sec: Option<u64>,
+    /// Words matched against the capture ID, description and tags
+    pub text: Vec<String>,
+    /// Also list captures whose data was pruned by retention
+    pub include_pruned: bool,
+}
+
+impl CatalogQuery {
+    /// Parse a search string such as `mac:AA:BB:CC tag:lobby date:2026-03`.
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut parsed = Self::default();
+        for token in query.split_whitespace() {
+            parsed.apply_token(token)?;
+        }
+        Ok(parsed)
+    }
+
+    /// Build a query from tool parameters: structured fields plus an optional
+    /// `query` search string.
+    pub fn from_params(params: &Value) -> Result<Self> {
+        let mut query = match params.get("query").and_then(|v| v.as_str()) {
+            Some(q) => Self::parse(q)?,
+            None => Self::default(),
+        };
+
+        let str_param = |key: &str| params.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+        if let Some(capture_type) = str_param("filter_type") {
+            query.capture_type = Some(capture_type);
+        }
+        if let Some(tag) = str_param("tag") {
+            query.tags.push(tag);
+        }
+        if let Some(tags) = params.get("tags").and_then(|v| v.as_array()) {
+            query.tags.extend(tags.iter().filter_map(|t| t.as_str()).map(str::to_string));
+        }
+        if let Some(mac) = str_param("mac") {
+            query.macs.push(normalize_mac(&mac));
+        }
+        if let Some(name) = str_param("device_name") {
+            query.device_name = Some(name.to_lowercase());
+        }
+        if let Some(channel) = params.get("channel").and_then(|v| v.as_u64()) {
+            query.channel = Some(channel as u8);
+        }
+        if let Some(engagement) = str_param("engagement") {
+            query.engagement = Some(engagement);
+        }
+        if let Some(since) = str_param("since") {
+            query.since = Some(parse_date_range(&since)?.0);
+        }
+        if let Some(until) = str_param("until") {
+            query.until = Some(parse_date_range(&until)?.1);
+        }
+        if let Some(min) = params.get("min_duration_sec").and_then(|v| v.as_u64()) {
+            query.min_duration_sec = Some(min);
+        }
+        if let Some(max) = params.get("max_duration_sec").and_then(|v| v.as_u64()) {
+            query.max_duration_sec = Some(max);
+        }
+        if let Some(include) = params.get("include_pruned").and_then(|v| v.as_bool()) {
+            query.include_pruned = include;
+        }
+
+        Ok(query)
+    }
+
+    fn apply_token(&mut self, token: &str) -> Result<()> {
+        if looks_like_mac(token) {
+            self.macs.push(normalize_mac(token));
+            return Ok(());
+        }
+
+        let Some((key, value)) = token.split_once(':') else {
+            self.text.push(token.to_lowercase());
+            return Ok(());
+        };
+
+        match key.to_lowercase().as_str() {
+            "type" => self.capture_type = Some(value.to_string()),
+            "tag" => self.tags.push(value.to_string()),
+            "mac" | "device" => self.macs.push(normalize_mac(value)),
+            "name" => self.device_name = Some(value.to_lowercase()),
+            "engagement" => self.engagement = Some(value.to_string()),
+            "channel" | "ch" => {
+                self.channel = Some(value.parse().map_err(|_| {
+                    UbertoothError::InvalidParameter(format!("Invalid channel in query: '{}'", value))
+                })?)
+            }
+            "date" | "in" => {
+                let (start, end) = parse_date_range(value)?;
+                self.since = Some(start);
+                self.until = Some(end);
+            }
+            "after" | "since" => self.since = Some(parse_date_range(value)?.0),
+            "before" => self.until = Some(parse_date_range(value)?.0),
+            "until" => self.until = Some(parse_date_range(value)?.1),
+            _ => self.text.push(token.to_lowercase()),
+        }
+        Ok(())
+    }
+
+    /// Whether a catalog entry satisfies every criterion.
+    pub fn matches(&self, entry: &CatalogEntry) -> bool {
+        if entry.pruned.is_some() && !self.include_pruned {
+            return false;
+        }
+        if self.capture_type.as_ref().is_some_and(|t| *t != entry.capture_type) {
+            return false;
+        }
+        if !self.tags.iter().all(|tag| entry.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))) {
+            return false;
+        }
+        if !self.macs.iter().all(|mac| entry.devices.iter().any(|d| d.mac.starts_with(mac.as_str()))) {
+            return false;
+        }
+        if let Some(name) = &self.device_name {
+            let found = entry
+                .devices
+                .iter()
+                .filter_map(|d| d.name.as_deref())
+                .any(|n| n.to_lowercase().contains(name));
+            if !found {
+                return false;
+            }
+        }
+        if self.channel.is_some_and(|ch| !entry.channels.contains(&ch)) {
+            return false;
+        }
+        if let Some(engagement) = &self.engagement {
+            if !entry.engagement.as_ref().is_some_and(|e| e.eq_ignore_ascii_case(engagement)) {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| entry.end < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| entry.start >= until) {
+            return false;
+        }
+        let duration = entry.duration_sec.unwrap_or(0);
+        if self.min_duration_sec.is_some_and(|min| duration < min)
+            || self.max_duration_sec.is_some_and(|max| duration > max)
+        {
+            return false;
+        }
+
+        self.text.iter().all(|word| {
+            entry.capture_id.to_lowercase().contains(word)
+                || entry.description.to_lowercase().contains(word)
+                || entry.tags.iter().any(|t| t.to_lowercase().contains(word))
+        })
+    }
+}
+
+/// Devices named in tags such as `mac:AA:BB:..` or `bd_addr:AA:BB:..`.
+fn tag_devices(tags: &[String]) -> Vec<DeviceSeen> {
+    tags.iter()
+        .filter_map(|tag| tag.split_once(':'))
+        .filter(|(key, _)| matches!(*key, "mac" | "bd_addr" | "device"))
+        .filter(|(_, value)| looks_like_mac(value))
+        .map(|(_, value)| DeviceSeen { mac: normalize_mac(value), name: None })
+        .collect()
+}
+
+/// At least three colon- or dash-separated hex octets.
+fn looks_like_mac(s: &str) -> bool {
+    let octets: Vec<&str> = s.trim_end_matches([':', '-']).split([':', '-']).collect();
+    (3..=6).contains(&octets.len())
+        && octets.iter().all(|o| o.len() == 2 && o.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn normalize_mac(mac: &str) -> String {
+    mac.trim_end_matches(['.', '*']).replace('-', ":").to_uppercase()
+}
+
+/// Parse `YYYY`, `YYYY-MM`, `YYYY-MM-DD` or an RFC 3339 timestamp into the
+/// `[start, end)` range it covers.
+pub fn parse_date_range(value: &str) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let invalid = || UbertoothError::InvalidParameter(format!("Invalid date '{}': use YYYY, YYYY-MM, YYYY-MM-DD or RFC 3339", value));
+
+    if let Ok(ts) = DateTime::parse_from_rfc3339(value) {
+        let ts = ts.with_timezone(&Utc);
+        return Ok((ts, ts));
+    }
+
+    let day = |y: i32, m: u32, d: u32| {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|dt| Utc.from_utc_datetime(&dt))
+    };
+
+    let parts: Vec<&str> = value.split('-').collect();
+    let nums: Vec<u32> = parts.iter().map(|p| p.parse().map_err(|_| invalid())).collect::<Result<_>>()?;
+    let range = match nums.as_slice() {
+        [y] => day(*y as i32, 1, 1).zip(day(*y as i32 + 1, 1, 1)),
+        [y, m] => {
+            let (ny, nm) = if *m == 12 { (*y + 1, 1) } else { (*y, m + 1) };
+            day(*y as i32, *m, 1).zip(day(ny as i32, nm, 1))
+        }
+        [y, m, d] => day(*y as i32, *m, *d).map(|start| (start, start + Duration::days(1))),
+        _ => None,
+    };
+
+    range.ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn metadata(id: &str, timestamp: &str, tags: &[&str]) -> CaptureMetadata {
+        CaptureMetadata {
+            capture_id: id.to_string(),
+            timestamp: DateTime::parse_from_rfc3339(timestamp).unwrap().with_timezone(&Utc),
+            capture_type: "btle_sniff".to_string(),
+            packet_count: 10,
+            duration_sec: Some(30),
+            file_size_bytes: 0,
+            pcap_path: format!("/nonexistent/{}.pcap", id),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            description: "Lobby survey".to_string(),
+            channels: vec![37, 38, 39],
+            engagement: Some("acme".to_string()),
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_query_matching() {
+        let devices = vec![DeviceSeen { mac: "AA:BB:CC:11:22:33".to_string(), name: Some("Keyboard K380".to_string()) }];
+        let entry = CatalogEntry::new(
+            &metadata("cap-1", "2026-03-14T10:00:00Z", &["lobby", "mac:DE:AD:BE:EF:00:01"]),
+            devices,
+            Path::new("/nonexistent/cap-1.json"),
+        );
+        assert_eq!(entry.devices.len(), 2);
+
+        let matches = |q: &str| CatalogQuery::parse(q).unwrap().matches(&entry);
+        assert!(matches("AA:BB:CC:11:22:33"));
+        assert!(matches("mac:aa:bb:cc tag:lobby date:2026-03"));
+        assert!(matches("DE:AD:BE:EF:00:01 channel:37 engagement:ACME name:keyboard survey"));
+        assert!(!matches("tag:lobby date:2026-04"));
+        assert!(!matches("AA:BB:CC:99:99:99"));
+        assert!(!matches("channel:12"));
+        assert!(!matches("before:2026-03-14"));
+        assert!(CatalogQuery::parse("date:2026-13").is_err());
+
+        let query = CatalogQuery::from_params(&json!({
+            "query": "tag:lobby",
+            "mac": "aa-bb-cc",
+            "since": "2026-03-01",
+            "until": "2026-03",
+            "min_duration_sec": 60
+        }))
+        .unwrap();
+        assert_eq!(query.macs, vec!["AA:BB:CC".to_string()]);
+        assert!(!query.matches(&entry));
+    }
+
+    #[test]
+    fn test_incremental_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let captures = dir.path().join("captures");
+        fs::create_dir_all(&captures).unwrap();
+
+        let write = |meta: &CaptureMetadata| {
+            fs::write(captures.join(format!("{}.json", meta.capture_id)), serde_json::to_string(meta).unwrap()).unwrap();
+        };
+        write(&metadata("cap-1", "2026-03-14T10:00:00Z", &["lobby"]));
+        write(&metadata("cap-2", "2026-04-02T10:00:00Z", &["garage"]));
+
+        let mut catalog = CaptureCatalog::open_at(dir.path().join(CATALOG_FILE)).unwrap();
+        let stats = catalog.refresh(&captures, |_| Vec::new()).unwrap();
+        assert_eq!((stats.added, stats.unchanged), (2, 0));
+
+        // Reopened from disk, nothing changed
+        let mut catalog = CaptureCatalog::open_at(dir.path().join(CATALOG_FILE)).unwrap();
+        let stats = catalog.refresh(&captures, |_| panic!("unchanged capture re-indexed")).unwrap();
+        assert_eq!((stats.added, stats.updated, stats.unchanged), (0, 0, 2));
+
+        fs::remove_file(captures.join("cap-2.json")).unwrap();
+        let stats = catalog.refresh(&captures, |_| Vec::new()).unwrap();
+        assert_eq!((stats.removed, stats.unchanged), (1, 1));
+        assert_eq!(catalog.len(), 1);
+
+        let query = CatalogQuery::parse("tag:lobby in:2026-03").unwrap();
+        assert_eq!(catalog.query(&query).len(), 1);
+    }
+}
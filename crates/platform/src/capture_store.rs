@@ -2,11 +2,24 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_core::storage;
+use ubertooth_protocol::pcapng;
+use ubertooth_protocol::spectrum::SpectrumRecording;
 use uuid::Uuid;
 
+use crate::config_store;
+use crate::custody::{CustodyConfig, CustodyLog, Provenance};
+use crate::encryption::{self, StoreCipher};
+use crate::retention;
+
+/// Capture type of spectrum waterfall recordings.
+pub const CAPTURE_TYPE_SPECTRUM: &str = "specan_recording";
+
 /// Capture metadata structure.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CaptureMetadata {
@@ -19,36 +32,191 @@ pub struct CaptureMetadata {
     pub pcap_path: String,
     pub tags: Vec<String>,
     pub description: String,
+    /// Channels the capture was taken on, when known
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<u8>,
+    /// Engagement (case, assessment) the capture belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub engagement: Option<String>,
+    /// Set when retention removed the capture data; the metadata stays behind
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pruned: Option<PruneRecord>,
+    /// Data hash, operator, host and device recorded at write time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Configuration preset loaded when the capture was taken
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_preset: Option<String>,
+    /// Analyst notes and bookmarks on individual packets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<PacketAnnotation>,
+}
+
+impl CaptureMetadata {
+    /// Annotation of one packet, if any.
+    pub fn annotation(&self, packet_index: usize) -> Option<&PacketAnnotation> {
+        self.annotations.iter().find(|a| a.packet_index == packet_index)
+    }
+
+    /// Packet notes by packet index, as written to PCAPNG packet comments.
+    pub fn packet_comments(&self) -> BTreeMap<usize, String> {
+        self.annotations
+            .iter()
+            .filter_map(|a| a.note.clone().map(|note| (a.packet_index, note)))
+            .collect()
+    }
+}
+
+/// Note and bookmark on one packet of a capture.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PacketAnnotation {
+    /// 0-based position of the packet in the capture
+    pub packet_index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub bookmarked: bool,
+    pub author: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Why and when retention removed a capture's data.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PruneRecord {
+    pub pruned_at: DateTime<Utc>,
+    /// Rule that triggered: "max_age" or "max_total_size"
+    pub rule: String,
+    pub reason: String,
+    /// Data size freed
+    pub freed_bytes: u64,
 }
 
 /// Capture storage manager.
 pub struct CaptureStore {
     base_path: PathBuf,
+    cipher: Option<StoreCipher>,
 }
 
 impl CaptureStore {
-    /// Create a new capture store at ~/.ubertooth/
+    /// Create a new capture store at the storage root (`~/.ubertooth/` by default).
     pub fn new() -> Result<Self> {
-        let home = dirs::home_dir()
-            .ok_or_else(|| UbertoothError::BackendError("Could not determine home directory".to_string()))?;
-
-        let base_path = home.join(".ubertooth");
+        Self::with_root(storage::storage_root()?)
+    }
 
+    /// Create a capture store at `base_path`, with encryption if a store
+    /// key is configured.
+    pub fn with_root(base_path: PathBuf) -> Result<Self> {
         // Create directories
-        fs::create_dir_all(base_path.join("captures"))?;
-        fs::create_dir_all(base_path.join("configs"))?;
+        fs::create_dir_all(base_path.join(storage::CAPTURES_DIR))?;
+        fs::create_dir_all(base_path.join(storage::CONFIGS_DIR))?;
+
+        let cipher = StoreCipher::load(&base_path)?;
+        Ok(Self { base_path, cipher })
+    }
+
+    /// Use `cipher` for files written from now on.
+    pub fn with_cipher(mut self, cipher: StoreCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Whether new capture files are encrypted at rest.
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// The store cipher, if encryption is configured.
+    pub fn cipher(&self) -> Option<&StoreCipher> {
+        self.cipher.as_ref()
+    }
+
+    /// Read a store file, decrypting it if needed.
+    pub fn read_file(&self, path: &Path) -> Result<Vec<u8>> {
+        encryption::read_file(self.cipher(), path)
+    }
+
+    /// Write a store file, encrypting it if the store is encrypted.
+    pub fn write_file(&self, path: &Path, data: &[u8]) -> Result<()> {
+        encryption::write_file(self.cipher(), path, data)
+    }
+
+    /// Decrypted contents of a capture's data file.
+    pub fn read_data(&self, metadata: &CaptureMetadata) -> Result<Vec<u8>> {
+        self.read_file(Path::new(&metadata.pcap_path))
+    }
+
+    /// A plaintext path to a capture's data file, for parsers and external
+    /// tools that need a file.
+    ///
+    /// Unencrypted data is used in place. Encrypted data is decrypted into a
+    /// temporary file next to the store that is removed when the returned
+    /// [`DataFile`] is dropped.
+    pub fn open_data(&self, metadata: &CaptureMetadata) -> Result<DataFile> {
+        let path = PathBuf::from(&metadata.pcap_path);
+        let mut header = [0u8; encryption::MAGIC.len()];
+        let encrypted = fs::File::open(&path)
+            .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut header))
+            .is_ok()
+            && encryption::is_encrypted(&header);
+        if !encrypted {
+            return Ok(DataFile::Plain(path));
+        }
+
+        let data = self.read_file(&path)?;
+        let suffix = path
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let mut tmp = tempfile::Builder::new()
+            .prefix(".decrypted-")
+            .suffix(&suffix)
+            .tempfile_in(&self.base_path)?;
+        std::io::Write::write_all(&mut tmp, &data)?;
+        Ok(DataFile::Decrypted(tmp))
+    }
+
+    /// Encrypt every plaintext file in an existing store.
+    pub fn migrate_encryption(&self) -> Result<MigrationReport> {
+        let cipher = self.cipher.as_ref().ok_or_else(|| {
+            UbertoothError::InvalidParameter("No store key is configured; nothing to encrypt with".to_string())
+        })?;
+
+        let mut files: Vec<PathBuf> = fs::read_dir(self.captures_dir())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                matches!(path.extension().and_then(|s| s.to_str()), Some("json" | "pcap" | "pcapng" | "specan"))
+            })
+            .collect();
+        let catalog = self.base_path.join(crate::capture_catalog::CATALOG_FILE);
+        if catalog.exists() {
+            files.push(catalog);
+        }
+
+        let mut report = MigrationReport::default();
+        for path in files {
+            match encryption::encrypt_in_place(cipher, &path) {
+                Ok(true) => report.encrypted += 1,
+                Ok(false) => report.already_encrypted += 1,
+                Err(e) => report.failed.push(format!("{}: {}", path.display(), e)),
+            }
+        }
 
-        Ok(Self { base_path })
+        Ok(report)
     }
 
     /// Get the captures directory path.
     pub fn captures_dir(&self) -> PathBuf {
-        self.base_path.join("captures")
+        self.base_path.join(storage::CAPTURES_DIR)
     }
 
     /// Get the configs directory path.
     pub fn configs_dir(&self) -> PathBuf {
-        self.base_path.join("configs")
+        self.base_path.join(storage::CONFIGS_DIR)
+    }
+
+    /// Get the storage root.
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
     }
 
     /// Generate a new capture ID.
@@ -57,17 +225,114 @@ impl CaptureStore {
     }
 
     /// Save capture metadata.
+    ///
+    /// A new capture has its data hashed and its provenance recorded, is
+    /// entered in the custody log, and the retention policy is applied
+    /// afterwards.
     pub fn save_metadata(&self, metadata: &CaptureMetadata) -> Result<()> {
         let path = self
             .captures_dir()
             .join(format!("{}.json", metadata.capture_id));
+        if path.exists() {
+            self.write_file(&path, serde_json::to_string_pretty(metadata)?.as_bytes())?;
+            return Ok(());
+        }
+
+        let mut metadata = metadata.clone();
+        let custody = CustodyConfig::load();
+        if metadata.engagement.is_none() {
+            metadata.engagement = custody.engagement.clone();
+        }
+        if metadata.config_preset.is_none() {
+            metadata.config_preset = config_store::active_preset();
+        }
+        if metadata.provenance.is_none() {
+            metadata.provenance = Provenance::record(Path::new(&metadata.pcap_path), &custody)
+                .map_err(|e| tracing::warn!("Capture {} not hashed: {}", metadata.capture_id, e))
+                .ok();
+        }
+
+        // Hashed as plaintext above, so provenance survives encryption
+        if let Some(cipher) = &self.cipher {
+            let data_path = Path::new(&metadata.pcap_path);
+            if data_path.is_file() {
+                encryption::encrypt_in_place(cipher, data_path)?;
+            }
+        }
+
+        self.write_file(&path, serde_json::to_string_pretty(&metadata)?.as_bytes())?;
 
-        let json = serde_json::to_string_pretty(metadata)?;
-        fs::write(path, json)?;
+        self.log_custody(
+            &metadata.capture_id,
+            "created",
+            json!({
+                "capture_type": metadata.capture_type,
+                "engagement": metadata.engagement,
+                "provenance": metadata.provenance
+            }),
+        )?;
+        retention::enforce(self, Some(&metadata.capture_id));
 
         Ok(())
     }
 
+    /// Custody log kept under the storage root.
+    pub fn custody_log(&self) -> CustodyLog {
+        CustodyLog::new(&self.base_path)
+    }
+
+    /// Record an action on a capture in the custody log.
+    pub fn log_custody(&self, capture_id: &str, action: &str, details: Value) -> Result<()> {
+        self.custody_log().append(capture_id, action, details)?;
+        Ok(())
+    }
+
+    /// Set the note and bookmark of one packet; a packet with neither loses
+    /// its annotation.
+    pub fn annotate(
+        &self,
+        capture_id: &str,
+        packet_index: usize,
+        note: Option<String>,
+        bookmarked: bool,
+    ) -> Result<CaptureMetadata> {
+        let mut metadata = self.load_metadata(capture_id)?;
+        let note = note.filter(|n| !n.trim().is_empty());
+
+        metadata.annotations.retain(|a| a.packet_index != packet_index);
+        if note.is_some() || bookmarked {
+            metadata.annotations.push(PacketAnnotation {
+                packet_index,
+                note: note.clone(),
+                bookmarked,
+                author: CustodyConfig::load().operator,
+                updated_at: Utc::now(),
+            });
+            metadata.annotations.sort_by_key(|a| a.packet_index);
+        }
+
+        self.save_metadata(&metadata)?;
+        self.log_custody(
+            capture_id,
+            "annotated",
+            json!({ "packet_index": packet_index, "note": note, "bookmarked": bookmarked }),
+        )?;
+
+        Ok(metadata)
+    }
+
+    /// Write a capture as PCAPNG with its packet notes as packet comments.
+    ///
+    /// Returns the number of comments written.
+    pub fn write_pcapng(&self, metadata: &CaptureMetadata, output: &Path) -> Result<usize> {
+        let data = self.read_data(metadata)?;
+        let mut file = std::io::BufWriter::new(fs::File::create(output)?);
+        let written = pcapng::write_with_comments(&data, &mut file, &metadata.packet_comments())
+            .map_err(|e| UbertoothError::ParseError(format!("Cannot write PCAPNG for {}: {}", metadata.capture_id, e)))?;
+        std::io::Write::flush(&mut file)?;
+        Ok(written)
+    }
+
     /// Load capture metadata.
     pub fn load_metadata(&self, capture_id: &str) -> Result<CaptureMetadata> {
         let path = self.captures_dir().join(format!("{}.json", capture_id));
@@ -76,12 +341,66 @@ impl CaptureStore {
             return Err(UbertoothError::CaptureNotFound(capture_id.to_string()));
         }
 
-        let json = fs::read_to_string(path)?;
-        let metadata: CaptureMetadata = serde_json::from_str(&json)?;
+        let metadata: CaptureMetadata = serde_json::from_slice(&self.read_file(&path)?)?;
+
+        Ok(metadata)
+    }
+
+    /// Save a spectrum recording as `<capture_id>.specan` with its metadata.
+    pub fn save_spectrum(
+        &self,
+        capture_id: &str,
+        recording: &SpectrumRecording,
+        tags: Vec<String>,
+        description: String,
+    ) -> Result<CaptureMetadata> {
+        let path = self.captures_dir().join(format!("{}.specan", capture_id));
+        recording
+            .save(&path)
+            .map_err(|e| UbertoothError::BackendError(format!("Failed to write spectrum recording: {}", e)))?;
+
+        let timestamp = DateTime::<Utc>::from_timestamp_millis(recording.header.start_unix_ms as i64)
+            .unwrap_or_else(Utc::now);
+
+        let metadata = CaptureMetadata {
+            capture_id: capture_id.to_string(),
+            timestamp,
+            capture_type: CAPTURE_TYPE_SPECTRUM.to_string(),
+            packet_count: recording.sweeps.len(),
+            duration_sec: Some((recording.duration_ms() as u64).div_ceil(1000)),
+            file_size_bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            pcap_path: path.to_string_lossy().to_string(),
+            tags,
+            description,
+            channels: Vec::new(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
+        };
+        self.save_metadata(&metadata)?;
 
         Ok(metadata)
     }
 
+    /// Load the spectrum recording stored for a capture.
+    pub fn load_spectrum(&self, capture_id: &str) -> Result<(CaptureMetadata, SpectrumRecording)> {
+        let metadata = self.load_metadata(capture_id)?;
+        if metadata.capture_type != CAPTURE_TYPE_SPECTRUM {
+            return Err(UbertoothError::InvalidParameter(format!(
+                "Capture {} is a '{}' capture, not a spectrum recording",
+                capture_id, metadata.capture_type
+            )));
+        }
+
+        let data = self.read_data(&metadata)?;
+        let recording = SpectrumRecording::read_from(&data[..])
+            .map_err(|e| UbertoothError::BackendError(format!("Failed to read spectrum recording: {}", e)))?;
+
+        Ok((metadata, recording))
+    }
+
     /// List all captures.
     pub fn list_captures(&self) -> Result<Vec<CaptureMetadata>> {
         let mut captures = Vec::new();
@@ -91,9 +410,13 @@ impl CaptureStore {
             let path = entry.path();
 
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                let json = fs::read_to_string(&path)?;
-                if let Ok(metadata) = serde_json::from_str::<CaptureMetadata>(&json) {
-                    captures.push(metadata);
+                match self.read_file(&path) {
+                    Ok(json) => {
+                        if let Ok(metadata) = serde_json::from_slice::<CaptureMetadata>(&json) {
+                            captures.push(metadata);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Skipping {}: {}", path.display(), e),
                 }
             }
         }
@@ -104,8 +427,47 @@ impl CaptureStore {
         Ok(captures)
     }
 
-    /// Delete a capture (both PCAP and metadata).
+    /// Remove a capture's data files but keep its metadata, recording why.
+    pub fn prune_capture(&self, capture_id: &str, rule: &str, reason: &str) -> Result<CaptureMetadata> {
+        let mut metadata = self.load_metadata(capture_id)?;
+
+        let mut freed_bytes = 0;
+        for path in self.data_paths(&metadata) {
+            if let Ok(meta) = fs::metadata(&path) {
+                fs::remove_file(&path)?;
+                freed_bytes += meta.len();
+            }
+        }
+
+        metadata.pruned = Some(PruneRecord {
+            pruned_at: Utc::now(),
+            rule: rule.to_string(),
+            reason: reason.to_string(),
+            freed_bytes,
+        });
+        self.save_metadata(&metadata)?;
+        self.log_custody(capture_id, "pruned", json!({ "rule": rule, "reason": reason, "freed_bytes": freed_bytes }))?;
+
+        Ok(metadata)
+    }
+
+    /// Data files that may belong to a capture.
+    fn data_paths(&self, metadata: &CaptureMetadata) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = ["pcap", "specan"]
+            .iter()
+            .map(|ext| self.captures_dir().join(format!("{}.{}", metadata.capture_id, ext)))
+            .collect();
+        let recorded = PathBuf::from(&metadata.pcap_path);
+        if !metadata.pcap_path.is_empty() && !paths.contains(&recorded) {
+            paths.push(recorded);
+        }
+        paths
+    }
+
+    /// Delete a capture (data file and metadata).
     pub fn delete_capture(&self, capture_id: &str) -> Result<()> {
+        self.log_custody(capture_id, "deleted", json!({}))?;
+
         // Delete metadata JSON
         let json_path = self.captures_dir().join(format!("{}.json", capture_id));
         if json_path.exists() {
@@ -118,6 +480,36 @@ impl CaptureStore {
             fs::remove_file(pcap_path)?;
         }
 
+        // Delete spectrum recording
+        let specan_path = self.captures_dir().join(format!("{}.specan", capture_id));
+        if specan_path.exists() {
+            fs::remove_file(specan_path)?;
+        }
+
         Ok(())
     }
 }
+
+/// Plaintext view of a capture's data file, see [`CaptureStore::open_data`].
+pub enum DataFile {
+    Plain(PathBuf),
+    Decrypted(tempfile::NamedTempFile),
+}
+
+impl DataFile {
+    /// Path to read the plaintext data from.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Plain(path) => path,
+            Self::Decrypted(tmp) => tmp.path(),
+        }
+    }
+}
+
+/// Outcome of [`CaptureStore::migrate_encryption`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    pub encrypted: usize,
+    pub already_encrypted: usize,
+    pub failed: Vec<String>,
+}
@@ -4,7 +4,9 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
 use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_core::storage;
 
 /// Configuration preset metadata.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,19 +34,65 @@ pub struct ConfigSettings {
     pub squelch: Option<i32>,
 }
 
+/// Per-dongle noise floor and RSSI calibration.
+///
+/// `offset_db` is added to RSSI readings from this dongle so that values
+/// from several Ubertooths line up on the same scale.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RssiCalibration {
+    pub serial: String,
+    pub calibrated: DateTime<Utc>,
+    pub low_freq_mhz: u16,
+    pub high_freq_mhz: u16,
+    pub sweep_count: usize,
+    /// Band noise floor (median of the per-channel floors) in dBm
+    pub noise_floor_dbm: i8,
+    /// Noise floor per MHz from `low_freq_mhz`
+    pub channel_noise_floor_dbm: Vec<Option<i8>>,
+    pub squelch_margin_db: i8,
+    pub recommended_squelch_dbm: i8,
+    pub offset_db: i16,
+    /// What the offset aligns to: "none", "serial:<serial>" or "absolute"
+    pub reference: String,
+}
+
+impl RssiCalibration {
+    /// Apply the calibration offset to a raw RSSI reading.
+    pub fn corrected(&self, rssi_dbm: i16) -> i16 {
+        rssi_dbm + self.offset_db
+    }
+
+    /// Noise floor on the common scale.
+    pub fn corrected_noise_floor(&self) -> i16 {
+        self.corrected(self.noise_floor_dbm as i16)
+    }
+}
+
+static ACTIVE_PRESET: OnceLock<RwLock<Option<String>>> = OnceLock::new();
+
+fn active_preset_slot() -> &'static RwLock<Option<String>> {
+    ACTIVE_PRESET.get_or_init(|| RwLock::new(None))
+}
+
+/// Record the preset most recently loaded onto the device.
+pub fn set_active_preset(name: Option<String>) {
+    *active_preset_slot().write().unwrap_or_else(|e| e.into_inner()) = name;
+}
+
+/// The preset most recently loaded, stamped on new captures.
+pub fn active_preset() -> Option<String> {
+    active_preset_slot().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
 /// Configuration storage manager.
 pub struct ConfigStore {
     configs_dir: PathBuf,
 }
 
 impl ConfigStore {
-    /// Create a new config store at ~/.ubertooth/configs/
+    /// Create a new config store at `<storage root>/configs/`.
     pub fn new() -> Result<Self> {
-        let home = dirs::home_dir()
-            .ok_or_else(|| UbertoothError::BackendError("Cannot find home directory".to_string()))?;
-
-        let ubertooth_dir = home.join(".ubertooth");
-        let configs_dir = ubertooth_dir.join("configs");
+        let configs_dir = storage::storage_root()?.join(storage::CONFIGS_DIR);
 
         // Create directory if it doesn't exist
         fs::create_dir_all(&configs_dir).map_err(|e| {
@@ -162,6 +210,72 @@ impl ConfigStore {
     pub fn config_exists(&self, name: &str) -> bool {
         self.configs_dir.join(format!("{}.json", name)).exists()
     }
+
+    /// Directory holding per-dongle RSSI calibrations.
+    pub fn calibration_dir(&self) -> PathBuf {
+        self.configs_dir.join("calibration")
+    }
+
+    /// Save (or replace) the calibration of a dongle.
+    pub fn save_calibration(&self, calibration: &RssiCalibration) -> Result<PathBuf> {
+        let dir = self.calibration_dir();
+        fs::create_dir_all(&dir).map_err(|e| {
+            UbertoothError::BackendError(format!("Failed to create calibration directory: {}", e))
+        })?;
+
+        let path = dir.join(format!("{}.json", sanitize_serial(&calibration.serial)));
+        let json = serde_json::to_string_pretty(calibration).map_err(|e| {
+            UbertoothError::BackendError(format!("Failed to serialize calibration: {}", e))
+        })?;
+        fs::write(&path, json).map_err(|e| {
+            UbertoothError::BackendError(format!("Failed to write calibration file: {}", e))
+        })?;
+
+        Ok(path)
+    }
+
+    /// Load the calibration of a dongle, if it has been calibrated.
+    pub fn load_calibration(&self, serial: &str) -> Result<Option<RssiCalibration>> {
+        let path = self.calibration_dir().join(format!("{}.json", sanitize_serial(serial)));
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let json = fs::read_to_string(&path).map_err(|e| {
+            UbertoothError::BackendError(format!("Failed to read calibration file: {}", e))
+        })?;
+        let calibration = serde_json::from_str(&json).map_err(|e| {
+            UbertoothError::BackendError(format!("Failed to parse calibration file: {}", e))
+        })?;
+
+        Ok(Some(calibration))
+    }
+
+    /// List the calibrations of all dongles.
+    pub fn list_calibrations(&self) -> Result<Vec<RssiCalibration>> {
+        let Ok(entries) = fs::read_dir(self.calibration_dir()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut calibrations: Vec<RssiCalibration> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("json"))
+            .filter_map(|p| fs::read_to_string(p).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        calibrations.sort_by(|a, b| a.serial.cmp(&b.serial));
+
+        Ok(calibrations)
+    }
+}
+
+/// Serial numbers become file names; keep them to safe characters.
+fn sanitize_serial(serial: &str) -> String {
+    serial
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
 }
 
 #[cfg(test)]
@@ -189,4 +303,25 @@ mod tests {
         assert!(json.contains("\"channel\":37"));
         assert!(json.contains("\"modulation\":\"BT_LOW_ENERGY\""));
     }
+
+    #[test]
+    fn test_rssi_calibration_offset() {
+        let calibration = RssiCalibration {
+            serial: "0000000000000000a1b2c3d4".to_string(),
+            calibrated: Utc::now(),
+            low_freq_mhz: 2402,
+            high_freq_mhz: 2480,
+            sweep_count: 50,
+            noise_floor_dbm: -97,
+            channel_noise_floor_dbm: vec![Some(-97); 79],
+            squelch_margin_db: 10,
+            recommended_squelch_dbm: -87,
+            offset_db: 3,
+            reference: "absolute".to_string(),
+        };
+
+        assert_eq!(calibration.corrected(-60), -57);
+        assert_eq!(calibration.corrected_noise_floor(), -94);
+        assert_eq!(sanitize_serial("ab/../c d"), "ab____c_d");
+    }
 }
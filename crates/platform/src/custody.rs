@@ -0,0 +1,479 @@
+//! Evidence integrity and chain of custody for captures.
+//!
+//! When a capture is first saved its data file is hashed (SHA-256) and the
+//! hash is stored in the metadata together with the operator, host and the
+//! Ubertooth serial and firmware ([`Provenance`]). [`verify_capture`]
+//! re-hashes the data to detect tampering.
+//!
+//! Every creation, access, export, tag change, prune and deletion is
+//! appended to `<storage root>/custody.log`, one JSON object per line. Each
+//! entry carries the hash of the previous one, so edits to or removal of
+//! earlier entries break the chain and show up in [`verify_log`].
+//!
+//! Operator and default engagement come from the `[custody]` section of the
+//! config file or `UBERTOOTH_OPERATOR` / `UBERTOOTH_ENGAGEMENT`:
+//!
+//! ```toml
+//! [custody]
+//! operator = "j.doe"
+//! engagement = "ACME-2026-03"
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use sysinfo::System;
+use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_core::storage;
+
+use crate::capture_store::{CaptureMetadata, CaptureStore};
+
+/// Custody log file name under the storage root.
+pub const CUSTODY_LOG_FILE: &str = "custody.log";
+
+/// Environment variable naming the operator.
+pub const OPERATOR_ENV: &str = "UBERTOOTH_OPERATOR";
+
+/// Environment variable naming the default engagement for new captures.
+pub const ENGAGEMENT_ENV: &str = "UBERTOOTH_ENGAGEMENT";
+
+/// `prev_hash` of the first log entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Operator and engagement settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustodyConfig {
+    pub operator: Option<String>,
+    /// Engagement assigned to new captures that don't name one
+    pub engagement: Option<String>,
+}
+
+impl CustodyConfig {
+    /// Load from the config file and environment; the operator falls back
+    /// to the login name.
+    pub fn load() -> Self {
+        let mut config: Self = storage::config_section("custody").unwrap_or_else(|e| {
+            tracing::warn!("Ignoring [custody] config: {}", e);
+            Self::default()
+        });
+
+        if let Some(operator) = non_empty_env(OPERATOR_ENV) {
+            config.operator = Some(operator);
+        }
+        if let Some(engagement) = non_empty_env(ENGAGEMENT_ENV) {
+            config.engagement = Some(engagement);
+        }
+        if config.operator.is_none() {
+            config.operator = non_empty_env("USER").or_else(|| non_empty_env("USERNAME"));
+        }
+
+        config
+    }
+}
+
+fn non_empty_env(key: &str) -> Option<String> {
+    std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Ubertooth the captures are being taken with.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceIdentity {
+    pub serial: Option<String>,
+    pub firmware: Option<String>,
+}
+
+impl DeviceIdentity {
+    /// Pick the serial and firmware out of a `device_connect`/`device_status` result.
+    pub fn from_status(status: &Value) -> Self {
+        let field = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| status.get(*key).and_then(|v| v.as_str()))
+                .filter(|v| !v.is_empty() && *v != "unknown")
+                .map(str::to_string)
+        };
+
+        Self {
+            serial: field(&["serial", "serial_number"]),
+            firmware: field(&["firmware_version", "firmware"]),
+        }
+    }
+}
+
+static DEVICE: OnceLock<RwLock<DeviceIdentity>> = OnceLock::new();
+
+fn device_slot() -> &'static RwLock<DeviceIdentity> {
+    DEVICE.get_or_init(|| RwLock::new(DeviceIdentity::default()))
+}
+
+/// Record the connected device; known fields are kept when `identity` lacks them.
+pub fn set_device(identity: DeviceIdentity) {
+    let mut current = device_slot().write().unwrap_or_else(|e| e.into_inner());
+    if identity.serial.is_some() {
+        current.serial = identity.serial;
+    }
+    if identity.firmware.is_some() {
+        current.firmware = identity.firmware;
+    }
+}
+
+/// The device most recently connected.
+pub fn current_device() -> DeviceIdentity {
+    device_slot().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Where, by whom and with what a capture was taken, plus its data hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub sha256: String,
+    pub size_bytes: u64,
+    pub hashed_at: DateTime<Utc>,
+    pub operator: Option<String>,
+    pub host: Option<String>,
+    pub device_serial: Option<String>,
+    pub firmware: Option<String>,
+}
+
+impl Provenance {
+    /// Hash `data_path` and record the current operator, host and device.
+    pub fn record(data_path: &Path, config: &CustodyConfig) -> Result<Self> {
+        let (sha256, size_bytes) = sha256_file(data_path)?;
+        let device = current_device();
+
+        Ok(Self {
+            sha256,
+            size_bytes,
+            hashed_at: Utc::now(),
+            operator: config.operator.clone(),
+            host: System::host_name(),
+            device_serial: device.serial,
+            firmware: device.firmware,
+        })
+    }
+}
+
+/// SHA-256 (lowercase hex) and size of a file.
+pub fn sha256_file(path: &Path) -> Result<(String, u64)> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    let mut size = 0u64;
+
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+
+    Ok((format!("{:x}", hasher.finalize()), size))
+}
+
+/// Result of re-hashing a capture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityStatus {
+    /// Data matches the hash taken at write time
+    Intact,
+    /// Data differs from the hash taken at write time
+    Modified,
+    /// Data file is gone although the capture was not pruned
+    Missing,
+    /// Data was removed by retention
+    Pruned,
+    /// Capture predates hashing; no reference hash
+    Unhashed,
+}
+
+/// Integrity check of one capture.
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityCheck {
+    pub capture_id: String,
+    pub status: IntegrityStatus,
+    pub pcap_path: String,
+    pub expected_sha256: Option<String>,
+    pub actual_sha256: Option<String>,
+    pub expected_size: Option<u64>,
+    pub actual_size: Option<u64>,
+}
+
+/// Re-hash a capture's data and compare it with the recorded hash.
+pub fn verify_capture(store: &CaptureStore, metadata: &CaptureMetadata) -> IntegrityCheck {
+    let expected = metadata.provenance.as_ref();
+    // Encrypted data is hashed after decryption, matching the provenance
+    let actual = store
+        .open_data(metadata)
+        .ok()
+        .and_then(|data| sha256_file(data.path()).ok());
+
+    let status = match (expected, &actual) {
+        (_, None) if metadata.pruned.is_some() => IntegrityStatus::Pruned,
+        (None, _) => IntegrityStatus::Unhashed,
+        (Some(_), None) => IntegrityStatus::Missing,
+        (Some(p), Some((sha256, size))) if p.sha256 == *sha256 && p.size_bytes == *size => IntegrityStatus::Intact,
+        (Some(_), Some(_)) => IntegrityStatus::Modified,
+    };
+
+    IntegrityCheck {
+        capture_id: metadata.capture_id.clone(),
+        status,
+        pcap_path: metadata.pcap_path.clone(),
+        expected_sha256: expected.map(|p| p.sha256.clone()),
+        actual_sha256: actual.as_ref().map(|(sha256, _)| sha256.clone()),
+        expected_size: expected.map(|p| p.size_bytes),
+        actual_size: actual.map(|(_, size)| size),
+    }
+}
+
+/// The hashed part of a custody log entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustodyRecord {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub capture_id: String,
+    /// created, accessed, exported, tagged, verified, pruned, deleted
+    pub action: String,
+    pub operator: Option<String>,
+    pub host: Option<String>,
+    #[serde(default)]
+    pub details: Value,
+    pub prev_hash: String,
+}
+
+/// One line of the custody log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CustodyEntry {
+    #[serde(flatten)]
+    pub record: CustodyRecord,
+    /// SHA-256 of the serialised record
+    pub hash: String,
+}
+
+impl CustodyRecord {
+    fn digest(&self) -> Result<String> {
+        let json = serde_json::to_string(self)?;
+        Ok(format!("{:x}", Sha256::digest(json.as_bytes())))
+    }
+}
+
+/// Append-only custody log.
+pub struct CustodyLog {
+    path: PathBuf,
+}
+
+/// Serialises appends within the process.
+static APPEND_LOCK: Mutex<()> = Mutex::new(());
+
+impl CustodyLog {
+    /// Custody log under a storage root.
+    pub fn new(storage_root: &Path) -> Self {
+        Self {
+            path: storage_root.join(CUSTODY_LOG_FILE),
+        }
+    }
+
+    /// Path of the log file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry for `capture_id`.
+    pub fn append(&self, capture_id: &str, action: &str, details: Value) -> Result<CustodyEntry> {
+        let _guard = APPEND_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let (seq, prev_hash) = match self.last_entry()? {
+            Some(last) => (last.record.seq + 1, last.hash),
+            None => (1, GENESIS_HASH.to_string()),
+        };
+
+        let record = CustodyRecord {
+            seq,
+            timestamp: Utc::now(),
+            capture_id: capture_id.to_string(),
+            action: action.to_string(),
+            operator: CustodyConfig::load().operator,
+            host: System::host_name(),
+            details,
+            prev_hash,
+        };
+        let entry = CustodyEntry {
+            hash: record.digest()?,
+            record,
+        };
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+
+        Ok(entry)
+    }
+
+    /// All entries, optionally only those for one capture.
+    pub fn entries(&self, capture_id: Option<&str>) -> Result<Vec<CustodyEntry>> {
+        let Ok(file) = File::open(&self.path) else {
+            return Ok(Vec::new());
+        };
+
+        let mut entries = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: CustodyEntry = serde_json::from_str(&line).map_err(|e| {
+                UbertoothError::ParseError(format!("Corrupt custody log entry: {}", e))
+            })?;
+            if capture_id.is_none_or(|id| entry.record.capture_id == id) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn last_entry(&self) -> Result<Option<CustodyEntry>> {
+        let Ok(text) = fs::read_to_string(&self.path) else {
+            return Ok(None);
+        };
+        match text.lines().rev().find(|l| !l.trim().is_empty()) {
+            Some(line) => Ok(Some(serde_json::from_str(line).map_err(|e| {
+                UbertoothError::ParseError(format!("Corrupt custody log entry: {}", e))
+            })?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Result of checking the custody log's hash chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainStatus {
+    pub entries: usize,
+    pub intact: bool,
+    /// Line (1-based) of the first entry that fails the check
+    pub first_broken_line: Option<usize>,
+    pub problem: Option<String>,
+}
+
+/// Check every entry's hash and its link to the previous entry.
+pub fn verify_log(log: &CustodyLog) -> Result<ChainStatus> {
+    let Ok(text) = fs::read_to_string(log.path()) else {
+        return Ok(ChainStatus { entries: 0, intact: true, first_broken_line: None, problem: None });
+    };
+
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut entries = 0;
+
+    let lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+    for (expected_seq, (idx, line)) in (1u64..).zip(lines) {
+        let broken = |problem: String| ChainStatus {
+            entries,
+            intact: false,
+            first_broken_line: Some(idx + 1),
+            problem: Some(problem),
+        };
+
+        let entry: CustodyEntry = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(e) => return Ok(broken(format!("Unreadable entry: {}", e))),
+        };
+        if entry.record.seq != expected_seq {
+            return Ok(broken(format!("Expected entry {}, found {}", expected_seq, entry.record.seq)));
+        }
+        if entry.record.prev_hash != prev_hash {
+            return Ok(broken("Entry does not link to the previous entry".to_string()));
+        }
+        if entry.record.digest()? != entry.hash {
+            return Ok(broken("Entry contents do not match its hash".to_string()));
+        }
+
+        prev_hash = entry.hash;
+        entries += 1;
+    }
+
+    Ok(ChainStatus { entries, intact: true, first_broken_line: None, problem: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_verify_capture_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let pcap = dir.path().join("cap-test.pcap");
+        fs::write(&pcap, b"packet data").unwrap();
+        let store = CaptureStore::with_root(dir.path().to_path_buf()).unwrap();
+
+        let mut metadata = CaptureMetadata {
+            capture_id: "cap-test".to_string(),
+            timestamp: Utc::now(),
+            capture_type: "btle_sniff".to_string(),
+            packet_count: 1,
+            duration_sec: None,
+            file_size_bytes: 11,
+            pcap_path: pcap.to_string_lossy().to_string(),
+            tags: Vec::new(),
+            description: String::new(),
+            channels: Vec::new(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
+        };
+        assert_eq!(verify_capture(&store, &metadata).status, IntegrityStatus::Unhashed);
+
+        metadata.provenance = Some(Provenance::record(&pcap, &CustodyConfig::default()).unwrap());
+        let provenance = metadata.provenance.as_ref().unwrap();
+        assert_eq!(provenance.sha256, format!("{:x}", Sha256::digest(b"packet data")));
+        assert_eq!(provenance.size_bytes, 11);
+        assert_eq!(verify_capture(&store, &metadata).status, IntegrityStatus::Intact);
+
+        // Still intact once encrypted at rest
+        let encrypted = CaptureStore::with_root(dir.path().to_path_buf())
+            .unwrap()
+            .with_cipher(crate::encryption::StoreCipher::from_key(&[1u8; 32]));
+        crate::encryption::encrypt_in_place(encrypted.cipher().unwrap(), &pcap).unwrap();
+        assert_eq!(verify_capture(&encrypted, &metadata).status, IntegrityStatus::Intact);
+
+        fs::write(&pcap, b"packet dat4").unwrap();
+        assert_eq!(verify_capture(&store, &metadata).status, IntegrityStatus::Modified);
+
+        fs::remove_file(&pcap).unwrap();
+        assert_eq!(verify_capture(&store, &metadata).status, IntegrityStatus::Missing);
+    }
+
+    #[test]
+    fn test_custody_log_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = CustodyLog::new(dir.path());
+
+        log.append("cap-1", "created", json!({ "sha256": "abc" })).unwrap();
+        log.append("cap-2", "created", json!({})).unwrap();
+        log.append("cap-1", "tagged", json!({ "added": ["lobby"] })).unwrap();
+
+        assert_eq!(log.entries(Some("cap-1")).unwrap().len(), 2);
+        let status = verify_log(&log).unwrap();
+        assert!(status.intact);
+        assert_eq!(status.entries, 3);
+
+        // Rewrite history: change the tag recorded in the last entry
+        let text = fs::read_to_string(log.path()).unwrap().replace("lobby", "garage");
+        fs::write(log.path(), text).unwrap();
+        let status = verify_log(&log).unwrap();
+        assert!(!status.intact);
+        assert_eq!(status.first_broken_line, Some(3));
+
+        // Drop an entry
+        let lines: Vec<String> = fs::read_to_string(log.path()).unwrap().lines().map(str::to_string).collect();
+        fs::write(log.path(), format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(verify_log(&log).unwrap().first_broken_line, Some(2));
+    }
+}
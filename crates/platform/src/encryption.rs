@@ -0,0 +1,352 @@
+//! Authenticated encryption of capture files at rest.
+//!
+//! Encryption is off unless a key source is configured:
+//!
+//! - a keyfile, from `UBERTOOTH_STORE_KEYFILE` or the config file:
+//!
+//!   ```toml
+//!   [encryption]
+//!   keyfile = "~/.config/ubertooth/store.key"
+//!   ```
+//!
+//! - or a passphrase in `UBERTOOTH_STORE_PASSPHRASE`, stretched with
+//!   Argon2id.
+//!
+//! Encrypted files start with [`MAGIC`], followed by a random 24-byte nonce
+//! and the XChaCha20-Poly1305 ciphertext. The file name is bound in as
+//! associated data, so a file renamed or swapped for another capture's
+//! fails to decrypt. `<storage root>/encryption.json` holds the KDF salt and
+//! a check value so the wrong key is reported instead of producing garbage.
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_core::storage;
+
+/// Header of every encrypted file.
+pub const MAGIC: &[u8; 8] = b"UBTENC01";
+
+/// Environment variable pointing at the keyfile.
+pub const KEYFILE_ENV: &str = "UBERTOOTH_STORE_KEYFILE";
+
+/// Environment variable holding the store passphrase.
+pub const PASSPHRASE_ENV: &str = "UBERTOOTH_STORE_PASSPHRASE";
+
+/// Key check file name under the storage root.
+pub const KEY_CHECK_FILE: &str = "encryption.json";
+
+const NONCE_LEN: usize = 24;
+const KEY_CHECK_PLAINTEXT: &[u8] = b"ubertooth capture store";
+
+/// `[encryption]` section of the config file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    pub keyfile: Option<String>,
+}
+
+/// Where the store key comes from.
+#[derive(Clone, PartialEq, Eq)]
+pub enum KeySource {
+    Keyfile(PathBuf),
+    Passphrase(String),
+}
+
+impl fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keyfile(path) => f.debug_tuple("Keyfile").field(path).finish(),
+            Self::Passphrase(_) => f.write_str("Passphrase(..)"),
+        }
+    }
+}
+
+impl KeySource {
+    /// Configured key source, if any (environment first, then config file).
+    pub fn configured() -> Result<Option<Self>> {
+        if let Some(passphrase) = std::env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty()) {
+            return Ok(Some(Self::Passphrase(passphrase)));
+        }
+        if let Some(keyfile) = std::env::var(KEYFILE_ENV).ok().filter(|p| !p.is_empty()) {
+            return Ok(Some(Self::Keyfile(storage::expand_home(&keyfile))));
+        }
+
+        let config: EncryptionConfig = storage::config_section("encryption")?;
+        Ok(config.keyfile.map(|keyfile| Self::Keyfile(storage::expand_home(&keyfile))))
+    }
+
+    fn kdf(&self) -> &'static str {
+        match self {
+            Self::Keyfile(_) => "keyfile",
+            Self::Passphrase(_) => "argon2id",
+        }
+    }
+}
+
+/// Salt and check value that tie a store to its key.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyCheck {
+    kdf: String,
+    salt: String,
+    /// [`KEY_CHECK_PLAINTEXT`] encrypted with the store key
+    check: String,
+}
+
+/// Cipher for one capture store.
+#[derive(Clone)]
+pub struct StoreCipher {
+    cipher: XChaCha20Poly1305,
+}
+
+impl fmt::Debug for StoreCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StoreCipher(..)")
+    }
+}
+
+impl StoreCipher {
+    /// Cipher from a raw 256-bit key.
+    pub fn from_key(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: XChaCha20Poly1305::new(Key::from_slice(key)),
+        }
+    }
+
+    /// Cipher for the store at `storage_root`, or `None` when encryption is
+    /// not configured.
+    pub fn load(storage_root: &Path) -> Result<Option<Self>> {
+        match KeySource::configured()? {
+            Some(source) => Ok(Some(Self::open(storage_root, &source)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Derive the key from `source` and check it against the store.
+    ///
+    /// The first time a store is opened with a key, the salt and check
+    /// value are written to `encryption.json`.
+    pub fn open(storage_root: &Path, source: &KeySource) -> Result<Self> {
+        let check_path = storage_root.join(KEY_CHECK_FILE);
+        let existing: Option<KeyCheck> = match fs::read_to_string(&check_path) {
+            Ok(json) => Some(serde_json::from_str(&json)?),
+            Err(_) => None,
+        };
+
+        let salt = match &existing {
+            Some(check) => hex::decode(&check.salt)
+                .map_err(|e| UbertoothError::ParseError(format!("Invalid salt in {}: {}", KEY_CHECK_FILE, e)))?,
+            None => {
+                let mut salt = vec![0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                salt
+            }
+        };
+        let cipher = Self::from_key(&derive_key(source, &salt)?);
+
+        match existing {
+            Some(check) => {
+                let sealed = hex::decode(&check.check).unwrap_or_default();
+                if check.kdf != source.kdf() || cipher.decrypt(&sealed, KEY_CHECK_FILE).is_err() {
+                    return Err(UbertoothError::BackendError(format!(
+                        "The configured {} does not match the key this capture store is encrypted with",
+                        if matches!(source, KeySource::Passphrase(_)) { "passphrase" } else { "keyfile" }
+                    )));
+                }
+            }
+            None => {
+                let check = KeyCheck {
+                    kdf: source.kdf().to_string(),
+                    salt: hex::encode(&salt),
+                    check: hex::encode(cipher.encrypt(KEY_CHECK_PLAINTEXT, KEY_CHECK_FILE)?),
+                };
+                fs::create_dir_all(storage_root)?;
+                fs::write(&check_path, serde_json::to_string_pretty(&check)?)?;
+            }
+        }
+
+        Ok(cipher)
+    }
+
+    /// Encrypt `plaintext` for the file called `name`.
+    pub fn encrypt(&self, plaintext: &[u8], name: &str) -> Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: plaintext, aad: name.as_bytes() })
+            .map_err(|_| UbertoothError::BackendError(format!("Failed to encrypt {}", name)))?;
+
+        let mut out = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    /// Decrypt and authenticate the contents of the file called `name`.
+    pub fn decrypt(&self, data: &[u8], name: &str) -> Result<Vec<u8>> {
+        if !is_encrypted(data) || data.len() < MAGIC.len() + NONCE_LEN {
+            return Err(UbertoothError::ParseError(format!("{} is not an encrypted store file", name)));
+        }
+
+        let nonce = XNonce::from_slice(&data[MAGIC.len()..MAGIC.len() + NONCE_LEN]);
+        self.cipher
+            .decrypt(nonce, Payload { msg: &data[MAGIC.len() + NONCE_LEN..], aad: name.as_bytes() })
+            .map_err(|_| {
+                UbertoothError::BackendError(format!(
+                    "Failed to decrypt {}: wrong key, or the file was modified",
+                    name
+                ))
+            })
+    }
+}
+
+/// Whether file contents are in the encrypted format.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Name used as associated data for a file.
+pub fn aad_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Read a store file, decrypting it when it is encrypted.
+///
+/// Plaintext files are returned as they are, so a store stays readable
+/// while it is being migrated.
+pub fn read_file(cipher: Option<&StoreCipher>, path: &Path) -> Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+    match cipher {
+        Some(cipher) => cipher.decrypt(&data, &aad_name(path)),
+        None => Err(UbertoothError::BackendError(format!(
+            "{} is encrypted; set {} or {} to read it",
+            path.display(),
+            KEYFILE_ENV,
+            PASSPHRASE_ENV
+        ))),
+    }
+}
+
+/// Write a store file, encrypting it when the store has a cipher.
+pub fn write_file(cipher: Option<&StoreCipher>, path: &Path, data: &[u8]) -> Result<()> {
+    match cipher {
+        Some(cipher) => fs::write(path, cipher.encrypt(data, &aad_name(path))?)?,
+        None => fs::write(path, data)?,
+    }
+    Ok(())
+}
+
+/// Encrypt a plaintext file in place; returns false if it already was.
+pub fn encrypt_in_place(cipher: &StoreCipher, path: &Path) -> Result<bool> {
+    let data = fs::read(path)?;
+    if is_encrypted(&data) {
+        return Ok(false);
+    }
+    let tmp = path.with_extension("enc.tmp");
+    fs::write(&tmp, cipher.encrypt(&data, &aad_name(path))?)?;
+    fs::rename(&tmp, path)?;
+    Ok(true)
+}
+
+fn derive_key(source: &KeySource, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    match source {
+        KeySource::Keyfile(path) => {
+            let contents = fs::read(path).map_err(|e| {
+                UbertoothError::BackendError(format!("Cannot read keyfile {}: {}", path.display(), e))
+            })?;
+            let text = String::from_utf8_lossy(&contents);
+            let bytes = match hex::decode(text.trim()) {
+                Ok(bytes) => bytes,
+                Err(_) => contents,
+            };
+            if bytes.len() != 32 {
+                return Err(UbertoothError::BackendError(format!(
+                    "Keyfile {} must hold a 256-bit key (64 hex characters or 32 raw bytes)",
+                    path.display()
+                )));
+            }
+            key.copy_from_slice(&bytes);
+        }
+        KeySource::Passphrase(passphrase) => {
+            argon2::Argon2::default()
+                .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                .map_err(|e| UbertoothError::BackendError(format!("Key derivation failed: {}", e)))?;
+        }
+    }
+    Ok(key)
+}
+
+/// Write a new random key to `path` as hex, readable only by the owner.
+pub fn generate_keyfile(path: &Path) -> Result<()> {
+    if path.exists() {
+        return Err(UbertoothError::BackendError(format!(
+            "Keyfile {} already exists",
+            path.display()
+        )));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+    fs::write(path, hex::encode(key))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_roundtrip_and_tamper() {
+        let cipher = StoreCipher::from_key(&[7u8; 32]);
+        let sealed = cipher.encrypt(b"pcap bytes", "cap-1.pcap").unwrap();
+
+        assert!(is_encrypted(&sealed));
+        assert_eq!(cipher.decrypt(&sealed, "cap-1.pcap").unwrap(), b"pcap bytes");
+
+        // Bound to the file name
+        assert!(cipher.decrypt(&sealed, "cap-2.pcap").is_err());
+
+        // Authenticated
+        let mut flipped = sealed.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(cipher.decrypt(&flipped, "cap-1.pcap").is_err());
+
+        // Wrong key
+        assert!(StoreCipher::from_key(&[8u8; 32]).decrypt(&sealed, "cap-1.pcap").is_err());
+    }
+
+    #[test]
+    fn test_key_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let keyfile = dir.path().join("store.key");
+        generate_keyfile(&keyfile).unwrap();
+        assert!(generate_keyfile(&keyfile).is_err());
+
+        let source = KeySource::Keyfile(keyfile);
+        let first = StoreCipher::open(dir.path(), &source).unwrap();
+        let sealed = first.encrypt(b"metadata", "cap-1.json").unwrap();
+
+        // Reopening with the same key works, another key is refused
+        let again = StoreCipher::open(dir.path(), &source).unwrap();
+        assert_eq!(again.decrypt(&sealed, "cap-1.json").unwrap(), b"metadata");
+        assert!(StoreCipher::open(dir.path(), &KeySource::Passphrase("hunter2".to_string())).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! Device fingerprint signature files.
+//!
+//! Fingerprinting uses the signatures bundled with `ubertooth-protocol`
+//! plus any `*.json` signature files in `<storage root>/fingerprints/`,
+//! loaded in name order. A file signature replaces the bundled (or earlier)
+//! signature with the same `id`, so teams can both add and correct entries.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_core::storage;
+use ubertooth_protocol::fingerprint::SignatureDatabase;
+
+/// Local fingerprint signature storage.
+pub struct SignatureRegistry {
+    signatures_dir: PathBuf,
+}
+
+impl SignatureRegistry {
+    /// Create a signature registry at `<storage root>/fingerprints/`.
+    pub fn new() -> Result<Self> {
+        let signatures_dir = storage::storage_dir(storage::FINGERPRINTS_DIR)?;
+
+        Ok(Self { signatures_dir })
+    }
+
+    /// Get the signature directory path.
+    pub fn signatures_dir(&self) -> &Path {
+        &self.signatures_dir
+    }
+
+    /// Signature files in the signature directory, sorted by name.
+    pub fn installed_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.signatures_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// The bundled signatures plus every installed signature file.
+    pub fn database(&self) -> Result<SignatureDatabase> {
+        let mut db = SignatureDatabase::bundled()
+            .map_err(|e| UbertoothError::BackendError(format!("Bundled fingerprint signatures unreadable: {}", e)))?;
+
+        for file in self.installed_files()? {
+            db.load_file(&file).map_err(|e| {
+                UbertoothError::InvalidParameter(format!("Invalid signature file {}: {}", file.display(), e))
+            })?;
+        }
+
+        Ok(db)
+    }
+}
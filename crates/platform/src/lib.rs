@@ -1,19 +1,32 @@
 //! Platform-specific implementations for the Ubertooth One connector.
 
 pub mod backend;
+pub mod bridge;
+pub mod bundle;
+pub mod calibration;
+pub mod capture_catalog;
 pub mod capture_store;
 pub mod config_store;
+pub mod custody;
+pub mod encryption;
+pub mod fingerprints;
+pub mod process;
+pub mod retention;
 pub mod sidecar;
 pub mod system_info;
+pub mod vendor_registry;
 
 #[cfg(feature = "rust-backend")]
 pub mod rust_usb;
 
 pub use backend::UbertoothBackendProvider;
+pub use capture_catalog::{CaptureCatalog, CatalogQuery};
 pub use capture_store::CaptureStore;
 pub use config_store::ConfigStore;
+pub use retention::RetentionPolicy;
 pub use sidecar::SidecarManager;
 pub use system_info::SystemInfo;
+pub use vendor_registry::VendorRegistry;
 
 #[cfg(feature = "rust-backend")]
 pub use rust_usb::RustUsbBackend;
@@ -0,0 +1,404 @@
+//! Asynchronous, cancellable subprocess execution.
+//!
+//! [`run`] starts a program without blocking the async runtime and stops it
+//! when it outlives its timeout, when its planned run time (`stop_after`)
+//! is over or when its [`CancelToken`] fires. Stopping sends SIGINT first so
+//! capture tools can flush and close their PCAP files, then kills the
+//! process if it has not exited within the grace period. Output written
+//! before the process stopped is returned, and a process whose future is
+//! dropped is killed rather than left running. [`run_streaming`] also hands
+//! each stdout line to a callback as soon as it is printed.
+//!
+//! On Unix each program runs in its own process group and is stopped as a
+//! group, so processes it forked (e.g. a wrapper script's tool) are stopped
+//! with it. Output still arriving more than the grace period after the
+//! program ended (from a background process holding its pipes) is dropped.
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::process::{ExitStatus, Stdio};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{mpsc, watch};
+use ubertooth_core::error::{Result, UbertoothError};
+
+/// Timeout for commands that are expected to return promptly.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Time a process gets to exit after SIGINT before it is killed.
+pub const INTERRUPT_GRACE: Duration = Duration::from_secs(3);
+
+/// Slack on top of a tool's own run time before it counts as hung.
+pub const DURATION_SLACK: Duration = Duration::from_secs(15);
+
+/// Cancels the commands started with it (and clones of it).
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancelToken {
+    /// A token that has not been cancelled.
+    pub fn new() -> Self {
+        Self {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// Cancel every command waiting on this token.
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Number of commands currently waiting on this token.
+    pub fn waiting(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Resolve once the token is cancelled.
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        // The sender lives as long as `self`, so this cannot fail
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+/// How a command is run.
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// Stop the process (and report a timeout) after this long
+    pub timeout: Option<Duration>,
+
+    /// Stop the process after this long as planned, e.g. a capture's duration
+    pub stop_after: Option<Duration>,
+
+    /// Time between SIGINT and kill
+    pub grace: Duration,
+
+    /// Stop the process when this token is cancelled
+    pub cancel: Option<CancelToken>,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Some(DEFAULT_TIMEOUT),
+            stop_after: None,
+            grace: INTERRUPT_GRACE,
+            cancel: None,
+        }
+    }
+}
+
+impl RunOptions {
+    /// Options for a tool that runs for `duration` and then exits by itself.
+    pub fn for_duration(duration: Duration) -> Self {
+        Self {
+            timeout: Some(duration + DURATION_SLACK),
+            ..Default::default()
+        }
+    }
+
+    /// Options for a tool that runs until stopped, stopping it after `duration`.
+    pub fn stop_after(duration: Duration) -> Self {
+        Self {
+            timeout: Some(duration + DURATION_SLACK),
+            stop_after: Some(duration),
+            ..Default::default()
+        }
+    }
+}
+
+/// Why a process ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    /// Exited by itself
+    Exited,
+    /// Stopped as planned after `stop_after`
+    Stopped,
+    /// Stopped after exceeding its timeout
+    TimedOut,
+    /// Stopped by its cancel token
+    Cancelled,
+}
+
+/// Result of [`run`].
+#[derive(Debug, Clone)]
+pub struct ProcessOutput {
+    pub termination: Termination,
+
+    /// Exit status (after SIGINT or kill when the process was stopped)
+    pub status: ExitStatus,
+
+    /// Everything written to stdout, including before an interruption
+    pub stdout: String,
+
+    /// Everything written to stderr, including before an interruption
+    pub stderr: String,
+}
+
+impl ProcessOutput {
+    /// Whether the process exited successfully or was stopped as planned.
+    pub fn success(&self) -> bool {
+        match self.termination {
+            Termination::Exited => self.status.success(),
+            Termination::Stopped => true,
+            Termination::TimedOut | Termination::Cancelled => false,
+        }
+    }
+
+    /// Whether the process was stopped before it was done.
+    pub fn interrupted(&self) -> bool {
+        matches!(self.termination, Termination::TimedOut | Termination::Cancelled)
+    }
+}
+
+async fn read_all<R: AsyncRead + Unpin>(pipe: Option<R>, done: CancelToken) -> String {
+    let mut buffer = Vec::new();
+    if let Some(mut pipe) = pipe {
+        // Keep what was read before an error or giving up
+        tokio::select! {
+            _ = pipe.read_to_end(&mut buffer) => {}
+            _ = done.cancelled() => {}
+        }
+    }
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
+/// Read the whole pipe, forwarding each complete line as it arrives.
+async fn read_lines<R: AsyncRead + Unpin>(pipe: Option<R>, lines: mpsc::UnboundedSender<String>, done: CancelToken) -> String {
+    let mut output = String::new();
+    let Some(pipe) = pipe else {
+        return output;
+    };
+    let mut reader = BufReader::new(pipe);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        let read = tokio::select! {
+            read = reader.read_until(b'\n', &mut buffer) => read,
+            _ = done.cancelled() => {
+                // Keep a partial last line
+                output.push_str(&String::from_utf8_lossy(&buffer));
+                break;
+            }
+        };
+        match read {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buffer);
+                output.push_str(&line);
+                let _ = lines.send(line.trim_end_matches(['\n', '\r']).to_string());
+            }
+        }
+    }
+    output
+}
+
+async fn sleep_or_forever(duration: Option<Duration>) {
+    match duration {
+        Some(duration) => tokio::time::sleep(duration).await,
+        None => std::future::pending().await,
+    }
+}
+
+fn wait_error(program: &str, e: std::io::Error) -> UbertoothError {
+    UbertoothError::BackendError(format!("Failed to wait for {}: {}", program, e))
+}
+
+/// Send `signal` to the process group led by `pid`.
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: libc::c_int) {
+    // SAFETY: kill(2) has no memory-safety requirements; the group is our child's
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), signal);
+    }
+}
+
+/// Send SIGINT, then kill the process if it is still running after `grace`.
+async fn interrupt(child: &mut Child, program: &str, grace: Duration) -> Result<ExitStatus> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        signal_group(pid, libc::SIGINT);
+        if let Ok(status) = tokio::time::timeout(grace, child.wait()).await {
+            return status.map_err(|e| wait_error(program, e));
+        }
+        tracing::warn!("{} ignored SIGINT for {:?}, killing it", program, grace);
+        signal_group(pid, libc::SIGKILL);
+    }
+
+    child.kill().await.map_err(|e| wait_error(program, e))?;
+    child.wait().await.map_err(|e| wait_error(program, e))
+}
+
+/// Wait for a pipe reader until `deadline`, then have it return what it read.
+async fn drain(mut reader: tokio::task::JoinHandle<String>, done: &CancelToken, deadline: tokio::time::Instant, program: &str) -> String {
+    if let Ok(output) = tokio::time::timeout_at(deadline, &mut reader).await {
+        return output.unwrap_or_default();
+    }
+    if !done.is_cancelled() {
+        tracing::warn!("Output of {} is still open after it ended, not waiting for it", program);
+        done.cancel();
+    }
+    reader.await.unwrap_or_default()
+}
+
+/// Run `program` with `args` until it exits or is stopped.
+pub async fn run(program: &str, args: &[&str], options: &RunOptions) -> Result<ProcessOutput> {
+    run_streaming(program, args, options, |_| {}).await
+}
+
+/// Like [`run`], calling `on_line` with each stdout line as soon as it is printed.
+pub async fn run_streaming<F>(program: &str, args: &[&str], options: &RunOptions, mut on_line: F) -> Result<ProcessOutput>
+where
+    F: FnMut(&str) + Send,
+{
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command
+        .spawn()
+        .map_err(|e| UbertoothError::BackendError(format!("Failed to execute {}: {}", program, e)))?;
+
+    let (line_tx, mut line_rx) = mpsc::unbounded_channel();
+    let drained = CancelToken::new();
+    let stdout = tokio::spawn(read_lines(child.stdout.take(), line_tx, drained.clone()));
+    let stderr = tokio::spawn(read_all(child.stderr.take(), drained.clone()));
+
+    let cancelled: Pin<Box<dyn Future<Output = ()> + Send>> = match &options.cancel {
+        Some(token) => Box::pin(token.cancelled()),
+        None => Box::pin(std::future::pending()),
+    };
+    tokio::pin!(cancelled);
+    let stop_after = sleep_or_forever(options.stop_after);
+    tokio::pin!(stop_after);
+    let timeout = sleep_or_forever(options.timeout);
+    tokio::pin!(timeout);
+
+    let (termination, exited) = loop {
+        tokio::select! {
+            Some(line) = line_rx.recv() => on_line(&line),
+            status = child.wait() => break (Termination::Exited, Some(status.map_err(|e| wait_error(program, e))?)),
+            _ = &mut stop_after => break (Termination::Stopped, None),
+            _ = &mut timeout => break (Termination::TimedOut, None),
+            _ = &mut cancelled => break (Termination::Cancelled, None),
+        }
+    };
+    let status = match exited {
+        Some(status) => status,
+        None => {
+            tracing::debug!("Stopping {} ({:?})", program, termination);
+            interrupt(&mut child, program, options.grace).await?
+        }
+    };
+
+    // Lines printed while exiting (e.g. on SIGINT) are still delivered, but
+    // a leftover process holding the pipes open is not waited for
+    let deadline = tokio::time::Instant::now() + options.grace;
+    let stdout = drain(stdout, &drained, deadline, program).await;
+    let stderr = drain(stderr, &drained, deadline, program).await;
+    while let Ok(line) = line_rx.try_recv() {
+        on_line(&line);
+    }
+
+    Ok(ProcessOutput {
+        termination,
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn options(timeout_ms: u64) -> RunOptions {
+        RunOptions {
+            timeout: Some(Duration::from_millis(timeout_ms)),
+            grace: Duration::from_secs(2),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exit_and_timeout() {
+        let output = run("sh", &["-c", "echo out; echo err >&2; exit 3"], &options(5_000)).await.unwrap();
+        assert_eq!(output.termination, Termination::Exited);
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!((output.stdout.as_str(), output.stderr.as_str()), ("out\n", "err\n"));
+        assert!(!output.success());
+
+        // Output written before the timeout is kept
+        let output = run("sh", &["-c", "echo partial; exec sleep 10"], &options(200)).await.unwrap();
+        assert_eq!(output.termination, Termination::TimedOut);
+        assert_eq!(output.stdout, "partial\n");
+        assert!(output.interrupted());
+
+        assert!(run("/nonexistent/tool", &[], &options(200)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_forked_processes_do_not_hold_the_run() {
+        let started = std::time::Instant::now();
+
+        // The shell's own child is stopped with it
+        let output = run("sh", &["-c", "echo partial; sleep 10; echo never"], &options(200)).await.unwrap();
+        assert_eq!(output.termination, Termination::TimedOut);
+        assert_eq!(output.stdout, "partial\n");
+
+        // A background process keeping the pipes open is not waited for
+        let mut opts = options(5_000);
+        opts.grace = Duration::from_millis(300);
+        let output = run("sh", &["-c", "echo done; sleep 10 & exit 0"], &opts).await.unwrap();
+        assert_eq!(output.termination, Termination::Exited);
+        assert_eq!(output.stdout, "done\n");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_sends_sigint_first() {
+        let token = CancelToken::new();
+        let mut opts = options(10_000);
+        opts.cancel = Some(token.clone());
+
+        let canceller = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            canceller.cancel();
+        });
+
+        // The script's INT handler runs, so it was interrupted rather than killed
+        let script = "trap 'echo flushed; exit 0' INT; echo started; while :; do sleep 0.05; done";
+        let mut lines = Vec::new();
+        let output = run_streaming("sh", &["-c", script], &opts, |line| lines.push(line.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(output.termination, Termination::Cancelled);
+        assert_eq!(output.stdout, "started\nflushed\n");
+        assert_eq!(lines, vec!["started", "flushed"]);
+        assert!(output.status.success());
+        assert!(token.is_cancelled());
+    }
+}
@@ -0,0 +1,404 @@
+//! Capture retention: storage quota, maximum age and keep-if-tagged rules.
+//!
+//! The policy is read from the `[retention]` section of the config file and
+//! can be overridden with environment variables (`UBERTOOTH_RETENTION_*`):
+//!
+//! ```toml
+//! [retention]
+//! max_total_size_mb = 2048
+//! max_age_days = 30
+//! keep_tags = ["keep", "evidence"]
+//! ```
+//!
+//! Pruning removes a capture's data files but keeps its metadata with a
+//! [`PruneRecord`](crate::capture_store::PruneRecord) saying when and why.
+//! Captures carrying any of the keep tags are never pruned. With no limits
+//! configured nothing is pruned.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use ubertooth_core::error::Result;
+use ubertooth_core::storage;
+
+use crate::capture_store::{CaptureMetadata, CaptureStore};
+
+/// Environment variable overriding `max_total_size_mb`.
+pub const MAX_SIZE_ENV: &str = "UBERTOOTH_RETENTION_MAX_SIZE_MB";
+
+/// Environment variable overriding `max_age_days`.
+pub const MAX_AGE_ENV: &str = "UBERTOOTH_RETENTION_MAX_AGE_DAYS";
+
+/// Environment variable overriding `keep_tags` (comma separated).
+pub const KEEP_TAGS_ENV: &str = "UBERTOOTH_RETENTION_KEEP_TAGS";
+
+/// Rule name for captures older than the maximum age.
+pub const RULE_MAX_AGE: &str = "max_age";
+
+/// Rule name for captures removed to get back under the quota.
+pub const RULE_MAX_TOTAL_SIZE: &str = "max_total_size";
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Retention policy for stored captures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// Quota for the data of all live captures
+    pub max_total_size_mb: Option<u64>,
+    /// Captures older than this are pruned
+    pub max_age_days: Option<u64>,
+    /// Captures with any of these tags are never pruned
+    pub keep_tags: Vec<String>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_total_size_mb: None,
+            max_age_days: None,
+            keep_tags: vec!["keep".to_string()],
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Load the policy from the config file and environment.
+    pub fn load() -> Result<Self> {
+        let mut policy: Self = storage::config_section("retention")?;
+
+        if let Some(mb) = std::env::var(MAX_SIZE_ENV).ok().and_then(|v| v.trim().parse().ok()) {
+            policy.max_total_size_mb = Some(mb).filter(|&mb| mb > 0);
+        }
+        if let Some(days) = std::env::var(MAX_AGE_ENV).ok().and_then(|v| v.trim().parse().ok()) {
+            policy.max_age_days = Some(days).filter(|&days| days > 0);
+        }
+        if let Ok(tags) = std::env::var(KEEP_TAGS_ENV) {
+            policy.keep_tags = split_tags(&tags);
+        }
+
+        Ok(policy)
+    }
+
+    /// Apply per-call overrides from tool parameters (`0` removes a limit).
+    pub fn with_params(mut self, params: &Value) -> Self {
+        if let Some(mb) = params.get("max_total_size_mb").and_then(|v| v.as_u64()) {
+            self.max_total_size_mb = Some(mb).filter(|&mb| mb > 0);
+        }
+        if let Some(days) = params.get("max_age_days").and_then(|v| v.as_u64()) {
+            self.max_age_days = Some(days).filter(|&days| days > 0);
+        }
+        if let Some(tags) = params.get("keep_tags").and_then(|v| v.as_array()) {
+            self.keep_tags = tags.iter().filter_map(|t| t.as_str()).map(str::to_string).collect();
+        }
+        self
+    }
+
+    /// Whether the policy never prunes anything.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_total_size_mb.is_none() && self.max_age_days.is_none()
+    }
+
+    /// Whether a capture is protected by a keep tag.
+    pub fn keeps(&self, metadata: &CaptureMetadata) -> bool {
+        metadata
+            .tags
+            .iter()
+            .any(|tag| self.keep_tags.iter().any(|keep| keep.eq_ignore_ascii_case(tag)))
+    }
+}
+
+/// A capture the policy would prune.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PruneCandidate {
+    pub capture_id: String,
+    pub capture_type: String,
+    pub timestamp: DateTime<Utc>,
+    pub file_size_bytes: u64,
+    pub rule: &'static str,
+    pub reason: String,
+}
+
+/// Storage used by live (unpruned) captures.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StorageUsage {
+    pub capture_count: usize,
+    pub pruned_count: usize,
+    pub used_bytes: u64,
+}
+
+impl StorageUsage {
+    /// Usage of a set of captures.
+    pub fn of(captures: &[CaptureMetadata]) -> Self {
+        let mut usage = Self::default();
+        for capture in captures {
+            if capture.pruned.is_some() {
+                usage.pruned_count += 1;
+            } else {
+                usage.capture_count += 1;
+                usage.used_bytes += capture.file_size_bytes;
+            }
+        }
+        usage
+    }
+}
+
+/// Outcome of a prune run (or preview).
+#[derive(Debug, Clone, Serialize)]
+pub struct PruneReport {
+    pub dry_run: bool,
+    pub policy: RetentionPolicy,
+    pub before: StorageUsage,
+    pub candidates: Vec<PruneCandidate>,
+    pub freed_bytes: u64,
+    /// Live data left after pruning
+    pub used_bytes_after: u64,
+    /// Still over quota because the rest is protected by keep tags
+    pub over_quota: bool,
+}
+
+/// Work out which captures `policy` prunes, oldest first.
+///
+/// The `protected` capture (one just saved) counts towards the quota but is
+/// never pruned itself.
+pub fn plan(
+    policy: &RetentionPolicy,
+    captures: &[CaptureMetadata],
+    protected: Option<&str>,
+    now: DateTime<Utc>,
+) -> Vec<PruneCandidate> {
+    let mut live: Vec<&CaptureMetadata> = captures.iter().filter(|c| c.pruned.is_none()).collect();
+    live.sort_by_key(|c| c.timestamp);
+
+    let mut used: u64 = live.iter().map(|c| c.file_size_bytes).sum();
+    let mut candidates = Vec::new();
+    let mut remaining = Vec::new();
+
+    let prunable = |c: &&CaptureMetadata| !policy.keeps(c) && protected != Some(c.capture_id.as_str());
+    for capture in live.into_iter().filter(prunable) {
+        let expired = policy
+            .max_age_days
+            .is_some_and(|days| now - capture.timestamp > Duration::days(days as i64));
+        if expired {
+            used -= capture.file_size_bytes;
+            candidates.push(candidate(
+                capture,
+                RULE_MAX_AGE,
+                format!(
+                    "Older than {} days (captured {})",
+                    policy.max_age_days.unwrap_or(0),
+                    capture.timestamp.format("%Y-%m-%d")
+                ),
+            ));
+        } else {
+            remaining.push(capture);
+        }
+    }
+
+    if let Some(limit) = policy.max_total_size_mb.map(|mb| mb * BYTES_PER_MB) {
+        for capture in remaining {
+            if used <= limit {
+                break;
+            }
+            candidates.push(candidate(
+                capture,
+                RULE_MAX_TOTAL_SIZE,
+                format!(
+                    "Storage over the {} MB quota ({:.1} MB used); oldest unprotected capture",
+                    limit / BYTES_PER_MB,
+                    used as f64 / BYTES_PER_MB as f64
+                ),
+            ));
+            used -= capture.file_size_bytes;
+        }
+    }
+
+    candidates
+}
+
+fn candidate(capture: &CaptureMetadata, rule: &'static str, reason: String) -> PruneCandidate {
+    PruneCandidate {
+        capture_id: capture.capture_id.clone(),
+        capture_type: capture.capture_type.clone(),
+        timestamp: capture.timestamp,
+        file_size_bytes: capture.file_size_bytes,
+        rule,
+        reason,
+    }
+}
+
+/// Apply `policy` to the store, sparing `protected`; with `dry_run` only
+/// report what would go.
+pub fn prune(store: &CaptureStore, policy: &RetentionPolicy, protected: Option<&str>, dry_run: bool) -> Result<PruneReport> {
+    let captures = store.list_captures()?;
+    let before = StorageUsage::of(&captures);
+    let candidates = plan(policy, &captures, protected, Utc::now());
+
+    let mut freed_bytes = 0;
+    if dry_run {
+        freed_bytes = candidates.iter().map(|c| c.file_size_bytes).sum();
+    } else {
+        for candidate in &candidates {
+            match store.prune_capture(&candidate.capture_id, candidate.rule, &candidate.reason) {
+                Ok(metadata) => {
+                    freed_bytes += metadata.pruned.as_ref().map(|p| p.freed_bytes).unwrap_or(0);
+                    tracing::info!("Pruned capture {}: {}", candidate.capture_id, candidate.reason);
+                }
+                Err(e) => tracing::warn!("Failed to prune capture {}: {}", candidate.capture_id, e),
+            }
+        }
+    }
+
+    let pruned_size: u64 = candidates.iter().map(|c| c.file_size_bytes).sum();
+    let used_bytes_after = before.used_bytes.saturating_sub(pruned_size);
+    let over_quota = policy
+        .max_total_size_mb
+        .is_some_and(|mb| used_bytes_after > mb * BYTES_PER_MB);
+
+    Ok(PruneReport {
+        dry_run,
+        policy: policy.clone(),
+        before,
+        candidates,
+        freed_bytes,
+        used_bytes_after,
+        over_quota,
+    })
+}
+
+/// Load the policy and prune; failures are logged, never returned.
+///
+/// Runs on agent startup and after each new capture, which is passed as
+/// `protected` so the data just recorded is not pruned by its own save.
+pub fn enforce(store: &CaptureStore, protected: Option<&str>) {
+    let policy = match RetentionPolicy::load() {
+        Ok(policy) => policy,
+        Err(e) => {
+            tracing::warn!("Retention policy not applied: {}", e);
+            return;
+        }
+    };
+    if policy.is_unlimited() {
+        return;
+    }
+
+    match prune(store, &policy, protected, false) {
+        Ok(report) if !report.candidates.is_empty() => tracing::info!(
+            "Retention pruned {} captures, freed {:.1} MB",
+            report.candidates.len(),
+            report.freed_bytes as f64 / BYTES_PER_MB as f64
+        ),
+        Ok(report) if report.over_quota => {
+            tracing::warn!("Capture storage over quota; remaining captures are protected by keep tags or new")
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Retention pruning failed: {}", e),
+    }
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',').map(str::trim).filter(|t| !t.is_empty()).map(str::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture_store::PruneRecord;
+    use serde_json::json;
+
+    fn capture(id: &str, age_days: i64, size_mb: u64, tags: &[&str], now: DateTime<Utc>) -> CaptureMetadata {
+        CaptureMetadata {
+            capture_id: id.to_string(),
+            timestamp: now - Duration::days(age_days),
+            capture_type: "btle_sniff".to_string(),
+            packet_count: 0,
+            duration_sec: Some(60),
+            file_size_bytes: size_mb * BYTES_PER_MB,
+            pcap_path: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            description: String::new(),
+            channels: Vec::new(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_plan_age_quota_and_keep_tags() {
+        let now = Utc::now();
+        let mut already_pruned = capture("cap-pruned", 90, 500, &[], now);
+        already_pruned.pruned = Some(PruneRecord {
+            pruned_at: now,
+            rule: RULE_MAX_AGE.to_string(),
+            reason: String::new(),
+            freed_bytes: 0,
+        });
+        let captures = vec![
+            capture("cap-old", 40, 10, &[], now),
+            capture("cap-old-kept", 45, 10, &["Keep"], now),
+            capture("cap-a", 10, 40, &[], now),
+            capture("cap-b", 5, 40, &[], now),
+            capture("cap-c", 1, 40, &[], now),
+            already_pruned,
+        ];
+
+        let unlimited = RetentionPolicy::default();
+        assert!(unlimited.is_unlimited());
+        assert!(plan(&unlimited, &captures, None, now).is_empty());
+
+        let policy = RetentionPolicy {
+            max_total_size_mb: Some(100),
+            max_age_days: Some(30),
+            ..RetentionPolicy::default()
+        };
+        let candidates = plan(&policy, &captures, None, now);
+        let pruned: Vec<(&str, &str)> = candidates.iter().map(|c| (c.capture_id.as_str(), c.rule)).collect();
+        // 140 MB live: cap-old ages out (130 MB), then the oldest goes for the quota (90 MB)
+        assert_eq!(pruned, vec![("cap-old", RULE_MAX_AGE), ("cap-a", RULE_MAX_TOTAL_SIZE)]);
+
+        let quota_only = RetentionPolicy {
+            max_total_size_mb: Some(5),
+            ..RetentionPolicy::default()
+        };
+        let candidates = plan(&quota_only, &captures, None, now);
+        assert_eq!(candidates.len(), 4);
+        assert!(candidates.iter().all(|c| c.capture_id != "cap-old-kept"));
+    }
+
+    #[test]
+    fn test_plan_spares_the_new_capture() {
+        let now = Utc::now();
+        let policy = RetentionPolicy {
+            max_total_size_mb: Some(50),
+            ..RetentionPolicy::default()
+        };
+
+        // A new capture bigger than the whole quota pushes out the older ones, not itself
+        let captures = vec![capture("cap-old", 3, 10, &[], now), capture("cap-new", 0, 80, &[], now)];
+        let candidates = plan(&policy, &captures, Some("cap-new"), now);
+        let pruned: Vec<&str> = candidates.iter().map(|c| c.capture_id.as_str()).collect();
+        assert_eq!(pruned, vec!["cap-old"]);
+
+        // With everything older kept, nothing can go
+        let captures = vec![capture("cap-kept", 3, 40, &["keep"], now), capture("cap-new", 0, 40, &[], now)];
+        assert!(plan(&policy, &captures, Some("cap-new"), now).is_empty());
+        assert_eq!(plan(&policy, &captures, None, now).len(), 1);
+    }
+
+    #[test]
+    fn test_policy_overrides() {
+        let policy = RetentionPolicy {
+            max_total_size_mb: Some(100),
+            ..RetentionPolicy::default()
+        }
+        .with_params(&json!({ "max_total_size_mb": 0, "max_age_days": 7, "keep_tags": ["evidence"] }));
+
+        assert_eq!(policy.max_total_size_mb, None);
+        assert_eq!(policy.max_age_days, Some(7));
+        assert_eq!(policy.keep_tags, vec!["evidence".to_string()]);
+        assert_eq!(split_tags(" keep, evidence ,,"), vec!["keep".to_string(), "evidence".to_string()]);
+    }
+}
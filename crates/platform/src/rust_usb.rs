@@ -4,15 +4,20 @@
 //! Ubertooth operations, achieving 100-200x speedup over Python backend.
 
 use async_trait::async_trait;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 use tracing::{debug, info, warn};
 use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_core::events::ToolEvent;
 use ubertooth_usb::UbertoothCommands;
 use ubertooth_usb::device_libusb::UbertoothDeviceLibusb;
 
 use crate::backend::UbertoothBackendProvider;
+use crate::calibration::{self, CalibrationRequest};
+use crate::capture_store::CaptureStore;
+use crate::custody::{self, DeviceIdentity};
+use crate::config_store::{ConfigStore, RssiCalibration};
 
 /// Native Rust USB backend.
 ///
@@ -38,6 +43,9 @@ impl RustUsbBackend {
         let device = Arc::new(Mutex::new(device));
         let commands = Arc::new(UbertoothCommands::new(device.clone()));
 
+        // Native scan results resolve vendors through the shared OUI database
+        crate::vendor_registry::ensure_loaded();
+
         Ok(Self {
             device,
             commands,
@@ -64,6 +72,9 @@ impl RustUsbBackend {
                 | "configure_channel"
                 | "configure_modulation"
                 | "configure_power"
+                | "configure_squelch"
+                | "configure_leds"
+                | "squelch_calibrate"
                 | "btle_scan"
                 | "btle_follow"
                 | "bt_specan"
@@ -81,9 +92,12 @@ impl RustUsbBackend {
             "configure_channel" => self.commands.configure_channel(params).await,
             "configure_modulation" => self.commands.configure_modulation(params).await,
             "configure_power" => self.commands.configure_power(params).await,
-            "btle_scan" => self.commands.btle_scan(params).await,
+            "configure_squelch" => self.commands.configure_squelch(params).await,
+            "configure_leds" => self.commands.configure_leds(params).await,
+            "squelch_calibrate" => self.squelch_calibrate(params).await,
+            "btle_scan" => self.btle_scan(params).await,
             "btle_follow" => self.commands.btle_follow(params).await,
-            "bt_specan" => self.commands.bt_specan(params).await,
+            "bt_specan" => self.bt_specan(params).await,
             _ => Err(UbertoothError::BackendError(format!(
                 "Method not implemented: {}",
                 method
@@ -92,6 +106,72 @@ impl RustUsbBackend {
     }
 }
 
+impl RustUsbBackend {
+    /// Stored RSSI calibration of the connected dongle, if any.
+    async fn calibration(&self) -> Option<RssiCalibration> {
+        calibration::for_device(self.commands.device_serial().await.ok().as_deref())
+    }
+
+    /// Run a native BLE scan and report RSSI on the calibrated scale.
+    async fn btle_scan(&self, params: Value) -> Result<Value> {
+        let mut result = self.commands.btle_scan(params).await?;
+        calibration::apply_to_result(&mut result, &["/devices_found"], self.calibration().await.as_ref());
+        Ok(result)
+    }
+
+    /// Run a native spectrum scan and store the waterfall recording.
+    async fn bt_specan(&self, params: Value) -> Result<Value> {
+        let (mut result, recording) = self.commands.bt_specan_recording(params).await?;
+
+        let store = CaptureStore::new()?;
+        let capture_id = CaptureStore::generate_capture_id("specan");
+        let (low, high) = (recording.header.low_freq_mhz, recording.header.high_freq_mhz);
+        let metadata = store.save_spectrum(
+            &capture_id,
+            &recording,
+            vec!["specan".to_string(), format!("{}-{}_MHz", low, high)],
+            format!("Spectrum scan {}-{} MHz", low, high),
+        )?;
+
+        result["capture_id"] = json!(capture_id);
+        result["capture_type"] = json!(metadata.capture_type);
+        result["recording_path"] = json!(metadata.pcap_path);
+
+        // The recording keeps raw readings; the summary is calibrated
+        calibration::apply_to_result(&mut result, &["/spectrum_data"], self.calibration().await.as_ref());
+        Ok(result)
+    }
+
+    /// Calibrate the noise floor with a native spectrum sweep, apply the
+    /// squelch and store the dongle's RSSI calibration.
+    async fn squelch_calibrate(&self, params: Value) -> Result<Value> {
+        let request = CalibrationRequest::from_params(&params)?;
+        let serial = self.commands.device_serial().await?;
+
+        let (_, recording) = self
+            .commands
+            .bt_specan_recording(json!({
+                "duration_sec": request.duration_sec,
+                "low_freq": request.low_freq_mhz,
+                "high_freq": request.high_freq_mhz
+            }))
+            .await?;
+        let calibration = calibration::calibrate(&ConfigStore::new()?, &serial, &recording, &request)?;
+
+        let applied = if request.apply {
+            let result = self
+                .commands
+                .configure_squelch(json!({ "squelch_level": calibration.recommended_squelch_dbm }))
+                .await?;
+            result["squelch_level"].as_i64()
+        } else {
+            None
+        };
+
+        Ok(calibration::calibration_result(&calibration, applied))
+    }
+}
+
 impl Default for RustUsbBackend {
     fn default() -> Self {
         Self::new().expect("Failed to create Rust USB backend")
@@ -106,7 +186,12 @@ impl UbertoothBackendProvider for RustUsbBackend {
             info!("Executing native USB method: {}", method);
 
             match self.execute_native(method, params.clone()).await {
-                Ok(result) => return Ok(result),
+                Ok(result) => {
+                    if matches!(method, "device_connect" | "device_status") {
+                        custody::set_device(DeviceIdentity::from_status(&result));
+                    }
+                    return Ok(result);
+                }
                 Err(e) => {
                     warn!("Native USB method failed: {} - {}", method, e);
 
@@ -175,4 +260,11 @@ impl UbertoothBackendProvider for RustUsbBackend {
     fn backend_type(&self) -> &str {
         "rust"
     }
+
+    fn set_event_sender(&self, events: broadcast::Sender<ToolEvent>) {
+        // Progress comes from the ubertooth-* tools run by the fallback
+        if let Some(fallback) = &self.python_fallback {
+            fallback.set_event_sender(events);
+        }
+    }
 }
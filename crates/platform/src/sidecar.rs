@@ -1,21 +1,43 @@
 //! Python sidecar manager for wrapping ubertooth-tools.
 
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use pcap_parser::*;
 use pcap_parser::traits::PcapReaderIterator;
 use serde_json::{json, Value};
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::broadcast;
 use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_core::events::ToolEvent;
+use ubertooth_protocol::compare::{self, CaptureProfile, CompareMode};
+use ubertooth_protocol::compat::{Feature, ToolVersions};
+use ubertooth_protocol::decode::{self, DecodedPacket};
+use ubertooth_protocol::export::{self, ExportField};
+use ubertooth_protocol::fingerprint;
+use ubertooth_protocol::interference;
+use ubertooth_protocol::merge::{self, MergeOptions};
+use ubertooth_protocol::pcapng;
+use ubertooth_protocol::spectrum::{self, SpectrumRecorder, SpectrumRecording};
+use ubertooth_protocol::tool_output::{self, OutputEvent};
+use ubertooth_protocol::trackers::{self, TrackerKind, TrackerSighting};
+use ubertooth_protocol::{assigned_numbers, AddressType, AdvertisingData, Beacon};
 
 use crate::backend::UbertoothBackendProvider;
-use crate::capture_store::{CaptureMetadata, CaptureStore};
-use crate::config_store::{ConfigMetadata, ConfigSettings, ConfigStore};
+use crate::bridge::Bridge;
+use crate::bundle;
+use crate::calibration::{self, CalibrationRequest};
+use crate::capture_catalog::{CaptureCatalog, CatalogQuery, DeviceSeen};
+use crate::capture_store::{CaptureMetadata, CaptureStore, PacketAnnotation, CAPTURE_TYPE_SPECTRUM};
+use crate::config_store::{self, ConfigMetadata, ConfigSettings, ConfigStore};
+use crate::custody::{self, DeviceIdentity, IntegrityCheck, IntegrityStatus};
+use crate::fingerprints::SignatureRegistry;
+use crate::process::{self, CancelToken, ProcessOutput, RunOptions, Termination};
+use crate::retention::{self, RetentionPolicy, StorageUsage};
+use crate::vendor_registry::{self, VendorRegistry};
 
 /// PCAP analysis results structure.
 #[derive(Debug)]
@@ -62,27 +84,37 @@ struct SecurityAnalysis {
 #[derive(Debug, Clone)]
 struct BleDevice {
     mac_address: String,
+    random_address: bool,
     name: Option<String>,
     rssi: i8,
     pdu_type: String,
     first_seen: f64,
     last_seen: f64,
     packet_count: usize,
+    advertising: Option<AdvertisingData>,
 }
 
 /// Python sidecar process manager.
 ///
 /// The sidecar wraps the ubertooth-* command-line tools and provides a
-/// simple interface for executing commands.
+/// simple interface for executing commands. The tools run through a
+/// persistent helper process (see [`crate::bridge`]), or directly when no
+/// Python interpreter is available.
 pub struct SidecarManager {
-    process: Arc<Mutex<Option<Child>>>,
+    bridge: Bridge,
+    cancel: std::sync::Mutex<CancelToken>,
+    events: std::sync::Mutex<Option<broadcast::Sender<ToolEvent>>>,
+    versions: std::sync::Mutex<Option<ToolVersions>>,
 }
 
 impl SidecarManager {
     /// Create a new sidecar manager (not started yet).
     pub fn new() -> Arc<Self> {
         Arc::new(Self {
-            process: Arc::new(Mutex::new(None)),
+            bridge: Bridge::new(),
+            cancel: std::sync::Mutex::new(CancelToken::new()),
+            events: std::sync::Mutex::new(None),
+            versions: std::sync::Mutex::new(None),
         })
     }
 
@@ -107,37 +139,177 @@ impl SidecarManager {
         Ok(())
     }
 
-    /// Spawn the Python sidecar process (if needed in future).
+    /// Run `program` through the sidecar helper, or directly if it cannot be started.
+    async fn spawn_streaming<F>(&self, program: &str, args: &[&str], options: &RunOptions, on_line: F) -> Result<ProcessOutput>
+    where
+        F: FnMut(&str) + Send,
+    {
+        if self.bridge.available().await {
+            self.bridge.run_streaming(program, args, options, on_line).await
+        } else {
+            tracing::debug!("Sidecar helper unavailable, running {} directly", program);
+            process::run_streaming(program, args, options, on_line).await
+        }
+    }
+
+    /// Cancel the ubertooth-* commands that are currently running.
     ///
-    /// For now, we'll call ubertooth-* tools directly without a persistent sidecar.
-    async fn _spawn(&self) -> Result<()> {
-        // Phase 1: We'll call ubertooth-* tools directly
-        // Phase 2: If needed, we could spawn a persistent Python process
-        Ok(())
+    /// Each command is sent SIGINT (so captures are flushed) and killed if it
+    /// does not exit within [`process::INTERRUPT_GRACE`]. Commands started
+    /// afterwards are not affected. Returns the number of commands cancelled.
+    pub fn cancel_running(&self) -> usize {
+        let mut token = self.cancel.lock().unwrap_or_else(|e| e.into_inner());
+        let running = token.waiting();
+        token.cancel();
+        *token = CancelToken::new();
+        running
+    }
+
+    /// Run options for a command cancellable through [`cancel_running`](Self::cancel_running).
+    fn run_options(&self, mut options: RunOptions) -> RunOptions {
+        let token = self.cancel.lock().unwrap_or_else(|e| e.into_inner());
+        options.cancel = Some(token.clone());
+        options
     }
 
-    /// Execute a ubertooth command-line tool.
+    /// Detect the host tools and firmware versions with `ubertooth-util`.
     ///
-    /// Filters out benign API version mismatch warnings from stderr.
-    async fn execute_ubertooth_command(
+    /// `-v` prints the firmware release and API version, `-V` the firmware
+    /// compile info, and either prints libubertooth's API mismatch warning.
+    /// Commands that fail (e.g. no device attached) contribute what they can.
+    async fn detect_versions(&self) -> ToolVersions {
+        let mut versions = ToolVersions::default();
+        let options = RunOptions {
+            timeout: Some(std::time::Duration::from_secs(10)),
+            ..Default::default()
+        };
+        for flag in ["-v", "-V"] {
+            match self.spawn_streaming("ubertooth-util", &[flag], &options, |_| {}).await {
+                Ok(output) => {
+                    versions.update(&output.stdout);
+                    versions.update(&output.stderr);
+                }
+                Err(e) => tracing::debug!("Version detection with ubertooth-util {} failed: {}", flag, e),
+            }
+        }
+        tracing::debug!("Detected ubertooth versions: {:?}", versions);
+
+        // Only cache once the device answered; it may be plugged in later
+        if versions.firmware_release.is_some() || versions.firmware_api.is_some() {
+            *self.versions.lock().unwrap_or_else(|e| e.into_inner()) = Some(versions.clone());
+        }
+        versions
+    }
+
+    /// The detected versions, detecting them on first use.
+    async fn tool_versions(&self) -> ToolVersions {
+        let cached = self.versions.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        match cached {
+            Some(versions) => versions,
+            None => self.detect_versions().await,
+        }
+    }
+
+    /// The detected versions and the compatibility matrix as JSON.
+    fn versions_json(versions: &ToolVersions) -> (Value, Value) {
+        let matrix: serde_json::Map<String, Value> = versions
+            .matrix()
+            .into_iter()
+            .map(|(feature, support)| {
+                let key = serde_json::to_value(feature).ok().and_then(|k| k.as_str().map(str::to_string)).unwrap_or_default();
+                (key, json!(support))
+            })
+            .collect();
+        (json!(versions), Value::Object(matrix))
+    }
+
+    /// Fail early when `feature` is known not to work with the installed versions.
+    async fn require(&self, feature: Feature) -> Result<()> {
+        self.tool_versions().await.check(feature).map_err(UbertoothError::Incompatible)
+    }
+
+    /// Report a parsed output event as `ToolEvent::Progress` for `method`.
+    fn emit_progress(&self, method: &str, tool: &str, event: &OutputEvent) {
+        let events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(sender) = events.as_ref() {
+            // No subscribers is fine
+            let _ = sender.send(ToolEvent::Progress {
+                tool_name: method.to_string(),
+                data: json!({
+                    "source": tool,
+                    "message": event.summary(),
+                    "event": event,
+                }),
+            });
+        }
+    }
+
+    /// Run a ubertooth command-line tool, returning its output however it ended.
+    ///
+    /// Use this for tools whose partial results matter when they are stopped
+    /// early (captures). A benign API version warning is removed from stderr,
+    /// a fatal one is replaced by an explanation.
+    async fn run_ubertooth_command(&self, tool: &str, args: &[&str], options: RunOptions) -> Result<ProcessOutput> {
+        self.run_ubertooth_command_streaming(tool, args, options, |_| {}).await
+    }
+
+    /// Run a ubertooth command-line tool, parsing its output line by line as it
+    /// runs. Every parsed event is reported as progress of `method` and returned
+    /// along with the output.
+    async fn stream_ubertooth_command(
         &self,
+        method: &str,
         tool: &str,
         args: &[&str],
-    ) -> Result<String> {
+        options: RunOptions,
+    ) -> Result<(ProcessOutput, Vec<OutputEvent>)> {
+        let mut parser = tool_output::parser_for(tool)
+            .ok_or_else(|| UbertoothError::BackendError(format!("No output parser for {}", tool)))?;
+        let mut events = Vec::new();
+
+        let output = self
+            .run_ubertooth_command_streaming(tool, args, options, |line| {
+                for event in parser.feed_line(line) {
+                    self.emit_progress(method, tool, &event);
+                    events.push(event);
+                }
+            })
+            .await?;
+        for event in parser.finish() {
+            self.emit_progress(method, tool, &event);
+            events.push(event);
+        }
+
+        Ok((output, events))
+    }
+
+    async fn run_ubertooth_command_streaming<F>(
+        &self,
+        tool: &str,
+        args: &[&str],
+        options: RunOptions,
+        on_line: F,
+    ) -> Result<ProcessOutput>
+    where
+        F: FnMut(&str) + Send,
+    {
         tracing::debug!("Executing: {} {:?}", tool, args);
 
-        let output = Command::new(tool)
-            .args(args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(|e| {
-                UbertoothError::BackendError(format!("Failed to execute {}: {}", tool, e))
-            })?;
+        let mut output = self.spawn_streaming(tool, args, &self.run_options(options), on_line).await?;
 
-        // Filter stderr to remove benign API version warnings
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let filtered_stderr = stderr
+        // API version warnings tell us the versions in use; only a firmware
+        // older than libubertooth is fatal, newer firmware is benign
+        let mut mismatch = None;
+        if output.stderr.contains("API version") {
+            let reported = ToolVersions::parse(&output.stderr);
+            tracing::debug!("{} reported API versions: {:?}", tool, reported);
+            if let Some(versions) = self.versions.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+                versions.update(&output.stderr);
+            }
+            mismatch = reported.api_mismatch();
+        }
+        output.stderr = output
+            .stderr
             .lines()
             .filter(|line| {
                 // Filter out API version mismatch warnings (firmware newer than libubertooth)
@@ -147,18 +319,49 @@ impl SidecarManager {
             })
             .collect::<Vec<_>>()
             .join("\n");
+        if let Some(reason) = mismatch {
+            output.stderr = format!("{}\n{}", reason, output.stderr).trim_end().to_string();
+        }
 
-        // Log filtered warnings at debug level for troubleshooting
-        if stderr.contains("API version") {
-            tracing::debug!("Filtered benign API version warning from {}", tool);
+        Ok(output)
+    }
+
+    /// Execute a ubertooth command-line tool with the default timeout.
+    ///
+    /// Filters out benign API version mismatch warnings from stderr.
+    async fn execute_ubertooth_command(
+        &self,
+        tool: &str,
+        args: &[&str],
+    ) -> Result<String> {
+        self.execute_ubertooth_command_with(tool, args, RunOptions::default()).await
+    }
+
+    /// Execute a ubertooth command-line tool, failing unless it exits successfully.
+    async fn execute_ubertooth_command_with(
+        &self,
+        tool: &str,
+        args: &[&str],
+        options: RunOptions,
+    ) -> Result<String> {
+        let output = self.run_ubertooth_command(tool, args, options).await?;
+
+        match output.termination {
+            Termination::TimedOut => {
+                return Err(UbertoothError::CommandFailed(format!("{} timed out", tool)));
+            }
+            Termination::Cancelled => {
+                return Err(UbertoothError::CommandFailed(format!("{} was cancelled", tool)));
+            }
+            Termination::Exited | Termination::Stopped => {}
         }
 
-        if !output.status.success() {
+        if !output.success() {
             // Only return actual errors, not filtered warnings
-            if !filtered_stderr.trim().is_empty() {
+            if !output.stderr.trim().is_empty() {
                 return Err(UbertoothError::CommandFailed(format!(
                     "{} failed: {}",
-                    tool, filtered_stderr
+                    tool, output.stderr
                 )));
             } else {
                 return Err(UbertoothError::CommandFailed(format!(
@@ -168,15 +371,17 @@ impl SidecarManager {
             }
         }
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        Ok(stdout)
+        Ok(output.stdout)
     }
 }
 
 impl Default for SidecarManager {
     fn default() -> Self {
         Self {
-            process: Arc::new(Mutex::new(None)),
+            bridge: Bridge::new(),
+            cancel: std::sync::Mutex::new(CancelToken::new()),
+            events: std::sync::Mutex::new(None),
+            versions: std::sync::Mutex::new(None),
         }
     }
 }
@@ -189,6 +394,7 @@ impl UbertoothBackendProvider for SidecarManager {
             "device_connect" => self.device_connect().await,
             "device_disconnect" => self.device_disconnect().await,
             "device_status" => self.device_status().await,
+            "cancel" => Ok(json!({ "success": true, "cancelled": self.cancel_running() })),
             "btle_scan" => self.btle_scan(params).await,
             "bt_specan" => self.bt_specan(params).await,
             "configure_channel" => self.configure_channel(params).await,
@@ -198,6 +404,10 @@ impl UbertoothBackendProvider for SidecarManager {
             "capture_get" => self.capture_get(params).await,
             "capture_delete" => self.capture_delete(params).await,
             "capture_tag" => self.capture_tag(params).await,
+            "capture_prune" => self.capture_prune(params).await,
+            "capture_verify" => self.capture_verify(params).await,
+            "capture_bundle_export" => self.capture_bundle_export(params).await,
+            "capture_bundle_import" => self.capture_bundle_import(params).await,
             "bt_analyze" => self.bt_analyze(params).await,
             "session_context" => self.session_context(params).await,
             "bt_scan" => self.bt_scan(params).await,
@@ -206,12 +416,14 @@ impl UbertoothBackendProvider for SidecarManager {
             "bt_discover" => self.bt_discover(params).await,
             "btle_follow" => self.btle_follow(params).await,
             "configure_squelch" => self.configure_squelch(params).await,
+            "squelch_calibrate" => self.squelch_calibrate(params).await,
             "configure_leds" => self.configure_leds(params).await,
             "bt_save_config" => self.bt_save_config(params).await,
             "bt_load_config" => self.bt_load_config(params).await,
             "config_list" => self.config_list(params).await,
             "config_delete" => self.config_delete(params).await,
             "bt_compare" => self.bt_compare(params).await,
+            "bt_tracker_detect" => self.bt_tracker_detect(params).await,
             "bt_decode" => self.bt_decode(params).await,
             "bt_fingerprint" => self.bt_fingerprint(params).await,
             "pcap_merge" => self.pcap_merge(params).await,
@@ -222,6 +434,12 @@ impl UbertoothBackendProvider for SidecarManager {
             "btle_mitm" => self.btle_mitm(params).await,
             "bt_spoof" => self.bt_spoof(params).await,
             "ubertooth_raw" => self.ubertooth_raw(params).await,
+            "oui_update" => self.oui_update(params).await,
+            "specan_list" => self.specan_list(params).await,
+            "specan_replay" => self.specan_replay(params).await,
+            "specan_slice" => self.specan_slice(params).await,
+            "specan_summary" => self.specan_summary(params).await,
+            "specan_interference" => self.specan_interference(params).await,
             _ => Err(UbertoothError::BackendError(format!(
                 "Method not implemented: {}",
                 method
@@ -230,22 +448,34 @@ impl UbertoothBackendProvider for SidecarManager {
     }
 
     async fn is_alive(&self) -> bool {
-        // Check if ubertooth-util responds
-        let result = Command::new("ubertooth-util")
-            .arg("-V")
-            .output();
-
-        result.is_ok()
+        // Report the helper as it is; a dead one is replaced by restart() or the next call
+        match self.bridge.check().await {
+            Some(alive) => {
+                if !alive {
+                    tracing::warn!("Sidecar helper is not alive");
+                }
+                alive
+            }
+            None => {
+                // No helper yet, or no interpreter and tools run directly
+                true
+            }
+        }
     }
 
     async fn restart(&self) -> Result<()> {
-        // No persistent process to restart in Phase 1
-        Ok(())
+        // The device may have been replugged or reflashed meanwhile
+        *self.versions.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        self.bridge.restart().await
     }
 
     fn backend_type(&self) -> &str {
         "python"
     }
+
+    fn set_event_sender(&self, events: broadcast::Sender<ToolEvent>) {
+        *self.events.lock().unwrap_or_else(|e| e.into_inner()) = Some(events);
+    }
 }
 
 impl SidecarManager {
@@ -254,6 +484,12 @@ impl SidecarManager {
         // Check if tools are installed
         Self::check_ubertooth_installed()?;
 
+        // The firmware may have been reflashed since the last connect
+        let versions = self.detect_versions().await;
+        if let Some(reason) = versions.api_mismatch() {
+            return Err(UbertoothError::Incompatible(reason));
+        }
+
         // Get device information using ubertooth-util
         let output = self
             .execute_ubertooth_command("ubertooth-util", &["-V"])
@@ -270,14 +506,23 @@ impl SidecarManager {
                     .nth(1)
                     .map(|s| s.to_string())
             })
+            .or_else(|| versions.firmware_release.clone())
             .unwrap_or_else(|| "unknown".to_string());
+        let serial = self.device_serial().await.ok();
+        let (versions, compatibility) = Self::versions_json(&versions);
 
-        Ok(json!({
+        let result = json!({
             "success": true,
             "device_id": "ubertooth-001",
+            "serial": serial,
             "firmware_version": firmware_version,
+            "versions": versions,
+            "compatibility": compatibility,
             "message": "Connected to Ubertooth One"
-        }))
+        });
+        custody::set_device(DeviceIdentity::from_status(&result));
+
+        Ok(result)
     }
 
     /// Device disconnect implementation.
@@ -311,14 +556,20 @@ impl SidecarManager {
                     .map(|s| s.to_string())
             })
             .unwrap_or_else(|| "unknown".to_string());
+        let (versions, compatibility) = Self::versions_json(&self.tool_versions().await);
 
-        Ok(json!({
+        let result = json!({
             "success": true,
             "connected": true,
             "device_id": "ubertooth-001",
             "firmware": firmware_version,
+            "versions": versions,
+            "compatibility": compatibility,
             "current_mode": "idle"
-        }))
+        });
+        custody::set_device(DeviceIdentity::from_status(&result));
+
+        Ok(result)
     }
 
     /// BLE scan implementation.
@@ -345,8 +596,20 @@ impl SidecarManager {
         // Generate capture ID
         let capture_id = CaptureStore::generate_capture_id("btle");
 
-        // BLE advertising channels
-        let channels = vec![37u64, 38u64, 39u64];
+        // BLE advertising channels; without -A the tool only listens on 37
+        self.require(Feature::BtlePcap).await?;
+        let versions = self.tool_versions().await;
+        let mut notes = Vec::new();
+        let channels = match versions.check(Feature::BtleAdvertisingChannel) {
+            Ok(()) => vec![37u64, 38u64, 39u64],
+            Err(reason) => {
+                tracing::warn!("Scanning channel 37 only: {}", reason);
+                notes.push(format!("Scanned channel 37 only: {}", reason));
+                vec![37u64]
+            }
+        };
+        let select_channel = channels.len() > 1;
+        let channel_list = channels.iter().map(u64::to_string).collect::<Vec<_>>().join(", ");
 
         // Split duration across channels
         let duration_per_channel = std::cmp::max(total_duration / channels.len() as u64, 1);
@@ -354,6 +617,8 @@ impl SidecarManager {
         // Scan each channel and collect results
         let mut channel_pcaps = Vec::new();
         let mut total_packets = 0u64;
+        let mut scanned_channels = Vec::new();
+        let mut cancelled = false;
 
         for ch in &channels {
             tracing::info!("Scanning channel {} for {}s...", ch, duration_per_channel);
@@ -366,11 +631,19 @@ impl SidecarManager {
                 .to_string();
 
             // Scan single channel
-            match self.scan_single_channel(*ch, duration_per_channel, &channel_pcap_str).await {
-                Ok(packet_count) => {
+            match self.scan_single_channel(select_channel.then_some(*ch), duration_per_channel, &channel_pcap_str).await {
+                Ok((packet_count, termination)) => {
                     total_packets += packet_count;
                     channel_pcaps.push(channel_pcap_str);
+                    scanned_channels.push(*ch);
                     tracing::info!("Channel {} scan complete: {} packets", ch, packet_count);
+
+                    // Keep what was captured so far, but don't start the next channel
+                    if termination == Termination::Cancelled {
+                        tracing::info!("BLE scan cancelled on channel {}", ch);
+                        cancelled = true;
+                        break;
+                    }
                 }
                 Err(e) => {
                     tracing::warn!("Channel {} scan failed: {}", ch, e);
@@ -414,7 +687,17 @@ impl SidecarManager {
             file_size_bytes: file_size,
             pcap_path: final_pcap_str.to_string(),
             tags: vec!["ble".to_string(), "multi-channel".to_string()],
-            description: format!("Multi-channel BLE scan (channels 37, 38, 39)"),
+            description: if cancelled {
+                format!("Multi-channel BLE scan (channels {}), cancelled after channel {}", channel_list, scanned_channels.last().copied().unwrap_or(37))
+            } else {
+                format!("Multi-channel BLE scan (channels {})", channel_list)
+            },
+            channels: scanned_channels.iter().map(|&ch| ch as u8).collect(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
         };
 
         // Save metadata
@@ -426,56 +709,83 @@ impl SidecarManager {
             file_size
         );
 
-        // Return result
-        Ok(json!({
+        // Extract advertisers from the merged capture
+        let devices_found: Vec<Value> = if file_size > 0 {
+            match store.open_data(&metadata).and_then(|data| Self::parse_pcap(&data.path().to_string_lossy())) {
+                Ok(analysis) => analysis.devices.iter().map(|dev| {
+                    json!({
+                        "mac_address": dev.mac_address,
+                        "address_type": AddressType::from_bit(dev.random_address).as_str(),
+                        "vendor": vendor_registry::vendor_for(&dev.mac_address, dev.random_address),
+                        "device_name": dev.name.as_deref().unwrap_or("Unknown"),
+                        "rssi_avg": dev.rssi,
+                        "packet_count": dev.packet_count,
+                        "advertising_data": dev.advertising.as_ref().map(AdvertisingData::to_json_summary)
+                    })
+                }).collect(),
+                Err(e) => {
+                    tracing::warn!("Failed to extract devices from capture: {}", e);
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+
+        let mut result = json!({
             "success": true,
             "capture_id": capture_id,
             "scan_duration_sec": total_duration,
-            "channels_scanned": channels,
-            "devices_found": [],  // TODO Phase 2: Parse PCAP to extract devices
+            "channels_scanned": scanned_channels,
+            "cancelled": cancelled,
+            "devices_found": devices_found,
             "total_packets": total_packets,
             "pcap_path": final_pcap_str,
+            "compatibility_notes": notes,
             "preview": [
-                format!("Scanned channels 37, 38, 39 ({}s each)", duration_per_channel),
+                format!("Scanned channels {} ({}s each)", channel_list, duration_per_channel),
                 format!("Captured {} BLE packets total", total_packets),
                 format!("Saved to: {}", final_pcap_str)
             ]
-        }))
+        });
+
+        // Report RSSI on the connected dongle's calibrated scale
+        let calibration = calibration::for_device(custody::current_device().serial.as_deref());
+        calibration::apply_to_result(&mut result, &["/devices_found"], calibration.as_ref());
+
+        Ok(result)
     }
 
     /// Scan a single BLE advertising channel
-    async fn scan_single_channel(&self, channel: u64, duration_sec: u64, pcap_path: &str) -> Result<u64> {
-        use tokio::time::Duration;
-        use std::process::Stdio;
-
+    ///
+    /// ubertooth-btle runs until stopped, so it is interrupted after
+    /// `duration_sec`; SIGINT lets it close the PCAP cleanly. Without a
+    /// `channel` the tool's default advertising channel (37) is used.
+    async fn scan_single_channel(&self, channel: Option<u64>, duration_sec: u64, pcap_path: &str) -> Result<(u64, Termination)> {
         // Build ubertooth-btle command
-        let channel_str = channel.to_string();
-        let args = vec![
-            "-n",              // Scan mode (don't follow connections)
-            "-A",
-            channel_str.as_str(),
-            "-q",
-            pcap_path,
-        ];
-
-        tracing::debug!("Executing: ubertooth-btle {:?}", args);
-
-        // Spawn process so we can kill it
-        let mut child = tokio::process::Command::new("ubertooth-btle")
-            .args(&args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| UbertoothError::BackendError(format!("Failed to spawn ubertooth-btle: {}", e)))?;
+        let channel_str = channel.map(|ch| ch.to_string());
+        let mut args = vec!["-n"]; // Scan mode (don't follow connections)
+        if let Some(channel_str) = &channel_str {
+            args.extend_from_slice(&["-A", channel_str.as_str()]);
+        }
+        args.extend_from_slice(&["-q", pcap_path]);
 
-        // Wait for duration, then kill process
-        tokio::time::sleep(Duration::from_secs(duration_sec)).await;
+        let options = RunOptions::stop_after(std::time::Duration::from_secs(duration_sec));
+        let (output, events) = self
+            .stream_ubertooth_command("btle_scan", "ubertooth-btle", &args, options)
+            .await?;
 
-        tracing::debug!("Duration elapsed, killing ubertooth-btle process...");
-        let _ = child.kill().await;
+        if output.termination == Termination::Exited && !output.status.success() {
+            return Err(UbertoothError::CommandFailed(format!(
+                "ubertooth-btle failed: {}",
+                output.stderr.trim()
+            )));
+        }
 
-        let output_result = child.wait_with_output().await
-            .map_err(|e| UbertoothError::BackendError(format!("Failed to wait for ubertooth-btle: {}", e)))?;
+        let printed = events.iter().filter(|e| matches!(e, OutputEvent::BlePacket(_))).count() as u64;
+        if printed > 0 {
+            return Ok((printed, output.termination));
+        }
 
         // Count packets from PCAP file size (rough estimate)
         let file_size = std::fs::metadata(pcap_path)
@@ -489,7 +799,7 @@ impl SidecarManager {
             0
         };
 
-        Ok(packet_count)
+        Ok((packet_count, output.termination))
     }
 
     /// Merge multiple PCAP files into one using mergecap or manual merge
@@ -560,6 +870,12 @@ impl SidecarManager {
             .and_then(|v| v.as_u64())
             .unwrap_or(10);
 
+        let rssi_threshold = params
+            .get("rssi_threshold")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(-90)
+            .clamp(-128, 0) as i8;
+
         tracing::info!(
             "Starting spectrum scan: {}-{} MHz, duration={}s",
             low_freq,
@@ -573,16 +889,86 @@ impl SidecarManager {
         // Generate capture ID
         let capture_id = CaptureStore::generate_capture_id("specan");
 
-        // Prepare output file path
-        let pcap_path = store.captures_dir().join(format!("{}.pcap", capture_id));
-        let pcap_path_str = pcap_path
-            .to_str()
-            .ok_or_else(|| UbertoothError::BackendError("Invalid path".to_string()))?;
+        // Record every sweep into a waterfall recording
+        let recording = self
+            .record_spectrum("bt_specan", low_freq as u16, high_freq as u16, duration_sec)
+            .await?;
 
+        let metadata = store.save_spectrum(
+            &capture_id,
+            &recording,
+            vec!["specan".to_string(), format!("{}-{}_MHz", low_freq, high_freq)],
+            format!("Spectrum scan {}-{} MHz", low_freq, high_freq),
+        )?;
+
+        // Per-frequency statistics over all sweeps
+        let peak = recording.peak_hold();
+        let average = recording.average();
+        let occupancy = recording.occupancy(rssi_threshold);
+        let mut scan_results = Vec::new();
+        let mut hotspots = Vec::new();
+        for (idx, freq) in recording.frequencies().into_iter().enumerate() {
+            let Some(rssi_avg) = average[idx] else {
+                continue;
+            };
+            let rssi_min = recording
+                .sweeps
+                .iter()
+                .map(|s| s.rssi[idx])
+                .filter(|v| *v != spectrum::NO_SAMPLE)
+                .min();
+
+            scan_results.push(json!({
+                "frequency_mhz": freq,
+                "channel": freq.saturating_sub(2402),
+                "rssi_avg": rssi_avg.round() as i64,
+                "rssi_max": peak[idx],
+                "rssi_min": rssi_min,
+                "activity_percent": occupancy[idx]
+            }));
+
+            // Identify hotspots (frequencies with high RSSI)
+            if let Some(rssi_max) = peak[idx].filter(|p| *p > -70) {
+                hotspots.push(json!({
+                    "frequency_mhz": freq,
+                    "rssi_max": rssi_max,
+                    "interpretation": "High activity detected"
+                }));
+            }
+        }
+
+        tracing::info!(
+            "Spectrum scan complete: {} sweeps, {} frequency points",
+            recording.sweeps.len(),
+            scan_results.len()
+        );
+
+        let mut result = json!({
+            "success": true,
+            "capture_id": capture_id,
+            "capture_type": metadata.capture_type,
+            "frequency_range": [low_freq, high_freq],
+            "duration_sec": duration_sec,
+            "sweep_count": recording.sweeps.len(),
+            "recording_path": metadata.pcap_path,
+            "scan_results": scan_results,
+            "hotspots": hotspots
+        });
+
+        // The recording keeps raw readings; the summary is calibrated
+        let calibration = calibration::for_device(custody::current_device().serial.as_deref());
+        calibration::apply_to_result(&mut result, &["/scan_results", "/hotspots"], calibration.as_ref());
+
+        Ok(result)
+    }
+
+    /// Run `ubertooth-specan` over a band and record the sweeps.
+    ///
+    /// Sweeps are reported as progress of `method` while the scan runs.
+    async fn record_spectrum(&self, method: &str, low_freq: u16, high_freq: u16, duration_sec: u64) -> Result<SpectrumRecording> {
         // Build ubertooth-specan command
         // -l: low frequency
         // -u: high (upper) frequency
-        // -t: timeout duration (estimated based on range)
         let low_str = low_freq.to_string();
         let high_str = high_freq.to_string();
         let args = vec![
@@ -592,88 +978,64 @@ impl SidecarManager {
             high_str.as_str(),
         ];
 
+        self.require(Feature::Specan).await?;
         tracing::debug!("Executing: ubertooth-specan {:?}", args);
 
-        // Execute ubertooth-specan (with timeout)
-        // Note: ubertooth-specan outputs to stdout, we'll capture it
-        let output = tokio::time::timeout(
-            tokio::time::Duration::from_secs(duration_sec + 5),
-            self.execute_ubertooth_command("ubertooth-specan", &args),
-        )
-        .await
-        .map_err(|_| UbertoothError::BackendError("Spectrum scan timed out".to_string()))?
-        ?;
-
-        tracing::debug!("ubertooth-specan output length: {} bytes", output.len());
+        let start_unix_ms = Utc::now().timestamp_millis() as u64;
 
-        // Parse output for RSSI data
-        // ubertooth-specan outputs frequency and RSSI values
-        let mut scan_results = Vec::new();
-        for line in output.lines().take(100) {
-            // Limit to first 100 lines for Phase 1
-            if let Some((freq_str, rssi_str)) = line.split_once(',') {
-                if let (Ok(freq), Ok(rssi)) = (freq_str.trim().parse::<i32>(), rssi_str.trim().parse::<i32>()) {
-                    let channel = (freq - 2402).max(0);
-                    scan_results.push(json!({
-                        "frequency_mhz": freq,
-                        "channel": channel,
-                        "rssi_avg": rssi,
-                        "rssi_max": rssi,
-                        "rssi_min": rssi,
-                        "activity_percent": if rssi > -80 { 50.0 } else { 0.0 }
-                    }));
-                }
-            }
+        // ubertooth-specan sweeps until stopped and writes sweeps to stdout;
+        // whatever it printed before being stopped (or cancelled) is kept
+        let options = RunOptions::stop_after(std::time::Duration::from_secs(duration_sec));
+        let (result, _) = self.stream_ubertooth_command(method, "ubertooth-specan", &args, options).await?;
+        if result.termination == Termination::Exited && !result.success() {
+            return Err(UbertoothError::CommandFailed(format!(
+                "ubertooth-specan failed: {}",
+                result.stderr.trim()
+            )));
         }
+        let output = result.stdout;
 
-        // Identify hotspots (frequencies with high RSSI)
-        let mut hotspots = Vec::new();
-        for result in &scan_results {
-            if let Some(rssi) = result.get("rssi_max").and_then(|v| v.as_i64()) {
-                if rssi > -70 {
-                    hotspots.push(json!({
-                        "frequency_mhz": result["frequency_mhz"],
-                        "rssi_max": rssi,
-                        "interpretation": "High activity detected"
-                    }));
-                }
-            }
-        }
+        tracing::debug!("ubertooth-specan output length: {} bytes", output.len());
 
-        // Create capture metadata
-        let file_size = std::fs::metadata(&pcap_path)
-            .map(|m| m.len())
-            .unwrap_or(0);
+        Ok(Self::parse_specan_output(&output, low_freq, high_freq, start_unix_ms, duration_sec))
+    }
 
-        let metadata = CaptureMetadata {
-            capture_id: capture_id.clone(),
-            timestamp: Utc::now(),
-            capture_type: "specan".to_string(),
-            packet_count: scan_results.len(),
-            duration_sec: Some(duration_sec),
-            file_size_bytes: file_size,
-            pcap_path: pcap_path_str.to_string(),
-            tags: vec!["specan".to_string(), format!("{}-{}_MHz", low_freq, high_freq)],
-            description: format!("Spectrum scan {}-{} MHz", low_freq, high_freq),
-        };
+    /// Parse `ubertooth-specan` output ("time, freq, rssi" or "freq, rssi" lines)
+    /// into a waterfall recording.
+    ///
+    /// Lines without a timestamp are spread evenly over the scan duration.
+    fn parse_specan_output(
+        output: &str,
+        low_freq: u16,
+        high_freq: u16,
+        start_unix_ms: u64,
+        duration_sec: u64,
+    ) -> SpectrumRecording {
+        let samples: Vec<(Option<f64>, u16, i8)> = output
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                match fields.as_slice() {
+                    [time, freq, rssi] => Some((Some(time.parse().ok()?), freq.parse().ok()?, rssi.parse().ok()?)),
+                    [freq, rssi] => Some((None, freq.parse().ok()?, rssi.parse().ok()?)),
+                    _ => None,
+                }
+            })
+            .collect();
 
-        // Save metadata
-        store.save_metadata(&metadata)?;
+        let first_time = samples.iter().find_map(|(t, _, _)| *t);
+        let total = samples.len().max(1) as u64;
+        let mut recorder = SpectrumRecorder::new(low_freq, high_freq, start_unix_ms, "ubertooth-specan");
 
-        tracing::info!(
-            "Spectrum scan complete: {} frequency points",
-            scan_results.len()
-        );
+        for (idx, (time, freq, rssi)) in samples.into_iter().enumerate() {
+            let offset_ms = match (time, first_time) {
+                (Some(t), Some(first)) => ((t - first).max(0.0) * 1000.0) as u32,
+                _ => (idx as u64 * duration_sec * 1000 / total) as u32,
+            };
+            recorder.push_sample(offset_ms, freq, rssi);
+        }
 
-        // Return result
-        Ok(json!({
-            "success": true,
-            "capture_id": capture_id,
-            "frequency_range": [low_freq, high_freq],
-            "duration_sec": duration_sec,
-            "scan_results": scan_results,
-            "hotspots": hotspots
-        }))
+        recorder.finish()
     }
 
     /// Configure channel implementation.
@@ -800,39 +1162,52 @@ impl SidecarManager {
         }))
     }
 
-    /// List captures implementation.
+    /// List and search captures through the capture catalog.
     async fn capture_list(&self, params: Value) -> Result<Value> {
         let store = CaptureStore::new()?;
 
-        let filter_type = params.get("filter_type").and_then(|v| v.as_str());
         let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
         let offset = params.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let reindex = params.get("reindex").and_then(|v| v.as_bool()).unwrap_or(false);
+        let query = CatalogQuery::from_params(&params)?;
 
-        // Get all captures
-        let mut all_captures = store.list_captures()?;
-
-        // Filter by type if specified
-        if let Some(filter) = filter_type {
-            all_captures.retain(|c| c.capture_type == filter);
+        // Bring the index up to date; only new or changed captures are parsed
+        let mut catalog = CaptureCatalog::open(&store)?;
+        if reindex {
+            catalog.clear();
         }
+        let index = catalog.refresh(&store.captures_dir(), |metadata| Self::catalog_devices(&store, metadata))?;
 
-        let total_count = all_captures.len();
+        let mut matches = catalog.query(&query);
+        match params.get("sort_by").and_then(|v| v.as_str()) {
+            Some("size") => matches.sort_by_key(|c| std::cmp::Reverse(c.file_size_bytes)),
+            Some("packet_count") => matches.sort_by_key(|c| std::cmp::Reverse(c.packet_count)),
+            _ => {}
+        }
+        if params.get("sort_order").and_then(|v| v.as_str()) == Some("asc") {
+            matches.reverse();
+        }
+        let total_count = matches.len();
 
         // Apply pagination
-        let captures: Vec<_> = all_captures
+        let captures: Vec<_> = matches
             .into_iter()
             .skip(offset)
             .take(limit)
             .map(|c| json!({
                 "capture_id": c.capture_id,
-                "timestamp": c.timestamp.to_rfc3339(),
+                "timestamp": c.start.to_rfc3339(),
                 "type": c.capture_type,
                 "packet_count": c.packet_count,
                 "duration_sec": c.duration_sec,
                 "file_size_bytes": c.file_size_bytes,
                 "pcap_path": c.pcap_path,
                 "tags": c.tags,
-                "description": c.description
+                "description": c.description,
+                "channels": c.channels,
+                "engagement": c.engagement,
+                "devices": c.devices,
+                "pruned": c.pruned
             }))
             .collect();
 
@@ -840,11 +1215,36 @@ impl SidecarManager {
             "success": true,
             "captures": captures,
             "total_count": total_count,
+            "indexed_count": catalog.len(),
+            "index": index,
             "offset": offset,
             "limit": limit
         }))
     }
 
+    /// Devices seen in a capture, for the capture catalog.
+    fn catalog_devices(store: &CaptureStore, metadata: &CaptureMetadata) -> Vec<DeviceSeen> {
+        let is_pcap = Path::new(&metadata.pcap_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e == "pcap" || e == "pcapng");
+        if !is_pcap {
+            return Vec::new();
+        }
+
+        match store.open_data(metadata).and_then(|data| Self::parse_pcap(&data.path().to_string_lossy())) {
+            Ok(analysis) => analysis
+                .devices
+                .into_iter()
+                .map(|d| DeviceSeen { mac: d.mac_address, name: d.name })
+                .collect(),
+            Err(e) => {
+                tracing::debug!("Not indexing devices of {}: {}", metadata.capture_id, e);
+                Vec::new()
+            }
+        }
+    }
+
     /// Get capture implementation.
     async fn capture_get(&self, params: Value) -> Result<Value> {
         let capture_id = params
@@ -857,6 +1257,7 @@ impl SidecarManager {
 
         let store = CaptureStore::new()?;
         let metadata = store.load_metadata(capture_id)?;
+        store.log_custody(capture_id, "accessed", json!({ "tool": "capture_get" }))?;
 
         // For Phase 1, return metadata without parsing PCAP
         // PCAP parsing will be added in Phase 2
@@ -909,10 +1310,16 @@ impl SidecarManager {
             });
 
         let new_description = params.get("description").and_then(|v| v.as_str());
+        let new_engagement = params.get("engagement").and_then(|v| v.as_str());
         let append_tags = params.get("append_tags").and_then(|v| v.as_bool()).unwrap_or(true);
 
         let store = CaptureStore::new()?;
         let mut metadata = store.load_metadata(capture_id)?;
+        let before = json!({
+            "tags": metadata.tags,
+            "description": metadata.description,
+            "engagement": metadata.engagement
+        });
 
         // Update tags
         if let Some(tags) = new_tags {
@@ -930,14 +1337,427 @@ impl SidecarManager {
             metadata.description = desc.to_string();
         }
 
+        // Update engagement (an empty string clears it)
+        if let Some(engagement) = new_engagement {
+            metadata.engagement = Some(engagement.to_string()).filter(|e| !e.is_empty());
+        }
+
         // Save updated metadata
         store.save_metadata(&metadata)?;
+        store.log_custody(
+            capture_id,
+            "tagged",
+            json!({
+                "before": before,
+                "after": {
+                    "tags": metadata.tags,
+                    "description": metadata.description,
+                    "engagement": metadata.engagement
+                }
+            }),
+        )?;
 
         Ok(json!({
             "success": true,
             "capture_id": capture_id,
             "tags": metadata.tags,
-            "description": metadata.description
+            "description": metadata.description,
+            "engagement": metadata.engagement
+        }))
+    }
+
+    /// Re-hash captures against their recorded hashes and check the custody log.
+    async fn capture_verify(&self, params: Value) -> Result<Value> {
+        let capture_id = params.get("capture_id").and_then(|v| v.as_str());
+        let store = CaptureStore::new()?;
+
+        let captures = match capture_id {
+            Some(id) => vec![store.load_metadata(id)?],
+            None => store.list_captures()?,
+        };
+
+        let checks: Vec<IntegrityCheck> = captures.iter().map(|c| custody::verify_capture(&store, c)).collect();
+        for check in &checks {
+            store.log_custody(&check.capture_id, "verified", json!({ "status": check.status }))?;
+        }
+
+        let count = |status: IntegrityStatus| checks.iter().filter(|c| c.status == status).count();
+        let tampered = count(IntegrityStatus::Modified) + count(IntegrityStatus::Missing);
+
+        let log = store.custody_log();
+        let chain = custody::verify_log(&log)?;
+        let include_history = params
+            .get("include_history")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(capture_id.is_some());
+        let history = match (include_history, capture_id) {
+            (true, Some(id)) => log.entries(Some(id))?,
+            _ => Vec::new(),
+        };
+
+        let message = if tampered > 0 {
+            format!("{} of {} captures have been modified or are missing", tampered, checks.len())
+        } else if !chain.intact {
+            "Captures verified, but the custody log has been altered".to_string()
+        } else {
+            format!("{} captures verified; custody log intact", checks.len())
+        };
+
+        Ok(json!({
+            "success": true,
+            "tampered": tampered > 0 || !chain.intact,
+            "summary": {
+                "intact": count(IntegrityStatus::Intact),
+                "modified": count(IntegrityStatus::Modified),
+                "missing": count(IntegrityStatus::Missing),
+                "pruned": count(IntegrityStatus::Pruned),
+                "unhashed": count(IntegrityStatus::Unhashed)
+            },
+            "checks": checks,
+            "custody_log": chain,
+            "history": history,
+            "message": message
+        }))
+    }
+
+    /// Preview or apply the capture retention policy.
+    async fn capture_prune(&self, params: Value) -> Result<Value> {
+        let dry_run = params.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(true);
+        let policy = RetentionPolicy::load()?.with_params(&params);
+
+        let store = CaptureStore::new()?;
+        let report = retention::prune(&store, &policy, None, dry_run)?;
+
+        let message = match (report.candidates.len(), dry_run) {
+            (0, _) if policy.is_unlimited() => "No retention limits configured; nothing to prune".to_string(),
+            (0, _) => "All captures are within the retention policy".to_string(),
+            (n, true) => format!(
+                "{} captures would be pruned, freeing {:.1} MB",
+                n,
+                report.freed_bytes as f64 / 1_048_576.0
+            ),
+            (n, false) => format!(
+                "Pruned {} captures, freed {:.1} MB",
+                n,
+                report.freed_bytes as f64 / 1_048_576.0
+            ),
+        };
+
+        let mut result = serde_json::to_value(&report)?;
+        result["success"] = json!(true);
+        result["message"] = json!(message);
+        Ok(result)
+    }
+
+    /// Write captures, their metadata and presets to a bundle for another operator.
+    async fn capture_bundle_export(&self, params: Value) -> Result<Value> {
+        let store = CaptureStore::new()?;
+
+        // Explicit IDs, or everything matching a catalog query
+        let capture_ids: Vec<String> = match params.get("capture_ids").and_then(|v| v.as_array()) {
+            Some(ids) => ids.iter().filter_map(|v| v.as_str().map(str::to_string)).collect(),
+            None => {
+                let query = CatalogQuery::from_params(&params)?;
+                if query == CatalogQuery::default() {
+                    return Err(UbertoothError::InvalidParameter(
+                        "Provide 'capture_ids' or a 'query' selecting the captures to bundle".to_string(),
+                    ));
+                }
+                let mut catalog = CaptureCatalog::open(&store)?;
+                catalog.refresh(&store.captures_dir(), |metadata| Self::catalog_devices(&store, metadata))?;
+                catalog.query(&query).iter().map(|e| e.capture_id.clone()).collect()
+            }
+        };
+        let configs: Vec<String> = params
+            .get("configs")
+            .and_then(|v| v.as_array())
+            .map(|names| names.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+
+        let output_path = match params.get("output_path").and_then(|v| v.as_str()) {
+            Some(path) => PathBuf::from(path),
+            None => ubertooth_core::storage::exports_dir()?.join(format!(
+                "bundle-{}.{}",
+                Utc::now().format("%Y%m%d-%H%M%S"),
+                bundle::BUNDLE_EXTENSION
+            )),
+        };
+
+        tracing::info!("Bundling {} captures into {}", capture_ids.len(), output_path.display());
+        let manifest = bundle::export_bundle(&store, &capture_ids, &configs, &output_path)?;
+        let bundle_size = std::fs::metadata(&output_path).map(|m| m.len()).unwrap_or(0);
+
+        Ok(json!({
+            "success": true,
+            "bundle_path": output_path.to_string_lossy(),
+            "bundle_size_bytes": bundle_size,
+            "capture_count": manifest.captures.len(),
+            "captures": manifest.captures.iter().map(|c| c.capture_id.clone()).collect::<Vec<_>>(),
+            "configs": manifest.configs.iter().map(|c| c.name.clone()).collect::<Vec<_>>(),
+            "message": format!(
+                "Bundled {} captures and {} presets ({:.1} MB of data)",
+                manifest.captures.len(),
+                manifest.configs.len(),
+                manifest.total_bytes() as f64 / 1_048_576.0
+            )
+        }))
+    }
+
+    /// Verify a capture bundle and register its contents in the local store.
+    async fn capture_bundle_import(&self, params: Value) -> Result<Value> {
+        let bundle_path = params
+            .get("bundle_path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'bundle_path'".to_string()))?;
+
+        let store = CaptureStore::new()?;
+        let report = bundle::import_bundle(&store, Path::new(bundle_path))?;
+
+        let count = |status: &str| report.captures.iter().filter(|c| c.status == status).count();
+        let message = format!(
+            "Imported {} captures ({} under a new ID), skipped {} already present",
+            count("imported") + count("renamed"),
+            count("renamed"),
+            count("duplicate")
+        );
+
+        let mut result = serde_json::to_value(&report)?;
+        result["success"] = json!(true);
+        result["message"] = json!(message);
+        Ok(result)
+    }
+
+    /// List stored spectrum waterfall recordings.
+    async fn specan_list(&self, params: Value) -> Result<Value> {
+        let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
+        let offset = params.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+
+        let store = CaptureStore::new()?;
+        let mut recordings = store.list_captures()?;
+        recordings.retain(|c| c.capture_type == CAPTURE_TYPE_SPECTRUM);
+        let total_count = recordings.len();
+
+        let mut entries = Vec::new();
+        for metadata in recordings.into_iter().skip(offset).take(limit) {
+            let (low, high) = match store.load_spectrum(&metadata.capture_id) {
+                Ok((_, rec)) => (Some(rec.header.low_freq_mhz), Some(rec.header.high_freq_mhz)),
+                Err(e) => {
+                    tracing::warn!("Unreadable spectrum recording {}: {}", metadata.capture_id, e);
+                    (None, None)
+                }
+            };
+            entries.push(json!({
+                "capture_id": metadata.capture_id,
+                "timestamp": metadata.timestamp.to_rfc3339(),
+                "sweep_count": metadata.packet_count,
+                "duration_sec": metadata.duration_sec,
+                "low_freq_mhz": low,
+                "high_freq_mhz": high,
+                "file_size_bytes": metadata.file_size_bytes,
+                "tags": metadata.tags,
+                "description": metadata.description
+            }));
+        }
+
+        Ok(json!({
+            "success": true,
+            "recordings": entries,
+            "total_count": total_count,
+            "offset": offset,
+            "limit": limit
+        }))
+    }
+
+    /// Replay the sweeps of a spectrum recording with pagination.
+    async fn specan_replay(&self, params: Value) -> Result<Value> {
+        let capture_id = params
+            .get("capture_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_id'".to_string()))?;
+        let offset = params.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize;
+
+        let store = CaptureStore::new()?;
+        let (_, recording) = store.load_spectrum(capture_id)?;
+        store.log_custody(capture_id, "accessed", json!({ "tool": "specan_replay" }))?;
+
+        let sweeps: Vec<Value> = recording
+            .sweeps
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .map(|s| json!({
+                "offset_ms": s.offset_ms,
+                "rssi": s.rssi.iter()
+                    .map(|v| (*v != spectrum::NO_SAMPLE).then_some(*v))
+                    .collect::<Vec<_>>()
+            }))
+            .collect();
+
+        Ok(json!({
+            "success": true,
+            "capture_id": capture_id,
+            "start_time": DateTime::<Utc>::from_timestamp_millis(recording.header.start_unix_ms as i64)
+                .map(|t| t.to_rfc3339()),
+            "source": recording.header.source,
+            "frequencies_mhz": recording.frequencies(),
+            "sweep_count": recording.sweeps.len(),
+            "offset": offset,
+            "limit": limit,
+            "sweeps": sweeps,
+            "has_more": offset + sweeps.len() < recording.sweeps.len()
+        }))
+    }
+
+    /// Cut a time window and/or frequency range out of a spectrum recording
+    /// and store it as a new recording.
+    async fn specan_slice(&self, params: Value) -> Result<Value> {
+        let capture_id = params
+            .get("capture_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_id'".to_string()))?;
+        let start_ms = params.get("start_ms").and_then(|v| v.as_u64()).map(|v| v as u32);
+        let end_ms = params.get("end_ms").and_then(|v| v.as_u64()).map(|v| v as u32);
+        let low_freq = params.get("low_freq").and_then(|v| v.as_u64()).map(|v| v as u16);
+        let high_freq = params.get("high_freq").and_then(|v| v.as_u64()).map(|v| v as u16);
+
+        let store = CaptureStore::new()?;
+        let (metadata, recording) = store.load_spectrum(capture_id)?;
+        store.log_custody(capture_id, "accessed", json!({ "tool": "specan_slice" }))?;
+
+        let mut slice = recording
+            .slice(start_ms, end_ms, low_freq, high_freq)
+            .map_err(|e| UbertoothError::InvalidParameter(e.to_string()))?;
+        slice.header.description = format!("Slice of {}", capture_id);
+
+        let slice_id = CaptureStore::generate_capture_id("specan");
+        let mut tags = metadata.tags.clone();
+        tags.push("slice".to_string());
+        let saved = store.save_spectrum(
+            &slice_id,
+            &slice,
+            tags,
+            format!(
+                "Slice of {} ({}-{} MHz)",
+                capture_id, slice.header.low_freq_mhz, slice.header.high_freq_mhz
+            ),
+        )?;
+
+        Ok(json!({
+            "success": true,
+            "capture_id": slice_id,
+            "source_capture_id": capture_id,
+            "low_freq_mhz": slice.header.low_freq_mhz,
+            "high_freq_mhz": slice.header.high_freq_mhz,
+            "sweep_count": slice.sweeps.len(),
+            "recording_path": saved.pcap_path
+        }))
+    }
+
+    /// Summarise a spectrum recording: peak hold, average, occupancy and
+    /// per-minute averages.
+    async fn specan_summary(&self, params: Value) -> Result<Value> {
+        let capture_id = params
+            .get("capture_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_id'".to_string()))?;
+        let threshold_dbm = params
+            .get("threshold_dbm")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(-90)
+            .clamp(-128, 0) as i8;
+
+        let store = CaptureStore::new()?;
+        let (_, recording) = store.load_spectrum(capture_id)?;
+        store.log_custody(capture_id, "accessed", json!({ "tool": "specan_summary" }))?;
+
+        let peak = recording.peak_hold();
+        let average = recording.average();
+        let occupancy = recording.occupancy(threshold_dbm);
+        let channels: Vec<Value> = recording
+            .frequencies()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, freq)| json!({
+                "frequency_mhz": freq,
+                "peak_dbm": peak[idx],
+                "average_dbm": average[idx].map(|v| (v * 10.0).round() / 10.0),
+                "occupancy_percent": (occupancy[idx] * 10.0).round() / 10.0
+            }))
+            .collect();
+
+        let busiest = channels
+            .iter()
+            .max_by(|a, b| {
+                let a = a["occupancy_percent"].as_f64().unwrap_or(0.0);
+                let b = b["occupancy_percent"].as_f64().unwrap_or(0.0);
+                a.total_cmp(&b)
+            })
+            .map(|c| c["frequency_mhz"].clone());
+
+        Ok(json!({
+            "success": true,
+            "capture_id": capture_id,
+            "low_freq_mhz": recording.header.low_freq_mhz,
+            "high_freq_mhz": recording.header.high_freq_mhz,
+            "sweep_count": recording.sweeps.len(),
+            "duration_ms": recording.duration_ms(),
+            "threshold_dbm": threshold_dbm,
+            "busiest_frequency_mhz": busiest,
+            "channels": channels,
+            "per_minute": recording.per_minute_averages()
+        }))
+    }
+
+    /// Classify interference in a spectrum recording, optionally lined up
+    /// against an AFH channel map.
+    async fn specan_interference(&self, params: Value) -> Result<Value> {
+        let capture_id = params
+            .get("capture_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_id'".to_string()))?;
+        let threshold_dbm = params
+            .get("threshold_dbm")
+            .and_then(|v| v.as_i64())
+            .map(|v| v.clamp(-128, 0) as i8)
+            .unwrap_or(interference::DEFAULT_THRESHOLD_DBM);
+
+        let store = CaptureStore::new()?;
+        let (_, recording) = store.load_spectrum(capture_id)?;
+        store.log_custody(capture_id, "accessed", json!({ "tool": "specan_interference" }))?;
+        let report = interference::classify(&recording, threshold_dbm);
+
+        let channels_avoided = Self::afh_avoided_channels(&params)?;
+        let afh = channels_avoided.map(|avoided| report.cross_reference(&avoided));
+
+        Ok(json!({
+            "success": true,
+            "capture_id": capture_id,
+            "threshold_dbm": report.threshold_dbm,
+            "sweep_count": report.sweep_count,
+            "interferers": report.interferers,
+            "affected_bt_channels": report.affected_bt_channels,
+            "afh_cross_reference": afh
+        }))
+    }
+
+    /// Avoided channels from an `afh_map` hex string or a `channels_avoided` list.
+    fn afh_avoided_channels(params: &Value) -> Result<Option<Vec<u8>>> {
+        if let Some(map) = params.get("afh_map").and_then(|v| v.as_str()) {
+            let used = interference::parse_afh_map(map).ok_or_else(|| {
+                UbertoothError::InvalidParameter(format!("Invalid AFH map '{}', expected 10 bytes of hex", map))
+            })?;
+            return Ok(Some(
+                (0..interference::BT_CHANNEL_COUNT).filter(|c| !used.contains(c)).collect(),
+            ));
+        }
+
+        Ok(params.get("channels_avoided").and_then(|v| v.as_array()).map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_u64())
+                .filter(|c| *c < interference::BT_CHANNEL_COUNT as u64)
+                .map(|c| c as u8)
+                .collect()
         }))
     }
 
@@ -960,6 +1780,7 @@ impl SidecarManager {
 
         let store = CaptureStore::new()?;
         let metadata = store.load_metadata(capture_id)?;
+        store.log_custody(capture_id, "accessed", json!({ "tool": "bt_analyze" }))?;
 
         let protocol_type = match metadata.capture_type.as_str() {
             "btle_sniff" => "BLE",
@@ -969,7 +1790,8 @@ impl SidecarManager {
         };
 
         // Phase 2 Complete: PCAP parsing + device extraction + timing + security analysis
-        let pcap_analysis = Self::parse_pcap(&metadata.pcap_path)?;
+        let data = store.open_data(&metadata)?;
+        let pcap_analysis = Self::parse_pcap(&data.path().to_string_lossy())?;
 
         // Build device list for JSON output
         let devices: Vec<Value> = pcap_analysis.devices.iter().map(|dev| {
@@ -981,7 +1803,13 @@ impl SidecarManager {
                 "pdu_type": dev.pdu_type,
                 "first_seen": dev.first_seen,
                 "last_seen": dev.last_seen,
-                "packet_count": dev.packet_count
+                "packet_count": dev.packet_count,
+                "address_type": AddressType::from_bit(dev.random_address).as_str(),
+                "vendor": vendor_registry::vendor_for(&dev.mac_address, dev.random_address),
+                "beacons": dev.advertising.as_ref()
+                    .map(|adv| adv.beacons().iter().map(Beacon::summary).collect::<Vec<_>>())
+                    .unwrap_or_default(),
+                "advertising_data": dev.advertising.as_ref().map(AdvertisingData::to_json_summary)
             })
         }).collect();
 
@@ -995,7 +1823,7 @@ impl SidecarManager {
             })
         }).collect();
 
-        Ok(json!({
+        let mut result = json!({
             "success": true,
             "capture_id": capture_id,
             "analysis": {
@@ -1025,7 +1853,14 @@ impl SidecarManager {
                 },
                 "note": "Phase 2 Complete: Full PCAP analysis with packet parsing, device extraction, timing analysis, and security observations."
             }
-        }))
+        });
+
+        // Calibrate with the dongle that recorded the capture
+        let recorded_by = metadata.provenance.as_ref().and_then(|p| p.device_serial.as_deref());
+        let calibration = calibration::for_device(recorded_by);
+        calibration::apply_to_result(&mut result, &["/analysis/devices"], calibration.as_ref());
+
+        Ok(result)
     }
 
     /// Parse PCAP/PCAPNG file and extract basic statistics, device information, and timing analysis.
@@ -1103,6 +1938,12 @@ impl SidecarManager {
                                             d.name = device_info.name.clone();
                                         }
                                         d.rssi = device_info.rssi;
+                                        if let Some(ad) = &device_info.advertising {
+                                            match d.advertising.as_mut() {
+                                                Some(existing) => existing.merge(ad),
+                                                None => d.advertising = Some(ad.clone()),
+                                            }
+                                        }
                                     })
                                     .or_insert(device_info);
                             }
@@ -1189,6 +2030,12 @@ impl SidecarManager {
                                             d.name = device_info.name.clone();
                                         }
                                         d.rssi = device_info.rssi;
+                                        if let Some(ad) = &device_info.advertising {
+                                            match d.advertising.as_mut() {
+                                                Some(existing) => existing.merge(ad),
+                                                None => d.advertising = Some(ad.clone()),
+                                            }
+                                        }
                                     })
                                     .or_insert(device_info);
                             }
@@ -1473,52 +2320,29 @@ impl SidecarManager {
             addr[5], addr[4], addr[3], addr[2], addr[1], addr[0]
         );
 
-        // Parse advertising data structures to find device name
-        let name = Self::extract_device_name(&ble_payload[6..]);
+        // Parse advertising data structures (name, services, manufacturer data, ...)
+        let advertising = Self::parse_advertising(pdu_header, &ble_payload[..length.min(ble_payload.len())]);
+        let name = advertising.as_ref().and_then(|ad| ad.name.clone());
 
         Some(BleDevice {
             mac_address,
+            random_address: pdu_header & 0x40 != 0,
             name,
             rssi: rssi_avg,
             pdu_type: pdu_type_name.to_string(),
             first_seen: timestamp,
             last_seen: timestamp,
             packet_count: 1,
+            advertising,
         })
-    }
-
-    /// Extract device name from BLE advertising data structures.
-    fn extract_device_name(ad_data: &[u8]) -> Option<String> {
-        let mut offset = 0;
-
-        while offset < ad_data.len() {
-            if offset + 1 >= ad_data.len() {
-                break;
-            }
-
-            let length = ad_data[offset] as usize;
-            if length == 0 {
-                break;
-            }
-
-            if offset + 1 + length > ad_data.len() {
-                break; // Incomplete structure
-            }
-
-            let ad_type = ad_data[offset + 1];
-            let data = &ad_data[offset + 2..offset + 1 + length];
-
-            // 0x08 = Shortened Local Name, 0x09 = Complete Local Name
-            if (ad_type == 0x08 || ad_type == 0x09) {
-                if let Ok(name) = String::from_utf8(data.to_vec()) {
-                    return Some(name);
-                }
-            }
-
-            offset += 1 + length;
-        }
+    }
 
-        None
+    /// Parse advertising data from an ADV_*/SCAN_RSP payload (AdvA + AD structures).
+    fn parse_advertising(pdu_header: u8, payload: &[u8]) -> Option<AdvertisingData> {
+        let mut advertising = AdvertisingData::parse(payload).ok()?;
+        // TxAdd (bit 6) selects public or random advertiser address
+        advertising.address_type = AddressType::from_bit(pdu_header & 0x40 != 0);
+        Some(advertising)
     }
 
     /// Extract BLE device information from a BLE RF format packet (linktype 251).
@@ -1593,12 +2417,13 @@ impl SidecarManager {
             addr[5], addr[4], addr[3], addr[2], addr[1], addr[0]
         );
 
-        // Parse advertising data to find name (only for ADV_* and SCAN_RSP packets)
-        let name = if matches!(pdu_type, 0x00 | 0x02 | 0x04 | 0x06) {
-            Self::extract_device_name(&payload[6..])
+        // Parse advertising data (only for ADV_* and SCAN_RSP packets)
+        let advertising = if matches!(pdu_type, 0x00 | 0x02 | 0x04 | 0x06) {
+            Self::parse_advertising(pdu_header, &payload[..length.min(payload.len())])
         } else {
             None
         };
+        let name = advertising.as_ref().and_then(|ad| ad.name.clone());
 
         tracing::debug!(
             "Extracted device: {} ({}), RSSI: {}, PDU: {}",
@@ -1608,17 +2433,43 @@ impl SidecarManager {
             pdu_type_name
         );
 
+        // AdvA type is TxAdd for ADV_* and RxAdd for SCAN_REQ/CONNECT_REQ
+        let random_address = if addr_offset == 6 {
+            pdu_header & 0x80 != 0
+        } else {
+            pdu_header & 0x40 != 0
+        };
+
         Some(BleDevice {
             mac_address,
+            random_address,
             name,
             rssi,
             pdu_type: pdu_type_name.to_string(),
             first_seen: timestamp,
             last_seen: timestamp,
             packet_count: 1,
+            advertising,
         })
     }
 
+    /// Install local IEEE OUI registry files for vendor lookups.
+    async fn oui_update(&self, params: Value) -> Result<Value> {
+        let registry_paths: Vec<PathBuf> = params
+            .get("registry_paths")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'registry_paths'".to_string()))?
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(PathBuf::from)
+            .collect();
+
+        tracing::info!("Installing {} OUI registry file(s)", registry_paths.len());
+
+        let registry = VendorRegistry::new()?;
+        registry.install(&registry_paths)
+    }
+
     /// Session context implementation - comprehensive AI orientation.
     ///
     /// Combines device_status + capture_list + configs into one response.
@@ -1664,11 +2515,12 @@ impl SidecarManager {
         // Calculate storage stats
         let store = CaptureStore::new()?;
         let all_captures = store.list_captures()?;
-        let total_size_bytes: u64 = all_captures
-            .iter()
-            .map(|m| m.file_size_bytes)
-            .sum();
-        let total_size_mb = total_size_bytes as f64 / 1_048_576.0;
+        let usage = StorageUsage::of(&all_captures);
+        let total_size_mb = usage.used_bytes as f64 / 1_048_576.0;
+        let policy = RetentionPolicy::load().unwrap_or_default();
+        let quota_used_percent = policy
+            .max_total_size_mb
+            .map(|mb| (usage.used_bytes as f64 / (mb as f64 * 1_048_576.0) * 1000.0).round() / 10.0);
 
         let timestamp = chrono::Utc::now().to_rfc3339();
 
@@ -1680,8 +2532,15 @@ impl SidecarManager {
             "saved_configs": [],  // TODO Phase 2: Config persistence
             "storage": {
                 "captures_dir": store.captures_dir().to_string_lossy(),
-                "captures_count": all_captures.len(),
-                "total_size_mb": format!("{:.1}", total_size_mb).parse::<f64>().unwrap_or(0.0)
+                "captures_count": usage.capture_count,
+                "pruned_count": usage.pruned_count,
+                "total_size_mb": format!("{:.1}", total_size_mb).parse::<f64>().unwrap_or(0.0),
+                "retention": {
+                    "max_total_size_mb": policy.max_total_size_mb,
+                    "max_age_days": policy.max_age_days,
+                    "keep_tags": policy.keep_tags,
+                    "quota_used_percent": quota_used_percent
+                }
             }
         }))
     }
@@ -1711,25 +2570,51 @@ impl SidecarManager {
         // Note: ubertooth-scan does not support PCAP output directly
         let duration_str = duration_sec.to_string();
 
-        let output = self.execute_ubertooth_command(
+        self.require(Feature::Scan).await?;
+        let (output, events) = self.stream_ubertooth_command(
+            "bt_scan",
             "ubertooth-scan",
-            &["-t", duration_str.as_str()]
+            &["-t", duration_str.as_str()],
+            RunOptions::for_duration(std::time::Duration::from_secs(duration_sec)),
         ).await?;
+        if output.termination == Termination::Exited && !output.success() {
+            return Err(UbertoothError::CommandFailed(format!(
+                "ubertooth-scan failed: {}",
+                output.stderr.trim()
+            )));
+        }
 
-        // Parse output for discovered devices
-        let mut devices_found = Vec::new();
-        for line in output.lines() {
-            // Parse device lines (format: BD_ADDR - Device Name)
-            if line.contains(':') && (line.len() > 17) {
-                let parts: Vec<&str> = line.splitn(2, " - ").collect();
-                if parts.len() >= 1 {
-                    let bd_addr = parts[0].trim();
-                    let name = if parts.len() > 1 { parts[1].trim() } else { "Unknown" };
-                    devices_found.push(json!({
-                        "bd_addr": bd_addr,
-                        "name": name
-                    }));
+        // Devices can be listed by both the inquiry and the sniffer; merge them
+        let mut devices_found: Vec<Value> = Vec::new();
+        for event in events {
+            match event {
+                OutputEvent::Device { address, name } => {
+                    match devices_found.iter_mut().find(|d| d["bd_addr"] == address.as_str()) {
+                        Some(device) => {
+                            if let Some(name) = name {
+                                device["name"] = json!(name);
+                            }
+                        }
+                        None => devices_found.push(json!({
+                            // BR/EDR addresses are always public
+                            "vendor": vendor_registry::vendor_for(&address, false),
+                            "bd_addr": address,
+                            "name": name.unwrap_or_else(|| "Unknown".to_string()),
+                            "details": {}
+                        })),
+                    }
+                }
+                OutputEvent::DeviceDetail { address, field, value } => {
+                    if let Some(device) = devices_found.iter_mut().find(|d| d["bd_addr"] == address.as_str()) {
+                        device["details"][field] = json!(value);
+                    }
+                }
+                OutputEvent::AfhMap { map, .. } => {
+                    if let Some(device) = devices_found.last_mut() {
+                        device["details"]["AFH map"] = json!(map);
+                    }
                 }
+                _ => {}
             }
         }
 
@@ -1752,6 +2637,12 @@ impl SidecarManager {
             pcap_path: pcap_path.to_string_lossy().to_string(),
             tags: Vec::new(),
             description: format!("Bluetooth Classic scan, {} devices found", total_devices),
+            channels: Vec::new(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
         };
         store.save_metadata(&metadata)?;
 
@@ -1789,31 +2680,47 @@ impl SidecarManager {
         let duration_str = duration_sec.to_string();
         let pcap_str = pcap_path.to_string_lossy().to_string();
 
-        let output = self.execute_ubertooth_command(
+        self.require(Feature::Follow).await?;
+        let (result, events) = self.stream_ubertooth_command(
+            "bt_follow",
             "ubertooth-follow",
-            &["-t", bd_addr, "-r", pcap_str.as_str(), "-d", duration_str.as_str()]
+            &["-t", bd_addr, "-r", pcap_str.as_str(), "-d", duration_str.as_str()],
+            RunOptions::for_duration(std::time::Duration::from_secs(duration_sec)),
         ).await?;
 
-        // Parse output for connection info and packet count
-        let connection_found = output.contains("Following") || output.contains("Connection");
-        let packet_count = output.lines()
-            .filter(|line| line.contains("packet"))
-            .count();
+        // A follow that was cancelled or hung still keeps whatever it captured
+        let captured = std::fs::metadata(&pcap_path).map(|m| m.len() > 0).unwrap_or(false);
+        if result.termination == Termination::Exited && !result.success() {
+            return Err(UbertoothError::CommandFailed(format!(
+                "ubertooth-follow failed: {}",
+                result.stderr.trim()
+            )));
+        }
+        if result.interrupted() && !captured {
+            return Err(UbertoothError::CommandFailed(match result.termination {
+                Termination::Cancelled => "ubertooth-follow was cancelled before capturing anything".to_string(),
+                _ => "ubertooth-follow timed out before capturing anything".to_string(),
+            }));
+        }
 
-        // Parse channel usage from output
+        // Packets of the target piconet, identified by the LAP (low 24 bits of the address)
+        let target_lap = bd_addr.replace(':', "").to_ascii_lowercase();
+        let target_lap = &target_lap[target_lap.len().saturating_sub(6)..];
+        let mut packet_count = 0;
         let mut channels_used = Vec::new();
-        for line in output.lines() {
-            if line.contains("channel") {
-                // Extract channel numbers (0-78)
-                for word in line.split_whitespace() {
-                    if let Ok(ch) = word.parse::<u8>() {
-                        if ch <= 78 && !channels_used.contains(&ch) {
-                            channels_used.push(ch);
-                        }
-                    }
+        for event in &events {
+            if let OutputEvent::BrPacket(packet) = event {
+                if packet.lap != target_lap {
+                    continue;
+                }
+                packet_count += 1;
+                if let Some(ch) = packet.channel.filter(|ch| *ch <= 78 && !channels_used.contains(ch)) {
+                    channels_used.push(ch);
                 }
             }
         }
+        channels_used.sort_unstable();
+        let connection_found = packet_count > 0;
 
         // Save metadata
         let file_size_bytes = if pcap_path.exists() {
@@ -1831,7 +2738,17 @@ impl SidecarManager {
             file_size_bytes,
             pcap_path: pcap_path.to_string_lossy().to_string(),
             tags: vec![format!("bd_addr:{}", bd_addr)],
-            description: format!("Following Bluetooth connection {}", bd_addr),
+            description: match result.termination {
+                Termination::Cancelled => format!("Following Bluetooth connection {} (cancelled, partial capture)", bd_addr),
+                Termination::TimedOut => format!("Following Bluetooth connection {} (timed out, partial capture)", bd_addr),
+                Termination::Exited | Termination::Stopped => format!("Following Bluetooth connection {}", bd_addr),
+            },
+            channels: Vec::new(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
         };
         store.save_metadata(&metadata)?;
 
@@ -1843,6 +2760,7 @@ impl SidecarManager {
             "packet_count": packet_count,
             "duration_sec": duration_sec,
             "channels_used": channels_used,
+            "termination": result.termination,
             "pcap_path": pcap_path.to_string_lossy()
         }))
     }
@@ -1868,61 +2786,92 @@ impl SidecarManager {
             args.extend_from_slice(&["-t", bd_addr_str.as_str()]);
         }
 
-        let output = self.execute_ubertooth_command("ubertooth-afh", &args).await?;
+        self.require(Feature::Afh).await?;
+        let (output, events) = self
+            .stream_ubertooth_command(
+                "afh_analyze",
+                "ubertooth-afh",
+                &args,
+                RunOptions::for_duration(std::time::Duration::from_secs(duration_sec)),
+            )
+            .await?;
+        if !output.success() {
+            return Err(UbertoothError::CommandFailed(match output.termination {
+                Termination::TimedOut => "ubertooth-afh timed out".to_string(),
+                Termination::Cancelled => "ubertooth-afh was cancelled".to_string(),
+                _ => format!("ubertooth-afh failed: {}", output.stderr.trim()),
+            }));
+        }
 
-        // Parse AFH channel map from output
+        // The latest channel map (and channel lists) the tool printed
         let mut afh_map = "0x0000000000000000000000".to_string();
         let mut channels_used = Vec::new();
         let mut channels_avoided = Vec::new();
-
-        for line in output.lines() {
-            if line.contains("AFH map:") || line.contains("Channel map:") {
-                // Extract hex map
-                if let Some(hex_start) = line.find("0x") {
-                    afh_map = line[hex_start..].split_whitespace().next()
-                        .unwrap_or("0x0000000000000000000000")
-                        .to_string();
-                }
-            }
-            if line.contains("Used:") || line.contains("Active:") {
-                // Parse channel list
-                for word in line.split_whitespace() {
-                    if let Ok(ch) = word.trim_matches(|c: char| !c.is_numeric()).parse::<u8>() {
-                        if ch <= 78 {
-                            channels_used.push(ch);
-                        }
-                    }
+        let mut map_updates = 0;
+
+        for event in events {
+            match event {
+                OutputEvent::AfhMap { map, .. } => {
+                    // Lists printed for an earlier map no longer apply
+                    afh_map = map;
+                    map_updates += 1;
+                    channels_used.clear();
+                    channels_avoided.clear();
                 }
+                OutputEvent::AfhChannels { avoided: false, channels } => channels_used = channels,
+                OutputEvent::AfhChannels { avoided: true, channels } => channels_avoided = channels,
+                _ => {}
             }
-            if line.contains("Avoided:") || line.contains("Disabled:") {
-                // Parse avoided channel list
-                for word in line.split_whitespace() {
-                    if let Ok(ch) = word.trim_matches(|c: char| !c.is_numeric()).parse::<u8>() {
-                        if ch <= 78 {
-                            channels_avoided.push(ch);
-                        }
-                    }
-                }
+        }
+
+        // Fall back to the hex map when the tool didn't print channel lists
+        if channels_used.is_empty() && channels_avoided.is_empty() {
+            if let Some(used) = interference::parse_afh_map(&afh_map).filter(|u| !u.is_empty()) {
+                channels_avoided = (0..interference::BT_CHANNEL_COUNT).filter(|c| !used.contains(c)).collect();
+                channels_used = used;
             }
         }
 
+        // Explain avoided channels from a spectrum recording when one is given
+        let cross_reference = match params.get("specan_capture_id").and_then(|v| v.as_str()) {
+            Some(specan_id) => {
+                let store = CaptureStore::new()?;
+                let (_, recording) = store.load_spectrum(specan_id)?;
+                store.log_custody(specan_id, "accessed", json!({ "tool": "afh_analyze" }))?;
+                let report = interference::classify(&recording, interference::DEFAULT_THRESHOLD_DBM);
+                Some((report.cross_reference(&channels_avoided), report.interferers))
+            }
+            None => None,
+        };
+
         let used_count = channels_used.len();
         let avoided_count = channels_avoided.len();
-        let interpretation = if used_count > 0 {
-            format!("Device uses {} channels, avoids {} channels (likely due to WiFi interference)",
-                    used_count, avoided_count)
-        } else {
-            "No AFH data captured yet".to_string()
+        let interpretation = match &cross_reference {
+            _ if used_count == 0 => "No AFH data captured yet".to_string(),
+            Some((xref, _)) => format!(
+                "Device uses {} channels, avoids {} channels: {} explained by measured interference, {} unexplained",
+                used_count,
+                avoided_count,
+                xref.explained.len(),
+                xref.unexplained.len()
+            ),
+            None => format!(
+                "Device uses {} channels, avoids {} channels (pass specan_capture_id to attribute avoided channels)",
+                used_count, avoided_count
+            ),
         };
 
         Ok(json!({
             "success": true,
             "bd_addr": bd_addr,
             "afh_map": afh_map,
+            "map_updates": map_updates,
             "channels_used": channels_used,
             "channels_avoided": channels_avoided,
             "used_count": used_count,
             "avoided_count": avoided_count,
+            "interferers": cross_reference.as_ref().map(|(_, i)| i),
+            "afh_cross_reference": cross_reference.as_ref().map(|(x, _)| x),
             "interpretation": interpretation
         }))
     }
@@ -1946,34 +2895,53 @@ impl SidecarManager {
         let duration_str = duration_sec.to_string();
         let pcap_str = pcap_path.to_string_lossy().to_string();
 
-        let output = self.execute_ubertooth_command(
+        self.require(Feature::RxPcap).await?;
+        let (output, events) = self.stream_ubertooth_command(
+            "bt_discover",
             "ubertooth-rx",
-            &["-d", duration_str.as_str(), "-q", pcap_str.as_str()]
+            &["-d", duration_str.as_str(), "-q", pcap_str.as_str()],
+            RunOptions::for_duration(std::time::Duration::from_secs(duration_sec)),
         ).await?;
+        if output.termination == Termination::Exited && !output.success() {
+            return Err(UbertoothError::CommandFailed(format!(
+                "ubertooth-rx failed: {}",
+                output.stderr.trim()
+            )));
+        }
 
-        // Parse output for piconets and packet count
-        let mut piconets_found = Vec::new();
+        // Piconets are identified by their LAP; the UAP is known once recovered
+        let mut piconets: Vec<(String, Option<u8>, usize)> = Vec::new();
         let mut total_packets = 0;
-
-        for line in output.lines() {
-            // Parse LAP (Lower Address Part) which identifies piconets
-            if line.contains("LAP:") || line.contains("lap") {
-                if let Some(lap_pos) = line.find("LAP:").or_else(|| line.find("lap")) {
-                    let lap_str = &line[lap_pos..];
-                    if let Some(hex_val) = lap_str.split_whitespace().nth(1) {
-                        if !piconets_found.iter().any(|v: &Value| v["lap"] == hex_val) {
-                            piconets_found.push(json!({
-                                "lap": hex_val
-                            }));
-                        }
+        for event in &events {
+            match event {
+                OutputEvent::BrPacket(packet) => {
+                    total_packets += 1;
+                    match piconets.iter_mut().find(|(lap, _, _)| *lap == packet.lap) {
+                        Some(piconet) => piconet.2 += 1,
+                        None => piconets.push((packet.lap.clone(), None, 1)),
                     }
                 }
-            }
-            // Count packets
-            if line.contains("packet") || line.contains("Packet") {
-                total_packets += 1;
+                OutputEvent::UapFound { lap: Some(lap), uap, .. } => {
+                    let uap = u8::from_str_radix(uap.trim_start_matches("0x"), 16).ok();
+                    if let Some(piconet) = piconets.iter_mut().find(|(l, _, _)| l == lap) {
+                        piconet.1 = uap;
+                    }
+                }
+                _ => {}
             }
         }
+        let piconets_found: Vec<Value> = piconets
+            .iter()
+            .map(|(lap, uap, packet_count)| {
+                let uap_str = uap.map(|u| format!("{:02x}", u)).unwrap_or_else(|| "??".to_string());
+                json!({
+                    "lap": lap,
+                    "bd_addr": format!("??:??:{}:{}:{}:{}", uap_str, &lap[0..2], &lap[2..4], &lap[4..6]),
+                    "uap": uap,
+                    "packet_count": packet_count
+                })
+            })
+            .collect();
 
         // Save metadata
         let file_size_bytes = if pcap_path.exists() {
@@ -1992,6 +2960,12 @@ impl SidecarManager {
             pcap_path: pcap_path.to_string_lossy().to_string(),
             tags: Vec::new(),
             description: format!("Promiscuous BT discovery, {} piconets found", piconets_found.len()),
+            channels: Vec::new(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
         };
         store.save_metadata(&metadata)?;
 
@@ -2029,34 +3003,42 @@ impl SidecarManager {
         let duration_str = duration_sec.to_string();
         let pcap_str = pcap_path.to_string_lossy().to_string();
 
-        let output = self.execute_ubertooth_command(
+        self.require(Feature::BtleFollow).await?;
+        let (output, events) = self.stream_ubertooth_command(
+            "btle_follow",
             "ubertooth-btle",
-            &["-f", "-a", access_address, "-r", pcap_str.as_str(), "-d", duration_str.as_str()]
+            &["-f", "-a", access_address, "-r", pcap_str.as_str(), "-d", duration_str.as_str()],
+            RunOptions::for_duration(std::time::Duration::from_secs(duration_sec)),
         ).await?;
+        if output.termination == Termination::Exited && !output.success() {
+            return Err(UbertoothError::CommandFailed(format!(
+                "ubertooth-btle failed: {}",
+                output.stderr.trim()
+            )));
+        }
 
-        // Parse output for connection info
+        // Every connection event hops to a new data channel
         let mut packets_captured = 0;
         let mut connection_events = 0;
-        let mut crc_valid = 0;
-        let mut crc_total = 0;
+        let mut valid_packets = 0;
+        let mut last_channel = None;
 
-        for line in output.lines() {
-            if line.contains("data:") || line.contains("Data packet") {
+        for event in &events {
+            if let OutputEvent::BlePacket(packet) = event {
                 packets_captured += 1;
-            }
-            if line.contains("connection event") || line.contains("CE:") {
-                connection_events += 1;
-            }
-            if line.contains("CRC") {
-                crc_total += 1;
-                if line.contains("OK") || line.contains("valid") || line.contains("pass") {
-                    crc_valid += 1;
+                if packet.aa_valid {
+                    valid_packets += 1;
+                }
+                if packet.kind == "data" && packet.channel_index != last_channel {
+                    connection_events += 1;
+                    last_channel = packet.channel_index;
                 }
             }
         }
 
-        let crc_valid_percent = if crc_total > 0 {
-            (crc_valid as f64 / crc_total as f64) * 100.0
+        // Share of packets ubertooth-btle reported as valid
+        let crc_valid_percent = if packets_captured > 0 {
+            (valid_packets as f64 / packets_captured as f64) * 100.0
         } else {
             0.0
         };
@@ -2078,6 +3060,12 @@ impl SidecarManager {
             pcap_path: pcap_path.to_string_lossy().to_string(),
             tags: vec![format!("access_address:{}", access_address)],
             description: format!("Following BLE connection {}", access_address),
+            channels: Vec::new(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
         };
         store.save_metadata(&metadata)?;
 
@@ -2123,6 +3111,54 @@ impl SidecarManager {
         }))
     }
 
+    /// Calibrate the noise floor, apply a squelch margin above it and store
+    /// the dongle's RSSI calibration.
+    async fn squelch_calibrate(&self, params: Value) -> Result<Value> {
+        let request = CalibrationRequest::from_params(&params)?;
+
+        tracing::info!(
+            "Calibrating noise floor: {}-{} MHz for {}s, margin {} dB",
+            request.low_freq_mhz,
+            request.high_freq_mhz,
+            request.duration_sec,
+            request.margin_db
+        );
+
+        let serial = self.device_serial().await?;
+        let recording = self
+            .record_spectrum("squelch_calibrate", request.low_freq_mhz, request.high_freq_mhz, request.duration_sec)
+            .await?;
+        let calibration = calibration::calibrate(&ConfigStore::new()?, &serial, &recording, &request)?;
+
+        let applied = if request.apply {
+            let result = self
+                .configure_squelch(json!({ "squelch_level": calibration.recommended_squelch_dbm }))
+                .await?;
+            result["squelch_level"].as_i64()
+        } else {
+            None
+        };
+
+        Ok(calibration::calibration_result(&calibration, applied))
+    }
+
+    /// Serial number of the attached dongle (`ubertooth-util -s`).
+    ///
+    /// Fails rather than guessing, so per-dongle state such as the RSSI
+    /// calibration is never shared between unidentified dongles.
+    async fn device_serial(&self) -> Result<String> {
+        let output = self.execute_ubertooth_command("ubertooth-util", &["-s"]).await?;
+        output
+            .lines()
+            .find(|line| line.to_lowercase().contains("serial"))
+            .and_then(|line| line.split(':').nth(1))
+            .map(|serial| serial.trim().to_string())
+            .filter(|serial| !serial.is_empty())
+            .ok_or_else(|| {
+                UbertoothError::BackendError("Could not read the device serial number from ubertooth-util -s".to_string())
+            })
+    }
+
     /// Configure LEDs implementation.
     ///
     /// Phase 2 Week 3: LED control via ubertooth-util.
@@ -2250,6 +3286,7 @@ impl SidecarManager {
         // Load config from file
         let store = ConfigStore::new()?;
         let config = store.load_config(config_name)?;
+        config_store::set_active_preset(Some(config.name.clone()));
 
         // Apply settings to device
         // TODO: Call configure_* methods to apply each setting
@@ -2290,10 +3327,26 @@ impl SidecarManager {
             })
             .collect();
 
+        let calibrations: Vec<Value> = store
+            .list_calibrations()?
+            .iter()
+            .map(|c| {
+                json!({
+                    "serial": c.serial,
+                    "calibrated": c.calibrated.to_rfc3339(),
+                    "noise_floor_dbm": c.noise_floor_dbm,
+                    "recommended_squelch_dbm": c.recommended_squelch_dbm,
+                    "rssi_offset_db": c.offset_db,
+                    "reference": c.reference
+                })
+            })
+            .collect();
+
         Ok(json!({
             "success": true,
             "configs": config_list,
-            "count": configs.len()
+            "count": configs.len(),
+            "calibrations": calibrations
         }))
     }
 
@@ -2319,317 +3372,530 @@ impl SidecarManager {
 
     // Phase 2 Week 5: Analysis tools
 
-    async fn bt_compare(&self, _params: Value) -> Result<Value> {
-        let capture_id_a = _params.get("capture_id_a")
+    async fn bt_compare(&self, params: Value) -> Result<Value> {
+        let capture_id_a = params.get("capture_id_a")
             .and_then(|v| v.as_str())
             .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_id_a'".to_string()))?;
 
-        let capture_id_b = _params.get("capture_id_b")
+        let capture_id_b = params.get("capture_id_b")
             .and_then(|v| v.as_str())
             .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_id_b'".to_string()))?;
 
-        let mode = _params.get("mode").and_then(|v| v.as_str()).unwrap_or("packets");
+        // `compare_mode` is the name older clients send
+        let mode_name = params
+            .get("mode")
+            .or_else(|| params.get("compare_mode"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("devices");
+        let mode = CompareMode::from_name(mode_name).ok_or_else(|| {
+            UbertoothError::InvalidParameter(format!(
+                "Unknown comparison mode '{}' (expected devices, packets or timing)",
+                mode_name
+            ))
+        })?;
 
-        tracing::info!("Comparing captures {} and {} (mode: {})", capture_id_a, capture_id_b, mode);
+        tracing::info!("Comparing captures {} and {} (mode: {})", capture_id_a, capture_id_b, mode.as_str());
 
         let store = CaptureStore::new()?;
 
-        // Load metadata for both captures
-        let meta_a = store.load_metadata(capture_id_a)?;
-        let meta_b = store.load_metadata(capture_id_b)?;
-
-        // Use capinfos to get detailed stats
-        let pcap_a = store.captures_dir().join(format!("{}.pcap", capture_id_a));
-        let pcap_b = store.captures_dir().join(format!("{}.pcap", capture_id_b));
+        let mut profiles = Vec::new();
+        let mut metadata = Vec::new();
+        for capture_id in [capture_id_a, capture_id_b] {
+            let meta = store.load_metadata(capture_id)?;
+            store.log_custody(capture_id, "accessed", json!({ "tool": "bt_compare" }))?;
+            let data = store.read_data(&meta)?;
+            let profile = CaptureProfile::from_capture(&data)
+                .map_err(|e| UbertoothError::ParseError(format!("Cannot decode capture {}: {}", capture_id, e)))?;
+            profiles.push(profile);
+            metadata.push(meta);
+        }
 
-        let stats_a = self.execute_ubertooth_command("capinfos", &[pcap_a.to_str().unwrap()]).await.unwrap_or_default();
-        let stats_b = self.execute_ubertooth_command("capinfos", &[pcap_b.to_str().unwrap()]).await.unwrap_or_default();
+        let diff = compare::compare(&profiles[0], &profiles[1]);
+        let differences = diff.differences(mode);
+
+        let mut comparison = json!({
+            "mode": mode.as_str(),
+            "similarity_percent": diff.similarity_percent,
+            "differences": differences,
+            "unique_to_a": diff.unique_to_a,
+            "unique_to_b": diff.unique_to_b,
+            "common_packets": diff.common_packets
+        });
+        let view = match mode {
+            CompareMode::Devices => json!({
+                "appeared": diff.devices_appeared,
+                "disappeared": diff.devices_disappeared,
+                "changed": diff.devices_changed,
+                "unchanged": diff.devices_unchanged
+            }),
+            CompareMode::Packets => json!({
+                "pdu_types": diff.pdu_types,
+                "channels": diff.channels,
+                "connections_a": diff.connections_a,
+                "connections_b": diff.connections_b
+            }),
+            CompareMode::Timing => json!({
+                "duration_sec_a": diff.duration_sec_a,
+                "duration_sec_b": diff.duration_sec_b,
+                "packet_rate_a": diff.packet_rate_a,
+                "packet_rate_b": diff.packet_rate_b,
+                "adv_intervals": diff.adv_intervals,
+                "connections_a": diff.connections_a,
+                "connections_b": diff.connections_b
+            }),
+        };
+        comparison[mode.as_str()] = view;
 
-        // Basic comparison based on metadata
-        let common_packets = std::cmp::min(meta_a.packet_count, meta_b.packet_count);
-        let unique_to_a = meta_a.packet_count.saturating_sub(common_packets);
-        let unique_to_b = meta_b.packet_count.saturating_sub(common_packets);
-        let total = meta_a.packet_count + meta_b.packet_count;
-        let similarity_percent = if total > 0 {
-            (common_packets as f64 * 200.0) / total as f64
-        } else {
-            0.0
+        let summary = |id: &str, meta: &CaptureMetadata, profile: &CaptureProfile| {
+            json!({
+                "id": id,
+                "type": meta.capture_type,
+                "packets": profile.packets,
+                "devices": profile.devices.len(),
+                "duration_sec": profile.duration_sec
+            })
         };
 
-        let mut differences = Vec::new();
-        if meta_a.capture_type != meta_b.capture_type {
-            differences.push(format!("Capture types differ: {} vs {}", meta_a.capture_type, meta_b.capture_type));
+        Ok(json!({
+            "success": true,
+            "capture_id_a": capture_id_a,
+            "capture_id_b": capture_id_b,
+            "comparison": comparison,
+            "capture_a": summary(capture_id_a, &metadata[0], &profiles[0]),
+            "capture_b": summary(capture_id_b, &metadata[1], &profiles[1])
+        }))
+    }
+
+    /// Detect item trackers (AirTag, SmartTag, Tile, ...) across captures.
+    ///
+    /// Sightings are linked into one tracker by address or, as trackers
+    /// rotate their address, by payload (see [`trackers::same_tracker`]), so a
+    /// tracker seen in captures taken at different times or places is
+    /// reported as persistent.
+    async fn bt_tracker_detect(&self, params: Value) -> Result<Value> {
+        let capture_ids: Vec<String> = params
+            .get("capture_ids")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_ids'".to_string()))?
+            .iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect();
+
+        if capture_ids.is_empty() {
+            return Err(UbertoothError::InvalidParameter("'capture_ids' must not be empty".to_string()));
+        }
+
+        let min_captures = params
+            .get("min_captures")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(2)
+            .max(1) as usize;
+
+        tracing::info!("Detecting trackers across {} capture(s)", capture_ids.len());
+
+        struct TrackerEntry {
+            kind: TrackerKind,
+            addresses: Vec<String>,
+            payload_ids: Vec<String>,
+            sightings: Vec<(f64, TrackerSighting)>,
+            linked_by_payload: bool,
+            random_address: bool,
+            separated: Option<bool>,
+            states: Vec<String>,
+            captures: Vec<String>,
+            packet_count: usize,
+            max_rssi: i8,
+        }
+
+        let store = CaptureStore::new()?;
+        let mut trackers: Vec<TrackerEntry> = Vec::new();
+        let mut captures_analyzed = Vec::new();
+
+        for capture_id in &capture_ids {
+            let metadata = store.load_metadata(capture_id)?;
+            store.log_custody(capture_id, "accessed", json!({ "tool": "bt_tracker_detect" }))?;
+            let data = store.open_data(&metadata)?;
+            let pcap_analysis = Self::parse_pcap(&data.path().to_string_lossy())?;
+            let mut found = 0;
+
+            for dev in &pcap_analysis.devices {
+                let Some(sighting) = dev.advertising.as_ref().and_then(trackers::classify) else {
+                    continue;
+                };
+                found += 1;
+
+                let by_address = trackers
+                    .iter()
+                    .position(|t| t.kind == sighting.kind && t.addresses.contains(&dev.mac_address));
+                let by_payload = || {
+                    trackers.iter().position(|t| {
+                        t.sightings
+                            .iter()
+                            .any(|(seen, earlier)| trackers::same_tracker(earlier, &sighting, dev.last_seen - seen))
+                    })
+                };
+                let idx = match by_address.or_else(by_payload) {
+                    Some(idx) => idx,
+                    None => {
+                        trackers.push(TrackerEntry {
+                            kind: sighting.kind,
+                            addresses: Vec::new(),
+                            payload_ids: Vec::new(),
+                            sightings: Vec::new(),
+                            linked_by_payload: false,
+                            random_address: dev.random_address,
+                            separated: None,
+                            states: Vec::new(),
+                            captures: Vec::new(),
+                            packet_count: 0,
+                            max_rssi: dev.rssi,
+                        });
+                        trackers.len() - 1
+                    }
+                };
+
+                let entry = &mut trackers[idx];
+                if !entry.addresses.contains(&dev.mac_address) {
+                    entry.linked_by_payload |= !entry.addresses.is_empty();
+                    entry.addresses.push(dev.mac_address.clone());
+                }
+                if sighting.separated.is_some() {
+                    entry.separated = Some(entry.separated.unwrap_or(false) || sighting.separated == Some(true));
+                }
+                if !entry.states.contains(&sighting.state) {
+                    entry.states.push(sighting.state.clone());
+                }
+                if let Some(id) = &sighting.payload_id {
+                    if !entry.payload_ids.contains(id) {
+                        entry.payload_ids.push(id.clone());
+                    }
+                }
+                entry.sightings.push((dev.last_seen, sighting));
+                if !entry.captures.contains(capture_id) {
+                    entry.captures.push(capture_id.clone());
+                }
+                entry.packet_count += dev.packet_count;
+                entry.max_rssi = entry.max_rssi.max(dev.rssi);
+            }
+
+            captures_analyzed.push(json!({
+                "capture_id": capture_id,
+                "timestamp": metadata.timestamp.to_rfc3339(),
+                "tags": metadata.tags,
+                "description": metadata.description,
+                "trackers_found": found
+            }));
+        }
+
+        let mut separated_count = 0;
+        let mut persistent_count = 0;
+        let mut high_risk_count = 0;
+
+        let tracker_list: Vec<Value> = trackers.iter().map(|t| {
+            let separated = t.separated == Some(true);
+            let persistent = t.captures.len() >= min_captures && capture_ids.len() >= min_captures;
+            let risk = match (separated, persistent) {
+                (true, true) => "high",
+                (true, false) | (false, true) => "medium",
+                (false, false) => "low",
+            };
+
+            separated_count += separated as usize;
+            persistent_count += persistent as usize;
+            high_risk_count += (risk == "high") as usize;
+
+            json!({
+                "mac_address": t.addresses[0],
+                "addresses": t.addresses,
+                "linked_by": if t.linked_by_payload { "payload" } else { "address" },
+                "payload_ids": t.payload_ids,
+                "tracker_type": t.kind.as_str(),
+                "tracker_name": t.kind.display_name(),
+                "vendor": vendor_registry::vendor_for(&t.addresses[0], t.random_address),
+                "separated_from_owner": t.separated,
+                "states": t.states,
+                "captures": t.captures,
+                "capture_count": t.captures.len(),
+                "persistent": persistent,
+                "packet_count": t.packet_count,
+                "max_rssi": t.max_rssi,
+                "risk": risk
+            })
+        }).collect();
+
+        let mut recommendations = Vec::new();
+        if high_risk_count > 0 {
+            recommendations.push(format!(
+                "{} separated tracker(s) followed across captures - locate and inspect them",
+                high_risk_count
+            ));
+        } else if separated_count > 0 {
+            recommendations.push(
+                "Separated tracker(s) present - capture again at a different time or place to check whether they follow"
+                    .to_string(),
+            );
+        }
+        if capture_ids.len() < min_captures {
+            recommendations.push(format!(
+                "Persistence needs at least {} captures taken at different times or places",
+                min_captures
+            ));
         }
-        if meta_a.packet_count != meta_b.packet_count {
-            differences.push(format!("Packet counts differ: {} vs {}", meta_a.packet_count, meta_b.packet_count));
+
+        // What the address and payload linking cannot see
+        let mut limitations = vec![
+            "Find My and Find My Device identifiers rotate with the address (every 15 minutes near the owner, \
+             at most daily while separated), so their sightings are linked only within one rotation period"
+                .to_string(),
+        ];
+        if trackers.iter().any(|t| matches!(t.kind, TrackerKind::Tile | TrackerKind::Chipolo)) {
+            limitations.push("Tile and Chipolo carry no payload identifier and are linked by address only".to_string());
         }
 
         Ok(json!({
             "success": true,
-            "comparison": {
-                "mode": mode,
-                "similarity_percent": similarity_percent,
-                "differences": differences,
-                "unique_to_a": unique_to_a,
-                "unique_to_b": unique_to_b,
-                "common_packets": common_packets
-            },
-            "capture_a": {
-                "id": capture_id_a,
-                "type": meta_a.capture_type,
-                "packets": meta_a.packet_count
+            "captures_analyzed": captures_analyzed,
+            "trackers": tracker_list,
+            "limitations": limitations,
+            "summary": {
+                "total_trackers": trackers.len(),
+                "separated": separated_count,
+                "persistent": persistent_count,
+                "high_risk": high_risk_count
             },
-            "capture_b": {
-                "id": capture_id_b,
-                "type": meta_b.capture_type,
-                "packets": meta_b.packet_count
-            }
+            "recommendations": recommendations
         }))
     }
 
-    /// Extract key fields from tshark JSON packet for display
-    fn extract_packet_summary(packet: &Value, index: usize) -> Value {
-        let layers = packet.get("_source").and_then(|s| s.get("layers"));
-
-        // Extract frame info
-        let frame = layers.and_then(|l| l.get("frame"));
-        let timestamp = frame
-            .and_then(|f| f.get("frame.time"))
-            .and_then(|t| t.as_str())
-            .unwrap_or("Unknown");
-        let frame_number = frame
-            .and_then(|f| f.get("frame.number"))
-            .and_then(|n| n.as_str())
-            .unwrap_or("0");
-
-        // Extract BLE RF info
-        let btle_rf = layers.and_then(|l| l.get("btle_rf"));
-        let channel = btle_rf
-            .and_then(|rf| rf.get("btle_rf.channel"))
-            .and_then(|c| c.as_str())
-            .unwrap_or("?");
-        let rssi = btle_rf
-            .and_then(|rf| rf.get("btle_rf.signal_dbm"))
-            .and_then(|r| r.as_str())
-            .unwrap_or("?");
-
-        // Extract BLE packet info
-        let btle = layers.and_then(|l| l.get("btle"));
-        let access_addr = btle
-            .and_then(|b| b.get("btle.access_address"))
-            .and_then(|a| a.as_str())
-            .unwrap_or("Unknown");
-
-        // Determine packet type
-        let pdu_type = if let Some(adv_header) = btle.and_then(|b| b.get("btle.advertising_header.pdu_type")) {
-            let pdu_val = adv_header.as_str().unwrap_or("?");
-            match pdu_val {
-                "0x00" | "0" => "ADV_IND",
-                "0x01" | "1" => "ADV_DIRECT_IND",
-                "0x02" | "2" => "ADV_NONCONN_IND",
-                "0x03" | "3" => "SCAN_REQ",
-                "0x04" | "4" => "SCAN_RSP",
-                "0x05" | "5" => "CONNECT_REQ",
-                "0x06" | "6" => "ADV_SCAN_IND",
-                _ => "ADV",
-            }
-        } else if btle.and_then(|b| b.get("btle.data_header")).is_some() {
-            "DATA"
-        } else {
-            "UNKNOWN"
+    /// Display summary of a decoded packet, with its protocol tree.
+    fn packet_summary(packet: &DecodedPacket) -> Value {
+        let timestamp = DateTime::<Utc>::from_timestamp_micros((packet.timestamp * 1_000_000.0).round() as i64)
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S%.6f").to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+        let mac_address = match (&packet.address, &packet.peer_address) {
+            (Some(address), Some(peer)) if peer != address => format!("{} ← {}", address, peer),
+            (Some(address), _) => address.clone(),
+            (None, _) => "N/A".to_string(),
         };
 
-        // Extract MAC addresses
-        let adv_addr = btle
-            .and_then(|b| b.get("btle.advertising_address"))
-            .and_then(|a| a.as_str())
-            .or_else(|| {
-                btle.and_then(|b| b.get("btle.initiator_address"))
-                    .and_then(|a| a.as_str())
+        // Resolve company IDs and 16-bit UUIDs against the SIG assigned numbers
+        let companies: Vec<Value> = packet.company_ids.iter().map(|id| {
+            json!({
+                "company_id": format!("0x{:04X}", id),
+                "name": assigned_numbers::company_name(*id)
             })
-            .unwrap_or("N/A");
-
-        let scan_addr = btle
-            .and_then(|b| b.get("btle.scanning_address"))
-            .and_then(|a| a.as_str())
-            .or_else(|| {
-                btle.and_then(|b| b.get("btle.advertiser_address"))
-                    .and_then(|a| a.as_str())
+        }).collect();
+        let services: Vec<Value> = packet.service_uuids.iter().map(|uuid| {
+            json!({
+                "uuid": format!("0x{:04X}", uuid),
+                "name": assigned_numbers::uuid16_name(*uuid)
             })
-            .unwrap_or("");
-
-        // Create summary text
-        let mac_display = if !scan_addr.is_empty() && scan_addr != adv_addr {
-            format!("{} ← {}", adv_addr, scan_addr)
-        } else {
-            adv_addr.to_string()
-        };
-
-        // Determine protocol layer
-        let protocol = if layers.and_then(|l| l.get("btl2cap")).is_some() {
-            "L2CAP"
-        } else if layers.and_then(|l| l.get("btatt")).is_some() {
-            "ATT"
-        } else if layers.and_then(|l| l.get("btsmp")).is_some() {
-            "SMP"
-        } else {
-            "BLE"
-        };
-
-        // Create summary
-        let summary = if pdu_type == "SCAN_REQ" {
-            format!("Scan request to {}", adv_addr)
-        } else if pdu_type == "SCAN_RSP" {
-            "Scan response".to_string()
-        } else if pdu_type.starts_with("ADV") {
-            format!("Advertisement from {}", adv_addr)
-        } else if pdu_type == "CONNECT_REQ" {
-            format!("Connection request to {}", adv_addr)
-        } else {
-            "Data packet".to_string()
-        };
+        }).collect();
 
         json!({
-            "index": index,
-            "frame_number": frame_number,
+            "index": packet.index,
+            "frame_number": (packet.index + 1).to_string(),
             "timestamp": timestamp,
-            "channel": channel,
-            "rssi": rssi,
-            "packet_type": pdu_type,
-            "mac_address": mac_display,
-            "protocol": protocol,
-            "summary": summary,
-            "access_addr": access_addr,
-            // Include full packet data for expanded view
-            "full_packet": packet
+            "time_epoch": packet.timestamp,
+            "channel": packet.channel.map(|c| c.to_string()).unwrap_or_else(|| "?".to_string()),
+            "rssi": packet.rssi.map(|r| r.to_string()).unwrap_or_else(|| "?".to_string()),
+            "packet_type": packet.packet_type,
+            "mac_address": mac_address,
+            "protocol": packet.protocol,
+            "summary": packet.summary,
+            "access_addr": packet.access_address.map(|aa| format!("0x{:08x}", aa)).unwrap_or_else(|| "Unknown".to_string()),
+            "length": packet.length,
+            "companies": companies,
+            "services": services,
+            "protocol_tree": packet.tree,
+            "error": packet.error
         })
     }
 
+    /// Whether a decoded packet carries the requested protocol layer.
+    fn matches_protocol_layer(packet: &DecodedPacket, layer: &str) -> bool {
+        match layer {
+            "l2cap" => matches!(packet.protocol.as_str(), "L2CAP" | "ATT" | "SMP"),
+            "att" | "gatt" => packet.protocol == "ATT",
+            "smp" => packet.protocol == "SMP",
+            _ => true,
+        }
+    }
+
     async fn bt_decode(&self, _params: Value) -> Result<Value> {
         let capture_id = _params.get("capture_id")
             .and_then(|v| v.as_str())
             .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_id'".to_string()))?;
 
-        let protocol = _params.get("protocol").and_then(|v| v.as_str()).unwrap_or("bluetooth");
+        let protocol_layer = _params.get("protocol_layer")
+            .or_else(|| _params.get("protocol"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("auto");
+        let packet_index = _params.get("packet_index").and_then(|v| v.as_u64()).map(|i| i as usize);
         let limit = _params.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize;
 
-        tracing::info!("Decoding capture {} (protocol: {}, limit: {})", capture_id, protocol, limit);
+        tracing::info!("Decoding capture {} (layer: {}, limit: {})", capture_id, protocol_layer, limit);
 
         let store = CaptureStore::new()?;
-        let pcap_path = store.captures_dir().join(format!("{}.pcap", capture_id));
-
-        // Use tshark to decode packets
-        let limit_str = limit.to_string();
-        let output = self.execute_ubertooth_command(
-            "tshark",
-            &["-r", pcap_path.to_str().unwrap(), "-c", limit_str.as_str(), "-T", "json"]
-        ).await.unwrap_or_else(|_| "[]".to_string());
-
-        // Parse JSON output from tshark
-        let tshark_packets: Vec<Value> = serde_json::from_str(&output).unwrap_or_else(|_| vec![]);
+        let metadata = store.load_metadata(capture_id)?;
+        store.log_custody(capture_id, "accessed", json!({ "tool": "bt_decode" }))?;
+        let data = store.read_data(&metadata)?;
+
+        // A specific packet may lie beyond the default limit
+        let read_limit = match packet_index {
+            Some(index) => index + 1,
+            None if protocol_layer == "auto" => limit,
+            None => usize::MAX,
+        };
+        let packets = decode::decode_capture(&data, Some(read_limit))
+            .map_err(|e| UbertoothError::ParseError(format!("Cannot decode capture {}: {}", capture_id, e)))?;
 
-        // Extract key fields for each packet
-        let decoded_packets: Vec<Value> = tshark_packets
+        let decoded_packets: Vec<Value> = packets
             .iter()
-            .enumerate()
-            .map(|(idx, pkt)| Self::extract_packet_summary(pkt, idx))
+            .filter(|p| packet_index.is_none_or(|index| p.index == index))
+            .filter(|p| Self::matches_protocol_layer(p, protocol_layer))
+            .take(limit)
+            .map(Self::packet_summary)
             .collect();
 
+        if let Some(index) = packet_index {
+            if decoded_packets.is_empty() && packets.len() <= index {
+                return Err(UbertoothError::InvalidParameter(format!(
+                    "Capture {} has only {} packets", capture_id, packets.len()
+                )));
+            }
+        }
+
+        let malformed_packets = packets.iter().filter(|p| p.error.is_some()).count();
         let packet_count = decoded_packets.len();
 
         Ok(json!({
             "success": true,
             "capture_id": capture_id,
-            "protocol": protocol,
+            "protocol": protocol_layer,
             "decoded_packets": decoded_packets,
             "packet_count": packet_count,
-            "limit": limit
+            "malformed_packets": malformed_packets,
+            "limit": limit,
+            "annotations": Self::packet_annotations(&store, &metadata)
         }))
     }
 
-    async fn bt_fingerprint(&self, _params: Value) -> Result<Value> {
-        let capture_id = _params.get("capture_id")
+    /// Saved packet notes and bookmarks, plus comments already in a PCAPNG
+    /// capture for packets without a saved note.
+    fn packet_annotations(store: &CaptureStore, metadata: &CaptureMetadata) -> Vec<PacketAnnotation> {
+        let mut annotations = metadata.annotations.clone();
+        let file_comments = store
+            .read_data(metadata)
+            .ok()
+            .and_then(|data| pcapng::read_comments(&data).ok())
+            .unwrap_or_default();
+
+        for (packet_index, note) in file_comments {
+            match annotations.iter_mut().find(|a| a.packet_index == packet_index) {
+                Some(existing) if existing.note.is_none() => existing.note = Some(note),
+                Some(_) => {}
+                None => annotations.push(PacketAnnotation {
+                    packet_index,
+                    note: Some(note),
+                    bookmarked: false,
+                    author: None,
+                    updated_at: metadata.timestamp,
+                }),
+            }
+        }
+        annotations.sort_by_key(|a| a.packet_index);
+        annotations
+    }
+
+    async fn bt_fingerprint(&self, params: Value) -> Result<Value> {
+        let capture_id = params.get("capture_id")
             .and_then(|v| v.as_str())
             .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_id'".to_string()))?;
 
-        let target_mac = _params.get("target_mac").and_then(|v| v.as_str());
+        let target_mac = params.get("target_mac").and_then(|v| v.as_str()).map(|m| m.to_uppercase());
+        let max_matches = params.get("max_matches").and_then(|v| v.as_u64()).unwrap_or(3).max(1) as usize;
 
         tracing::info!("Fingerprinting capture {} (target: {:?})", capture_id, target_mac);
 
-        let store = CaptureStore::new()?;
-        let pcap_path = store.captures_dir().join(format!("{}.pcap", capture_id));
+        let registry = SignatureRegistry::new()?;
+        let signatures = registry.database()?;
+        vendor_registry::ensure_loaded();
 
-        // Use tshark to extract device info
-        let output = self.execute_ubertooth_command(
-            "tshark",
-            &["-r", pcap_path.to_str().unwrap(), "-T", "fields", "-e", "bluetooth.addr", "-e", "bluetooth.name"]
-        ).await.unwrap_or_default();
-
-        let mut indicators = Vec::new();
-        let mut manufacturer = "Unknown".to_string();
-        let mut device_type = "Unknown".to_string();
-        let mut confidence = 0.0;
-
-        // Parse output for device patterns
-        for line in output.lines() {
-            let parts: Vec<&str> = line.split('\t').collect();
-            if parts.len() >= 1 {
-                let addr = parts[0].trim();
-                if !addr.is_empty() {
-                    indicators.push(format!("BD_ADDR: {}", addr));
-
-                    // Simple OUI-based manufacturer detection
-                    let oui = &addr[0..8];
-                    manufacturer = match oui {
-                        "00:1A:7D" => "Apple".to_string(),
-                        "00:25:00" => "Samsung".to_string(),
-                        "00:23:12" => "Intel".to_string(),
-                        _ => "Unknown".to_string(),
-                    };
-
-                    if manufacturer != "Unknown" {
-                        confidence = 0.8;
-                    }
-                }
-            }
-            if parts.len() >= 2 {
-                let name = parts[1].trim();
-                if !name.is_empty() {
-                    indicators.push(format!("Device name: {}", name));
-
-                    // Infer device type from name
-                    if name.to_lowercase().contains("phone") {
-                        device_type = "Smartphone".to_string();
-                        confidence = 0.9;
-                    } else if name.to_lowercase().contains("headset") || name.to_lowercase().contains("buds") {
-                        device_type = "Audio device".to_string();
-                        confidence = 0.85;
-                    }
-                }
+        let store = CaptureStore::new()?;
+        let metadata = store.load_metadata(capture_id)?;
+        store.log_custody(capture_id, "accessed", json!({ "tool": "bt_fingerprint" }))?;
+        let data = store.read_data(&metadata)?;
+        let mut features = fingerprint::extract_features(&data)
+            .map_err(|e| UbertoothError::ParseError(format!("Cannot decode capture {}: {}", capture_id, e)))?;
+
+        if let Some(target) = &target_mac {
+            features.retain(|device| &device.address == target);
+            if features.is_empty() {
+                return Err(UbertoothError::InvalidParameter(format!(
+                    "Device {} does not advertise in capture {}",
+                    target, capture_id
+                )));
             }
         }
 
-        let device_mac = target_mac.unwrap_or("unknown");
+        let devices: Vec<Value> = features
+            .iter()
+            .map(|device| {
+                json!({
+                    "address": device.address,
+                    "features": device,
+                    "matches": signatures.identify(device, max_matches)
+                })
+            })
+            .collect();
 
-        Ok(json!({
-            "success": true,
-            "device": {
-                "mac_address": device_mac,
+        // Summary of the target (or the most confidently identified device)
+        let best = devices.iter().max_by(|a, b| {
+            let confidence = |d: &Value| d["matches"][0]["confidence"].as_f64().unwrap_or(0.0);
+            confidence(a).total_cmp(&confidence(b))
+        });
+        let device = best.map(|best| {
+            let top = &best["matches"][0];
+            let indicators: Vec<String> = top["evidence"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|e| e["matched"] == true)
+                .map(|e| format!("{}: {}", e["feature"].as_str().unwrap_or(""), e["observed"].as_str().unwrap_or("")))
+                .collect();
+            json!({
+                "mac_address": best["address"],
                 "fingerprint": {
-                    "manufacturer": manufacturer,
-                    "device_type": device_type,
-                    "os_version": null,
-                    "confidence": confidence
+                    "manufacturer": top.get("manufacturer").cloned().unwrap_or(json!("Unknown")),
+                    "device_type": top.get("device_type").cloned().unwrap_or(json!("Unknown")),
+                    "os_version": top.get("os").cloned().unwrap_or(Value::Null),
+                    "confidence": top.get("confidence").cloned().unwrap_or(json!(0.0))
                 },
                 "indicators": indicators
+            })
+        });
+
+        Ok(json!({
+            "success": true,
+            "capture_id": capture_id,
+            "device": device,
+            "devices": devices,
+            "signatures": {
+                "count": signatures.signatures().len(),
+                "directory": registry.signatures_dir().to_string_lossy(),
+                "files": registry.installed_files()?
             }
         }))
     }
 
     async fn pcap_merge(&self, _params: Value) -> Result<Value> {
-        let capture_ids = _params.get("capture_ids")
+        let capture_ids: Vec<&str> = _params.get("capture_ids")
             .and_then(|v| v.as_array())
-            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_ids' array".to_string()))?;
+            .ok_or_else(|| UbertoothError::InvalidParameter("Missing 'capture_ids' array".to_string()))?
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect();
 
         if capture_ids.len() < 2 {
             return Err(UbertoothError::InvalidParameter(
@@ -2637,52 +3903,48 @@ impl SidecarManager {
             ));
         }
 
+        let output_name = _params.get("output_name").and_then(|v| v.as_str());
+        let options = MergeOptions {
+            sort_by_timestamp: _params.get("sort_by_timestamp").and_then(|v| v.as_bool()).unwrap_or(true),
+            dedup_window: _params.get("deduplicate").and_then(|v| v.as_bool()).unwrap_or(false).then(|| {
+                _params.get("dedup_window_ms")
+                    .and_then(|v| v.as_u64())
+                    .map(std::time::Duration::from_millis)
+                    .unwrap_or(merge::DEFAULT_DEDUP_WINDOW)
+            }),
+        };
+
         tracing::info!("Merging {} captures", capture_ids.len());
 
         let store = CaptureStore::new()?;
         let capture_id = CaptureStore::generate_capture_id("merged");
-        let output_path = store.captures_dir().join(format!("{}.pcap", capture_id));
-
-        // Build input file list
-        let mut input_paths = Vec::new();
-        for id_val in capture_ids {
-            if let Some(id) = id_val.as_str() {
-                let path = store.captures_dir().join(format!("{}.pcap", id));
-                if path.exists() {
-                    input_paths.push(path.to_string_lossy().to_string());
-                } else {
-                    return Err(UbertoothError::CaptureNotFound(id.to_string()));
-                }
+
+        let mut sources = Vec::new();
+        for id in &capture_ids {
+            let metadata = store.load_metadata(id)?;
+            if !Path::new(&metadata.pcap_path).exists() {
+                return Err(UbertoothError::CaptureNotFound(id.to_string()));
             }
+            let data = store.read_data(&metadata)?;
+            store.log_custody(id, "accessed", json!({ "tool": "pcap_merge", "merged_into": capture_id }))?;
+            sources.push((metadata, data));
         }
 
-        // Use mergecap to merge PCAP files
-        let mut args = vec!["-w", output_path.to_str().unwrap()];
-        let input_refs: Vec<&str> = input_paths.iter().map(|s| s.as_str()).collect();
-        args.extend(input_refs);
+        let inputs: Vec<&[u8]> = sources.iter().map(|(_, data)| data.as_slice()).collect();
+        let mut merged = Vec::new();
+        let report = merge::merge(&inputs, &mut merged, &options)
+            .map_err(|e| UbertoothError::ParseError(format!("Cannot merge captures: {}", e)))?;
 
-        self.execute_ubertooth_command("mergecap", &args).await?;
+        let output_path = store.captures_dir().join(format!("{}.{}", capture_id, report.extension()));
+        std::fs::write(&output_path, &merged)?;
 
-        // Count total packets in merged file
-        let capinfos_output = self.execute_ubertooth_command(
-            "capinfos",
-            &[output_path.to_str().unwrap()]
-        ).await.unwrap_or_default();
-
-        let mut total_packets = 0;
-        for line in capinfos_output.lines() {
-            if line.contains("Number of packets") {
-                if let Some(num_str) = line.split(':').nth(1) {
-                    total_packets = num_str.trim().parse().unwrap_or(0);
-                }
-            }
-        }
+        let mut channels: Vec<u8> = sources.iter().flat_map(|(m, _)| m.channels.iter().copied()).collect();
+        channels.sort_unstable();
+        channels.dedup();
 
-        // Save metadata
-        let file_size_bytes = if output_path.exists() {
-            std::fs::metadata(&output_path)?.len()
-        } else {
-            0
+        let description = match output_name {
+            Some(name) => format!("{} (merged from {} source captures)", name, capture_ids.len()),
+            None => format!("Merged from {} source captures", capture_ids.len()),
         };
 
         let metadata = CaptureMetadata {
@@ -2690,19 +3952,39 @@ impl SidecarManager {
             timestamp: Utc::now(),
             capture_type: "merged".to_string(),
             duration_sec: None,
-            packet_count: total_packets,
-            file_size_bytes,
+            packet_count: report.total_packets,
+            file_size_bytes: merged.len() as u64,
             pcap_path: output_path.to_string_lossy().to_string(),
             tags: vec!["merged".to_string()],
-            description: format!("Merged from {} source captures", capture_ids.len()),
+            description,
+            channels,
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
         };
         store.save_metadata(&metadata)?;
 
+        let source_counts: Vec<Value> = capture_ids.iter().zip(&report.sources).map(|(id, count)| {
+            json!({
+                "capture_id": id,
+                "packets_read": count.packets_read,
+                "packets_written": count.packets_written,
+                "duplicates_removed": count.duplicates_removed,
+                "linktypes": count.linktypes
+            })
+        }).collect();
+
         Ok(json!({
             "success": true,
             "capture_id": capture_id,
             "source_captures": capture_ids.len(),
-            "total_packets": total_packets,
+            "sources": source_counts,
+            "total_packets": report.total_packets,
+            "duplicates_removed": report.duplicates_removed,
+            "format": report.extension(),
+            "linktypes": report.linktypes,
             "pcap_path": output_path.to_string_lossy()
         }))
     }
@@ -2717,60 +3999,55 @@ impl SidecarManager {
 
         tracing::info!("Exporting capture {} to format {}", capture_id, format);
 
-        let store = CaptureStore::new()?;
-        let input_path = store.captures_dir().join(format!("{}.pcap", capture_id));
+        let fields = match _params.get("fields").and_then(|v| v.as_array()) {
+            Some(names) => ExportField::parse_list(names.iter().filter_map(|v| v.as_str()))
+                .map_err(|e| UbertoothError::InvalidParameter(e.to_string()))?,
+            None => ExportField::DEFAULT.to_vec(),
+        };
+        if !matches!(format, "pcap" | "pcapng" | "csv" | "json" | "jsonl") {
+            return Err(UbertoothError::InvalidParameter(
+                format!("Unsupported format: {}", format)
+            ));
+        }
 
-        if !input_path.exists() {
+        let store = CaptureStore::new()?;
+        let metadata = store.load_metadata(capture_id)?;
+        if !Path::new(&metadata.pcap_path).exists() {
             return Err(UbertoothError::CaptureNotFound(capture_id.to_string()));
         }
 
-        // Determine export path
+        // Determine export path; exports are plaintext, so never into the captures directory
         let export_path = if let Some(path) = output_path {
             PathBuf::from(path)
         } else {
-            store.captures_dir().join(format!("{}.{}", capture_id, format))
+            ubertooth_core::storage::exports_dir()?.join(format!("{}.{}", capture_id, format))
         };
 
-        // Use tshark or editcap for format conversion
+        let mut comments_written = 0;
+        let mut packet_count = metadata.packet_count;
         match format {
-            "pcap" => {
-                // Just copy the file
-                std::fs::copy(&input_path, &export_path)?;
-            }
             "pcapng" => {
-                // Use editcap to convert to pcapng
-                self.execute_ubertooth_command(
-                    "editcap",
-                    &["-F", "pcapng", input_path.to_str().unwrap(), export_path.to_str().unwrap()]
-                ).await?;
-            }
-            "json" => {
-                // Use tshark to export to JSON
-                let json_output = self.execute_ubertooth_command(
-                    "tshark",
-                    &["-r", input_path.to_str().unwrap(), "-T", "json"]
-                ).await?;
-                std::fs::write(&export_path, json_output)?;
+                // Packet notes become PCAPNG packet comments
+                comments_written = store.write_pcapng(&metadata, &export_path)?;
             }
-            "csv" => {
-                // Use tshark to export to CSV
-                let csv_output = self.execute_ubertooth_command(
-                    "tshark",
-                    &["-r", input_path.to_str().unwrap(), "-T", "fields", "-E", "header=y", "-E", "separator=,"]
-                ).await?;
-                std::fs::write(&export_path, csv_output)?;
+            "pcap" => {
+                std::fs::write(&export_path, store.read_data(&metadata)?)?;
             }
             _ => {
-                return Err(UbertoothError::InvalidParameter(
-                    format!("Unsupported format: {}", format)
-                ));
+                // Text formats are decoded and written packet by packet
+                let data = store.read_data(&metadata)?;
+                let mut file = std::io::BufWriter::new(File::create(&export_path)?);
+                let written = match format {
+                    "csv" => export::write_csv(&data, &mut file, &fields),
+                    "jsonl" => export::write_jsonl(&data, &mut file, &fields),
+                    _ => export::write_json(&data, &mut file, &fields),
+                }
+                .map_err(|e| UbertoothError::ParseError(format!("Cannot export capture {}: {}", capture_id, e)))?;
+                file.flush()?;
+                packet_count = written;
             }
         }
 
-        // Get packet count
-        let metadata = store.load_metadata(capture_id)?;
-        let packet_count = metadata.packet_count;
-
         // Get exported file size
         let file_size_bytes = if export_path.exists() {
             std::fs::metadata(&export_path)?.len()
@@ -2778,12 +4055,29 @@ impl SidecarManager {
             0
         };
 
+        // Hash the export so the deliverable can be tied back to the capture
+        let export_sha256 = custody::sha256_file(&export_path).ok().map(|(sha256, _)| sha256);
+        store.log_custody(
+            capture_id,
+            "exported",
+            json!({
+                "format": format,
+                "export_path": export_path.to_string_lossy(),
+                "sha256": export_sha256,
+                "source_sha256": metadata.provenance.as_ref().map(|p| p.sha256.clone())
+            }),
+        )?;
+
         Ok(json!({
             "success": true,
             "export_path": export_path.to_string_lossy(),
             "format": format,
             "packet_count": packet_count,
-            "file_size_bytes": file_size_bytes
+            "fields": matches!(format, "csv" | "json" | "jsonl")
+                .then(|| fields.iter().map(|f| f.name()).collect::<Vec<_>>()),
+            "file_size_bytes": file_size_bytes,
+            "comments_written": comments_written,
+            "sha256": export_sha256
         }))
     }
 
@@ -2860,9 +4154,10 @@ impl SidecarManager {
         let interval_str = advertising_interval.to_string();
         let pcap_str = pcap_path.to_string_lossy().to_string();
 
-        let output = self.execute_ubertooth_command(
+        let output = self.execute_ubertooth_command_with(
             "ubertooth-btle",
-            &["-a", "-m", mac_address, "-i", interval_str.as_str(), "-d", duration_str.as_str(), "-r", pcap_str.as_str()]
+            &["-a", "-m", mac_address, "-i", interval_str.as_str(), "-d", duration_str.as_str(), "-r", pcap_str.as_str()],
+            RunOptions::for_duration(std::time::Duration::from_secs(duration_sec)),
         ).await?;
 
         // Parse output for connection events
@@ -2889,6 +4184,12 @@ impl SidecarManager {
             pcap_path: pcap_path.to_string_lossy().to_string(),
             tags: vec![format!("mac:{}", mac_address)],
             description: format!("BLE slave mode, {} connections", connections_received),
+            channels: Vec::new(),
+            engagement: None,
+            pruned: None,
+            provenance: None,
+            config_preset: None,
+            annotations: Vec::new(),
         };
         store.save_metadata(&metadata)?;
 
@@ -3000,10 +4301,11 @@ mod tests {
             .with_max_level(tracing::Level::DEBUG)
             .try_init();
 
-        let pcap_path = format!(
-            "{}/.ubertooth/captures/cap-btle-06b8b707-431f-4b7c-8eda-fb02b7e253d3.pcap",
-            std::env::var("HOME").unwrap()
-        );
+        let pcap_path = ubertooth_core::storage::storage_root()
+            .unwrap()
+            .join("captures/cap-btle-06b8b707-431f-4b7c-8eda-fb02b7e253d3.pcap")
+            .to_string_lossy()
+            .to_string();
 
         if !std::path::Path::new(&pcap_path).exists() {
             println!("PCAP file not found, skipping test: {}", pcap_path);
@@ -3053,4 +4355,14 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_versions_json() {
+        let versions = ToolVersions::parse("libubertooth 1.1 (2017-03-R2), libbtbb 1.0 (2017-03-R2)");
+        let (versions, compatibility) = SidecarManager::versions_json(&versions);
+        assert_eq!(versions["host_release"], "2017-03-R2");
+        assert_eq!(compatibility["btle_follow"]["status"], "supported");
+        assert_eq!(compatibility["btle_advertising_channel"]["status"], "unsupported");
+        assert!(compatibility["afh"]["reason"].as_str().unwrap().contains("2018-06-R1"));
+    }
 }
@@ -0,0 +1,148 @@
+//! IEEE OUI vendor registry management.
+//!
+//! Vendor lookups use the registry bundled with `ubertooth-protocol` until a
+//! newer one is installed with the `oui_update` tool. Installed registry
+//! files are kept in `<storage root>/oui/` and loaded on the first lookup.
+
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use ubertooth_core::error::{Result, UbertoothError};
+use ubertooth_core::storage;
+use ubertooth_protocol::oui::{self, OuiDatabase};
+
+static LOAD_INSTALLED: Once = Once::new();
+
+/// Local OUI registry storage.
+pub struct VendorRegistry {
+    registry_dir: PathBuf,
+}
+
+impl VendorRegistry {
+    /// Create a vendor registry at `<storage root>/oui/`.
+    pub fn new() -> Result<Self> {
+        let registry_dir = storage::storage_dir(storage::OUI_DIR)?;
+
+        Ok(Self { registry_dir })
+    }
+
+    /// Get the registry directory path.
+    pub fn registry_dir(&self) -> &Path {
+        &self.registry_dir
+    }
+
+    /// Registry files installed in the registry directory, sorted by name.
+    pub fn installed_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.registry_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                name.ends_with(".csv") || name.ends_with(".csv.gz")
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// Build a database from the bundled registry plus the given files.
+    fn build_database(files: &[PathBuf]) -> Result<OuiDatabase> {
+        let mut db = OuiDatabase::bundled()
+            .map_err(|e| UbertoothError::BackendError(format!("Bundled OUI registry unreadable: {}", e)))?;
+
+        for file in files {
+            db.load_file(file).map_err(|e| {
+                UbertoothError::InvalidParameter(format!("Invalid OUI registry {}: {}", file.display(), e))
+            })?;
+        }
+
+        Ok(db)
+    }
+
+    /// Load the installed registry files into the process-wide database.
+    pub fn load_installed(&self) -> Result<usize> {
+        let files = self.installed_files()?;
+        if files.is_empty() {
+            return Ok(0);
+        }
+
+        let db = Self::build_database(&files)?;
+        oui::set_database(db);
+        Ok(files.len())
+    }
+
+    /// Validate and install local IEEE registry CSV files, then reload.
+    pub fn install(&self, sources: &[PathBuf]) -> Result<Value> {
+        if sources.is_empty() {
+            return Err(UbertoothError::InvalidParameter("No registry files given".to_string()));
+        }
+
+        // Validate everything before copying anything
+        let mut files = self.installed_files()?;
+        for source in sources {
+            if !source.is_file() {
+                return Err(UbertoothError::InvalidParameter(format!(
+                    "Registry file not found: {}",
+                    source.display()
+                )));
+            }
+            let mut probe = OuiDatabase::default();
+            let count = probe.load_file(source).map_err(|e| {
+                UbertoothError::InvalidParameter(format!("Invalid OUI registry {}: {}", source.display(), e))
+            })?;
+            if count == 0 {
+                return Err(UbertoothError::InvalidParameter(format!(
+                    "No MA-L/MA-M/MA-S assignments in {}",
+                    source.display()
+                )));
+            }
+        }
+
+        let mut installed = Vec::new();
+        for source in sources {
+            let name = source
+                .file_name()
+                .ok_or_else(|| UbertoothError::InvalidParameter(format!("Invalid path: {}", source.display())))?;
+            let target = self.registry_dir.join(name);
+            fs::copy(source, &target)?;
+            if !files.contains(&target) {
+                files.push(target.clone());
+            }
+            installed.push(target.to_string_lossy().to_string());
+        }
+        files.sort();
+
+        let db = Self::build_database(&files)?;
+        let (ma_l, ma_m, ma_s) = db.counts();
+        let total = db.len();
+        oui::set_database(db);
+
+        Ok(json!({
+            "success": true,
+            "files_installed": installed,
+            "registry_dir": self.registry_dir.to_string_lossy(),
+            "assignments": {
+                "ma_l": ma_l,
+                "ma_m": ma_m,
+                "ma_s": ma_s,
+                "total": total
+            }
+        }))
+    }
+}
+
+/// Load installed registry files into the process-wide database (once).
+pub fn ensure_loaded() {
+    LOAD_INSTALLED.call_once(|| {
+        let loaded = VendorRegistry::new().and_then(|registry| registry.load_installed());
+        if let Err(e) = loaded {
+            tracing::warn!("Failed to load installed OUI registry, using bundled data: {}", e);
+        }
+    });
+}
+
+/// Vendor name for a public address, or `None` for randomized addresses.
+pub fn vendor_for(mac: &str, random: bool) -> Option<String> {
+    ensure_loaded();
+    oui::vendor_for(mac, random)
+}
@@ -0,0 +1,1105 @@
+//! BLE advertising data (AD structure) parsing.
+//!
+//! Covers the AD types listed in the Bluetooth SIG Assigned Numbers document,
+//! section 2.3 "Common Data Types". Types with a defined layout are decoded
+//! into typed fields; opaque containers (mesh PDUs, BIGInfo, encrypted data,
+//! etc.) are kept in `raw_ad_structures` and can be named with [`ad_type_name`].
+
+pub use crate::assigned_numbers::ad_type_name;
+
+use crate::assigned_numbers::{appearance_name, company_name, uuid128_name, uuid16_name};
+use crate::beacons::{self, Beacon};
+use crate::error::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// AD type codes (Assigned Numbers, section 2.3).
+pub mod ad_type {
+    pub const FLAGS: u8 = 0x01;
+    pub const INCOMPLETE_UUIDS_16: u8 = 0x02;
+    pub const COMPLETE_UUIDS_16: u8 = 0x03;
+    pub const INCOMPLETE_UUIDS_32: u8 = 0x04;
+    pub const COMPLETE_UUIDS_32: u8 = 0x05;
+    pub const INCOMPLETE_UUIDS_128: u8 = 0x06;
+    pub const COMPLETE_UUIDS_128: u8 = 0x07;
+    pub const SHORTENED_LOCAL_NAME: u8 = 0x08;
+    pub const COMPLETE_LOCAL_NAME: u8 = 0x09;
+    pub const TX_POWER_LEVEL: u8 = 0x0A;
+    pub const CLASS_OF_DEVICE: u8 = 0x0D;
+    pub const SIMPLE_PAIRING_HASH_C192: u8 = 0x0E;
+    pub const SIMPLE_PAIRING_RANDOMIZER_R192: u8 = 0x0F;
+    pub const SECURITY_MANAGER_TK_VALUE: u8 = 0x10;
+    pub const SECURITY_MANAGER_OOB_FLAGS: u8 = 0x11;
+    pub const PERIPHERAL_CONNECTION_INTERVAL_RANGE: u8 = 0x12;
+    pub const SOLICITATION_UUIDS_16: u8 = 0x14;
+    pub const SOLICITATION_UUIDS_128: u8 = 0x15;
+    pub const SERVICE_DATA_16: u8 = 0x16;
+    pub const PUBLIC_TARGET_ADDRESS: u8 = 0x17;
+    pub const RANDOM_TARGET_ADDRESS: u8 = 0x18;
+    pub const APPEARANCE: u8 = 0x19;
+    pub const ADVERTISING_INTERVAL: u8 = 0x1A;
+    pub const LE_BLUETOOTH_DEVICE_ADDRESS: u8 = 0x1B;
+    pub const LE_ROLE: u8 = 0x1C;
+    pub const SIMPLE_PAIRING_HASH_C256: u8 = 0x1D;
+    pub const SIMPLE_PAIRING_RANDOMIZER_R256: u8 = 0x1E;
+    pub const SOLICITATION_UUIDS_32: u8 = 0x1F;
+    pub const SERVICE_DATA_32: u8 = 0x20;
+    pub const SERVICE_DATA_128: u8 = 0x21;
+    pub const LE_SC_CONFIRMATION_VALUE: u8 = 0x22;
+    pub const LE_SC_RANDOM_VALUE: u8 = 0x23;
+    pub const URI: u8 = 0x24;
+    pub const INDOOR_POSITIONING: u8 = 0x25;
+    pub const TRANSPORT_DISCOVERY_DATA: u8 = 0x26;
+    pub const LE_SUPPORTED_FEATURES: u8 = 0x27;
+    pub const CHANNEL_MAP_UPDATE_INDICATION: u8 = 0x28;
+    pub const PB_ADV: u8 = 0x29;
+    pub const MESH_MESSAGE: u8 = 0x2A;
+    pub const MESH_BEACON: u8 = 0x2B;
+    pub const BIGINFO: u8 = 0x2C;
+    pub const BROADCAST_CODE: u8 = 0x2D;
+    pub const RESOLVABLE_SET_IDENTIFIER: u8 = 0x2E;
+    pub const ADVERTISING_INTERVAL_LONG: u8 = 0x2F;
+    pub const BROADCAST_NAME: u8 = 0x30;
+    pub const ENCRYPTED_ADVERTISING_DATA: u8 = 0x31;
+    pub const PAWR_TIMING_INFORMATION: u8 = 0x32;
+    pub const ELECTRONIC_SHELF_LABEL: u8 = 0x34;
+    pub const INFORMATION_3D_DATA: u8 = 0x3D;
+    pub const MANUFACTURER_SPECIFIC_DATA: u8 = 0xFF;
+}
+
+/// BLE address type (TxAdd bit of the advertising PDU header).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddressType {
+    Public,
+    Random,
+}
+
+impl AddressType {
+    /// Address type from the TxAdd/RxAdd header bit.
+    pub fn from_bit(random: bool) -> Self {
+        if random {
+            AddressType::Random
+        } else {
+            AddressType::Public
+        }
+    }
+
+    /// Lowercase name used in JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressType::Public => "public",
+            AddressType::Random => "random",
+        }
+    }
+}
+
+/// LE Role AD value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeRole {
+    PeripheralOnly,
+    CentralOnly,
+    PeripheralPreferred,
+    CentralPreferred,
+    Reserved(u8),
+}
+
+impl LeRole {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0x00 => LeRole::PeripheralOnly,
+            0x01 => LeRole::CentralOnly,
+            0x02 => LeRole::PeripheralPreferred,
+            0x03 => LeRole::CentralPreferred,
+            other => LeRole::Reserved(other),
+        }
+    }
+
+    /// Human-readable role description.
+    pub fn description(&self) -> &'static str {
+        match self {
+            LeRole::PeripheralOnly => "Only Peripheral role supported",
+            LeRole::CentralOnly => "Only Central role supported",
+            LeRole::PeripheralPreferred => "Peripheral and Central supported, Peripheral preferred",
+            LeRole::CentralPreferred => "Peripheral and Central supported, Central preferred",
+            LeRole::Reserved(_) => "Reserved",
+        }
+    }
+}
+
+/// Peripheral Connection Interval Range (units of 1.25 ms).
+///
+/// `None` means the peripheral has no specific minimum/maximum (0xFFFF).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionIntervalRange {
+    pub min: Option<u16>,
+    pub max: Option<u16>,
+}
+
+impl ConnectionIntervalRange {
+    /// Minimum interval in milliseconds.
+    pub fn min_ms(&self) -> Option<f64> {
+        self.min.map(|v| v as f64 * 1.25)
+    }
+
+    /// Maximum interval in milliseconds.
+    pub fn max_ms(&self) -> Option<f64> {
+        self.max.map(|v| v as f64 * 1.25)
+    }
+}
+
+/// LE Bluetooth Device Address AD value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeDeviceAddress {
+    pub address: [u8; 6],
+    pub address_type: AddressType,
+}
+
+/// Channel Map Update Indication AD value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelMapUpdate {
+    /// 37-bit data channel map (little-endian)
+    pub channel_map: [u8; 5],
+    /// Connection event counter at which the map takes effect
+    pub instant: u16,
+}
+
+/// Out-of-band pairing values carried in AD structures.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OobData {
+    pub simple_pairing_hash_c192: Option<Vec<u8>>,
+    pub simple_pairing_randomizer_r192: Option<Vec<u8>>,
+    pub simple_pairing_hash_c256: Option<Vec<u8>>,
+    pub simple_pairing_randomizer_r256: Option<Vec<u8>>,
+    pub security_manager_tk: Option<Vec<u8>>,
+    pub security_manager_oob_flags: Option<u8>,
+    pub le_sc_confirmation: Option<Vec<u8>>,
+    pub le_sc_random: Option<Vec<u8>>,
+}
+
+impl OobData {
+    /// Check whether any OOB value is present.
+    pub fn is_empty(&self) -> bool {
+        *self == OobData::default()
+    }
+}
+
+/// Parsed BLE advertising data structures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvertisingData {
+    /// Advertiser address (first 6 bytes)
+    pub address: [u8; 6],
+
+    /// Address type (public/random from PDU header)
+    pub address_type: AddressType,
+
+    /// AD type codes in the order they appeared
+    pub ad_types: Vec<u8>,
+
+    /// Complete or shortened local name
+    pub name: Option<String>,
+
+    /// Whether `name` came from a Complete Local Name structure
+    pub name_complete: bool,
+
+    /// Flags
+    pub flags: Option<u8>,
+
+    /// TX power level (dBm)
+    pub tx_power: Option<i8>,
+
+    /// Service UUIDs (16-bit)
+    pub service_uuids_16: Vec<u16>,
+
+    /// Service UUIDs (32-bit)
+    pub service_uuids_32: Vec<u32>,
+
+    /// Service UUIDs (128-bit)
+    pub service_uuids_128: Vec<u128>,
+
+    /// Service solicitation UUIDs (16-bit)
+    pub solicited_uuids_16: Vec<u16>,
+
+    /// Service solicitation UUIDs (32-bit)
+    pub solicited_uuids_32: Vec<u32>,
+
+    /// Service solicitation UUIDs (128-bit)
+    pub solicited_uuids_128: Vec<u128>,
+
+    /// Manufacturer specific data blocks (company ID + data), in order
+    pub manufacturer_data: Vec<(u16, Vec<u8>)>,
+
+    /// Service data (16-bit UUID)
+    pub service_data: Vec<(u16, Vec<u8>)>,
+
+    /// Service data (32-bit UUID)
+    pub service_data_32: Vec<(u32, Vec<u8>)>,
+
+    /// Service data (128-bit UUID)
+    pub service_data_128: Vec<(u128, Vec<u8>)>,
+
+    /// Appearance value
+    pub appearance: Option<u16>,
+
+    /// Class of Device (24-bit)
+    pub class_of_device: Option<u32>,
+
+    /// LE Role
+    pub le_role: Option<LeRole>,
+
+    /// URI with the scheme expanded
+    pub uri: Option<String>,
+
+    /// Advertising interval (units of 0.625 ms)
+    pub advertising_interval: Option<u32>,
+
+    /// Peripheral connection interval range
+    pub peripheral_connection_interval: Option<ConnectionIntervalRange>,
+
+    /// Public target addresses
+    pub public_target_addresses: Vec<[u8; 6]>,
+
+    /// Random target addresses
+    pub random_target_addresses: Vec<[u8; 6]>,
+
+    /// LE Bluetooth Device Address
+    pub le_device_address: Option<LeDeviceAddress>,
+
+    /// LE Supported Features bit field
+    pub le_supported_features: Option<Vec<u8>>,
+
+    /// Channel Map Update Indication
+    pub channel_map_update: Option<ChannelMapUpdate>,
+
+    /// Resolvable Set Identifier
+    pub resolvable_set_identifier: Option<[u8; 6]>,
+
+    /// Broadcast name (LE Audio)
+    pub broadcast_name: Option<String>,
+
+    /// Out-of-band pairing data
+    pub oob: OobData,
+
+    /// Raw AD structures for unparsed types
+    pub raw_ad_structures: Vec<(u8, Vec<u8>)>,
+}
+
+impl AdvertisingData {
+    /// Create empty advertising data for an address.
+    pub fn new(address: [u8; 6], address_type: AddressType) -> Self {
+        Self {
+            address,
+            address_type,
+            ad_types: Vec::new(),
+            name: None,
+            name_complete: false,
+            flags: None,
+            tx_power: None,
+            service_uuids_16: Vec::new(),
+            service_uuids_32: Vec::new(),
+            service_uuids_128: Vec::new(),
+            solicited_uuids_16: Vec::new(),
+            solicited_uuids_32: Vec::new(),
+            solicited_uuids_128: Vec::new(),
+            manufacturer_data: Vec::new(),
+            service_data: Vec::new(),
+            service_data_32: Vec::new(),
+            service_data_128: Vec::new(),
+            appearance: None,
+            class_of_device: None,
+            le_role: None,
+            uri: None,
+            advertising_interval: None,
+            peripheral_connection_interval: None,
+            public_target_addresses: Vec::new(),
+            random_target_addresses: Vec::new(),
+            le_device_address: None,
+            le_supported_features: None,
+            channel_map_update: None,
+            resolvable_set_identifier: None,
+            broadcast_name: None,
+            oob: OobData::default(),
+            raw_ad_structures: Vec::new(),
+        }
+    }
+
+    /// Parse advertising data from BLE payload (AdvA followed by AD structures)
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        if payload.len() < 6 {
+            return Err(ProtocolError::Truncated("Payload too short for address".to_string()));
+        }
+
+        // Extract address (first 6 bytes)
+        let mut address = [0u8; 6];
+        address.copy_from_slice(&payload[0..6]);
+
+        // Address type is set by the caller from the PDU header
+        let mut ad_data = Self::new(address, AddressType::Public);
+        ad_data.parse_ad_structures(&payload[6..]);
+
+        Ok(ad_data)
+    }
+
+    /// Parse a sequence of AD structures (length, type, data) into this value.
+    ///
+    /// Parsing stops at the first zero-length or truncated structure.
+    pub fn parse_ad_structures(&mut self, ad_bytes: &[u8]) {
+        let mut offset = 0;
+
+        while offset + 1 < ad_bytes.len() {
+            let length = ad_bytes[offset] as usize;
+            if length == 0 {
+                break;
+            }
+
+            if offset + 1 + length > ad_bytes.len() {
+                break; // Incomplete structure
+            }
+
+            let ad_type = ad_bytes[offset + 1];
+            let data = &ad_bytes[offset + 2..offset + 1 + length];
+
+            self.ad_types.push(ad_type);
+            self.apply_structure(ad_type, data);
+
+            offset += 1 + length;
+        }
+    }
+
+    /// Decode one AD structure into the matching typed field.
+    fn apply_structure(&mut self, code: u8, data: &[u8]) {
+        use ad_type::*;
+
+        match code {
+            FLAGS => {
+                if let Some(&flags) = data.first() {
+                    self.flags = Some(flags);
+                }
+            }
+            INCOMPLETE_UUIDS_16 | COMPLETE_UUIDS_16 => {
+                push_unique(&mut self.service_uuids_16, uuids_16(data));
+            }
+            INCOMPLETE_UUIDS_32 | COMPLETE_UUIDS_32 => {
+                push_unique(&mut self.service_uuids_32, uuids_32(data));
+            }
+            INCOMPLETE_UUIDS_128 | COMPLETE_UUIDS_128 => {
+                push_unique(&mut self.service_uuids_128, uuids_128(data));
+            }
+            SHORTENED_LOCAL_NAME | COMPLETE_LOCAL_NAME => {
+                let complete = code == COMPLETE_LOCAL_NAME;
+                // Never let a shortened name replace a complete one
+                if complete || !self.name_complete {
+                    if let Ok(name) = String::from_utf8(data.to_vec()) {
+                        self.name = Some(name);
+                        self.name_complete = complete;
+                    }
+                }
+            }
+            TX_POWER_LEVEL => {
+                if let Some(&power) = data.first() {
+                    self.tx_power = Some(power as i8);
+                }
+            }
+            CLASS_OF_DEVICE if data.len() >= 3 => {
+                self.class_of_device = Some(u32::from_le_bytes([data[0], data[1], data[2], 0]));
+            }
+            SIMPLE_PAIRING_HASH_C192 => self.oob.simple_pairing_hash_c192 = Some(data.to_vec()),
+            SIMPLE_PAIRING_RANDOMIZER_R192 => self.oob.simple_pairing_randomizer_r192 = Some(data.to_vec()),
+            SIMPLE_PAIRING_HASH_C256 => self.oob.simple_pairing_hash_c256 = Some(data.to_vec()),
+            SIMPLE_PAIRING_RANDOMIZER_R256 => self.oob.simple_pairing_randomizer_r256 = Some(data.to_vec()),
+            SECURITY_MANAGER_TK_VALUE => self.oob.security_manager_tk = Some(data.to_vec()),
+            SECURITY_MANAGER_OOB_FLAGS => self.oob.security_manager_oob_flags = data.first().copied(),
+            LE_SC_CONFIRMATION_VALUE => self.oob.le_sc_confirmation = Some(data.to_vec()),
+            LE_SC_RANDOM_VALUE => self.oob.le_sc_random = Some(data.to_vec()),
+            PERIPHERAL_CONNECTION_INTERVAL_RANGE if data.len() >= 4 => {
+                let min = u16::from_le_bytes([data[0], data[1]]);
+                let max = u16::from_le_bytes([data[2], data[3]]);
+                self.peripheral_connection_interval = Some(ConnectionIntervalRange {
+                    min: (min != 0xFFFF).then_some(min),
+                    max: (max != 0xFFFF).then_some(max),
+                });
+            }
+            SOLICITATION_UUIDS_16 => push_unique(&mut self.solicited_uuids_16, uuids_16(data)),
+            SOLICITATION_UUIDS_32 => push_unique(&mut self.solicited_uuids_32, uuids_32(data)),
+            SOLICITATION_UUIDS_128 => push_unique(&mut self.solicited_uuids_128, uuids_128(data)),
+            SERVICE_DATA_16 if data.len() >= 2 => {
+                let uuid = u16::from_le_bytes([data[0], data[1]]);
+                self.service_data.push((uuid, data[2..].to_vec()));
+            }
+            SERVICE_DATA_32 if data.len() >= 4 => {
+                let uuid = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+                self.service_data_32.push((uuid, data[4..].to_vec()));
+            }
+            SERVICE_DATA_128 if data.len() >= 16 => {
+                let mut bytes = [0u8; 16];
+                bytes.copy_from_slice(&data[..16]);
+                self.service_data_128.push((u128::from_le_bytes(bytes), data[16..].to_vec()));
+            }
+            PUBLIC_TARGET_ADDRESS => push_unique(&mut self.public_target_addresses, addresses(data)),
+            RANDOM_TARGET_ADDRESS => push_unique(&mut self.random_target_addresses, addresses(data)),
+            APPEARANCE if data.len() >= 2 => {
+                self.appearance = Some(u16::from_le_bytes([data[0], data[1]]));
+            }
+            ADVERTISING_INTERVAL if data.len() >= 2 => {
+                self.advertising_interval = Some(u16::from_le_bytes([data[0], data[1]]) as u32);
+            }
+            ADVERTISING_INTERVAL_LONG if data.len() >= 3 => {
+                let mut bytes = [0u8; 4];
+                let len = data.len().min(4);
+                bytes[..len].copy_from_slice(&data[..len]);
+                self.advertising_interval = Some(u32::from_le_bytes(bytes));
+            }
+            LE_BLUETOOTH_DEVICE_ADDRESS if data.len() >= 7 => {
+                let mut address = [0u8; 6];
+                address.copy_from_slice(&data[..6]);
+                self.le_device_address = Some(LeDeviceAddress {
+                    address,
+                    address_type: AddressType::from_bit(data[6] & 0x01 == 0x01),
+                });
+            }
+            LE_ROLE => {
+                self.le_role = data.first().map(|&role| LeRole::from_u8(role));
+            }
+            URI => self.uri = decode_uri(data),
+            LE_SUPPORTED_FEATURES => self.le_supported_features = Some(data.to_vec()),
+            CHANNEL_MAP_UPDATE_INDICATION if data.len() >= 7 => {
+                let mut channel_map = [0u8; 5];
+                channel_map.copy_from_slice(&data[..5]);
+                self.channel_map_update = Some(ChannelMapUpdate {
+                    channel_map,
+                    instant: u16::from_le_bytes([data[5], data[6]]),
+                });
+            }
+            RESOLVABLE_SET_IDENTIFIER if data.len() >= 6 => {
+                let mut rsi = [0u8; 6];
+                rsi.copy_from_slice(&data[..6]);
+                self.resolvable_set_identifier = Some(rsi);
+            }
+            BROADCAST_NAME => {
+                if let Ok(name) = String::from_utf8(data.to_vec()) {
+                    self.broadcast_name = Some(name);
+                }
+            }
+            MANUFACTURER_SPECIFIC_DATA if data.len() >= 2 => {
+                let company_id = u16::from_le_bytes([data[0], data[1]]);
+                self.manufacturer_data.push((company_id, data[2..].to_vec()));
+            }
+            _ => {
+                // Opaque containers, unknown types and structures too short to decode
+                self.raw_ad_structures.push((code, data.to_vec()));
+            }
+        }
+    }
+
+    /// Merge data from another advertisement of the same device.
+    ///
+    /// Used to combine ADV_* and SCAN_RSP payloads. Scalar fields already set
+    /// are kept; lists are extended without duplicates.
+    pub fn merge(&mut self, other: &AdvertisingData) {
+        push_unique(&mut self.ad_types, other.ad_types.iter().copied());
+
+        if other.name.is_some() && (self.name.is_none() || (other.name_complete && !self.name_complete)) {
+            self.name = other.name.clone();
+            self.name_complete = other.name_complete;
+        }
+
+        merge_option(&mut self.flags, &other.flags);
+        merge_option(&mut self.tx_power, &other.tx_power);
+        merge_option(&mut self.appearance, &other.appearance);
+        merge_option(&mut self.class_of_device, &other.class_of_device);
+        merge_option(&mut self.le_role, &other.le_role);
+        merge_option(&mut self.uri, &other.uri);
+        merge_option(&mut self.advertising_interval, &other.advertising_interval);
+        merge_option(&mut self.peripheral_connection_interval, &other.peripheral_connection_interval);
+        merge_option(&mut self.le_device_address, &other.le_device_address);
+        merge_option(&mut self.le_supported_features, &other.le_supported_features);
+        merge_option(&mut self.channel_map_update, &other.channel_map_update);
+        merge_option(&mut self.resolvable_set_identifier, &other.resolvable_set_identifier);
+        merge_option(&mut self.broadcast_name, &other.broadcast_name);
+        if self.oob.is_empty() {
+            self.oob = other.oob.clone();
+        }
+
+        push_unique(&mut self.service_uuids_16, other.service_uuids_16.iter().copied());
+        push_unique(&mut self.service_uuids_32, other.service_uuids_32.iter().copied());
+        push_unique(&mut self.service_uuids_128, other.service_uuids_128.iter().copied());
+        push_unique(&mut self.solicited_uuids_16, other.solicited_uuids_16.iter().copied());
+        push_unique(&mut self.solicited_uuids_32, other.solicited_uuids_32.iter().copied());
+        push_unique(&mut self.solicited_uuids_128, other.solicited_uuids_128.iter().copied());
+        push_unique(&mut self.public_target_addresses, other.public_target_addresses.iter().copied());
+        push_unique(&mut self.random_target_addresses, other.random_target_addresses.iter().copied());
+
+        // Payload-carrying blocks: keep the latest payload per identifier
+        for (id, data) in &other.manufacturer_data {
+            replace_or_push(&mut self.manufacturer_data, *id, data);
+        }
+        for (uuid, data) in &other.service_data {
+            replace_or_push(&mut self.service_data, *uuid, data);
+        }
+        for (uuid, data) in &other.service_data_32 {
+            replace_or_push(&mut self.service_data_32, *uuid, data);
+        }
+        for (uuid, data) in &other.service_data_128 {
+            replace_or_push(&mut self.service_data_128, *uuid, data);
+        }
+        for (code, data) in &other.raw_ad_structures {
+            replace_or_push(&mut self.raw_ad_structures, *code, data);
+        }
+    }
+
+    /// Advertising interval in milliseconds (from the Advertising Interval AD types).
+    pub fn advertising_interval_ms(&self) -> Option<f64> {
+        self.advertising_interval.map(|v| v as f64 * 0.625)
+    }
+
+    /// Decode beacon and vendor-protocol frames (iBeacon, Eddystone, Apple
+    /// Continuity, Fast Pair, ...) from manufacturer and service data.
+    pub fn beacons(&self) -> Vec<Beacon> {
+        beacons::decode(self)
+    }
+
+    /// Format address as MAC string (XX:XX:XX:XX:XX:XX)
+    pub fn address_string(&self) -> String {
+        format_address(&self.address)
+    }
+
+    /// Compact JSON view of the decoded fields for scan and analysis results.
+    ///
+    /// Only fields that were present in the advertisement are included.
+    pub fn to_json_summary(&self) -> Value {
+        let mut out = Map::new();
+
+        if let Some(flags) = self.flags {
+            out.insert("flags".to_string(), json!(format!("0x{:02X}", flags)));
+        }
+        if let Some(tx_power) = self.tx_power {
+            out.insert("tx_power_dbm".to_string(), json!(tx_power));
+        }
+        if let Some(appearance) = self.appearance {
+            out.insert("appearance".to_string(), json!(format!("0x{:04X}", appearance)));
+            if let Some(name) = appearance_name(appearance) {
+                out.insert("appearance_name".to_string(), json!(name));
+            }
+        }
+        if let Some(cod) = self.class_of_device {
+            out.insert("class_of_device".to_string(), json!(format!("0x{:06X}", cod)));
+        }
+        if let Some(role) = self.le_role {
+            out.insert("le_role".to_string(), json!(role.description()));
+        }
+        if let Some(uri) = &self.uri {
+            out.insert("uri".to_string(), json!(uri));
+        }
+        if let Some(interval_ms) = self.advertising_interval_ms() {
+            out.insert("advertising_interval_ms".to_string(), json!(interval_ms));
+        }
+        if let Some(range) = self.peripheral_connection_interval {
+            out.insert(
+                "peripheral_connection_interval_ms".to_string(),
+                json!({ "min": range.min_ms(), "max": range.max_ms() }),
+            );
+        }
+
+        let service_uuids: Vec<Value> = self
+            .service_uuids_16
+            .iter()
+            .map(|u| uuid_entry(format_uuid16(*u), uuid16_name(*u)))
+            .chain(self.service_uuids_32.iter().map(|u| uuid_entry(format_uuid32(*u), None)))
+            .chain(self.service_uuids_128.iter().map(|u| uuid_entry(format_uuid128(*u), uuid128_name(*u))))
+            .collect();
+        if !service_uuids.is_empty() {
+            out.insert("service_uuids".to_string(), json!(service_uuids));
+        }
+
+        let solicited: Vec<Value> = self
+            .solicited_uuids_16
+            .iter()
+            .map(|u| uuid_entry(format_uuid16(*u), uuid16_name(*u)))
+            .chain(self.solicited_uuids_32.iter().map(|u| uuid_entry(format_uuid32(*u), None)))
+            .chain(self.solicited_uuids_128.iter().map(|u| uuid_entry(format_uuid128(*u), uuid128_name(*u))))
+            .collect();
+        if !solicited.is_empty() {
+            out.insert("solicited_service_uuids".to_string(), json!(solicited));
+        }
+
+        if !self.manufacturer_data.is_empty() {
+            let blocks: Vec<Value> = self
+                .manufacturer_data
+                .iter()
+                .map(|(company_id, data)| {
+                    json!({
+                        "company_id": format!("0x{:04X}", company_id),
+                        "company_name": company_name(*company_id),
+                        "data_hex": hex(data),
+                    })
+                })
+                .collect();
+            out.insert("manufacturer_data".to_string(), json!(blocks));
+        }
+
+        let service_data: Vec<Value> = self
+            .service_data
+            .iter()
+            .map(|(uuid, data)| (uuid_entry(format_uuid16(*uuid), uuid16_name(*uuid)), data))
+            .chain(self.service_data_32.iter().map(|(uuid, data)| (uuid_entry(format_uuid32(*uuid), None), data)))
+            .chain(
+                self.service_data_128
+                    .iter()
+                    .map(|(uuid, data)| (uuid_entry(format_uuid128(*uuid), uuid128_name(*uuid)), data)),
+            )
+            .map(|(mut entry, data)| {
+                entry["data_hex"] = json!(hex(data));
+                entry
+            })
+            .collect();
+        if !service_data.is_empty() {
+            out.insert("service_data".to_string(), json!(service_data));
+        }
+
+        let beacons: Vec<Value> = self.beacons().iter().map(Beacon::to_json).collect();
+        if !beacons.is_empty() {
+            out.insert("beacons".to_string(), json!(beacons));
+        }
+
+        let targets: Vec<String> = self
+            .public_target_addresses
+            .iter()
+            .chain(self.random_target_addresses.iter())
+            .map(format_address)
+            .collect();
+        if !targets.is_empty() {
+            out.insert("target_addresses".to_string(), json!(targets));
+        }
+        if let Some(addr) = &self.le_device_address {
+            out.insert(
+                "le_device_address".to_string(),
+                json!({
+                    "address": format_address(&addr.address),
+                    "address_type": addr.address_type.as_str(),
+                }),
+            );
+        }
+        if let Some(name) = &self.broadcast_name {
+            out.insert("broadcast_name".to_string(), json!(name));
+        }
+        if self.resolvable_set_identifier.is_some() {
+            out.insert("resolvable_set_identifier".to_string(), json!(true));
+        }
+        if !self.oob.is_empty() {
+            out.insert("oob_pairing_data".to_string(), json!(true));
+        }
+
+        let other_types: Vec<String> = self
+            .raw_ad_structures
+            .iter()
+            .map(|(code, _)| match ad_type_name(*code) {
+                Some(name) => format!("{} (0x{:02X})", name, code),
+                None => format!("0x{:02X}", code),
+            })
+            .collect();
+        if !other_types.is_empty() {
+            out.insert("other_ad_types".to_string(), json!(other_types));
+        }
+
+        Value::Object(out)
+    }
+}
+
+/// Format a 6-byte little-endian BD_ADDR as XX:XX:XX:XX:XX:XX.
+pub fn format_address(addr: &[u8; 6]) -> String {
+    format!(
+        "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
+        addr[5], addr[4], addr[3], addr[2], addr[1], addr[0]
+    )
+}
+
+/// Format a 16-bit UUID as 0xXXXX.
+pub fn format_uuid16(uuid: u16) -> String {
+    format!("0x{:04X}", uuid)
+}
+
+/// Format a 32-bit UUID as 0xXXXXXXXX.
+pub fn format_uuid32(uuid: u32) -> String {
+    format!("0x{:08X}", uuid)
+}
+
+/// Format a 128-bit UUID in canonical 8-4-4-4-12 form.
+pub fn format_uuid128(uuid: u128) -> String {
+    let hex = format!("{:032x}", uuid);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// JSON entry for a UUID with its assigned-numbers name, if known.
+fn uuid_entry(uuid: String, name: Option<&str>) -> Value {
+    json!({ "uuid": uuid, "name": name })
+}
+
+/// Lowercase hex encoding of a byte slice.
+pub(crate) fn hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Expand the URI AD value: the first code point selects the scheme.
+///
+/// Code 0x01 means the scheme is spelled out in the remaining text; unknown
+/// codes are dropped and the remainder returned as-is.
+fn decode_uri(data: &[u8]) -> Option<String> {
+    let text = String::from_utf8(data.to_vec()).ok()?;
+    let mut chars = text.chars();
+    let scheme_code = chars.next()? as u32;
+    let rest: String = chars.collect();
+
+    let scheme = URI_SCHEMES
+        .iter()
+        .find(|(code, _)| *code == scheme_code)
+        .map(|(_, scheme)| *scheme)
+        .unwrap_or("");
+
+    Some(format!("{}{}", scheme, rest))
+}
+
+/// Assigned Numbers "URI Schemes" table (code point, scheme prefix).
+const URI_SCHEMES: &[(u32, &str)] = &[
+    (0x01, ""),
+    (0x02, "aaa:"),
+    (0x03, "aaas:"),
+    (0x04, "about:"),
+    (0x05, "acap:"),
+    (0x06, "acct:"),
+    (0x07, "cap:"),
+    (0x08, "cid:"),
+    (0x09, "coap:"),
+    (0x0A, "coaps:"),
+    (0x0B, "crid:"),
+    (0x0C, "data:"),
+    (0x0D, "dav:"),
+    (0x0E, "dict:"),
+    (0x0F, "dns:"),
+    (0x10, "file:"),
+    (0x11, "ftp:"),
+    (0x12, "geo:"),
+    (0x13, "go:"),
+    (0x14, "gopher:"),
+    (0x15, "h323:"),
+    (0x16, "http:"),
+    (0x17, "https:"),
+    (0x18, "iax:"),
+    (0x19, "icap:"),
+    (0x1A, "im:"),
+    (0x1B, "imap:"),
+    (0x1C, "info:"),
+    (0x1D, "ipp:"),
+    (0x1E, "ipps:"),
+    (0x1F, "iris:"),
+    (0x20, "iris.beep:"),
+    (0x21, "iris.xpc:"),
+    (0x22, "iris.xpcs:"),
+    (0x23, "iris.lwz:"),
+    (0x24, "jabber:"),
+    (0x25, "ldap:"),
+    (0x26, "mailto:"),
+    (0x27, "mid:"),
+    (0x28, "msrp:"),
+    (0x29, "msrps:"),
+    (0x2A, "mtqp:"),
+    (0x2B, "mupdate:"),
+    (0x2C, "news:"),
+    (0x2D, "nfs:"),
+    (0x2E, "ni:"),
+    (0x2F, "nih:"),
+    (0x30, "nntp:"),
+    (0x31, "opaquelocktoken:"),
+    (0x32, "pkcs11:"),
+    (0x33, "pop:"),
+    (0x34, "pres:"),
+    (0x35, "reload:"),
+    (0x36, "rtsp:"),
+    (0x37, "rtsps:"),
+    (0x38, "rtspu:"),
+    (0x39, "service:"),
+    (0x3A, "session:"),
+    (0x3B, "shttp:"),
+    (0x3C, "sieve:"),
+    (0x3D, "sip:"),
+    (0x3E, "sips:"),
+    (0x3F, "sms:"),
+    (0x40, "snmp:"),
+    (0x41, "soap.beep:"),
+    (0x42, "soap.beeps:"),
+    (0x43, "stun:"),
+    (0x44, "stuns:"),
+    (0x45, "tag:"),
+    (0x46, "tel:"),
+    (0x47, "telnet:"),
+    (0x48, "tftp:"),
+    (0x49, "thismessage:"),
+    (0x4A, "tn3270:"),
+    (0x4B, "tip:"),
+    (0x4C, "turn:"),
+    (0x4D, "turns:"),
+    (0x4E, "tv:"),
+    (0x4F, "urn:"),
+    (0x50, "vemmi:"),
+    (0x51, "ws:"),
+    (0x52, "wss:"),
+    (0x53, "xcon:"),
+    (0x54, "xcon-userid:"),
+    (0x55, "xmlrpc.beep:"),
+    (0x56, "xmlrpc.beeps:"),
+    (0x57, "xmpp:"),
+    (0x58, "z39.50r:"),
+    (0x59, "z39.50s:"),
+    (0x5A, "acr:"),
+    (0x5B, "adiumxtra:"),
+    (0x5C, "afp:"),
+    (0x5D, "afs:"),
+    (0x5E, "aim:"),
+    (0x5F, "apt:"),
+    (0x60, "attachment:"),
+    (0x61, "aw:"),
+    (0x62, "barion:"),
+    (0x63, "beshare:"),
+    (0x64, "bitcoin:"),
+    (0x65, "bolo:"),
+    (0x66, "callto:"),
+    (0x67, "chrome:"),
+    (0x68, "chrome-extension:"),
+    (0x69, "com-eventbrite-attendee:"),
+    (0x6A, "content:"),
+    (0x6B, "cvs:"),
+    (0x6C, "dlna-playsingle:"),
+    (0x6D, "dlna-playcontainer:"),
+    (0x6E, "dtn:"),
+    (0x6F, "dvb:"),
+    (0x70, "ed2k:"),
+    (0x71, "facetime:"),
+    (0x72, "feed:"),
+    (0x73, "feedready:"),
+    (0x74, "finger:"),
+    (0x75, "fish:"),
+    (0x76, "gg:"),
+    (0x77, "git:"),
+    (0x78, "gizmoproject:"),
+    (0x79, "gtalk:"),
+    (0x7A, "ham:"),
+    (0x7B, "hcp:"),
+    (0x7C, "icon:"),
+    (0x7D, "ipn:"),
+    (0x7E, "irc:"),
+    (0x7F, "irc6:"),
+    (0x80, "ircs:"),
+    (0x81, "itms:"),
+    (0x82, "jar:"),
+    (0x83, "jms:"),
+    (0x84, "keyparc:"),
+    (0x85, "lastfm:"),
+    (0x86, "ldaps:"),
+    (0x87, "magnet:"),
+    (0x88, "maps:"),
+    (0x89, "market:"),
+    (0x8A, "message:"),
+    (0x8B, "mms:"),
+    (0x8C, "ms-help:"),
+    (0x8D, "ms-settings-power:"),
+    (0x8E, "msnim:"),
+    (0x8F, "mumble:"),
+    (0x90, "mvn:"),
+    (0x91, "notes:"),
+    (0x92, "oid:"),
+    (0x93, "palm:"),
+    (0x94, "paparazzi:"),
+    (0x95, "pkcs11:"),
+    (0x96, "platform:"),
+    (0x97, "proxy:"),
+    (0x98, "psyc:"),
+    (0x99, "query:"),
+    (0x9A, "res:"),
+    (0x9B, "resource:"),
+    (0x9C, "rmi:"),
+    (0x9D, "rsync:"),
+    (0x9E, "rtmp:"),
+    (0x9F, "secondlife:"),
+    (0xA0, "sftp:"),
+    (0xA1, "sgn:"),
+    (0xA2, "skype:"),
+    (0xA3, "smb:"),
+    (0xA4, "soldat:"),
+    (0xA5, "spotify:"),
+    (0xA6, "ssh:"),
+    (0xA7, "steam:"),
+    (0xA8, "svn:"),
+    (0xA9, "teamspeak:"),
+    (0xAA, "things:"),
+    (0xAB, "udp:"),
+    (0xAC, "unreal:"),
+    (0xAD, "ut2004:"),
+    (0xAE, "ventrilo:"),
+    (0xAF, "view-source:"),
+    (0xB0, "webcal:"),
+    (0xB1, "wtai:"),
+    (0xB2, "wyciwyg:"),
+    (0xB3, "xfire:"),
+    (0xB4, "xri:"),
+    (0xB5, "ymsgr:"),
+    (0xB6, "example:"),
+    (0xB7, "ms-settings-cloudstorage:"),
+];
+
+fn uuids_16(data: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]]))
+}
+
+fn uuids_32(data: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    data.chunks_exact(4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+}
+
+fn uuids_128(data: &[u8]) -> impl Iterator<Item = u128> + '_ {
+    data.chunks_exact(16).map(|c| {
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(c);
+        u128::from_le_bytes(bytes)
+    })
+}
+
+fn addresses(data: &[u8]) -> impl Iterator<Item = [u8; 6]> + '_ {
+    data.chunks_exact(6).map(|c| {
+        let mut addr = [0u8; 6];
+        addr.copy_from_slice(c);
+        addr
+    })
+}
+
+fn push_unique<T: PartialEq>(target: &mut Vec<T>, items: impl Iterator<Item = T>) {
+    for item in items {
+        if !target.contains(&item) {
+            target.push(item);
+        }
+    }
+}
+
+fn merge_option<T: Clone>(target: &mut Option<T>, other: &Option<T>) {
+    if target.is_none() {
+        target.clone_from(other);
+    }
+}
+
+fn replace_or_push<K: PartialEq + Copy>(target: &mut Vec<(K, Vec<u8>)>, key: K, data: &[u8]) {
+    match target.iter_mut().find(|(k, _)| *k == key) {
+        Some(entry) => entry.1 = data.to_vec(),
+        None => target.push((key, data.to_vec())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDR: [u8; 6] = [0x66, 0x55, 0x44, 0x33, 0x22, 0x11];
+
+    fn payload(ad: &[u8]) -> Vec<u8> {
+        let mut data = ADDR.to_vec();
+        data.extend_from_slice(ad);
+        data
+    }
+
+    #[test]
+    fn test_parse_extended_ad_types() {
+        let data = payload(&[
+            0x02, 0x01, 0x06, // Flags
+            0x05, 0x05, 0x78, 0x56, 0x34, 0x12, // 32-bit UUID 0x12345678
+            0x03, 0x19, 0xC1, 0x03, // Appearance 0x03C1
+            0x02, 0x1C, 0x02, // LE Role
+            0x05, 0x12, 0x06, 0x00, 0xFF, 0xFF, // Conn interval 6..none
+            0x03, 0x1A, 0xA0, 0x00, // Adv interval 160 * 0.625
+            0x04, 0x0D, 0x0C, 0x02, 0x5A, // Class of Device 0x5A020C
+            0x0C, 0x24, 0x17, b'/', b'/', b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0x2F, // URI
+        ]);
+
+        let ad = AdvertisingData::parse(&data).unwrap();
+
+        assert_eq!(ad.flags, Some(0x06));
+        assert_eq!(ad.service_uuids_32, vec![0x12345678]);
+        assert_eq!(ad.appearance, Some(0x03C1));
+        assert_eq!(ad.le_role, Some(LeRole::PeripheralPreferred));
+        assert_eq!(ad.peripheral_connection_interval.unwrap().min_ms(), Some(7.5));
+        assert_eq!(ad.peripheral_connection_interval.unwrap().max, None);
+        assert_eq!(ad.advertising_interval_ms(), Some(100.0));
+        assert_eq!(ad.class_of_device, Some(0x5A020C));
+        assert_eq!(ad.uri.as_deref(), Some("https://example/"));
+        assert_eq!(ad.ad_types, vec![0x01, 0x05, 0x19, 0x1C, 0x12, 0x1A, 0x0D, 0x24]);
+        assert!(ad.raw_ad_structures.is_empty());
+    }
+
+    #[test]
+    fn test_parse_multiple_manufacturer_blocks_and_service_data() {
+        let mut uuid128 = [0u8; 16];
+        uuid128[0] = 0xAA;
+        uuid128[15] = 0xBB;
+
+        let mut ad_bytes = vec![
+            0x05, 0xFF, 0x4C, 0x00, 0x10, 0x05, // Apple
+            0x04, 0xFF, 0x06, 0x00, 0x01, // Microsoft
+            0x07, 0x20, 0x01, 0x02, 0x03, 0x04, 0xDE, 0xAD, // Service data 32
+            0x12, 0x21, // Service data 128 (type + UUID + 1 byte)
+        ];
+        ad_bytes.extend_from_slice(&uuid128);
+        ad_bytes.push(0x42);
+        ad_bytes.extend_from_slice(&[0x03, 0x2B, 0x00, 0x01]); // Mesh Beacon (opaque)
+
+        let ad = AdvertisingData::parse(&payload(&ad_bytes)).unwrap();
+
+        assert_eq!(ad.manufacturer_data.len(), 2);
+        assert_eq!(ad.manufacturer_data[0], (0x004C, vec![0x10, 0x05]));
+        assert_eq!(ad.manufacturer_data[1], (0x0006, vec![0x01]));
+        assert_eq!(ad.service_data_32, vec![(0x04030201, vec![0xDE, 0xAD])]);
+        assert_eq!(ad.service_data_128.len(), 1);
+        assert_eq!(ad.service_data_128[0].1, vec![0x42]);
+        assert_eq!(
+            format_uuid128(ad.service_data_128[0].0),
+            "bb000000-0000-0000-0000-0000000000aa"
+        );
+        assert_eq!(ad.raw_ad_structures, vec![(0x2B, vec![0x00, 0x01])]);
+
+        let summary = ad.to_json_summary();
+        assert_eq!(summary["manufacturer_data"].as_array().unwrap().len(), 2);
+        assert_eq!(summary["other_ad_types"][0], "Mesh Beacon (0x2B)");
+    }
+
+    #[test]
+    fn test_decode_uri_schemes() {
+        // Code points above 0x7F are UTF-8 encoded
+        let mut mailto = "\u{26}".as_bytes().to_vec();
+        mailto.extend_from_slice(b"a@b.c");
+        assert_eq!(decode_uri(&mailto).as_deref(), Some("mailto:a@b.c"));
+
+        let mut ssh = "\u{A6}".as_bytes().to_vec();
+        ssh.extend_from_slice(b"//host");
+        assert_eq!(decode_uri(&ssh).as_deref(), Some("ssh://host"));
+
+        let mut spelled = vec![0x01];
+        spelled.extend_from_slice(b"custom:x");
+        assert_eq!(decode_uri(&spelled).as_deref(), Some("custom:x"));
+
+        assert_eq!(URI_SCHEMES.last(), Some(&(0xB7, "ms-settings-cloudstorage:")));
+    }
+
+    #[test]
+    fn test_merge_scan_response() {
+        let adv = AdvertisingData::parse(&payload(&[0x02, 0x01, 0x06, 0x04, 0x08, b'a', b'b', b'c'])).unwrap();
+        let rsp = AdvertisingData::parse(&payload(&[0x06, 0x09, b'a', b'b', b'c', b'd', b'e', 0x02, 0x0A, 0xF4])).unwrap();
+
+        let mut merged = adv.clone();
+        merged.merge(&rsp);
+
+        assert_eq!(merged.name.as_deref(), Some("abcde"));
+        assert!(merged.name_complete);
+        assert_eq!(merged.flags, Some(0x06));
+        assert_eq!(merged.tx_power, Some(-12));
+    }
+}
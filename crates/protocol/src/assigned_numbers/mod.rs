@@ -0,0 +1,139 @@
+//! Embedded Bluetooth SIG assigned-numbers database.
+//!
+//! Company identifiers, 16-bit service/characteristic/descriptor UUIDs,
+//! appearance values and GAP advertising data types are compiled into the
+//! binary, so lookups work offline. `data.rs` currently holds a curated
+//! subset; `scripts/update-assigned-numbers.py` regenerates it in full from
+//! the SIG's upstream YAML.
+
+mod data;
+
+use data::*;
+
+/// Bluetooth Base UUID (0000xxxx-0000-1000-8000-00805F9B34FB) with the
+/// 16/32-bit part zeroed.
+const BASE_UUID: u128 = 0x0000_0000_0000_1000_8000_0080_5F9B_34FB;
+
+fn lookup<K: Ord + Copy>(table: &'static [(K, &'static str)], key: K) -> Option<&'static str> {
+    table
+        .binary_search_by_key(&key, |(k, _)| *k)
+        .ok()
+        .map(|idx| table[idx].1)
+}
+
+/// Company name for a manufacturer specific data company identifier.
+pub fn company_name(company_id: u16) -> Option<&'static str> {
+    lookup(COMPANY_IDS, company_id)
+}
+
+/// Name of a 16-bit service UUID.
+///
+/// Falls back to the member UUID table, which names the company the UUID
+/// was assigned to (e.g. 0xFEAA => "Google LLC").
+pub fn service_name(uuid: u16) -> Option<&'static str> {
+    lookup(SERVICE_UUIDS, uuid).or_else(|| lookup(MEMBER_UUIDS, uuid))
+}
+
+/// Name of a 16-bit GATT characteristic UUID.
+pub fn characteristic_name(uuid: u16) -> Option<&'static str> {
+    lookup(CHARACTERISTIC_UUIDS, uuid)
+}
+
+/// Name of a 16-bit GATT descriptor UUID.
+pub fn descriptor_name(uuid: u16) -> Option<&'static str> {
+    lookup(DESCRIPTOR_UUIDS, uuid)
+}
+
+/// Name of any 16-bit UUID: service, member, characteristic or descriptor.
+pub fn uuid16_name(uuid: u16) -> Option<&'static str> {
+    service_name(uuid)
+        .or_else(|| characteristic_name(uuid))
+        .or_else(|| descriptor_name(uuid))
+}
+
+/// Shorten a 128-bit UUID built on the Bluetooth Base UUID to its 16-bit form.
+pub fn as_uuid16(uuid: u128) -> Option<u16> {
+    let short = (uuid >> 96) as u32;
+    if uuid & !(0xFFFF_FFFFu128 << 96) == BASE_UUID && short <= 0xFFFF {
+        Some(short as u16)
+    } else {
+        None
+    }
+}
+
+/// Name of a 128-bit UUID, if it is a SIG-assigned UUID on the Base UUID.
+pub fn uuid128_name(uuid: u128) -> Option<&'static str> {
+    as_uuid16(uuid).and_then(uuid16_name)
+}
+
+/// Human-readable appearance ("Category" or "Category: Subcategory").
+pub fn appearance_name(appearance: u16) -> Option<String> {
+    // Appearance is a 10-bit category and a 6-bit subcategory
+    let category = lookup(APPEARANCE_CATEGORIES, appearance >> 6)?;
+
+    if appearance & 0x3F == 0 {
+        return Some(category.to_string());
+    }
+
+    Some(match lookup(APPEARANCE_SUBCATEGORIES, appearance) {
+        Some(sub) => format!("{}: {}", category, sub),
+        None => format!("{}: 0x{:02X}", category, appearance & 0x3F),
+    })
+}
+
+/// GAP name of an advertising data (AD) type code.
+pub fn ad_type_name(code: u8) -> Option<&'static str> {
+    lookup(AD_TYPES, code)
+}
+
+/// Number of entries in each embedded table, for diagnostics.
+pub fn table_sizes() -> [(&'static str, usize); 8] {
+    [
+        ("company_ids", COMPANY_IDS.len()),
+        ("service_uuids", SERVICE_UUIDS.len()),
+        ("member_uuids", MEMBER_UUIDS.len()),
+        ("characteristic_uuids", CHARACTERISTIC_UUIDS.len()),
+        ("descriptor_uuids", DESCRIPTOR_UUIDS.len()),
+        ("appearance_categories", APPEARANCE_CATEGORIES.len()),
+        ("appearance_subcategories", APPEARANCE_SUBCATEGORIES.len()),
+        ("ad_types", AD_TYPES.len()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_sorted() {
+        fn sorted<K: Ord>(table: &[(K, &str)]) -> bool {
+            table.windows(2).all(|w| w[0].0 < w[1].0)
+        }
+
+        assert!(sorted(COMPANY_IDS));
+        assert!(sorted(SERVICE_UUIDS));
+        assert!(sorted(MEMBER_UUIDS));
+        assert!(sorted(CHARACTERISTIC_UUIDS));
+        assert!(sorted(DESCRIPTOR_UUIDS));
+        assert!(sorted(APPEARANCE_CATEGORIES));
+        assert!(sorted(APPEARANCE_SUBCATEGORIES));
+        assert!(sorted(AD_TYPES));
+    }
+
+    #[test]
+    fn test_lookups() {
+        assert_eq!(company_name(0x004C), Some("Apple, Inc."));
+        assert_eq!(service_name(0x180F), Some("Battery Service"));
+        assert_eq!(service_name(0x184E), Some("Audio Stream Control"));
+        assert_eq!(service_name(0xFEAA), Some("Google LLC"));
+        assert_eq!(characteristic_name(0x2A19), Some("Battery Level"));
+        assert_eq!(ad_type_name(0xFF), Some("Manufacturer Specific Data"));
+        assert_eq!(appearance_name(0x00C1).as_deref(), Some("Watch: Sports Watch"));
+        assert_eq!(appearance_name(0x0040).as_deref(), Some("Phone"));
+
+        let battery_128 = 0x0000_180F_0000_1000_8000_0080_5F9B_34FBu128;
+        assert_eq!(as_uuid16(battery_128), Some(0x180F));
+        assert_eq!(uuid128_name(battery_128), Some("Battery Service"));
+        assert_eq!(as_uuid16(0x1234), None);
+    }
+}
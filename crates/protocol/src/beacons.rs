@@ -0,0 +1,719 @@
+//! Beacon and vendor-protocol decoders.
+//!
+//! Most advertisements carry their payload in manufacturer-specific or
+//! service data. This module decodes the common formats found there:
+//!
+//! - iBeacon and the other Apple Continuity messages (company 0x004C)
+//! - AltBeacon (any company, `BE AC` beacon code)
+//! - Eddystone UID/URL/TLM/EID (service data 0xFEAA)
+//! - Google Fast Pair (service data 0xFE2C)
+//! - Microsoft Connected Devices Platform (company 0x0006)
+//!
+//! Decoders never fail: frames that don't match a known layout are skipped
+//! and stay visible as hex in the advertising data.
+
+use crate::advertising::{format_uuid128, hex, AdvertisingData};
+use serde_json::{json, Value};
+
+/// Apple, Inc. company identifier
+pub const COMPANY_APPLE: u16 = 0x004C;
+
+/// Microsoft company identifier
+pub const COMPANY_MICROSOFT: u16 = 0x0006;
+
+/// Eddystone service UUID
+pub const UUID_EDDYSTONE: u16 = 0xFEAA;
+
+/// Google Fast Pair service UUID
+pub const UUID_FAST_PAIR: u16 = 0xFE2C;
+
+/// Apple Continuity message types.
+pub mod continuity_type {
+    pub const IBEACON: u8 = 0x02;
+    pub const AIRPRINT: u8 = 0x03;
+    pub const AIRDROP: u8 = 0x05;
+    pub const HOMEKIT: u8 = 0x06;
+    pub const PROXIMITY_PAIRING: u8 = 0x07;
+    pub const HEY_SIRI: u8 = 0x08;
+    pub const AIRPLAY_TARGET: u8 = 0x09;
+    pub const AIRPLAY_SOURCE: u8 = 0x0A;
+    pub const MAGIC_SWITCH: u8 = 0x0B;
+    pub const HANDOFF: u8 = 0x0C;
+    pub const TETHERING_TARGET: u8 = 0x0D;
+    pub const TETHERING_SOURCE: u8 = 0x0E;
+    pub const NEARBY_ACTION: u8 = 0x0F;
+    pub const NEARBY_INFO: u8 = 0x10;
+    pub const FIND_MY: u8 = 0x12;
+}
+
+/// Eddystone telemetry frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EddystoneTlm {
+    /// Unencrypted telemetry (version 0x00)
+    Plain {
+        /// Battery voltage in mV (`None` if not supported)
+        battery_mv: Option<u16>,
+        /// Beacon temperature in °C (`None` if not supported)
+        temperature_c: Option<f32>,
+        /// Advertising PDUs sent since power-up
+        adv_count: u32,
+        /// Time since power-up in seconds
+        uptime_s: f64,
+    },
+    /// Encrypted telemetry (version 0x01)
+    Encrypted,
+}
+
+/// Decoded Apple Continuity message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContinuityMessage {
+    /// Nearby Info: device activity and state
+    NearbyInfo { activity: u8, status_flags: u8, data_flags: Option<u8> },
+    /// Nearby Action: setup and sharing prompts
+    NearbyAction { action: u8, flags: u8 },
+    /// Proximity Pairing: AirPods and Beats headphones
+    ProximityPairing {
+        model: u16,
+        /// Pod battery levels in percent (`None` if unknown)
+        pod_battery: [Option<u8>; 2],
+        /// Case battery level in percent (`None` if unknown)
+        case_battery: Option<u8>,
+    },
+    /// Handoff activity advertisement
+    Handoff { clipboard: bool, sequence: u16 },
+    /// AirDrop with truncated contact hashes
+    AirDrop,
+    /// AirPlay receiver, with its IPv4 address when present
+    AirPlayTarget { ipv4: Option<[u8; 4]> },
+    /// Instant Hotspot source
+    TetheringSource { battery: u8, cell_bars: u8 },
+    /// Find My network: `separated` is set for offline-finding broadcasts
+    /// sent while the device is away from its owner, which also carry 22
+    /// bytes of the rotating public key (the rest is in the address)
+    FindMy { separated: bool, status: u8, key_fragment: Vec<u8> },
+    /// Any other message type, kept raw
+    Other { message_type: u8, data: Vec<u8> },
+}
+
+/// Decoded Google Fast Pair service data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FastPair {
+    /// Discoverable mode: the 24-bit model ID
+    Discoverable { model_id: u32 },
+    /// Not discoverable: account key filter and optional battery levels
+    NotDiscoverable {
+        /// Whether the seeker should show a pairing UI
+        show_ui: bool,
+        /// Battery levels in percent (`None` if unknown)
+        battery: Vec<Option<u8>>,
+    },
+}
+
+/// A decoded beacon or vendor-protocol frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Beacon {
+    IBeacon { uuid: u128, major: u16, minor: u16, measured_power: i8 },
+    AltBeacon { company_id: u16, beacon_id: [u8; 20], reference_rssi: i8, reserved: u8 },
+    EddystoneUid { tx_power: i8, namespace: [u8; 10], instance: [u8; 6] },
+    EddystoneUrl { tx_power: i8, url: String },
+    EddystoneTlm(EddystoneTlm),
+    EddystoneEid { tx_power: i8, eid: [u8; 8] },
+    AppleContinuity(ContinuityMessage),
+    FastPair(FastPair),
+    MicrosoftCdp { device_type: u8, version: u8 },
+}
+
+impl Beacon {
+    /// Short machine-readable frame type.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Beacon::IBeacon { .. } => "ibeacon",
+            Beacon::AltBeacon { .. } => "altbeacon",
+            Beacon::EddystoneUid { .. } => "eddystone_uid",
+            Beacon::EddystoneUrl { .. } => "eddystone_url",
+            Beacon::EddystoneTlm(_) => "eddystone_tlm",
+            Beacon::EddystoneEid { .. } => "eddystone_eid",
+            Beacon::AppleContinuity(_) => "apple_continuity",
+            Beacon::FastPair(_) => "google_fast_pair",
+            Beacon::MicrosoftCdp { .. } => "microsoft_cdp",
+        }
+    }
+
+    /// One-line human-readable description, e.g. "iBeacon major 1 minor 2".
+    pub fn summary(&self) -> String {
+        match self {
+            Beacon::IBeacon { uuid, major, minor, measured_power } => format!(
+                "iBeacon {} major {} minor {} ({} dBm @ 1m)",
+                format_uuid128(*uuid),
+                major,
+                minor,
+                measured_power
+            ),
+            Beacon::AltBeacon { beacon_id, reference_rssi, .. } => {
+                format!("AltBeacon {} ({} dBm @ 1m)", hex(beacon_id), reference_rssi)
+            }
+            Beacon::EddystoneUid { namespace, instance, .. } => {
+                format!("Eddystone-UID namespace {} instance {}", hex(namespace), hex(instance))
+            }
+            Beacon::EddystoneUrl { url, .. } => format!("Eddystone-URL {}", url),
+            Beacon::EddystoneTlm(EddystoneTlm::Encrypted) => "Eddystone-TLM (encrypted)".to_string(),
+            Beacon::EddystoneTlm(EddystoneTlm::Plain { battery_mv, temperature_c, uptime_s, .. }) => {
+                let mut parts = Vec::new();
+                if let Some(mv) = battery_mv {
+                    parts.push(format!("battery {} mV", mv));
+                }
+                if let Some(temp) = temperature_c {
+                    parts.push(format!("{:.1} °C", temp));
+                }
+                parts.push(format!("up {:.0}s", uptime_s));
+                format!("Eddystone-TLM ({})", parts.join(", "))
+            }
+            Beacon::EddystoneEid { eid, .. } => format!("Eddystone-EID {}", hex(eid)),
+            Beacon::AppleContinuity(msg) => msg.summary(),
+            Beacon::FastPair(FastPair::Discoverable { model_id }) => {
+                format!("Google Fast Pair (model 0x{:06X}, discoverable)", model_id)
+            }
+            Beacon::FastPair(FastPair::NotDiscoverable { show_ui, battery }) => {
+                let mut parts = vec![if *show_ui { "pairing UI" } else { "no pairing UI" }.to_string()];
+                if !battery.is_empty() {
+                    let levels: Vec<String> = battery.iter().map(|b| percent(*b)).collect();
+                    parts.push(format!("battery {}", levels.join("/")));
+                }
+                format!("Google Fast Pair (not discoverable, {})", parts.join(", "))
+            }
+            Beacon::MicrosoftCdp { device_type, .. } => match cdp_device_type_name(*device_type) {
+                Some(name) => format!("Microsoft CDP ({})", name),
+                None => format!("Microsoft CDP (device type {})", device_type),
+            },
+        }
+    }
+
+    /// JSON view with the frame type, summary and decoded fields.
+    pub fn to_json(&self) -> Value {
+        let mut out = match self {
+            Beacon::IBeacon { uuid, major, minor, measured_power } => json!({
+                "uuid": format_uuid128(*uuid),
+                "major": major,
+                "minor": minor,
+                "measured_power_dbm": measured_power,
+            }),
+            Beacon::AltBeacon { company_id, beacon_id, reference_rssi, reserved } => json!({
+                "company_id": format!("0x{:04X}", company_id),
+                "beacon_id": hex(beacon_id),
+                "reference_rssi_dbm": reference_rssi,
+                "manufacturer_reserved": reserved,
+            }),
+            Beacon::EddystoneUid { tx_power, namespace, instance } => json!({
+                "tx_power_dbm": tx_power,
+                "namespace": hex(namespace),
+                "instance": hex(instance),
+            }),
+            Beacon::EddystoneUrl { tx_power, url } => json!({ "tx_power_dbm": tx_power, "url": url }),
+            Beacon::EddystoneTlm(EddystoneTlm::Encrypted) => json!({ "encrypted": true }),
+            Beacon::EddystoneTlm(EddystoneTlm::Plain { battery_mv, temperature_c, adv_count, uptime_s }) => json!({
+                "encrypted": false,
+                "battery_mv": battery_mv,
+                "temperature_c": temperature_c,
+                "adv_count": adv_count,
+                "uptime_s": uptime_s,
+            }),
+            Beacon::EddystoneEid { tx_power, eid } => json!({ "tx_power_dbm": tx_power, "eid": hex(eid) }),
+            Beacon::AppleContinuity(msg) => msg.to_json(),
+            Beacon::FastPair(FastPair::Discoverable { model_id }) => json!({
+                "discoverable": true,
+                "model_id": format!("0x{:06X}", model_id),
+            }),
+            Beacon::FastPair(FastPair::NotDiscoverable { show_ui, battery }) => json!({
+                "discoverable": false,
+                "show_ui": show_ui,
+                "battery_percent": battery,
+            }),
+            Beacon::MicrosoftCdp { device_type, version } => json!({
+                "device_type": device_type,
+                "device_type_name": cdp_device_type_name(*device_type),
+                "version": version,
+            }),
+        };
+
+        out["type"] = json!(self.kind());
+        out["summary"] = json!(self.summary());
+        out
+    }
+}
+
+impl ContinuityMessage {
+    /// Continuity message type code.
+    pub fn message_type(&self) -> u8 {
+        use continuity_type::*;
+        match self {
+            ContinuityMessage::NearbyInfo { .. } => NEARBY_INFO,
+            ContinuityMessage::NearbyAction { .. } => NEARBY_ACTION,
+            ContinuityMessage::ProximityPairing { .. } => PROXIMITY_PAIRING,
+            ContinuityMessage::Handoff { .. } => HANDOFF,
+            ContinuityMessage::AirDrop => AIRDROP,
+            ContinuityMessage::AirPlayTarget { .. } => AIRPLAY_TARGET,
+            ContinuityMessage::TetheringSource { .. } => TETHERING_SOURCE,
+            ContinuityMessage::FindMy { .. } => FIND_MY,
+            ContinuityMessage::Other { message_type, .. } => *message_type,
+        }
+    }
+
+    /// One-line human-readable description.
+    pub fn summary(&self) -> String {
+        let name = continuity_type_name(self.message_type());
+        match self {
+            ContinuityMessage::NearbyInfo { activity, .. } => {
+                format!("Apple {} ({})", name, nearby_activity_name(*activity))
+            }
+            ContinuityMessage::NearbyAction { action, .. } => match nearby_action_name(*action) {
+                Some(action) => format!("Apple {} ({})", name, action),
+                None => format!("Apple {} (action 0x{:02X})", name, action),
+            },
+            ContinuityMessage::ProximityPairing { model, pod_battery, case_battery } => format!(
+                "Apple {} ({}, pods {}/{}, case {})",
+                name,
+                proximity_model_name(*model).unwrap_or("unknown model"),
+                percent(pod_battery[0]),
+                percent(pod_battery[1]),
+                percent(*case_battery)
+            ),
+            ContinuityMessage::Handoff { clipboard: true, .. } => format!("Apple {} (clipboard shared)", name),
+            ContinuityMessage::AirPlayTarget { ipv4: Some(ip) } => {
+                format!("Apple {} ({}.{}.{}.{})", name, ip[0], ip[1], ip[2], ip[3])
+            }
+            ContinuityMessage::TetheringSource { battery, cell_bars } => {
+                format!("Apple {} (battery {}%, {} bars)", name, battery, cell_bars)
+            }
+            ContinuityMessage::FindMy { separated: true, .. } => format!("Apple {} (separated from owner)", name),
+            ContinuityMessage::FindMy { separated: false, .. } => format!("Apple {} (owner nearby)", name),
+            _ => format!("Apple {}", name),
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let mut out = match self {
+            ContinuityMessage::NearbyInfo { activity, status_flags, data_flags } => json!({
+                "activity": activity,
+                "activity_name": nearby_activity_name(*activity),
+                "status_flags": format!("0x{:X}", status_flags),
+                "data_flags": data_flags.map(|f| format!("0x{:02X}", f)),
+            }),
+            ContinuityMessage::NearbyAction { action, flags } => json!({
+                "action": action,
+                "action_name": nearby_action_name(*action),
+                "flags": format!("0x{:02X}", flags),
+            }),
+            ContinuityMessage::ProximityPairing { model, pod_battery, case_battery } => json!({
+                "model": format!("0x{:04X}", model),
+                "model_name": proximity_model_name(*model),
+                "pod_battery_percent": pod_battery,
+                "case_battery_percent": case_battery,
+            }),
+            ContinuityMessage::Handoff { clipboard, sequence } => json!({
+                "clipboard": clipboard,
+                "sequence": sequence,
+            }),
+            ContinuityMessage::AirPlayTarget { ipv4 } => json!({
+                "ipv4": ipv4.map(|ip| format!("{}.{}.{}.{}", ip[0], ip[1], ip[2], ip[3])),
+            }),
+            ContinuityMessage::TetheringSource { battery, cell_bars } => json!({
+                "battery_percent": battery,
+                "cell_bars": cell_bars,
+            }),
+            ContinuityMessage::FindMy { separated, status, key_fragment } => json!({
+                "separated": separated,
+                "status": format!("0x{:02X}", status),
+                "key_fragment_hex": (!key_fragment.is_empty()).then(|| hex(key_fragment)),
+            }),
+            ContinuityMessage::AirDrop => json!({}),
+            ContinuityMessage::Other { data, .. } => json!({ "data_hex": hex(data) }),
+        };
+
+        out["message_type"] = json!(format!("0x{:02X}", self.message_type()));
+        out["message_name"] = json!(continuity_type_name(self.message_type()));
+        out
+    }
+}
+
+/// Decode every recognised beacon or vendor frame in the advertising data.
+pub fn decode(adv: &AdvertisingData) -> Vec<Beacon> {
+    let mut beacons = Vec::new();
+
+    for (company_id, data) in &adv.manufacturer_data {
+        match *company_id {
+            COMPANY_APPLE => beacons.extend(decode_apple(data)),
+            COMPANY_MICROSOFT => beacons.extend(decode_microsoft_cdp(data)),
+            _ => {}
+        }
+        beacons.extend(decode_altbeacon(*company_id, data));
+    }
+
+    for (uuid, data) in &adv.service_data {
+        match *uuid {
+            UUID_EDDYSTONE => beacons.extend(decode_eddystone(data)),
+            UUID_FAST_PAIR => beacons.extend(decode_fast_pair(data)),
+            _ => {}
+        }
+    }
+
+    beacons
+}
+
+/// Decode Apple manufacturer data as a sequence of Continuity TLVs.
+pub fn decode_apple(data: &[u8]) -> Vec<Beacon> {
+    let mut beacons = Vec::new();
+    let mut offset = 0;
+
+    while offset + 2 <= data.len() {
+        let message_type = data[offset];
+        let length = data[offset + 1] as usize;
+        let Some(value) = data.get(offset + 2..offset + 2 + length) else {
+            break;
+        };
+        offset += 2 + length;
+
+        if message_type == continuity_type::IBEACON {
+            if let Some(beacon) = decode_ibeacon(value) {
+                beacons.push(beacon);
+            }
+            continue;
+        }
+
+        beacons.push(Beacon::AppleContinuity(decode_continuity(message_type, value)));
+    }
+
+    beacons
+}
+
+fn decode_ibeacon(value: &[u8]) -> Option<Beacon> {
+    if value.len() != 21 {
+        return None;
+    }
+    Some(Beacon::IBeacon {
+        uuid: u128::from_be_bytes(value[0..16].try_into().ok()?),
+        major: u16::from_be_bytes([value[16], value[17]]),
+        minor: u16::from_be_bytes([value[18], value[19]]),
+        measured_power: value[20] as i8,
+    })
+}
+
+fn decode_continuity(message_type: u8, value: &[u8]) -> ContinuityMessage {
+    use continuity_type::*;
+
+    let other = || ContinuityMessage::Other { message_type, data: value.to_vec() };
+
+    match message_type {
+        NEARBY_INFO if !value.is_empty() => ContinuityMessage::NearbyInfo {
+            activity: value[0] & 0x0F,
+            status_flags: value[0] >> 4,
+            data_flags: value.get(1).copied(),
+        },
+        NEARBY_ACTION if value.len() >= 2 => ContinuityMessage::NearbyAction { action: value[1], flags: value[0] },
+        PROXIMITY_PAIRING if value.len() >= 6 => ContinuityMessage::ProximityPairing {
+            model: u16::from_be_bytes([value[1], value[2]]),
+            pod_battery: [battery_nibble(value[4] >> 4), battery_nibble(value[4] & 0x0F)],
+            case_battery: battery_nibble(value[5] & 0x0F),
+        },
+        HANDOFF if value.len() >= 3 => ContinuityMessage::Handoff {
+            clipboard: value[0] & 0x08 != 0,
+            sequence: u16::from_le_bytes([value[1], value[2]]),
+        },
+        AIRDROP => ContinuityMessage::AirDrop,
+        AIRPLAY_TARGET => ContinuityMessage::AirPlayTarget {
+            ipv4: value.get(2..6).and_then(|ip| ip.try_into().ok()),
+        },
+        TETHERING_SOURCE if value.len() >= 6 => ContinuityMessage::TetheringSource {
+            battery: value[2],
+            cell_bars: value[5],
+        },
+        FIND_MY if !value.is_empty() => ContinuityMessage::FindMy {
+            separated: value.len() >= 25,
+            status: value[0],
+            key_fragment: value.get(1..23).filter(|_| value.len() >= 25).unwrap_or_default().to_vec(),
+        },
+        _ => other(),
+    }
+}
+
+/// Decode an AltBeacon frame (`BE AC` beacon code after the company ID).
+pub fn decode_altbeacon(company_id: u16, data: &[u8]) -> Option<Beacon> {
+    if data.len() != 24 || data[0..2] != [0xBE, 0xAC] {
+        return None;
+    }
+    Some(Beacon::AltBeacon {
+        company_id,
+        beacon_id: data[2..22].try_into().ok()?,
+        reference_rssi: data[22] as i8,
+        reserved: data[23],
+    })
+}
+
+/// Decode Eddystone service data (UUID 0xFEAA).
+pub fn decode_eddystone(data: &[u8]) -> Option<Beacon> {
+    let frame_type = *data.first()?;
+
+    match frame_type {
+        0x00 if data.len() >= 18 => Some(Beacon::EddystoneUid {
+            tx_power: data[1] as i8,
+            namespace: data[2..12].try_into().ok()?,
+            instance: data[12..18].try_into().ok()?,
+        }),
+        0x10 if data.len() >= 3 => Some(Beacon::EddystoneUrl {
+            tx_power: data[1] as i8,
+            url: decode_eddystone_url(data[2], &data[3..])?,
+        }),
+        0x20 if data.len() >= 2 && data[1] == 0x01 => Some(Beacon::EddystoneTlm(EddystoneTlm::Encrypted)),
+        0x20 if data.len() >= 14 && data[1] == 0x00 => {
+            let battery = u16::from_be_bytes([data[2], data[3]]);
+            let temperature = i16::from_be_bytes([data[4], data[5]]);
+            Some(Beacon::EddystoneTlm(EddystoneTlm::Plain {
+                battery_mv: (battery != 0).then_some(battery),
+                // Signed 8.8 fixed point; 0x8000 means "not supported"
+                temperature_c: (temperature != i16::MIN).then(|| temperature as f32 / 256.0),
+                adv_count: u32::from_be_bytes([data[6], data[7], data[8], data[9]]),
+                uptime_s: u32::from_be_bytes([data[10], data[11], data[12], data[13]]) as f64 / 10.0,
+            }))
+        }
+        0x30 if data.len() >= 10 => Some(Beacon::EddystoneEid {
+            tx_power: data[1] as i8,
+            eid: data[2..10].try_into().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+fn decode_eddystone_url(scheme: u8, encoded: &[u8]) -> Option<String> {
+    const SCHEMES: [&str; 4] = ["http://www.", "https://www.", "http://", "https://"];
+    const EXPANSIONS: [&str; 14] = [
+        ".com/", ".org/", ".edu/", ".net/", ".info/", ".biz/", ".gov/", ".com", ".org", ".edu", ".net", ".info",
+        ".biz", ".gov",
+    ];
+
+    let mut url = SCHEMES.get(scheme as usize)?.to_string();
+    for &byte in encoded {
+        match EXPANSIONS.get(byte as usize) {
+            Some(expansion) => url.push_str(expansion),
+            None if (0x21..0x7F).contains(&byte) => url.push(byte as char),
+            None => return None,
+        }
+    }
+    Some(url)
+}
+
+/// Decode Google Fast Pair service data (UUID 0xFE2C).
+pub fn decode_fast_pair(data: &[u8]) -> Option<Beacon> {
+    if data.len() == 3 {
+        let model_id = u32::from_be_bytes([0, data[0], data[1], data[2]]);
+        return Some(Beacon::FastPair(FastPair::Discoverable { model_id }));
+    }
+
+    // Flags byte, then length/type-prefixed fields
+    let mut offset = 1;
+    let mut show_ui = None;
+    let mut battery = Vec::new();
+    while offset < data.len() {
+        let header = data[offset];
+        let length = (header >> 4) as usize;
+        let value = data.get(offset + 1..offset + 1 + length)?;
+        offset += 1 + length;
+
+        match header & 0x0F {
+            0x0 => show_ui = Some(true),
+            0x2 => show_ui = Some(false),
+            0x3 | 0x4 => {
+                battery = value
+                    .iter()
+                    .map(|b| (b & 0x7F <= 100).then_some(b & 0x7F))
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+
+    Some(Beacon::FastPair(FastPair::NotDiscoverable { show_ui: show_ui?, battery }))
+}
+
+/// Decode Microsoft Connected Devices Platform manufacturer data.
+pub fn decode_microsoft_cdp(data: &[u8]) -> Option<Beacon> {
+    // Scenario type 1 is the CDP Bluetooth beacon
+    if data.len() < 4 || data[0] != 0x01 {
+        return None;
+    }
+    Some(Beacon::MicrosoftCdp {
+        device_type: data[1] & 0x1F,
+        version: data[1] >> 5,
+    })
+}
+
+/// Name of an Apple Continuity message type.
+pub fn continuity_type_name(message_type: u8) -> &'static str {
+    use continuity_type::*;
+    match message_type {
+        IBEACON => "iBeacon",
+        AIRPRINT => "AirPrint",
+        AIRDROP => "AirDrop",
+        HOMEKIT => "HomeKit",
+        PROXIMITY_PAIRING => "Proximity Pairing",
+        HEY_SIRI => "Hey Siri",
+        AIRPLAY_TARGET => "AirPlay Target",
+        AIRPLAY_SOURCE => "AirPlay Source",
+        MAGIC_SWITCH => "Magic Switch",
+        HANDOFF => "Handoff",
+        TETHERING_TARGET => "Tethering Target",
+        TETHERING_SOURCE => "Instant Hotspot",
+        NEARBY_ACTION => "Nearby Action",
+        NEARBY_INFO => "Nearby Info",
+        FIND_MY => "Find My",
+        _ => "Continuity",
+    }
+}
+
+/// Nearby Info activity level (low nibble of the first byte).
+fn nearby_activity_name(activity: u8) -> &'static str {
+    match activity {
+        0x00 => "activity unknown",
+        0x01 => "activity reporting disabled",
+        0x03 => "screen off",
+        0x05 => "audio playing, screen locked",
+        0x07 => "screen on",
+        0x09 => "screen on, video playing",
+        0x0A => "watch on wrist and unlocked",
+        0x0B => "recent user interaction",
+        0x0D => "driving",
+        0x0E => "phone or FaceTime call",
+        _ => "unknown activity",
+    }
+}
+
+/// Nearby Action type.
+fn nearby_action_name(action: u8) -> Option<&'static str> {
+    Some(match action {
+        0x01 => "Apple TV setup",
+        0x04 => "mobile backup",
+        0x05 => "Apple Watch setup",
+        0x06 => "Apple TV pairing",
+        0x07 => "internet relay",
+        0x08 => "Wi-Fi password sharing",
+        0x09 => "iOS setup",
+        0x0A => "repair",
+        0x0B => "speaker setup",
+        0x0C => "Apple Pay",
+        0x0D => "whole-home audio setup",
+        0x0E => "developer tools pairing",
+        0x0F => "answered call",
+        0x10 => "ended call",
+        0x13 => "remote autofill",
+        0x14 => "companion link proximity",
+        _ => return None,
+    })
+}
+
+/// Proximity Pairing device model.
+fn proximity_model_name(model: u16) -> Option<&'static str> {
+    Some(match model {
+        0x0220 => "AirPods",
+        0x0F20 => "AirPods (2nd generation)",
+        0x1320 => "AirPods (3rd generation)",
+        0x0E20 => "AirPods Pro",
+        0x1420 => "AirPods Pro (2nd generation)",
+        0x0A20 => "AirPods Max",
+        0x0320 => "Powerbeats3",
+        0x0B20 => "Powerbeats Pro",
+        0x0520 => "BeatsX",
+        0x0620 => "Beats Solo3",
+        0x0920 => "Beats Studio3",
+        0x0C20 => "Beats Solo Pro",
+        0x1020 => "Beats Flex",
+        0x1120 => "Beats Studio Buds",
+        0x1220 => "Beats Fit Pro",
+        _ => return None,
+    })
+}
+
+/// Microsoft CDP device type.
+fn cdp_device_type_name(device_type: u8) -> Option<&'static str> {
+    Some(match device_type {
+        1 => "Xbox One",
+        6 => "iPhone",
+        7 => "iPad",
+        8 => "Android device",
+        9 => "Windows 10 Desktop",
+        11 => "Windows 10 Phone",
+        12 => "Linux device",
+        13 => "Windows IoT",
+        14 => "Surface Hub",
+        15 => "Windows laptop",
+        16 => "Windows tablet",
+        _ => return None,
+    })
+}
+
+/// Battery nibble in tens of percent; 0xF means unknown.
+fn battery_nibble(value: u8) -> Option<u8> {
+    (value <= 10).then_some(value * 10)
+}
+
+fn percent(level: Option<u8>) -> String {
+    match level {
+        Some(level) => format!("{}%", level),
+        None => "?".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advertising::AddressType;
+
+    #[test]
+    fn test_decode_apple_frames() {
+        let mut adv = AdvertisingData::new([0; 6], AddressType::Random);
+        let mut ibeacon = vec![0x02, 0x15];
+        ibeacon.extend_from_slice(&0xE2C56DB5_DFFB_48D2_B060_D0F5A71096E0u128.to_be_bytes());
+        ibeacon.extend_from_slice(&[0x00, 0x01, 0x00, 0x02, 0xC5]);
+        adv.manufacturer_data.push((COMPANY_APPLE, ibeacon));
+        adv.manufacturer_data.push((0x0118, vec![0x10, 0x05, 0x07, 0x1C, 0xAA, 0xBB, 0xCC]));
+
+        // Nearby Info and AirPods in one Apple block
+        let continuity = vec![0x10, 0x02, 0x07, 0x1C, 0x07, 0x06, 0x01, 0x0E, 0x20, 0x00, 0x98, 0x05];
+        adv.manufacturer_data.push((COMPANY_APPLE, continuity));
+
+        let beacons = decode(&adv);
+        assert_eq!(beacons.len(), 3);
+        assert_eq!(
+            beacons[0].summary(),
+            "iBeacon e2c56db5-dffb-48d2-b060-d0f5a71096e0 major 1 minor 2 (-59 dBm @ 1m)"
+        );
+        assert_eq!(beacons[1].summary(), "Apple Nearby Info (screen on)");
+        assert_eq!(beacons[2].summary(), "Apple Proximity Pairing (AirPods Pro, pods 90%/80%, case 50%)");
+        assert_eq!(beacons[2].to_json()["type"], "apple_continuity");
+    }
+
+    #[test]
+    fn test_decode_service_data_frames() {
+        let url = decode_eddystone(&[0x10, 0xEB, 0x03, b'g', b'o', b'o', b'.', b'g', b'l', 0x00]).unwrap();
+        assert_eq!(url.summary(), "Eddystone-URL https://goo.gl.com/");
+
+        let tlm = decode_eddystone(&[0x20, 0x00, 0x0B, 0xB8, 0x17, 0x80, 0, 0, 0, 10, 0, 0, 0x01, 0xF4]).unwrap();
+        assert_eq!(
+            tlm,
+            Beacon::EddystoneTlm(EddystoneTlm::Plain {
+                battery_mv: Some(3000),
+                temperature_c: Some(23.5),
+                adv_count: 10,
+                uptime_s: 50.0,
+            })
+        );
+
+        let fast_pair = decode_fast_pair(&[0x2D, 0x7A, 0x23]).unwrap();
+        assert_eq!(fast_pair.summary(), "Google Fast Pair (model 0x2D7A23, discoverable)");
+
+        let cdp = decode_microsoft_cdp(&[0x01, 0x09, 0x20, 0x00, 1, 2, 3, 4]).unwrap();
+        assert_eq!(cdp.summary(), "Microsoft CDP (Windows 10 Desktop)");
+
+        let mut alt = vec![0xBE, 0xAC];
+        alt.extend_from_slice(&[0x11; 20]);
+        alt.extend_from_slice(&[0xC5, 0x00]);
+        assert!(matches!(decode_altbeacon(0x0118, &alt), Some(Beacon::AltBeacon { reference_rssi: -59, .. })));
+    }
+}
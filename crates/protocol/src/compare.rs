@@ -0,0 +1,760 @@
+//! Semantic comparison of two captures.
+//!
+//! Each capture is decoded into a [`CaptureProfile`]: the devices it saw
+//! (name, signal, advertising interval, payloads), its PDU-type and channel
+//! distributions, connection events and a multiset of packet contents.
+//! [`compare`] diffs two profiles; [`CaptureDiff::differences`] lists the
+//! differences relevant to one [`CompareMode`].
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::decode::{self, linktype, ProtocolNode};
+use crate::error::Result;
+use crate::reader::PacketReader;
+
+/// Advertising PDUs closer together than this belong to one advertising event.
+const ADV_EVENT_GAP_SEC: f64 = 0.010;
+
+/// Advertising intervals differing by less than this fraction are unchanged.
+const INTERVAL_TOLERANCE: f64 = 0.2;
+
+/// Payload variants kept per device.
+const MAX_PAYLOADS: usize = 16;
+
+/// Which aspect of the captures to compare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareMode {
+    /// Devices that appeared, disappeared or changed
+    Devices,
+    /// Packet contents, PDU types, channels and connection events
+    Packets,
+    /// Duration, packet rate, advertising intervals and connection timing
+    Timing,
+}
+
+impl CompareMode {
+    /// Mode with the given name (`devices`, `packets`, `timing`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "devices" => Some(CompareMode::Devices),
+            "packets" => Some(CompareMode::Packets),
+            "timing" => Some(CompareMode::Timing),
+            _ => None,
+        }
+    }
+
+    /// Lowercase name used in JSON output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompareMode::Devices => "devices",
+            CompareMode::Packets => "packets",
+            CompareMode::Timing => "timing",
+        }
+    }
+}
+
+/// Coarse signal band for an RSSI value.
+pub fn rssi_band(rssi: f64) -> &'static str {
+    if rssi >= -60.0 {
+        "strong"
+    } else if rssi >= -75.0 {
+        "good"
+    } else if rssi >= -90.0 {
+        "fair"
+    } else {
+        "weak"
+    }
+}
+
+/// What one capture saw of a device.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceProfile {
+    pub address: String,
+    pub name: Option<String>,
+    pub packets: usize,
+    pub rssi_mean: Option<f64>,
+    pub rssi_band: Option<&'static str>,
+    pub adv_interval_ms: Option<f64>,
+    /// Distinct advertising payloads (AD structures, hex)
+    pub payloads: BTreeSet<String>,
+    pub first_seen: f64,
+    pub last_seen: f64,
+    #[serde(skip)]
+    rssi_samples: Vec<i8>,
+    #[serde(skip)]
+    adv_times: Vec<f64>,
+}
+
+impl DeviceProfile {
+    fn new(address: &str, timestamp: f64) -> Self {
+        Self {
+            address: address.to_string(),
+            name: None,
+            packets: 0,
+            rssi_mean: None,
+            rssi_band: None,
+            adv_interval_ms: None,
+            payloads: BTreeSet::new(),
+            first_seen: timestamp,
+            last_seen: timestamp,
+            rssi_samples: Vec::new(),
+            adv_times: Vec::new(),
+        }
+    }
+
+    fn finish(&mut self) {
+        if !self.rssi_samples.is_empty() {
+            let mean = self.rssi_samples.iter().map(|r| *r as f64).sum::<f64>() / self.rssi_samples.len() as f64;
+            self.rssi_mean = Some((mean * 10.0).round() / 10.0);
+            self.rssi_band = Some(rssi_band(mean));
+        }
+
+        // Median gap between advertising events (one event spans channels 37-39)
+        let mut events: Vec<f64> = Vec::new();
+        for &t in &self.adv_times {
+            if events.last().is_none_or(|last| t - last > ADV_EVENT_GAP_SEC) {
+                events.push(t);
+            }
+        }
+        let mut gaps: Vec<f64> = events.windows(2).map(|w| (w[1] - w[0]) * 1000.0).collect();
+        if !gaps.is_empty() {
+            gaps.sort_by(|a, b| a.total_cmp(b));
+            self.adv_interval_ms = Some((gaps[gaps.len() / 2] * 10.0).round() / 10.0);
+        }
+    }
+}
+
+/// A connection request seen in a capture.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionEvent {
+    pub timestamp: f64,
+    pub initiator: Option<String>,
+    pub advertiser: Option<String>,
+    pub access_address: Option<String>,
+    pub interval_ms: Option<f64>,
+}
+
+impl ConnectionEvent {
+    fn pair(&self) -> (Option<&str>, Option<&str>) {
+        (self.initiator.as_deref(), self.advertiser.as_deref())
+    }
+}
+
+/// Everything [`compare`] needs to know about one capture.
+#[derive(Debug, Clone, Default)]
+pub struct CaptureProfile {
+    pub packets: usize,
+    pub duration_sec: f64,
+    pub pdu_types: BTreeMap<String, usize>,
+    pub channels: BTreeMap<u8, usize>,
+    pub devices: BTreeMap<String, DeviceProfile>,
+    pub connections: Vec<ConnectionEvent>,
+    /// Packet contents without per-reception fields (RF header, CRC), with counts
+    signatures: HashMap<Vec<u8>, usize>,
+}
+
+impl CaptureProfile {
+    /// Decode a PCAP or PCAPNG capture into a profile.
+    pub fn from_capture(input: &[u8]) -> Result<Self> {
+        let mut profile = CaptureProfile::default();
+        let mut first: Option<f64> = None;
+        let mut last = 0.0f64;
+
+        for packet in PacketReader::new(input)? {
+            let packet = packet?;
+            let timestamp = packet.timestamp.as_secs_f64();
+            first.get_or_insert(timestamp);
+            last = last.max(timestamp);
+
+            let decoded = decode::decode_packet(packet.linktype, &packet.data);
+            profile.packets += 1;
+            *profile.pdu_types.entry(decoded.packet_type.clone()).or_insert(0) += 1;
+            if let Some(channel) = decoded.channel {
+                *profile.channels.entry(channel).or_insert(0) += 1;
+            }
+            *profile.signatures.entry(signature(packet.linktype, &packet.data).to_vec()).or_insert(0) += 1;
+
+            if decoded.packet_type == "CONNECT_REQ" {
+                let ll_data = find_layer(&decoded.tree, "btle_ll_data");
+                profile.connections.push(ConnectionEvent {
+                    timestamp,
+                    initiator: decoded.peer_address.clone(),
+                    advertiser: decoded.address.clone(),
+                    access_address: ll_data
+                        .and_then(|n| n.fields.get("access_address"))
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string),
+                    interval_ms: ll_data.and_then(|n| n.fields.get("interval_ms")).and_then(|v| v.as_f64()),
+                });
+            }
+
+            // Devices are the transmitters of advertising PDUs
+            let advertises = matches!(
+                decoded.packet_type.as_str(),
+                "ADV_IND" | "ADV_DIRECT_IND" | "ADV_NONCONN_IND" | "ADV_SCAN_IND" | "SCAN_RSP"
+            );
+            let Some(address) = decoded.address.as_deref().filter(|_| advertises) else {
+                continue;
+            };
+            let device = profile
+                .devices
+                .entry(address.to_string())
+                .or_insert_with(|| DeviceProfile::new(address, timestamp));
+            device.packets += 1;
+            device.last_seen = timestamp;
+            if decoded.name.is_some() {
+                device.name = decoded.name.clone();
+            }
+            if let Some(rssi) = decoded.rssi {
+                device.rssi_samples.push(rssi);
+            }
+            if decoded.packet_type != "SCAN_RSP" {
+                device.adv_times.push(timestamp);
+            }
+            if let Some(pdu) = decode::advertising_pdu(packet.linktype, &packet.data) {
+                if pdu.len() > 12 && device.payloads.len() < MAX_PAYLOADS {
+                    device.payloads.insert(pdu[12..].iter().map(|b| format!("{:02x}", b)).collect());
+                }
+            }
+        }
+
+        profile.duration_sec = first.map(|f| last - f).unwrap_or(0.0);
+        for device in profile.devices.values_mut() {
+            device.finish();
+        }
+        Ok(profile)
+    }
+
+    /// Packets per second over the capture.
+    pub fn packet_rate(&self) -> f64 {
+        if self.duration_sec > 0.0 {
+            self.packets as f64 / self.duration_sec
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Packet bytes that identify its content: BLE PDUs without the RF header
+/// and CRC, BR/EDR packets from the LAP on.
+fn signature(linktype: u32, data: &[u8]) -> &[u8] {
+    let ll = match linktype {
+        linktype::BLUETOOTH_LE_LL_WITH_PHDR => data.get(10..).unwrap_or(&[]),
+        linktype::BLUETOOTH_LE_LL => data,
+        linktype::BLUETOOTH_BREDR_BB => return data.get(8..).unwrap_or(data),
+        _ => return data,
+    };
+    match ll.get(5) {
+        Some(&length) => ll.get(..6 + length as usize).unwrap_or(ll),
+        None => ll,
+    }
+}
+
+fn find_layer<'a>(node: &'a ProtocolNode, layer: &str) -> Option<&'a ProtocolNode> {
+    if node.layer == layer {
+        return Some(node);
+    }
+    node.children.iter().find_map(|child| find_layer(child, layer))
+}
+
+/// One difference between the captures.
+#[derive(Debug, Clone, Serialize)]
+pub struct Difference {
+    /// `device_appeared`, `device_disappeared`, `device_changed`, `pdu_type`,
+    /// `channel`, `connection`, `timing`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub field: String,
+    pub value_a: String,
+    pub value_b: String,
+    pub interpretation: String,
+}
+
+impl Difference {
+    fn new(kind: &'static str, field: impl Into<String>, value_a: impl ToString, value_b: impl ToString, interpretation: String) -> Self {
+        Self {
+            kind,
+            field: field.into(),
+            value_a: value_a.to_string(),
+            value_b: value_b.to_string(),
+            interpretation,
+        }
+    }
+}
+
+/// A device seen in both captures with different characteristics.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceChange {
+    pub address: String,
+    pub name: Option<String>,
+    pub changes: Vec<Difference>,
+}
+
+/// Counts of one key (PDU type, channel) in both captures.
+#[derive(Debug, Clone, Serialize)]
+pub struct CountDelta<K> {
+    pub key: K,
+    pub count_a: usize,
+    pub count_b: usize,
+    /// Share of each capture's packets, in percent
+    pub percent_a: f64,
+    pub percent_b: f64,
+}
+
+/// Advertising interval of a device seen in both captures.
+#[derive(Debug, Clone, Serialize)]
+pub struct IntervalDelta {
+    pub address: String,
+    pub interval_ms_a: f64,
+    pub interval_ms_b: f64,
+}
+
+/// Result of [`compare`].
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureDiff {
+    /// Dice similarity of packet contents, in percent
+    pub similarity_percent: f64,
+    pub common_packets: usize,
+    pub unique_to_a: usize,
+    pub unique_to_b: usize,
+    pub devices_appeared: Vec<DeviceProfile>,
+    pub devices_disappeared: Vec<DeviceProfile>,
+    pub devices_changed: Vec<DeviceChange>,
+    pub devices_unchanged: usize,
+    pub pdu_types: Vec<CountDelta<String>>,
+    pub channels: Vec<CountDelta<u8>>,
+    pub connections_a: Vec<ConnectionEvent>,
+    pub connections_b: Vec<ConnectionEvent>,
+    pub duration_sec_a: f64,
+    pub duration_sec_b: f64,
+    pub packet_rate_a: f64,
+    pub packet_rate_b: f64,
+    pub adv_intervals: Vec<IntervalDelta>,
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (count as f64 * 1000.0 / total as f64).round() / 10.0
+    }
+}
+
+fn count_deltas<K: Ord + Clone>(a: &BTreeMap<K, usize>, b: &BTreeMap<K, usize>, total_a: usize, total_b: usize) -> Vec<CountDelta<K>> {
+    let keys: BTreeSet<&K> = a.keys().chain(b.keys()).collect();
+    keys.into_iter()
+        .map(|key| {
+            let (count_a, count_b) = (a.get(key).copied().unwrap_or(0), b.get(key).copied().unwrap_or(0));
+            CountDelta {
+                key: key.clone(),
+                count_a,
+                count_b,
+                percent_a: percent(count_a, total_a),
+                percent_b: percent(count_b, total_b),
+            }
+        })
+        .collect()
+}
+
+fn device_changes(a: &DeviceProfile, b: &DeviceProfile) -> Vec<Difference> {
+    let mut changes = Vec::new();
+    let label = |field: &str| format!("{} {}", a.address, field);
+    let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+
+    if a.name.is_some() && b.name.is_some() && a.name != b.name {
+        changes.push(Difference::new(
+            "device_changed",
+            label("name"),
+            show(&a.name),
+            show(&b.name),
+            "Advertised name changed".to_string(),
+        ));
+    }
+    if let (Some(band_a), Some(band_b)) = (a.rssi_band, b.rssi_band) {
+        if band_a != band_b {
+            changes.push(Difference::new(
+                "device_changed",
+                label("rssi_band"),
+                format!("{} ({:.1} dBm)", band_a, a.rssi_mean.unwrap_or_default()),
+                format!("{} ({:.1} dBm)", band_b, b.rssi_mean.unwrap_or_default()),
+                "Signal strength band changed; the device or receiver moved".to_string(),
+            ));
+        }
+    }
+    if let (Some(ia), Some(ib)) = (a.adv_interval_ms, b.adv_interval_ms) {
+        if (ia - ib).abs() > ia.max(ib) * INTERVAL_TOLERANCE {
+            changes.push(Difference::new(
+                "device_changed",
+                label("adv_interval_ms"),
+                ia,
+                ib,
+                "Advertising interval changed; the device may have switched mode or state".to_string(),
+            ));
+        }
+    }
+    if !a.payloads.is_empty() && !b.payloads.is_empty() && a.payloads.is_disjoint(&b.payloads) {
+        changes.push(Difference::new(
+            "device_changed",
+            label("payload"),
+            a.payloads.iter().next().cloned().unwrap_or_default(),
+            b.payloads.iter().next().cloned().unwrap_or_default(),
+            "Advertising payload changed; rotating or state-dependent data".to_string(),
+        ));
+    }
+    changes
+}
+
+/// Diff two capture profiles.
+pub fn compare(a: &CaptureProfile, b: &CaptureProfile) -> CaptureDiff {
+    let common_packets: usize = a
+        .signatures
+        .iter()
+        .map(|(sig, count)| (*count).min(b.signatures.get(sig).copied().unwrap_or(0)))
+        .sum();
+    let total = a.packets + b.packets;
+    let similarity_percent = if total > 0 {
+        (common_packets as f64 * 2000.0 / total as f64).round() / 10.0
+    } else {
+        0.0
+    };
+
+    let mut devices_changed = Vec::new();
+    let mut devices_unchanged = 0;
+    let mut adv_intervals = Vec::new();
+    for (address, device_a) in &a.devices {
+        let Some(device_b) = b.devices.get(address) else { continue };
+        let changes = device_changes(device_a, device_b);
+        if changes.is_empty() {
+            devices_unchanged += 1;
+        } else {
+            devices_changed.push(DeviceChange {
+                address: address.clone(),
+                name: device_b.name.clone().or_else(|| device_a.name.clone()),
+                changes,
+            });
+        }
+        if let (Some(ia), Some(ib)) = (device_a.adv_interval_ms, device_b.adv_interval_ms) {
+            adv_intervals.push(IntervalDelta {
+                address: address.clone(),
+                interval_ms_a: ia,
+                interval_ms_b: ib,
+            });
+        }
+    }
+
+    CaptureDiff {
+        similarity_percent,
+        common_packets,
+        unique_to_a: a.packets - common_packets,
+        unique_to_b: b.packets - common_packets,
+        devices_appeared: b.devices.values().filter(|d| !a.devices.contains_key(&d.address)).cloned().collect(),
+        devices_disappeared: a.devices.values().filter(|d| !b.devices.contains_key(&d.address)).cloned().collect(),
+        devices_changed,
+        devices_unchanged,
+        pdu_types: count_deltas(&a.pdu_types, &b.pdu_types, a.packets, b.packets),
+        channels: count_deltas(&a.channels, &b.channels, a.packets, b.packets),
+        connections_a: a.connections.clone(),
+        connections_b: b.connections.clone(),
+        duration_sec_a: a.duration_sec,
+        duration_sec_b: b.duration_sec,
+        packet_rate_a: a.packet_rate(),
+        packet_rate_b: b.packet_rate(),
+        adv_intervals,
+    }
+}
+
+impl CaptureDiff {
+    /// Differences relevant to `mode`, most significant first.
+    pub fn differences(&self, mode: CompareMode) -> Vec<Difference> {
+        let mut out = Vec::new();
+        let describe = |d: &DeviceProfile| match &d.name {
+            Some(name) => format!("{} ({})", d.address, name),
+            None => d.address.clone(),
+        };
+
+        match mode {
+            CompareMode::Devices => {
+                for device in &self.devices_appeared {
+                    out.push(Difference::new(
+                        "device_appeared",
+                        describe(device),
+                        "absent",
+                        format!("{} packets", device.packets),
+                        "Device only present in capture B".to_string(),
+                    ));
+                }
+                for device in &self.devices_disappeared {
+                    out.push(Difference::new(
+                        "device_disappeared",
+                        describe(device),
+                        format!("{} packets", device.packets),
+                        "absent",
+                        "Device only present in capture A".to_string(),
+                    ));
+                }
+                out.extend(self.devices_changed.iter().flat_map(|c| c.changes.iter().cloned()));
+            }
+            CompareMode::Packets => {
+                // Distribution shifts of at least 5 percentage points
+                for delta in &self.pdu_types {
+                    if (delta.percent_a - delta.percent_b).abs() >= 5.0 || delta.count_a == 0 || delta.count_b == 0 {
+                        out.push(Difference::new(
+                            "pdu_type",
+                            &delta.key,
+                            format!("{} ({}%)", delta.count_a, delta.percent_a),
+                            format!("{} ({}%)", delta.count_b, delta.percent_b),
+                            format!("{} share changed", delta.key),
+                        ));
+                    }
+                }
+                for delta in &self.channels {
+                    if (delta.percent_a - delta.percent_b).abs() >= 5.0 || delta.count_a == 0 || delta.count_b == 0 {
+                        out.push(Difference::new(
+                            "channel",
+                            format!("channel {}", delta.key),
+                            format!("{} ({}%)", delta.count_a, delta.percent_a),
+                            format!("{} ({}%)", delta.count_b, delta.percent_b),
+                            "Channel usage changed".to_string(),
+                        ));
+                    }
+                }
+                out.extend(self.connection_differences());
+            }
+            CompareMode::Timing => {
+                out.push(Difference::new(
+                    "timing",
+                    "duration_sec",
+                    format!("{:.1}", self.duration_sec_a),
+                    format!("{:.1}", self.duration_sec_b),
+                    "Capture duration".to_string(),
+                ));
+                out.push(Difference::new(
+                    "timing",
+                    "packets_per_sec",
+                    format!("{:.1}", self.packet_rate_a),
+                    format!("{:.1}", self.packet_rate_b),
+                    "Packet rate".to_string(),
+                ));
+                for interval in &self.adv_intervals {
+                    let (ia, ib) = (interval.interval_ms_a, interval.interval_ms_b);
+                    if (ia - ib).abs() > ia.max(ib) * INTERVAL_TOLERANCE {
+                        out.push(Difference::new(
+                            "timing",
+                            format!("{} adv_interval_ms", interval.address),
+                            ia,
+                            ib,
+                            "Advertising interval changed".to_string(),
+                        ));
+                    }
+                }
+                out.extend(self.connection_differences());
+            }
+        }
+        out
+    }
+
+    /// Connections between device pairs seen in only one capture, or with a new interval.
+    fn connection_differences(&self) -> Vec<Difference> {
+        let mut out = Vec::new();
+        let label = |c: &ConnectionEvent| {
+            format!(
+                "connection {} -> {}",
+                c.initiator.as_deref().unwrap_or("?"),
+                c.advertiser.as_deref().unwrap_or("?")
+            )
+        };
+        let interval = |c: &ConnectionEvent| c.interval_ms.map(|i| format!("{:.2} ms", i)).unwrap_or_else(|| "?".to_string());
+
+        for event in &self.connections_b {
+            match self.connections_a.iter().find(|a| a.pair() == event.pair()) {
+                None => out.push(Difference::new(
+                    "connection",
+                    label(event),
+                    "absent",
+                    format!("at {:.3}", event.timestamp),
+                    "New connection in capture B".to_string(),
+                )),
+                Some(a) if a.interval_ms != event.interval_ms => out.push(Difference::new(
+                    "connection",
+                    label(event),
+                    interval(a),
+                    interval(event),
+                    "Connection requested with different parameters".to_string(),
+                )),
+                Some(_) => {}
+            }
+        }
+        for event in &self.connections_a {
+            if !self.connections_b.iter().any(|b| b.pair() == event.pair()) {
+                out.push(Difference::new(
+                    "connection",
+                    label(event),
+                    format!("at {:.3}", event.timestamp),
+                    "absent",
+                    "Connection only in capture A".to_string(),
+                ));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pcap_file::pcap::{PcapHeader, PcapPacket, PcapWriter};
+    use pcap_file::DataLink;
+    use std::time::Duration;
+
+    /// PHDR + ADV_NONCONN_IND from `addr` with one manufacturer data byte.
+    fn adv(addr: u8, rssi: i8, data: u8) -> Vec<u8> {
+        let mut packet = vec![0, rssi as u8, 0, 0, 0, 0, 0, 0, 0x03, 0x00];
+        packet.extend_from_slice(&[0xD6, 0xBE, 0x89, 0x8E, 0x02, 11, addr, 0, 0, 0, 0, 0xC0, 4, 0xFF, 0x4C, 0x00, data, 0, 0, 0]);
+        packet
+    }
+
+    fn connect(initiator: u8, advertiser: u8, interval: u16) -> Vec<u8> {
+        let mut packet = vec![0, 0xC4, 0, 0, 0, 0, 0, 0, 0x03, 0x00];
+        packet.extend_from_slice(&[0xD6, 0xBE, 0x89, 0x8E, 0x05, 34, initiator, 0, 0, 0, 0, 0xC0, advertiser, 0, 0, 0, 0, 0xC0]);
+        let mut ll = [0u8; 22];
+        ll[10..12].copy_from_slice(&interval.to_le_bytes());
+        packet.extend_from_slice(&ll);
+        packet.extend_from_slice(&[0, 0, 0]);
+        packet
+    }
+
+    fn capture(packets: &[(u64, Vec<u8>)]) -> CaptureProfile {
+        let header = PcapHeader {
+            datalink: DataLink::BLUETOOTH_LE_LL_WITH_PHDR,
+            ..Default::default()
+        };
+        let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+        for (ms, data) in packets {
+            writer
+                .write_packet(&PcapPacket::new(Duration::from_millis(*ms), data.len() as u32, data))
+                .unwrap();
+        }
+        CaptureProfile::from_capture(&writer.into_writer()).unwrap()
+    }
+
+    #[test]
+    fn test_device_differences() {
+        // Device 1 advertises every 100 ms in A and every 1 s in B, device 2 leaves, device 3 arrives
+        let mut a: Vec<(u64, Vec<u8>)> = (0..5).map(|i| (i * 100, adv(1, -50, 1))).collect();
+        a.push((50, adv(2, -70, 1)));
+        let mut b: Vec<(u64, Vec<u8>)> = (0..5).map(|i| (i * 1000, adv(1, -85, 1))).collect();
+        b.push((60, adv(3, -70, 1)));
+
+        let (a, b) = (capture(&a), capture(&b));
+        assert_eq!(a.devices["C0:00:00:00:00:01"].adv_interval_ms, Some(100.0));
+
+        let diff = compare(&a, &b);
+        assert_eq!(diff.devices_appeared[0].address, "C0:00:00:00:00:03");
+        assert_eq!(diff.devices_disappeared[0].address, "C0:00:00:00:00:02");
+        let fields: Vec<&str> = diff.devices_changed[0].changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, ["C0:00:00:00:00:01 rssi_band", "C0:00:00:00:00:01 adv_interval_ms"]);
+
+        // Identical content regardless of RSSI: device 1 packets are common
+        assert_eq!(diff.common_packets, 5);
+        assert_eq!((diff.unique_to_a, diff.unique_to_b), (1, 1));
+        assert_eq!(diff.differences(CompareMode::Devices).len(), 4);
+    }
+
+    #[test]
+    fn test_packet_and_timing_differences() {
+        let a = capture(&[(0, adv(1, -50, 1)), (10, connect(9, 1, 24))]);
+        let b = capture(&[(0, adv(1, -50, 2)), (10, connect(9, 1, 80)), (20, connect(8, 1, 24))]);
+        let diff = compare(&a, &b);
+
+        // Payload rotated
+        assert_eq!(diff.devices_changed[0].changes[0].field, "C0:00:00:00:00:01 payload");
+        assert_eq!(diff.similarity_percent, 0.0);
+
+        let packets = diff.differences(CompareMode::Packets);
+        let connect_share = packets.iter().find(|d| d.field == "CONNECT_REQ").unwrap();
+        assert_eq!((connect_share.value_a.as_str(), connect_share.value_b.as_str()), ("1 (50%)", "2 (66.7%)"));
+        let connections: Vec<&Difference> = packets.iter().filter(|d| d.kind == "connection").collect();
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections[0].value_b, "100.00 ms");
+
+        let timing = diff.differences(CompareMode::Timing);
+        assert_eq!(timing[0].field, "duration_sec");
+        assert_eq!(CompareMode::from_name("timing"), Some(CompareMode::Timing));
+    }
+
+    #[test]
+    fn test_each_mode_reports_its_own_differences() {
+        // Device 1 slows from 100 ms to 1 s, device 2 arrives, the connection is only in A
+        let mut a: Vec<(u64, Vec<u8>)> = (0..4).map(|i| (i * 100, adv(1, -50, 1))).collect();
+        a.push((310, connect(9, 1, 24)));
+        let mut b: Vec<(u64, Vec<u8>)> = (0..4).map(|i| (i * 1000, adv(1, -50, 1))).collect();
+        b.push((5, adv(2, -50, 1)));
+        let diff = compare(&capture(&a), &capture(&b));
+
+        let kinds = |mode| diff.differences(mode).iter().map(|d| d.kind).collect::<Vec<_>>();
+        assert_eq!(kinds(CompareMode::Devices), ["device_appeared", "device_changed"]);
+        assert_eq!(kinds(CompareMode::Packets), ["pdu_type", "pdu_type", "connection"]);
+        assert_eq!(kinds(CompareMode::Timing), ["timing", "timing", "timing", "connection"]);
+
+        let timing = diff.differences(CompareMode::Timing);
+        assert_eq!((timing[0].value_a.as_str(), timing[0].value_b.as_str()), ("0.3", "3.0"));
+        assert_eq!(timing[2].field, "C0:00:00:00:00:01 adv_interval_ms");
+        assert_eq!(timing[3].interpretation, "Connection only in capture A");
+    }
+
+    #[test]
+    fn test_identical_and_disjoint_captures() {
+        let a = capture(&[(0, adv(1, -50, 1)), (100, adv(1, -50, 1)), (50, adv(2, -70, 2))]);
+        let same = compare(&a, &a);
+        assert_eq!(same.similarity_percent, 100.0);
+        assert_eq!(same.devices_unchanged, 2);
+        assert!(same.differences(CompareMode::Devices).is_empty());
+        assert!(same.differences(CompareMode::Packets).is_empty());
+
+        let b = capture(&[(0, adv(3, -50, 3)), (100, adv(4, -50, 4))]);
+        let diff = compare(&a, &b);
+        assert_eq!(diff.similarity_percent, 0.0);
+        assert_eq!((diff.common_packets, diff.unique_to_a, diff.unique_to_b), (0, 3, 2));
+        assert_eq!((diff.devices_appeared.len(), diff.devices_disappeared.len()), (2, 2));
+        assert!(diff.devices_changed.is_empty());
+        assert!(diff.adv_intervals.is_empty());
+    }
+
+    #[test]
+    fn test_empty_captures() {
+        let empty = capture(&[]);
+        assert_eq!((empty.packets, empty.duration_sec, empty.packet_rate()), (0, 0.0, 0.0));
+
+        let diff = compare(&empty, &empty);
+        assert_eq!(diff.similarity_percent, 0.0);
+        assert!(diff.differences(CompareMode::Devices).is_empty());
+        assert!(diff.differences(CompareMode::Packets).is_empty());
+        let timing = diff.differences(CompareMode::Timing);
+        assert!(timing.iter().all(|d| d.value_a == "0.0" && d.value_b == "0.0"));
+
+        // Everything in a non-empty capture is new, with no division by zero
+        let a = capture(&[(0, adv(1, -50, 1))]);
+        let diff = compare(&empty, &a);
+        assert_eq!(diff.devices_appeared.len(), 1);
+        assert_eq!((diff.unique_to_a, diff.unique_to_b), (0, 1));
+        assert_eq!(diff.pdu_types[0].percent_a, 0.0);
+        assert_eq!(diff.differences(CompareMode::Packets)[0].value_a, "0 (0%)");
+    }
+
+    #[test]
+    fn test_rotated_address_is_not_linked() {
+        // The same payload from a new random address: profiles are keyed by
+        // address, so the device disappears and another appears with equal payloads
+        let a = capture(&[(0, adv(1, -50, 7)), (100, adv(1, -50, 7))]);
+        let b = capture(&[(0, adv(2, -50, 7)), (100, adv(2, -50, 7))]);
+        let diff = compare(&a, &b);
+
+        assert_eq!(diff.devices_disappeared[0].address, "C0:00:00:00:00:01");
+        assert_eq!(diff.devices_appeared[0].address, "C0:00:00:00:00:02");
+        assert_eq!(diff.devices_appeared[0].payloads, diff.devices_disappeared[0].payloads);
+        assert!(diff.devices_changed.is_empty());
+        // The advertiser address is part of the PDU, so no packet is common
+        assert_eq!(diff.common_packets, 0);
+    }
+}
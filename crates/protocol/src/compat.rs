@@ -0,0 +1,411 @@
+//! ubertooth-tools and firmware version compatibility.
+//!
+//! Three versions decide whether a command works: the host tools release
+//! (`ubertooth-*` and libubertooth), the USB API version libubertooth was
+//! built against, and the release and API version of the firmware on the
+//! device. [`ToolVersions`] collects them from `ubertooth-util -v` / `-V`
+//! output and from the API version warnings the tools print on stderr:
+//!
+//! ```text
+//! Firmware version: 2020-12-R1 (API:1.07)
+//! ubertooth 2020-12-R1 (mikeryan@steel) Fri Dec 25 13:55:05 PST 2020
+//! libubertooth 1.1 (2020-12-R1), libbtbb 1.0 (2020-12-R1)
+//! Ubertooth API version 1.07 is newer than that supported by libubertooth (1.06).
+//! ```
+//!
+//! [`Feature`] lists the flags and tools the backends rely on together with
+//! the first host and firmware release that supports them.
+//! [`ToolVersions::check`] explains why a feature cannot be used; versions
+//! that could not be detected never block a feature.
+
+use serde::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A USB API version as reported by `UBERTOOTH_GET_API_VERSION`.
+///
+/// The firmware encodes it as `major << 8 | minor` and the tools print both
+/// bytes in hex, so `0x0107` is shown as `1.07`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ApiVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl ApiVersion {
+    pub const fn new(major: u8, minor: u8) -> Self {
+        Self { major, minor }
+    }
+
+    /// Decode the raw `major << 8 | minor` value.
+    pub fn from_raw(raw: u16) -> Self {
+        Self::new((raw >> 8) as u8, raw as u8)
+    }
+
+    /// Decode the little-endian reply to `UBERTOOTH_GET_API_VERSION`.
+    pub fn from_le_bytes(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [low, high, ..] => Some(Self::from_raw(u16::from_le_bytes([*low, *high]))),
+            _ => None,
+        }
+    }
+
+    /// Parse the printed form, e.g. `1.07`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (major, minor) = text.split_once('.')?;
+        if major.is_empty() || major.len() > 2 || minor.len() != 2 {
+            return None;
+        }
+        Some(Self::new(
+            u8::from_str_radix(major, 16).ok()?,
+            u8::from_str_radix(minor, 16).ok()?,
+        ))
+    }
+}
+
+impl fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:x}.{:02x}", self.major, self.minor)
+    }
+}
+
+impl Serialize for ApiVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A dated ubertooth release, e.g. `2020-12-R1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Release {
+    pub year: u16,
+    pub month: u8,
+    pub revision: u8,
+}
+
+impl Release {
+    pub const fn new(year: u16, month: u8, revision: u8) -> Self {
+        Self { year, month, revision }
+    }
+
+    /// Parse `YYYY-MM-R<n>`. Development builds (`git-...`) are not releases.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('-');
+        let year = parts.next()?;
+        let month = parts.next()?;
+        let revision = parts.next()?.strip_prefix('R')?;
+        if parts.next().is_some() || year.len() != 4 || month.len() != 2 {
+            return None;
+        }
+        let month: u8 = month.parse().ok()?;
+        if !(1..=12).contains(&month) {
+            return None;
+        }
+        Some(Self::new(year.parse().ok()?, month, revision.parse().ok()?))
+    }
+}
+
+impl fmt::Display for Release {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{:02}-R{}", self.year, self.month, self.revision)
+    }
+}
+
+/// A flag or tool whose availability depends on the installed versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// `ubertooth-btle -A`: choose the advertising channel to listen on
+    BtleAdvertisingChannel,
+    /// `ubertooth-btle -f`: follow connections
+    BtleFollow,
+    /// `ubertooth-btle -q` / `-r`: write a PCAP file
+    BtlePcap,
+    /// `ubertooth-rx -q`: write a PCAP file
+    RxPcap,
+    /// `ubertooth-follow`: follow a BR/EDR piconet
+    Follow,
+    /// `ubertooth-afh`: recover the AFH channel map
+    Afh,
+    /// `ubertooth-scan`: inquiry scan combined with sniffing
+    Scan,
+    /// `ubertooth-specan -l` / `-u`: sweep a frequency range
+    Specan,
+    /// USB `UBERTOOTH_GET_API_VERSION` request
+    ApiVersionCommand,
+}
+
+/// The releases that introduced a [`Feature`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Requirement {
+    /// Command line the feature corresponds to
+    pub usage: &'static str,
+    /// First host tools release that supports it
+    pub min_host: Option<Release>,
+    /// First firmware release that supports it
+    pub min_firmware: Option<Release>,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 9] = [
+        Feature::BtleAdvertisingChannel,
+        Feature::BtleFollow,
+        Feature::BtlePcap,
+        Feature::RxPcap,
+        Feature::Follow,
+        Feature::Afh,
+        Feature::Scan,
+        Feature::Specan,
+        Feature::ApiVersionCommand,
+    ];
+
+    /// The compatibility matrix row for this feature.
+    pub fn requirement(self) -> Requirement {
+        let (usage, min_host, min_firmware) = match self {
+            Feature::BtleAdvertisingChannel => ("ubertooth-btle -A", Some(Release::new(2018, 6, 1)), None),
+            Feature::BtleFollow => ("ubertooth-btle -f", None, None),
+            Feature::BtlePcap => ("ubertooth-btle -q/-r", None, None),
+            Feature::RxPcap => ("ubertooth-rx -q", None, None),
+            Feature::Follow => ("ubertooth-follow", None, None),
+            Feature::Afh => (
+                "ubertooth-afh",
+                Some(Release::new(2018, 6, 1)),
+                Some(Release::new(2018, 6, 1)),
+            ),
+            Feature::Scan => ("ubertooth-scan", None, None),
+            Feature::Specan => ("ubertooth-specan -l/-u", None, None),
+            Feature::ApiVersionCommand => ("UBERTOOTH_GET_API_VERSION", None, Some(Release::new(2017, 3, 1))),
+        };
+        Requirement { usage, min_host, min_firmware }
+    }
+}
+
+/// Whether a feature can be used with the detected versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum Support {
+    Supported,
+    /// A version it depends on could not be detected
+    Unknown,
+    Unsupported(String),
+}
+
+/// Versions of the host tools and the firmware.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ToolVersions {
+    /// Host tools release, e.g. `2020-12-R1`
+    pub host_release: Option<String>,
+    /// libubertooth library version, e.g. `1.1`
+    pub lib_version: Option<String>,
+    /// USB API version libubertooth expects
+    pub lib_api: Option<ApiVersion>,
+    /// Firmware release (or `git-...` build), e.g. `2020-12-R1`
+    pub firmware_release: Option<String>,
+    /// USB API version the firmware implements
+    pub firmware_api: Option<ApiVersion>,
+}
+
+/// The words of `line` that could be versions or releases.
+fn tokens(line: &str) -> impl Iterator<Item = &str> {
+    line.split(|c: char| !(c.is_ascii_alphanumeric() || c == '.' || c == '-'))
+        .map(|t| t.trim_end_matches('.'))
+        .filter(|t| !t.is_empty())
+}
+
+impl ToolVersions {
+    /// Collect the versions printed by `ubertooth-util -v` / `-V` or any tool's stderr.
+    pub fn parse(output: &str) -> Self {
+        let mut versions = Self::default();
+        versions.update(output);
+        versions
+    }
+
+    /// Add the versions found in `output`, keeping those already known.
+    pub fn update(&mut self, output: &str) {
+        for line in output.lines().map(str::trim) {
+            let lower = line.to_ascii_lowercase();
+
+            // "Firmware version: 2020-12-R1 (API:1.07)" or "Firmware revision: ..."
+            if let Some(rest) = lower.strip_prefix("firmware version:").or_else(|| lower.strip_prefix("firmware revision:")) {
+                let rest = &line[line.len() - rest.len()..];
+                if let Some(release) = rest.split_whitespace().next() {
+                    self.firmware_release = Some(release.to_string());
+                }
+                if let Some((_, api)) = rest.split_once("API:") {
+                    self.firmware_api = ApiVersion::parse(api.trim_end_matches(')').trim()).or(self.firmware_api);
+                }
+                continue;
+            }
+
+            // "libubertooth 1.1 (2020-12-R1), libbtbb 1.0 (2020-12-R1)"
+            if let Some(rest) = line.strip_prefix("libubertooth ") {
+                let mut words = tokens(rest);
+                self.lib_version = words.next().map(str::to_string);
+                if let Some(release) = words.next() {
+                    self.host_release = Some(release.to_string());
+                }
+                continue;
+            }
+
+            // Firmware compile info: "ubertooth 2020-12-R1 (mikeryan@steel) Fri Dec 25 ..."
+            if let Some(rest) = line.strip_prefix("ubertooth ") {
+                if self.firmware_release.is_none() {
+                    self.firmware_release = rest.split_whitespace().next().map(str::to_string);
+                }
+                continue;
+            }
+
+            // Mismatch warnings name the firmware API first, then libubertooth's
+            if lower.contains("api version") {
+                let apis: Vec<ApiVersion> = tokens(line).filter_map(ApiVersion::parse).collect();
+                if let Some(firmware) = apis.first() {
+                    self.firmware_api = Some(*firmware);
+                }
+                if apis.len() > 1 {
+                    self.lib_api = apis.last().copied();
+                }
+                if lower.contains("libubertooth") {
+                    if let Some(release) = tokens(line).find(|t| Release::parse(t).is_some()) {
+                        self.host_release = Some(release.to_string());
+                    }
+                }
+            }
+        }
+
+        // The tools only warn when the API versions differ
+        if self.lib_api.is_none() && self.firmware_api.is_some() && !output.to_ascii_lowercase().contains("api version") {
+            self.lib_api = self.firmware_api;
+        }
+    }
+
+    /// Whether nothing was detected.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Why the firmware cannot be used with the host tools at all, if so.
+    ///
+    /// libubertooth refuses firmware with an older API than its own; newer
+    /// firmware only triggers a warning.
+    pub fn api_mismatch(&self) -> Option<String> {
+        let (lib, firmware) = (self.lib_api?, self.firmware_api?);
+        if firmware >= lib {
+            return None;
+        }
+        let host = self
+            .host_release
+            .as_deref()
+            .map(|r| format!(" ({})", r))
+            .unwrap_or_default();
+        Some(format!(
+            "the firmware implements USB API {} but the installed ubertooth-tools{} require {}; \
+             flash the firmware that matches the host tools with ubertooth-dfu",
+            firmware, host, lib
+        ))
+    }
+
+    /// Whether `feature` works with these versions.
+    pub fn support(&self, feature: Feature) -> Support {
+        if let Some(reason) = self.api_mismatch() {
+            return Support::Unsupported(reason);
+        }
+
+        let requirement = feature.requirement();
+        let mut unknown = false;
+        for (kind, installed, required) in [
+            ("ubertooth-tools", &self.host_release, requirement.min_host),
+            ("firmware", &self.firmware_release, requirement.min_firmware),
+        ] {
+            let Some(required) = required else { continue };
+            let Some(installed) = installed else {
+                unknown = true;
+                continue;
+            };
+            // Development builds are assumed to be recent
+            let Some(release) = Release::parse(installed) else { continue };
+            if release.cmp(&required) == Ordering::Less {
+                return Support::Unsupported(format!(
+                    "{} needs {} {} or newer, but {} is installed",
+                    requirement.usage, kind, required, installed
+                ));
+            }
+        }
+
+        if unknown {
+            Support::Unknown
+        } else {
+            Support::Supported
+        }
+    }
+
+    /// `Ok` unless `feature` is known not to work, with the reason otherwise.
+    pub fn check(&self, feature: Feature) -> std::result::Result<(), String> {
+        match self.support(feature) {
+            Support::Unsupported(reason) => Err(reason),
+            Support::Supported | Support::Unknown => Ok(()),
+        }
+    }
+
+    /// Support for every feature, in [`Feature::ALL`] order.
+    pub fn matrix(&self) -> Vec<(Feature, Support)> {
+        Feature::ALL.iter().map(|f| (*f, self.support(*f))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_versions() {
+        let versions = ToolVersions::parse(
+            "Firmware version: 2020-12-R1 (API:1.07)\n\
+             ubertooth 2020-12-R1 (mikeryan@steel) Fri Dec 25 13:55:05 PST 2020\n\
+             libubertooth 1.1 (2020-12-R1), libbtbb 1.0 (2020-12-R1)\n",
+        );
+        assert_eq!(versions.firmware_release.as_deref(), Some("2020-12-R1"));
+        assert_eq!(versions.firmware_api, Some(ApiVersion::new(1, 7)));
+        assert_eq!(versions.lib_api, Some(ApiVersion::new(1, 7)));
+        assert_eq!(versions.lib_version.as_deref(), Some("1.1"));
+        assert_eq!(versions.host_release.as_deref(), Some("2020-12-R1"));
+        assert_eq!(versions.api_mismatch(), None);
+        assert!(versions.matrix().iter().all(|(_, s)| *s == Support::Supported));
+
+        // Newer firmware only warns
+        let mut newer = ToolVersions::parse("Firmware version: git-7a8a4b6 (API:1.07)");
+        newer.update("Ubertooth API version 1.07 is newer than that supported by libubertooth (1.06).\nThings will still work, but you might want to update your host tools.");
+        assert_eq!((newer.firmware_api, newer.lib_api), (Some(ApiVersion::new(1, 7)), Some(ApiVersion::new(1, 6))));
+        assert_eq!(newer.api_mismatch(), None);
+        assert_eq!(newer.support(Feature::Afh), Support::Unknown);
+
+        assert_eq!(ApiVersion::from_le_bytes(&[0x07, 0x01, 0, 0]), Some(ApiVersion::new(1, 7)));
+        assert_eq!(ApiVersion::parse("1.0a").map(|v| v.to_string()).as_deref(), Some("1.0a"));
+        assert!(Release::parse("2018-12-R1") > Release::parse("2018-06-R2"));
+        assert_eq!(Release::parse("git-7a8a4b6"), None);
+    }
+
+    #[test]
+    fn test_incompatible_versions() {
+        // Older firmware is refused by libubertooth
+        let versions =
+            ToolVersions::parse("Ubertooth API version 1.02 found, libubertooth 2020-12-R1 requires 1.07.");
+        assert_eq!(versions.host_release.as_deref(), Some("2020-12-R1"));
+        let reason = versions.check(Feature::BtlePcap).unwrap_err();
+        assert!(reason.contains("USB API 1.02") && reason.contains("require 1.07"), "{}", reason);
+
+        // Old host tools lack newer flags
+        let old = ToolVersions {
+            host_release: Some("2017-03-R2".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            old.check(Feature::BtleAdvertisingChannel),
+            Err("ubertooth-btle -A needs ubertooth-tools 2018-06-R1 or newer, but 2017-03-R2 is installed".to_string())
+        );
+        assert_eq!(old.support(Feature::BtleFollow), Support::Supported);
+        // The firmware release is unknown, so AFH is not ruled out by it
+        assert!(matches!(old.support(Feature::Afh), Support::Unsupported(_)));
+        assert_eq!(ToolVersions::default().support(Feature::Afh), Support::Unknown);
+        assert!(ToolVersions::default().check(Feature::Afh).is_ok());
+    }
+}
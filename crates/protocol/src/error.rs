@@ -0,0 +1,17 @@
+//! Protocol parsing error types.
+
+use thiserror::Error;
+
+/// Errors raised while parsing Bluetooth protocol data.
+#[derive(Debug, Error)]
+pub enum ProtocolError {
+    /// Input is shorter than the structure requires
+    #[error("Truncated data: {0}")]
+    Truncated(String),
+
+    /// Input is structurally invalid
+    #[error("Malformed data: {0}")]
+    Malformed(String),
+}
+
+pub type Result<T> = std::result::Result<T, ProtocolError>;
@@ -0,0 +1,265 @@
+//! Capture export to CSV and JSON Lines.
+//!
+//! Packets are decoded one at a time and written as they are decoded, so
+//! exporting a large capture needs no more memory than the capture itself.
+//! Both formats share one column set ([`ExportField`]); callers choose which
+//! columns to write and in which order.
+
+use serde_json::{json, Map, Value};
+use std::io::Write;
+
+use crate::decode::{self, DecodedPacket};
+use crate::error::{ProtocolError, Result};
+use crate::reader::PacketReader;
+
+/// A column of an exported packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportField {
+    Index,
+    Timestamp,
+    Channel,
+    Rssi,
+    AccessAddress,
+    PduType,
+    Address,
+    PeerAddress,
+    Name,
+    CompanyId,
+    Uuids,
+    CrcValid,
+    Protocol,
+    Length,
+    Summary,
+}
+
+impl ExportField {
+    /// Every field, in the default column order.
+    pub const ALL: [ExportField; 15] = [
+        ExportField::Index,
+        ExportField::Timestamp,
+        ExportField::Channel,
+        ExportField::Rssi,
+        ExportField::AccessAddress,
+        ExportField::PduType,
+        ExportField::Address,
+        ExportField::PeerAddress,
+        ExportField::Name,
+        ExportField::CompanyId,
+        ExportField::Uuids,
+        ExportField::CrcValid,
+        ExportField::Protocol,
+        ExportField::Length,
+        ExportField::Summary,
+    ];
+
+    /// Columns written when the caller does not choose.
+    pub const DEFAULT: [ExportField; 11] = [
+        ExportField::Timestamp,
+        ExportField::Channel,
+        ExportField::Rssi,
+        ExportField::AccessAddress,
+        ExportField::PduType,
+        ExportField::Address,
+        ExportField::PeerAddress,
+        ExportField::Name,
+        ExportField::CompanyId,
+        ExportField::Uuids,
+        ExportField::CrcValid,
+    ];
+
+    /// Column name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportField::Index => "index",
+            ExportField::Timestamp => "timestamp",
+            ExportField::Channel => "channel",
+            ExportField::Rssi => "rssi",
+            ExportField::AccessAddress => "access_address",
+            ExportField::PduType => "pdu_type",
+            ExportField::Address => "address",
+            ExportField::PeerAddress => "peer_address",
+            ExportField::Name => "name",
+            ExportField::CompanyId => "company_id",
+            ExportField::Uuids => "uuids",
+            ExportField::CrcValid => "crc_valid",
+            ExportField::Protocol => "protocol",
+            ExportField::Length => "length",
+            ExportField::Summary => "summary",
+        }
+    }
+
+    /// Field with the given column name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.name() == name)
+    }
+
+    /// Parse a list of column names, rejecting unknown ones.
+    pub fn parse_list<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Vec<Self>> {
+        names
+            .into_iter()
+            .map(|name| {
+                Self::from_name(name).ok_or_else(|| {
+                    let known: Vec<&str> = Self::ALL.iter().map(|f| f.name()).collect();
+                    ProtocolError::Malformed(format!("Unknown export field '{}' (expected one of {})", name, known.join(", ")))
+                })
+            })
+            .collect()
+    }
+
+    /// JSON value of this field for a packet (`null` when absent).
+    pub fn value(&self, packet: &DecodedPacket) -> Value {
+        match self {
+            ExportField::Index => json!(packet.index),
+            ExportField::Timestamp => json!(packet.timestamp),
+            ExportField::Channel => json!(packet.channel),
+            ExportField::Rssi => json!(packet.rssi),
+            ExportField::AccessAddress => json!(packet.access_address.map(|aa| format!("0x{:08x}", aa))),
+            ExportField::PduType => json!(packet.packet_type),
+            ExportField::Address => json!(packet.address),
+            ExportField::PeerAddress => json!(packet.peer_address),
+            ExportField::Name => json!(packet.name),
+            ExportField::CompanyId => json!(packet.company_ids.iter().map(|id| format!("0x{:04X}", id)).collect::<Vec<_>>()),
+            ExportField::Uuids => json!(packet.service_uuids.iter().map(|uuid| format!("0x{:04X}", uuid)).collect::<Vec<_>>()),
+            ExportField::CrcValid => json!(packet.crc_valid),
+            ExportField::Protocol => json!(packet.protocol),
+            ExportField::Length => json!(packet.length),
+            ExportField::Summary => json!(packet.summary),
+        }
+    }
+
+    /// CSV cell of this field; lists are joined with `;`.
+    fn cell(&self, packet: &DecodedPacket) -> String {
+        match self {
+            ExportField::Timestamp => format!("{:.6}", packet.timestamp),
+            _ => match self.value(packet) {
+                Value::Null => String::new(),
+                Value::String(text) => text,
+                Value::Array(items) => items.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(";"),
+                other => other.to_string(),
+            },
+        }
+    }
+}
+
+/// Decode every packet of `input` and pass it to `write`, in capture order.
+fn for_each_packet(input: &[u8], mut write: impl FnMut(&DecodedPacket) -> Result<()>) -> Result<usize> {
+    let reader = PacketReader::new(input)?;
+    let mut count = 0;
+    for packet in reader {
+        let packet = packet?;
+        let mut decoded = decode::decode_packet(packet.linktype, &packet.data);
+        decoded.index = count;
+        decoded.timestamp = packet.timestamp.as_secs_f64();
+        write(&decoded)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn io_error(e: std::io::Error) -> ProtocolError {
+    ProtocolError::Malformed(format!("Write failed: {}", e))
+}
+
+fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// Write `input` as CSV with a header row. Returns the number of packets.
+pub fn write_csv<W: Write>(input: &[u8], mut output: W, fields: &[ExportField]) -> Result<usize> {
+    let header: Vec<&str> = fields.iter().map(|f| f.name()).collect();
+    writeln!(output, "{}", header.join(",")).map_err(io_error)?;
+
+    for_each_packet(input, |packet| {
+        let row: Vec<String> = fields.iter().map(|f| csv_escape(&f.cell(packet))).collect();
+        writeln!(output, "{}", row.join(",")).map_err(io_error)
+    })
+}
+
+/// Write `input` as JSON Lines, one object per packet. Returns the number of packets.
+pub fn write_jsonl<W: Write>(input: &[u8], mut output: W, fields: &[ExportField]) -> Result<usize> {
+    for_each_packet(input, |packet| {
+        let object: Map<String, Value> = fields.iter().map(|f| (f.name().to_string(), f.value(packet))).collect();
+        writeln!(output, "{}", Value::Object(object)).map_err(io_error)
+    })
+}
+
+/// Write `input` as a single JSON array of packet objects. Returns the number of packets.
+pub fn write_json<W: Write>(input: &[u8], mut output: W, fields: &[ExportField]) -> Result<usize> {
+    output.write_all(b"[").map_err(io_error)?;
+    let count = for_each_packet(input, |packet| {
+        if packet.index > 0 {
+            output.write_all(b",").map_err(io_error)?;
+        }
+        let object: Map<String, Value> = fields.iter().map(|f| (f.name().to_string(), f.value(packet))).collect();
+        write!(output, "\n  {}", Value::Object(object)).map_err(io_error)
+    })?;
+    output.write_all(b"\n]\n").map_err(io_error)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pcap_file::pcap::{PcapHeader, PcapPacket, PcapWriter};
+    use pcap_file::DataLink;
+    use std::time::Duration;
+
+    fn capture() -> Vec<u8> {
+        // PHDR (CRC checked and valid) + ADV_IND with name "a,b" and Apple data, then SCAN_REQ
+        let mut adv = vec![0, 0xC4, 0, 0, 0, 0, 0, 0, 0x03, 0x0C];
+        let payload = [1, 2, 3, 4, 5, 6, 4, 0x09, b'a', b',', b'b', 3, 0xFF, 0x4C, 0x00];
+        adv.extend_from_slice(&[0xD6, 0xBE, 0x89, 0x8E, 0x00, payload.len() as u8]);
+        adv.extend_from_slice(&payload);
+        adv.extend_from_slice(&[0, 0, 0]);
+
+        let mut scan = vec![12, 0, 0, 0, 0, 0, 0, 0, 0x00, 0x00];
+        scan.extend_from_slice(&[0xD6, 0xBE, 0x89, 0x8E, 0x03, 12, 7, 7, 7, 7, 7, 7, 1, 2, 3, 4, 5, 6, 0, 0, 0]);
+
+        let header = PcapHeader {
+            datalink: DataLink::BLUETOOTH_LE_LL_WITH_PHDR,
+            ..Default::default()
+        };
+        let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+        for (i, data) in [adv, scan].iter().enumerate() {
+            writer
+                .write_packet(&PcapPacket::new(Duration::from_micros(1_500_000 + i as u64), data.len() as u32, data))
+                .unwrap();
+        }
+        writer.into_writer()
+    }
+
+    #[test]
+    fn test_csv_default_columns() {
+        let mut out = Vec::new();
+        assert_eq!(write_csv(&capture(), &mut out, &ExportField::DEFAULT).unwrap(), 2);
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines[0],
+            "timestamp,channel,rssi,access_address,pdu_type,address,peer_address,name,company_id,uuids,crc_valid"
+        );
+        assert_eq!(lines[1], "1.500000,37,-60,0x8e89bed6,ADV_IND,06:05:04:03:02:01,,\"a,b\",0x004C,,true");
+        assert_eq!(lines[2], "1.500001,38,,0x8e89bed6,SCAN_REQ,06:05:04:03:02:01,07:07:07:07:07:07,,,,");
+    }
+
+    #[test]
+    fn test_jsonl_selected_fields() {
+        let fields = ExportField::parse_list(["index", "pdu_type", "company_id"]).unwrap();
+        let mut out = Vec::new();
+        write_jsonl(&capture(), &mut out, &fields).unwrap();
+        let rows: Vec<Value> = String::from_utf8(out).unwrap().lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(rows[0], json!({ "index": 0, "pdu_type": "ADV_IND", "company_id": ["0x004C"] }));
+        assert_eq!(rows[1]["pdu_type"], "SCAN_REQ");
+
+        let mut out = Vec::new();
+        write_json(&capture(), &mut out, &fields).unwrap();
+        let array: Vec<Value> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(array, rows);
+
+        assert!(ExportField::parse_list(["rssi", "bogus"]).is_err());
+    }
+}
@@ -0,0 +1,886 @@
+//! Feature-based device fingerprinting.
+//!
+//! [`extract_features`] collects, per advertising device of a capture, the
+//! features that tell device families apart:
+//!
+//! - AD structure order, flags and TX power level
+//! - Advertising interval and its jitter
+//! - Manufacturer data shape (company, length, leading bytes) and service UUIDs
+//! - `LL_VERSION_IND` and feature sets exchanged on its connections
+//! - Device Information (and GAP Device Name) values read over GATT
+//!
+//! A [`SignatureDatabase`] scores those features against known device
+//! signatures. A bundled database (`data/fingerprints.json`) ships with the
+//! crate; further JSON files in the same format add signatures or replace
+//! bundled ones with the same `id`. Every match lists the evidence for and
+//! against it, feature by feature.
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use crate::advertising::AdvertisingData;
+use crate::decode::{self, ADVERTISING_ACCESS_ADDRESS};
+use crate::error::{ProtocolError, Result};
+use crate::oui;
+use crate::reader::PacketReader;
+
+/// Bundled signature database.
+static BUNDLED_SIGNATURES: &str = include_str!("../data/fingerprints.json");
+
+/// Advertising PDUs closer together than this belong to one advertising event.
+const ADV_EVENT_GAP_SEC: f64 = 0.010;
+
+/// Matches below this confidence are not reported.
+const MIN_CONFIDENCE: f64 = 0.3;
+
+/// Share of its weight an unobserved feature counts against a signature.
+const UNOBSERVED_PENALTY: f64 = 0.25;
+
+/// Matched weight needed for full confidence; less evidence scales it down.
+const FULL_EVIDENCE_WEIGHT: f64 = 3.0;
+
+/// GATT characteristics whose values are collected, by name.
+const GATT_CHARACTERISTICS: [(u16, &str); 7] = [
+    (0x2A00, "device_name"),
+    (0x2A24, "model_number"),
+    (0x2A25, "serial_number"),
+    (0x2A26, "firmware_revision"),
+    (0x2A27, "hardware_revision"),
+    (0x2A28, "software_revision"),
+    (0x2A29, "manufacturer_name"),
+];
+
+/// Characteristic declaration attribute type.
+const UUID_CHARACTERISTIC: u16 = 0x2803;
+
+/// Layout of one manufacturer data block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManufacturerShape {
+    pub company_id: u16,
+    /// Data length after the company ID
+    pub length: usize,
+    /// Data after the company ID, hex
+    pub data: String,
+}
+
+/// `LL_VERSION_IND` parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct LlVersion {
+    pub version: u8,
+    pub company_id: u16,
+    pub subversion: u16,
+}
+
+/// Fingerprinting features of one device.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeviceFeatures {
+    pub address: String,
+    pub random_address: bool,
+    pub oui_vendor: Option<String>,
+    pub name: Option<String>,
+    pub packets: usize,
+    /// AD types of the first advertisement, in order
+    pub ad_types: Vec<u8>,
+    pub flags: Option<u8>,
+    pub tx_power: Option<i8>,
+    /// Median gap between advertising events
+    pub adv_interval_ms: Option<f64>,
+    /// Standard deviation of the gaps between advertising events
+    pub adv_jitter_ms: Option<f64>,
+    pub manufacturer: Vec<ManufacturerShape>,
+    /// 16-bit service UUIDs, advertised or carrying service data
+    pub service_uuids: BTreeSet<u16>,
+    /// Versions exchanged on the device's connections (either side)
+    pub ll_versions: Vec<LlVersion>,
+    /// Feature sets exchanged on the device's connections (either side)
+    pub ll_features: Vec<u64>,
+    /// GATT values read from the device, by characteristic name
+    pub gatt: BTreeMap<String, String>,
+    #[serde(skip)]
+    adv_times: Vec<f64>,
+}
+
+impl DeviceFeatures {
+    fn finish(&mut self) {
+        let mut events: Vec<f64> = Vec::new();
+        for &t in &self.adv_times {
+            if events.last().is_none_or(|last| t - last > ADV_EVENT_GAP_SEC) {
+                events.push(t);
+            }
+        }
+        let mut gaps: Vec<f64> = events.windows(2).map(|w| (w[1] - w[0]) * 1000.0).collect();
+        if gaps.is_empty() {
+            return;
+        }
+        let mean = gaps.iter().sum::<f64>() / gaps.len() as f64;
+        let variance = gaps.iter().map(|g| (g - mean).powi(2)).sum::<f64>() / gaps.len() as f64;
+        gaps.sort_by(|a, b| a.total_cmp(b));
+        self.adv_interval_ms = Some(round1(gaps[gaps.len() / 2]));
+        self.adv_jitter_ms = Some(round1(variance.sqrt()));
+    }
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn u16_le(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes([*data.get(offset)?, *data.get(offset + 1)?]))
+}
+
+/// ATT state of one connection, for following GATT reads.
+#[derive(Default)]
+struct Connection {
+    device: String,
+    /// Attribute type of the outstanding Read By Type Request
+    pending_type: Option<u16>,
+    /// Handle of the outstanding Read Request
+    pending_handle: Option<u16>,
+    /// Characteristic UUIDs by value handle, from discovered declarations
+    handles: HashMap<u16, u16>,
+}
+
+impl Connection {
+    fn att(&mut self, att: &[u8], device: &mut DeviceFeatures) {
+        let Some((&opcode, params)) = att.split_first() else {
+            return;
+        };
+        match opcode {
+            // Read By Type Request: start, end, 16-bit type
+            0x08 if params.len() == 6 => self.pending_type = u16_le(params, 4),
+            // Read By Type Response: entry length, then handle + value entries
+            0x09 if !params.is_empty() => {
+                let length = params[0] as usize;
+                if length < 2 {
+                    return;
+                }
+                for entry in params[1..].chunks_exact(length) {
+                    let value = &entry[2..];
+                    match self.pending_type {
+                        // Declaration: properties, value handle, 16-bit UUID
+                        Some(UUID_CHARACTERISTIC) if value.len() == 5 => {
+                            if let (Some(handle), Some(uuid)) = (u16_le(value, 1), u16_le(value, 3)) {
+                                self.handles.insert(handle, uuid);
+                            }
+                        }
+                        Some(uuid) => record_gatt(device, uuid, value),
+                        None => {}
+                    }
+                }
+            }
+            0x0A => self.pending_handle = u16_le(params, 0),
+            0x0B => {
+                if let Some(uuid) = self.pending_handle.take().and_then(|h| self.handles.get(&h)) {
+                    record_gatt(device, *uuid, params);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn record_gatt(device: &mut DeviceFeatures, uuid: u16, value: &[u8]) {
+    if let Some((_, name)) = GATT_CHARACTERISTICS.iter().find(|(u, _)| *u == uuid) {
+        let text = String::from_utf8_lossy(value).trim_end_matches('\0').trim().to_string();
+        if !text.is_empty() {
+            device.gatt.insert(name.to_string(), text);
+        }
+    }
+}
+
+/// Collect the fingerprinting features of every advertising device in a capture.
+pub fn extract_features(input: &[u8]) -> Result<Vec<DeviceFeatures>> {
+    let mut devices: BTreeMap<String, DeviceFeatures> = BTreeMap::new();
+    let mut connections: HashMap<u32, Connection> = HashMap::new();
+
+    for packet in PacketReader::new(input)? {
+        let packet = packet?;
+        let Some(ll) = decode::link_layer_pdu(packet.linktype, &packet.data) else {
+            continue;
+        };
+        let access_address = u32::from_le_bytes([ll[0], ll[1], ll[2], ll[3]]);
+        let (header, payload) = (ll[4], &ll[6..]);
+
+        if access_address != ADVERTISING_ACCESS_ADDRESS {
+            let Some(connection) = connections.get_mut(&access_address) else {
+                continue;
+            };
+            let Some(device) = devices.get_mut(&connection.device) else {
+                continue;
+            };
+            match header & 0x03 {
+                // LL control PDU
+                0x03 => match payload.split_first() {
+                    Some((0x0C, params)) if params.len() >= 5 => {
+                        let version = LlVersion {
+                            version: params[0],
+                            company_id: u16_le(params, 1).unwrap_or_default(),
+                            subversion: u16_le(params, 3).unwrap_or_default(),
+                        };
+                        if !device.ll_versions.contains(&version) {
+                            device.ll_versions.push(version);
+                        }
+                    }
+                    Some((0x08 | 0x09 | 0x0E, params)) if params.len() >= 8 => {
+                        let features = u64::from_le_bytes(params[..8].try_into().unwrap_or_default());
+                        if !device.ll_features.contains(&features) {
+                            device.ll_features.push(features);
+                        }
+                    }
+                    _ => {}
+                },
+                // Start of an L2CAP PDU on the ATT channel
+                0x02 if payload.len() > 4 && u16_le(payload, 2) == Some(0x0004) => {
+                    connection.att(&payload[4..], device);
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let pdu_type = header & 0x0F;
+        let tx_random = header & 0x40 != 0;
+        let timestamp = packet.timestamp.as_secs_f64();
+
+        match pdu_type {
+            // CONNECT_IND: InitA, AdvA, LLData (access address first)
+            0x05 if payload.len() >= 34 => {
+                let advertiser = decode::decode_packet(packet.linktype, &packet.data).address;
+                if let Some(address) = advertiser.filter(|a| devices.contains_key(a)) {
+                    let access_address = u32::from_le_bytes([payload[12], payload[13], payload[14], payload[15]]);
+                    connections.insert(
+                        access_address,
+                        Connection {
+                            device: address,
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+            // ADV_IND, ADV_NONCONN_IND, SCAN_RSP, ADV_SCAN_IND
+            0x00 | 0x02 | 0x04 | 0x06 => {
+                let Ok(mut advertising) = AdvertisingData::parse(payload) else {
+                    continue;
+                };
+                advertising.address_type = crate::AddressType::from_bit(tx_random);
+                let address = advertising.address_string();
+                let device = devices.entry(address.clone()).or_insert_with(|| DeviceFeatures {
+                    oui_vendor: oui::vendor_for(&address, tx_random),
+                    random_address: tx_random,
+                    address,
+                    ..Default::default()
+                });
+                device.packets += 1;
+
+                if pdu_type != 0x04 {
+                    device.adv_times.push(timestamp);
+                    if device.ad_types.is_empty() {
+                        device.ad_types = advertising.ad_types.clone();
+                    }
+                }
+                if advertising.name.is_some() {
+                    device.name = advertising.name.clone();
+                }
+                device.flags = advertising.flags.or(device.flags);
+                device.tx_power = advertising.tx_power.or(device.tx_power);
+                for (company_id, data) in &advertising.manufacturer_data {
+                    let shape = ManufacturerShape {
+                        company_id: *company_id,
+                        length: data.len(),
+                        data: hex(data),
+                    };
+                    if !device.manufacturer.contains(&shape) && device.manufacturer.len() < 8 {
+                        device.manufacturer.push(shape);
+                    }
+                }
+                device.service_uuids.extend(advertising.service_uuids_16.iter().copied());
+                device.service_uuids.extend(advertising.service_data.iter().map(|(uuid, _)| *uuid));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(devices
+        .into_values()
+        .map(|mut device| {
+            device.finish();
+            device
+        })
+        .collect())
+}
+
+/// An integer written in a signature file as a number or a `0x` hex string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hex(pub u64);
+
+impl Serialize for Hex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{:04X}", self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Hex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Number(n) => n.as_u64().map(Hex).ok_or_else(|| de::Error::custom("expected an unsigned integer")),
+            serde_json::Value::String(s) => {
+                let digits = s.trim_start_matches("0x").trim_start_matches("0X");
+                u64::from_str_radix(digits, 16)
+                    .map(Hex)
+                    .map_err(|_| de::Error::custom(format!("invalid hex value '{}'", s)))
+            }
+            _ => Err(de::Error::custom("expected a number or a hex string")),
+        }
+    }
+}
+
+/// A known device family and the features that identify it.
+///
+/// Every feature is optional; a signature is scored on the features it sets.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Signature {
+    /// Unique identifier; a later file replaces the signature with the same id
+    pub id: String,
+    pub manufacturer: String,
+    pub device_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Substrings of the advertised name, any of which matches (case-insensitive)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub name_contains: Vec<String>,
+    /// Substring of the OUI vendor of a public address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oui_vendor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub random_address: Option<bool>,
+    /// AD types of an advertisement, in order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ad_types: Option<Vec<Hex>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<Hex>,
+    /// Inclusive TX power range (dBm)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_power: Option<[i8; 2]>,
+    /// Inclusive advertising interval range (ms)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adv_interval_ms: Option<[f64; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_jitter_ms: Option<f64>,
+    /// Company ID of a manufacturer data block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_id: Option<Hex>,
+    /// Leading bytes (hex) of that block's data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer_prefix: Option<String>,
+    /// Data length of that block
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer_length: Option<usize>,
+    /// Service UUIDs that must all be present
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub service_uuids: Vec<Hex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ll_version: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ll_company_id: Option<Hex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ll_subversion: Option<Hex>,
+    /// Feature bits that must all be set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ll_features: Option<Hex>,
+    /// Substrings of GATT values by characteristic name (case-insensitive)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub gatt: BTreeMap<String, String>,
+}
+
+/// How one feature of a signature compared with the device.
+#[derive(Debug, Clone, Serialize)]
+pub struct Evidence {
+    pub feature: &'static str,
+    pub expected: String,
+    /// `None` when the capture does not show this feature
+    pub observed: Option<String>,
+    /// `None` when not observed
+    pub matched: Option<bool>,
+    pub weight: f64,
+}
+
+/// A ranked signature match.
+#[derive(Debug, Clone, Serialize)]
+pub struct FingerprintMatch {
+    pub signature: String,
+    pub manufacturer: String,
+    pub device_type: String,
+    pub os: Option<String>,
+    /// 0.0-1.0
+    pub confidence: f64,
+    pub evidence: Vec<Evidence>,
+}
+
+#[derive(Deserialize)]
+struct SignatureFile {
+    signatures: Vec<Signature>,
+}
+
+fn contains_ci(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+fn list<T: ToString>(items: impl IntoIterator<Item = T>) -> String {
+    items.into_iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+impl Signature {
+    /// Compare every feature this signature sets against `device`.
+    fn evidence(&self, device: &DeviceFeatures) -> Vec<Evidence> {
+        let mut out = Vec::new();
+        let mut check = |feature: &'static str, weight: f64, expected: String, observed: Option<(String, bool)>| {
+            out.push(Evidence {
+                feature,
+                expected,
+                matched: observed.as_ref().map(|(_, m)| *m),
+                observed: observed.map(|(o, _)| o),
+                weight,
+            });
+        };
+
+        if !self.name_contains.is_empty() {
+            let observed = device.name.as_ref().map(|name| {
+                (name.clone(), self.name_contains.iter().any(|n| contains_ci(name, n)))
+            });
+            check("name", 2.0, list(&self.name_contains), observed);
+        }
+        if let Some(vendor) = &self.oui_vendor {
+            let observed = device.oui_vendor.as_ref().map(|v| (v.clone(), contains_ci(v, vendor)));
+            check("oui_vendor", 1.5, vendor.clone(), observed);
+        }
+        if let Some(random) = self.random_address {
+            let observed = Some((device.random_address.to_string(), device.random_address == random));
+            check("random_address", 0.5, random.to_string(), observed);
+        }
+        if let Some(ad_types) = &self.ad_types {
+            let expected: Vec<u8> = ad_types.iter().map(|t| t.0 as u8).collect();
+            let observed = (!device.ad_types.is_empty()).then(|| {
+                (list(device.ad_types.iter().map(|t| format!("0x{:02X}", t))), device.ad_types == expected)
+            });
+            check("ad_types", 1.0, list(expected.iter().map(|t| format!("0x{:02X}", t))), observed);
+        }
+        if let Some(flags) = self.flags {
+            let observed = device.flags.map(|f| (format!("0x{:02X}", f), f as u64 == flags.0));
+            check("flags", 0.5, format!("0x{:02X}", flags.0), observed);
+        }
+        if let Some([min, max]) = self.tx_power {
+            let observed = device.tx_power.map(|p| (format!("{} dBm", p), (min..=max).contains(&p)));
+            check("tx_power", 0.5, format!("{}..{} dBm", min, max), observed);
+        }
+        if let Some([min, max]) = self.adv_interval_ms {
+            let observed = device.adv_interval_ms.map(|i| (format!("{} ms", i), i >= min && i <= max));
+            check("adv_interval", 1.0, format!("{}..{} ms", min, max), observed);
+        }
+        if let Some(max) = self.max_jitter_ms {
+            let observed = device.adv_jitter_ms.map(|j| (format!("{} ms", j), j <= max));
+            check("adv_jitter", 0.5, format!("<= {} ms", max), observed);
+        }
+        if let Some(company_id) = self.company_id {
+            let company_id = company_id.0 as u16;
+            let blocks: Vec<&ManufacturerShape> = device.manufacturer.iter().filter(|m| m.company_id == company_id).collect();
+            let observed = (!device.manufacturer.is_empty()).then(|| {
+                (list(device.manufacturer.iter().map(|m| format!("0x{:04X}", m.company_id))), !blocks.is_empty())
+            });
+            check("company_id", 1.5, format!("0x{:04X}", company_id), observed);
+
+            // Payload shape is only meaningful within the right company's block
+            if let Some(prefix) = &self.manufacturer_prefix {
+                let prefix = prefix.to_lowercase();
+                let observed = blocks.first().map(|_| {
+                    let hit = blocks.iter().find(|m| m.data.starts_with(&prefix)).unwrap_or(&blocks[0]);
+                    (hit.data.chars().take(prefix.len().max(8)).collect(), hit.data.starts_with(&prefix))
+                });
+                check("manufacturer_prefix", 2.0, prefix, observed);
+            }
+            if let Some(length) = self.manufacturer_length {
+                let observed = blocks.first().map(|_| {
+                    let lengths = list(blocks.iter().map(|m| m.length));
+                    (lengths, blocks.iter().any(|m| m.length == length))
+                });
+                check("manufacturer_length", 1.0, length.to_string(), observed);
+            }
+        }
+        if !self.service_uuids.is_empty() {
+            let observed = (!device.service_uuids.is_empty()).then(|| {
+                (
+                    list(device.service_uuids.iter().map(|u| format!("0x{:04X}", u))),
+                    self.service_uuids.iter().all(|u| device.service_uuids.contains(&(u.0 as u16))),
+                )
+            });
+            check("service_uuids", 1.5, list(self.service_uuids.iter().map(|u| format!("0x{:04X}", u.0))), observed);
+        }
+
+        let versions = || list(device.ll_versions.iter().map(|v| format!("{}/0x{:04X}/0x{:04X}", v.version, v.company_id, v.subversion)));
+        let ll_observed = |hit: bool| (!device.ll_versions.is_empty()).then(|| (versions(), hit));
+        if let Some(version) = self.ll_version {
+            check("ll_version", 1.0, version.to_string(), ll_observed(device.ll_versions.iter().any(|v| v.version == version)));
+        }
+        if let Some(company_id) = self.ll_company_id {
+            let hit = device.ll_versions.iter().any(|v| v.company_id as u64 == company_id.0);
+            check("ll_company_id", 2.0, format!("0x{:04X}", company_id.0), ll_observed(hit));
+        }
+        if let Some(subversion) = self.ll_subversion {
+            let hit = device.ll_versions.iter().any(|v| v.subversion as u64 == subversion.0);
+            check("ll_subversion", 2.5, format!("0x{:04X}", subversion.0), ll_observed(hit));
+        }
+        if let Some(features) = self.ll_features {
+            let observed = (!device.ll_features.is_empty()).then(|| {
+                (
+                    list(device.ll_features.iter().map(|f| format!("0x{:016X}", f))),
+                    device.ll_features.iter().any(|f| f & features.0 == features.0),
+                )
+            });
+            check("ll_features", 1.5, format!("0x{:016X}", features.0), observed);
+        }
+        for (characteristic, expected) in &self.gatt {
+            let observed = device.gatt.get(characteristic).map(|v| (v.clone(), contains_ci(v, expected)));
+            let feature = GATT_CHARACTERISTICS
+                .iter()
+                .find(|(_, name)| name == characteristic)
+                .map(|(_, name)| *name)
+                .unwrap_or("gatt");
+            check(feature, 3.0, expected.clone(), observed);
+        }
+        out
+    }
+
+    fn score(&self, device: &DeviceFeatures) -> Option<FingerprintMatch> {
+        let evidence = self.evidence(device);
+        let weight = |state: Option<bool>| evidence.iter().filter(|e| e.matched == state).map(|e| e.weight).sum::<f64>();
+        let (matched, mismatched, unobserved) = (weight(Some(true)), weight(Some(false)), weight(None));
+        if matched == 0.0 {
+            return None;
+        }
+        let confidence = matched / (matched + mismatched + UNOBSERVED_PENALTY * unobserved)
+            * (matched / FULL_EVIDENCE_WEIGHT).min(1.0);
+        Some(FingerprintMatch {
+            signature: self.id.clone(),
+            manufacturer: self.manufacturer.clone(),
+            device_type: self.device_type.clone(),
+            os: self.os.clone(),
+            confidence: (confidence * 100.0).round() / 100.0,
+            evidence,
+        })
+    }
+}
+
+/// A set of device signatures.
+#[derive(Debug, Clone, Default)]
+pub struct SignatureDatabase {
+    signatures: Vec<Signature>,
+}
+
+impl SignatureDatabase {
+    /// The signatures bundled with the crate.
+    pub fn bundled() -> Result<Self> {
+        let mut db = Self::default();
+        db.load_str(BUNDLED_SIGNATURES)?;
+        Ok(db)
+    }
+
+    /// Add the signatures of a JSON signature file (`{"signatures": [...]}`),
+    /// replacing those with the same id. Returns the number loaded.
+    pub fn load_str(&mut self, text: &str) -> Result<usize> {
+        let file: SignatureFile =
+            serde_json::from_str(text).map_err(|e| ProtocolError::Malformed(format!("Signature file: {}", e)))?;
+        for signature in &file.signatures {
+            if signature.id.is_empty() {
+                return Err(ProtocolError::Malformed("Signature without an id".to_string()));
+            }
+            if signature.manufacturer_prefix.as_ref().is_some_and(|p| p.len() % 2 != 0 || !p.chars().all(|c| c.is_ascii_hexdigit())) {
+                return Err(ProtocolError::Malformed(format!("Signature '{}': manufacturer_prefix must be hex bytes", signature.id)));
+            }
+            if (signature.manufacturer_prefix.is_some() || signature.manufacturer_length.is_some()) && signature.company_id.is_none() {
+                return Err(ProtocolError::Malformed(format!(
+                    "Signature '{}': manufacturer_prefix and manufacturer_length need company_id",
+                    signature.id
+                )));
+            }
+            if let Some(characteristic) = signature.gatt.keys().find(|k| !GATT_CHARACTERISTICS.iter().any(|(_, n)| n == k)) {
+                return Err(ProtocolError::Malformed(format!(
+                    "Signature '{}': unknown GATT characteristic '{}' (expected one of {})",
+                    signature.id,
+                    characteristic,
+                    list(GATT_CHARACTERISTICS.iter().map(|(_, n)| n))
+                )));
+            }
+        }
+
+        let count = file.signatures.len();
+        for signature in file.signatures {
+            match self.signatures.iter_mut().find(|s| s.id == signature.id) {
+                Some(existing) => *existing = signature,
+                None => self.signatures.push(signature),
+            }
+        }
+        Ok(count)
+    }
+
+    /// Add the signatures of a JSON signature file on disk.
+    pub fn load_file(&mut self, path: &Path) -> Result<usize> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ProtocolError::Malformed(format!("Cannot read {}: {}", path.display(), e)))?;
+        self.load_str(&text)
+    }
+
+    /// All signatures, in load order.
+    pub fn signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+
+    /// Up to `limit` matches for `device`, most confident first.
+    pub fn identify(&self, device: &DeviceFeatures, limit: usize) -> Vec<FingerprintMatch> {
+        let mut matches: Vec<FingerprintMatch> = self
+            .signatures
+            .iter()
+            .filter_map(|s| s.score(device))
+            .filter(|m| m.confidence >= MIN_CONFIDENCE)
+            .collect();
+        matches.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then_with(|| b.evidence.iter().filter(|e| e.matched == Some(true)).count().cmp(&a.evidence.iter().filter(|e| e.matched == Some(true)).count()))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pcap_file::pcap::{PcapHeader, PcapPacket, PcapWriter};
+    use pcap_file::DataLink;
+    use std::time::Duration;
+
+    const CONNECTION_AA: [u8; 4] = [0x11, 0x22, 0x33, 0x44];
+
+    fn ll(access_address: [u8; 4], header: u8, payload: &[u8]) -> Vec<u8> {
+        let mut packet = access_address.to_vec();
+        packet.extend_from_slice(&[header, payload.len() as u8]);
+        packet.extend_from_slice(payload);
+        packet.extend_from_slice(&[0, 0, 0]);
+        packet
+    }
+
+    fn adv() -> Vec<u8> {
+        // ADV_IND from C0:..:01: flags 0x06, TX power -8, Apple proximity-pairing data
+        let payload = [
+            1, 0, 0, 0, 0, 0xC0, 2, 0x01, 0x06, 2, 0x0A, 0xF8, 7, 0xFF, 0x4C, 0x00, 0x07, 0x19, 0x01, 0x0E,
+        ];
+        ll([0xD6, 0xBE, 0x89, 0x8E], 0x40, &payload)
+    }
+
+    fn capture() -> Vec<u8> {
+        let mut packets: Vec<(u64, Vec<u8>)> = Vec::new();
+        // Advertising events every 100 ms +/- a few ms, each on two channels
+        for (i, delay) in [0u64, 3, 1, 5, 2].iter().enumerate() {
+            let t = i as u64 * 100_000 + delay * 1000;
+            packets.push((t, adv()));
+            packets.push((t + 500, adv()));
+        }
+
+        // CONNECT_IND from an initiator to the device, then LL and ATT traffic
+        let mut connect = vec![9, 9, 9, 9, 9, 9, 1, 0, 0, 0, 0, 0xC0];
+        connect.extend_from_slice(&CONNECTION_AA);
+        connect.extend_from_slice(&[0; 18]);
+        let data = |header: u8, payload: &[u8]| ll(CONNECTION_AA, header, payload);
+        packets.push((500_000, ll([0xD6, 0xBE, 0x89, 0x8E], 0x45, &connect)));
+        packets.push((510_000, data(0x03, &[0x0C, 0x0B, 0x0F, 0x00, 0x34, 0x12])));
+        packets.push((520_000, data(0x03, &[0x09, 0x01, 0x01, 0, 0, 0, 0, 0, 0])));
+        // Characteristic discovery: value handle 0x0010 is Model Number String
+        packets.push((530_000, data(0x02, &[7, 0, 4, 0, 0x08, 0x01, 0x00, 0xFF, 0xFF, 0x03, 0x28])));
+        packets.push((540_000, data(0x02, &[9, 0, 4, 0, 0x09, 7, 0x0F, 0x00, 0x02, 0x10, 0x00, 0x24, 0x2A])));
+        packets.push((550_000, data(0x02, &[3, 0, 4, 0, 0x0A, 0x10, 0x00])));
+        packets.push((560_000, data(0x02, &[7, 0, 4, 0, 0x0B, b'A', b'2', b'0', b'8', b'4', 0])));
+        // Read By Type for Manufacturer Name String returns the value directly
+        packets.push((570_000, data(0x02, &[7, 0, 4, 0, 0x08, 0x01, 0x00, 0xFF, 0xFF, 0x29, 0x2A])));
+        packets.push((580_000, data(0x02, &[11, 0, 4, 0, 0x09, 9, 0x20, 0x00, b'A', b'p', b'p', b'l', b'e', b' ', b'I'])));
+        pcap(&packets)
+    }
+
+    fn pcap(packets: &[(u64, Vec<u8>)]) -> Vec<u8> {
+        let header = PcapHeader {
+            datalink: DataLink::BLUETOOTH_LE_LL,
+            ..Default::default()
+        };
+        let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+        for (micros, data) in packets {
+            writer.write_packet(&PcapPacket::new(Duration::from_micros(*micros), data.len() as u32, data)).unwrap();
+        }
+        writer.into_writer()
+    }
+
+    /// A random-address device advertising `name` and one manufacturer data block.
+    fn device(name: &str, company_id: u16, data: &str) -> DeviceFeatures {
+        DeviceFeatures {
+            address: "C0:00:00:00:00:01".to_string(),
+            random_address: true,
+            name: Some(name.to_string()),
+            ad_types: vec![0x01, 0x09, 0xFF],
+            flags: Some(0x06),
+            tx_power: Some(-8),
+            manufacturer: vec![ManufacturerShape {
+                company_id,
+                length: data.len() / 2,
+                data: data.to_string(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn database(signatures: &str) -> SignatureDatabase {
+        let mut db = SignatureDatabase::default();
+        db.load_str(&format!(r#"{{"signatures": [{}]}}"#, signatures)).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_extract_features() {
+        let devices = extract_features(&capture()).unwrap();
+        assert_eq!(devices.len(), 1);
+        let device = &devices[0];
+        assert_eq!(device.address, "C0:00:00:00:00:01");
+        assert!(device.random_address);
+        assert_eq!(device.ad_types, [0x01, 0x0A, 0xFF]);
+        assert_eq!((device.flags, device.tx_power), (Some(0x06), Some(-8)));
+        assert_eq!(device.manufacturer[0].company_id, 0x004C);
+        assert_eq!(device.manufacturer[0].data, "0719010e");
+        assert_eq!(device.adv_interval_ms, Some(103.0));
+        assert!(device.adv_jitter_ms.unwrap() > 1.0);
+        assert_eq!(device.ll_versions, [LlVersion { version: 0x0B, company_id: 0x000F, subversion: 0x1234 }]);
+        assert_eq!(device.ll_features, [0x0101]);
+        assert_eq!(device.gatt["model_number"], "A2084");
+        assert_eq!(device.gatt["manufacturer_name"], "Apple I");
+    }
+
+    #[test]
+    fn test_signature_matching() {
+        let device = &extract_features(&capture()).unwrap()[0];
+
+        let mut db = SignatureDatabase::bundled().unwrap();
+        let bundled = db.signatures().len();
+        let matches = db.identify(device, 3);
+        assert_eq!(matches[0].signature, "apple-airpods");
+        let prefix = matches[0].evidence.iter().find(|e| e.feature == "manufacturer_prefix").unwrap();
+        assert_eq!(prefix.matched, Some(true));
+
+        // A team signature replaces a bundled one and can use connection and GATT features
+        let added = db
+            .load_str(
+                r#"{"signatures": [{
+                    "id": "apple-airpods", "manufacturer": "Apple", "device_type": "AirPods Pro",
+                    "company_id": "0x004C", "manufacturer_prefix": "0719",
+                    "ll_company_id": 15, "ll_features": "0x0001",
+                    "gatt": {"model_number": "a2084"}, "adv_interval_ms": [20, 30]
+                }]}"#,
+            )
+            .unwrap();
+        assert_eq!((added, db.signatures().len()), (1, bundled));
+        let best = &db.identify(device, 1)[0];
+        assert_eq!(best.device_type, "AirPods Pro");
+        let interval = best.evidence.iter().find(|e| e.feature == "adv_interval").unwrap();
+        assert_eq!((interval.observed.as_deref(), interval.matched), (Some("103 ms"), Some(false)));
+        assert!(best.confidence < 1.0 && best.confidence > 0.8);
+
+        assert!(db.load_str(r#"{"signatures": [{"id": "x", "manufacturer_prefix": "07"}]}"#).is_err());
+        assert!(db.load_str(r#"{"signatures": [{"id": "x", "gatt": {"colour": "red"}}]}"#).is_err());
+        assert!(db.load_str(r#"{"signatures": [{"id": "x", "bogus": 1}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_ranking_and_confidence() {
+        let db = database(
+            r#"
+            {"id": "company", "company_id": "0x004C"},
+            {"id": "shape", "company_id": "0x004C", "manufacturer_prefix": "0719", "name_contains": ["pods"]},
+            {"id": "advertising", "random_address": true, "flags": 6, "tx_power": [-10, 0]},
+            {"id": "unobserved", "company_id": "0x004C", "ll_company_id": 15},
+            {"id": "too-little", "random_address": true}
+            "#,
+        );
+        let device = device("Bob's AirPods", 0x004C, "0719010e");
+        let matches = db.identify(&device, 10);
+        let ranked: Vec<(&str, f64)> = matches.iter().map(|m| (m.signature.as_str(), m.confidence)).collect();
+
+        // Full evidence is certain; 1.5 of 3.0 matched weight halves confidence, and
+        // equal confidence goes to the signature with more matching features
+        assert_eq!(ranked, [("shape", 1.0), ("advertising", 0.5), ("company", 0.5), ("unobserved", 0.38)]);
+        // An unobserved feature only counts a quarter of its weight against the match
+        let unobserved = matches.iter().find(|m| m.signature == "unobserved").unwrap();
+        let ll = unobserved.evidence.iter().find(|e| e.feature == "ll_company_id").unwrap();
+        assert_eq!((ll.observed.as_deref(), ll.matched), (None, None));
+
+        assert_eq!(db.identify(&device, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_conflicting_signals() {
+        let db = database(
+            r#"
+            {"id": "name-only", "name_contains": ["airpods"], "company_id": "0x0006", "manufacturer_prefix": "0719"},
+            {"id": "company-only", "name_contains": ["galaxy"], "company_id": "0x004C"},
+            {"id": "nothing", "name_contains": ["galaxy"], "company_id": "0x0075"}
+            "#,
+        );
+        let device = device("AirPods", 0x004C, "0719010e");
+
+        // The name matches but the company contradicts it: reported with the conflict as evidence
+        let matches = db.identify(&device, 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].signature.as_str(), matches[0].confidence), ("name-only", 0.33));
+        let company = matches[0].evidence.iter().find(|e| e.feature == "company_id").unwrap();
+        assert_eq!((company.observed.as_deref(), company.matched), (Some("0x004C"), Some(false)));
+        // The payload shape is not compared inside another company's block
+        let prefix = matches[0].evidence.iter().find(|e| e.feature == "manufacturer_prefix").unwrap();
+        assert_eq!(prefix.matched, None);
+
+        // A heavier mismatch drops the match below the reporting threshold
+        let company_only = db.signatures()[1].score(&device).unwrap();
+        assert!(company_only.confidence < MIN_CONFIDENCE);
+        // Nothing matched at all: no score
+        assert!(db.signatures()[2].score(&device).is_none());
+    }
+
+    #[test]
+    fn test_unknown_and_empty_devices() {
+        let db = SignatureDatabase::bundled().unwrap();
+
+        // No advertising data at all: only the address type can be compared
+        let empty = DeviceFeatures::default();
+        assert!(db.identify(&empty, 10).is_empty());
+        for signature in db.signatures() {
+            let observed = signature.evidence(&empty).iter().filter(|e| e.matched.is_some()).map(|e| e.feature).collect::<Vec<_>>();
+            assert!(observed.iter().all(|f| *f == "random_address"), "{}: {:?}", signature.id, observed);
+        }
+
+        // An unregistered company and an unknown name match nothing bundled
+        let unknown = device("Thermostat", 0xFFFF, "0102");
+        assert!(db.identify(&unknown, 10).is_empty());
+
+        // Captures with no devices, or advertisements without AD data, yield no features to match
+        assert!(extract_features(&pcap(&[])).unwrap().is_empty());
+        let bare = ll([0xD6, 0xBE, 0x89, 0x8E], 0x42, &[2, 0, 0, 0, 0, 0xC0]);
+        let devices = extract_features(&pcap(&[(0, bare.clone()), (100_000, bare)])).unwrap();
+        assert_eq!(devices.len(), 1);
+        assert!(devices[0].ad_types.is_empty() && devices[0].manufacturer.is_empty());
+        assert_eq!(devices[0].adv_interval_ms, Some(100.0));
+        assert!(db.identify(&devices[0], 10).is_empty());
+    }
+}
@@ -0,0 +1,643 @@
+//! Interference classification from spectrum recordings.
+//!
+//! Labels what is occupying the 2.4 GHz band in a [`SpectrumRecording`]:
+//!
+//! - 802.11 20 MHz and 40 MHz channels, mapped to Wi-Fi channel numbers
+//! - Narrowband continuous carriers (video senders, stuck transmitters)
+//! - Microwave ovens (wide, wandering bursts with a partial duty cycle)
+//! - Frequency-hopping activity (other Bluetooth piconets, proprietary FHSS)
+//!
+//! The report lists the Bluetooth channels each interferer covers so it can
+//! be cross-referenced with the AFH channel map of a connection.
+
+use crate::spectrum::{SpectrumRecording, NO_SAMPLE};
+use serde::{Deserialize, Serialize};
+
+/// Default RSSI above which a sample counts as activity.
+pub const DEFAULT_THRESHOLD_DBM: i8 = -80;
+
+/// Number of BR/EDR channels (2402 + k MHz).
+pub const BT_CHANNEL_COUNT: u8 = 79;
+
+/// Half width of the occupied part of a 20 MHz OFDM channel.
+const WIFI_HALF_WIDTH_MHZ: u16 = 8;
+
+/// Share of a Wi-Fi channel's bins that must be active for a sweep to count
+/// as a frame on that channel.
+const WIFI_FRAME_FILL: f64 = 0.6;
+
+/// Minimum share of sweeps an interferer must be seen in.
+const MIN_ACTIVITY: f64 = 0.05;
+
+/// Share of sweeps two adjacent Wi-Fi channels must be active together to be
+/// treated as one 40 MHz channel.
+const WIFI_40_JOINT: f64 = 0.8;
+
+/// Minimum occupancy of a narrowband carrier.
+const CARRIER_OCCUPANCY: f64 = 0.9;
+
+/// Maximum width of a narrowband carrier in MHz.
+const CARRIER_MAX_WIDTH: usize = 3;
+
+/// Band where microwave ovens radiate.
+const MICROWAVE_BAND_MHZ: (u16, u16) = (2440, 2480);
+
+/// Minimum width of a microwave oven burst in MHz.
+const MICROWAVE_MIN_WIDTH: usize = 8;
+
+/// Minimum spread of burst centres across sweeps (MHz, standard deviation).
+const MICROWAVE_MIN_DRIFT_MHZ: f64 = 1.5;
+
+/// Minimum number of distinct frequencies for frequency-hopping activity.
+const HOPPING_MIN_CHANNELS: usize = 10;
+
+/// Interferer type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterfererKind {
+    Wifi20,
+    Wifi40,
+    NarrowbandCarrier,
+    MicrowaveOven,
+    FrequencyHopping,
+}
+
+impl InterfererKind {
+    /// Machine-readable name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InterfererKind::Wifi20 => "wifi_20",
+            InterfererKind::Wifi40 => "wifi_40",
+            InterfererKind::NarrowbandCarrier => "narrowband_carrier",
+            InterfererKind::MicrowaveOven => "microwave_oven",
+            InterfererKind::FrequencyHopping => "frequency_hopping",
+        }
+    }
+}
+
+/// A classified interferer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interferer {
+    pub kind: InterfererKind,
+    /// Human-readable label, e.g. "Wi-Fi channel 6 (20 MHz)"
+    pub label: String,
+    pub low_freq_mhz: u16,
+    pub high_freq_mhz: u16,
+    pub center_freq_mhz: u16,
+    /// Wi-Fi channel numbers (one for 20 MHz, two for 40 MHz)
+    pub wifi_channels: Vec<u8>,
+    /// Percentage of sweeps the interferer was active in (duty cycle)
+    pub activity_percent: f64,
+    pub peak_dbm: Option<i8>,
+    /// 0.0 - 1.0
+    pub confidence: f64,
+    /// BR/EDR channels inside the interferer's span
+    pub bt_channels: Vec<u8>,
+}
+
+/// Labelled interference report for a recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterferenceReport {
+    pub threshold_dbm: i8,
+    pub sweep_count: usize,
+    pub interferers: Vec<Interferer>,
+    /// BR/EDR channels covered by a static interferer (everything except
+    /// frequency hoppers, which AFH does not avoid)
+    pub affected_bt_channels: Vec<u8>,
+}
+
+/// An AFH-avoided channel and the interferers that explain it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AvoidedChannel {
+    pub channel: u8,
+    pub interferers: Vec<String>,
+}
+
+/// Interference report lined up against an AFH channel map.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AfhCrossReference {
+    /// Avoided channels with a detected interferer
+    pub explained: Vec<AvoidedChannel>,
+    /// Avoided channels with no interferer in the recording
+    pub unexplained: Vec<u8>,
+    /// Channels covered by an interferer that the connection still uses
+    pub interfered_in_use: Vec<u8>,
+}
+
+impl InterferenceReport {
+    /// Cross-reference with the channels an AFH map avoids.
+    pub fn cross_reference(&self, channels_avoided: &[u8]) -> AfhCrossReference {
+        let mut explained = Vec::new();
+        let mut unexplained = Vec::new();
+
+        let mut avoided: Vec<u8> = channels_avoided.iter().copied().filter(|c| *c < BT_CHANNEL_COUNT).collect();
+        avoided.sort_unstable();
+        avoided.dedup();
+
+        for channel in &avoided {
+            let labels: Vec<String> = self
+                .interferers
+                .iter()
+                .filter(|i| i.kind != InterfererKind::FrequencyHopping && i.bt_channels.contains(channel))
+                .map(|i| i.label.clone())
+                .collect();
+            if labels.is_empty() {
+                unexplained.push(*channel);
+            } else {
+                explained.push(AvoidedChannel { channel: *channel, interferers: labels });
+            }
+        }
+
+        let interfered_in_use = self
+            .affected_bt_channels
+            .iter()
+            .copied()
+            .filter(|c| avoided.binary_search(c).is_err())
+            .collect();
+
+        AfhCrossReference { explained, unexplained, interfered_in_use }
+    }
+}
+
+/// Centre frequency of a 2.4 GHz Wi-Fi channel (1-14).
+pub fn wifi_channel_center(channel: u8) -> Option<u16> {
+    match channel {
+        1..=13 => Some(2407 + 5 * channel as u16),
+        14 => Some(2484),
+        _ => None,
+    }
+}
+
+/// BR/EDR channels between two frequencies (inclusive).
+pub fn bt_channels_in(low_freq_mhz: u16, high_freq_mhz: u16) -> Vec<u8> {
+    (0..BT_CHANNEL_COUNT)
+        .filter(|c| (low_freq_mhz..=high_freq_mhz).contains(&(2402 + *c as u16)))
+        .collect()
+}
+
+/// Channels marked as used in an AFH map such as `0x7fffffffffffffffff7f`.
+///
+/// The map is 10 bytes in over-the-air order: bit 0 of the first byte is
+/// channel 0, bit 6 of the last byte is channel 78.
+pub fn parse_afh_map(map: &str) -> Option<Vec<u8>> {
+    let hex = map.trim().trim_start_matches("0x").trim_start_matches("0X");
+    if hex.len() != 20 {
+        return None;
+    }
+
+    let mut used = Vec::new();
+    for byte_idx in 0..10 {
+        let byte = u8::from_str_radix(&hex[byte_idx * 2..byte_idx * 2 + 2], 16).ok()?;
+        for bit in 0..8 {
+            let channel = (byte_idx * 8 + bit) as u8;
+            if channel < BT_CHANNEL_COUNT && byte & (1 << bit) != 0 {
+                used.push(channel);
+            }
+        }
+    }
+    Some(used)
+}
+
+/// Classify the interference in a recording.
+pub fn classify(recording: &SpectrumRecording, threshold_dbm: i8) -> InterferenceReport {
+    let mut analysis = Analysis::new(recording, threshold_dbm);
+    let mut interferers = Vec::new();
+
+    // Carriers first so their bins don't distort the wideband detectors
+    interferers.extend(analysis.carriers());
+    interferers.extend(analysis.microwave());
+    interferers.extend(analysis.wifi());
+    interferers.extend(analysis.hopping());
+    interferers.sort_by_key(|i| i.low_freq_mhz);
+
+    let mut affected_bt_channels: Vec<u8> = interferers
+        .iter()
+        .filter(|i| i.kind != InterfererKind::FrequencyHopping)
+        .flat_map(|i| i.bt_channels.iter().copied())
+        .collect();
+    affected_bt_channels.sort_unstable();
+    affected_bt_channels.dedup();
+
+    InterferenceReport {
+        threshold_dbm,
+        sweep_count: recording.sweeps.len(),
+        interferers,
+        affected_bt_channels,
+    }
+}
+
+struct Analysis<'a> {
+    recording: &'a SpectrumRecording,
+    threshold_dbm: i8,
+    /// Bins already attributed to an interferer
+    claimed: Vec<bool>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(recording: &'a SpectrumRecording, threshold_dbm: i8) -> Self {
+        Self {
+            recording,
+            threshold_dbm,
+            claimed: vec![false; recording.channel_count()],
+        }
+    }
+
+    fn low(&self) -> u16 {
+        self.recording.header.low_freq_mhz
+    }
+
+    fn freq(&self, idx: usize) -> u16 {
+        self.low() + idx as u16
+    }
+
+    fn index(&self, freq: u16) -> Option<usize> {
+        let idx = freq.checked_sub(self.low())? as usize;
+        (idx < self.claimed.len()).then_some(idx)
+    }
+
+    fn active(&self, value: i8) -> bool {
+        value != NO_SAMPLE && value >= self.threshold_dbm
+    }
+
+    fn sweeps(&self) -> f64 {
+        self.recording.sweeps.len().max(1) as f64
+    }
+
+    fn claim(&mut self, low: u16, high: u16) {
+        for freq in low..=high {
+            if let Some(idx) = self.index(freq) {
+                self.claimed[idx] = true;
+            }
+        }
+    }
+
+    fn peak(&self, low: u16, high: u16) -> Option<i8> {
+        let (Some(first), Some(last)) = (self.index(low.max(self.low())), self.index(high)) else {
+            return None;
+        };
+        self.recording
+            .sweeps
+            .iter()
+            .flat_map(|s| s.rssi[first..=last].iter().copied())
+            .filter(|v| *v != NO_SAMPLE)
+            .max()
+    }
+
+    fn interferer(
+        &self,
+        kind: InterfererKind,
+        label: String,
+        (low, high): (u16, u16),
+        wifi_channels: Vec<u8>,
+        activity: f64,
+        confidence: f64,
+    ) -> Interferer {
+        Interferer {
+            kind,
+            label,
+            low_freq_mhz: low,
+            high_freq_mhz: high,
+            center_freq_mhz: (low + high) / 2,
+            wifi_channels,
+            activity_percent: round1(activity * 100.0),
+            peak_dbm: self.peak(low, high),
+            confidence: round2(confidence.clamp(0.0, 1.0)),
+            bt_channels: bt_channels_in(low, high),
+        }
+    }
+
+    /// Narrow runs of bins that are almost always above the threshold.
+    fn carriers(&mut self) -> Vec<Interferer> {
+        let occupancy: Vec<f64> = self
+            .recording
+            .occupancy(self.threshold_dbm)
+            .into_iter()
+            .map(|p| p / 100.0)
+            .collect();
+
+        let mut found = Vec::new();
+        let mut idx = 0;
+        while idx < occupancy.len() {
+            if occupancy[idx] < CARRIER_OCCUPANCY {
+                idx += 1;
+                continue;
+            }
+            let start = idx;
+            while idx < occupancy.len() && occupancy[idx] >= CARRIER_OCCUPANCY {
+                idx += 1;
+            }
+            let end = idx - 1;
+            if end - start + 1 > CARRIER_MAX_WIDTH {
+                continue;
+            }
+
+            let level = occupancy[start..=end].iter().sum::<f64>() / (end - start + 1) as f64;
+            let isolated = [start.checked_sub(1), Some(end + 1)]
+                .into_iter()
+                .flatten()
+                .filter_map(|i| occupancy.get(i))
+                .all(|o| *o < level / 2.0);
+            if !isolated {
+                continue;
+            }
+
+            let (low, high) = (self.freq(start), self.freq(end));
+            let label = if low == high {
+                format!("Narrowband carrier at {} MHz", low)
+            } else {
+                format!("Narrowband carrier at {}-{} MHz", low, high)
+            };
+            found.push(self.interferer(InterfererKind::NarrowbandCarrier, label, (low, high), Vec::new(), level, level));
+            self.claim(low, high);
+        }
+        found
+    }
+
+    /// Wide bursts in the upper band whose centre wanders from sweep to sweep
+    /// and that are only on part of the time.
+    fn microwave(&mut self) -> Option<Interferer> {
+        let first = self.index(MICROWAVE_BAND_MHZ.0.max(self.low()))?;
+        let last = self
+            .index(MICROWAVE_BAND_MHZ.1)
+            .unwrap_or(self.claimed.len() - 1);
+        if first > last {
+            return None;
+        }
+
+        let mut centres = Vec::new();
+        let mut span: Option<(usize, usize)> = None;
+        for sweep in &self.recording.sweeps {
+            // Widest contiguous burst in the band
+            let mut best: Option<(usize, usize)> = None;
+            let mut run_start = None;
+            for idx in first..=last + 1 {
+                let on = idx <= last && !self.claimed[idx] && self.active(sweep.rssi[idx]);
+                match (on, run_start) {
+                    (true, None) => run_start = Some(idx),
+                    (false, Some(start)) => {
+                        if best.is_none_or(|(s, e)| idx - start > e - s + 1) {
+                            best = Some((start, idx - 1));
+                        }
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Some((s, e)) = best.filter(|(s, e)| e - s + 1 >= MICROWAVE_MIN_WIDTH) {
+                centres.push((s + e) as f64 / 2.0);
+                span = Some(span.map_or((s, e), |(ls, le)| (ls.min(s), le.max(e))));
+            }
+        }
+
+        let duty = centres.len() as f64 / self.sweeps();
+        if centres.len() < 3 || !(0.2..=0.8).contains(&duty) {
+            return None;
+        }
+        let mean = centres.iter().sum::<f64>() / centres.len() as f64;
+        let drift = (centres.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / centres.len() as f64).sqrt();
+        if drift < MICROWAVE_MIN_DRIFT_MHZ {
+            return None;
+        }
+
+        let (s, e) = span?;
+        let (low, high) = (self.freq(s), self.freq(e));
+        self.claim(low, high);
+        Some(self.interferer(
+            InterfererKind::MicrowaveOven,
+            format!("Microwave oven ({:.0}% duty cycle)", duty * 100.0),
+            (low, high),
+            Vec::new(),
+            duty,
+            0.5 + drift / 10.0,
+        ))
+    }
+
+    /// Per-sweep frame flags and fill for a 20 MHz Wi-Fi channel.
+    fn wifi_frames(&self, channel: u8) -> Option<(Vec<bool>, f64)> {
+        let center = wifi_channel_center(channel)?;
+        let bins: Vec<usize> = (center - WIFI_HALF_WIDTH_MHZ..=center + WIFI_HALF_WIDTH_MHZ)
+            .filter_map(|f| self.index(f))
+            .filter(|i| !self.claimed[*i])
+            .collect();
+        // Need most of the channel inside the recording and not already
+        // attributed to something else
+        if bins.len() <= WIFI_HALF_WIDTH_MHZ as usize + 1 {
+            return None;
+        }
+
+        let mut fill_sum = 0.0;
+        let frames = self
+            .recording
+            .sweeps
+            .iter()
+            .map(|sweep| {
+                let sampled: Vec<i8> = bins.iter().map(|i| sweep.rssi[*i]).filter(|v| *v != NO_SAMPLE).collect();
+                if sampled.is_empty() {
+                    return false;
+                }
+                let fill = sampled.iter().filter(|v| self.active(**v)).count() as f64 / sampled.len() as f64;
+                let frame = fill >= WIFI_FRAME_FILL;
+                if frame {
+                    fill_sum += fill;
+                }
+                frame
+            })
+            .collect();
+        Some((frames, fill_sum / self.sweeps()))
+    }
+
+    /// 20/40 MHz 802.11 channels.
+    fn wifi(&mut self) -> Vec<Interferer> {
+        let mut candidates: Vec<(u8, Vec<bool>, f64)> = (1..=14u8)
+            .filter_map(|c| self.wifi_frames(c).map(|(frames, score)| (c, frames, score)))
+            .filter(|(_, frames, _)| frames.iter().filter(|f| **f).count() as f64 / self.sweeps() >= MIN_ACTIVITY)
+            .collect();
+        // Best fit first; ties go to the lower channel
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        // Overlapping channels (less than 20 MHz apart) can't both be in use
+        let mut accepted: Vec<(u8, Vec<bool>, f64)> = Vec::new();
+        for candidate in candidates {
+            if accepted.iter().all(|(c, _, _)| c.abs_diff(candidate.0) >= 4) {
+                accepted.push(candidate);
+            }
+        }
+        accepted.sort_by_key(|(c, _, _)| *c);
+
+        let mut found = Vec::new();
+        let mut idx = 0;
+        while idx < accepted.len() {
+            let (channel, frames, score) = &accepted[idx];
+            let activity = |f: &[bool]| f.iter().filter(|x| **x).count() as f64 / self.sweeps();
+            let confidence = |a: f64| if a > 0.0 { score / a } else { 0.0 };
+
+            // A channel 20 MHz up that is busy in the same sweeps is the
+            // secondary half of a 40 MHz channel
+            let pair = accepted.get(idx + 1).filter(|(next, next_frames, _)| {
+                let both = frames.iter().zip(next_frames).filter(|(a, b)| **a && **b).count();
+                let either = frames.iter().zip(next_frames).filter(|(a, b)| **a || **b).count();
+                *next == channel + 4 && either > 0 && both as f64 / either as f64 >= WIFI_40_JOINT
+            });
+
+            let center = wifi_channel_center(*channel).unwrap_or_default();
+            if let Some((next, next_frames, _)) = pair {
+                let merged: Vec<bool> = frames.iter().zip(next_frames).map(|(a, b)| *a || *b).collect();
+                let a = activity(&merged);
+                let span = (center - WIFI_HALF_WIDTH_MHZ, center + 20 + WIFI_HALF_WIDTH_MHZ);
+                found.push(self.interferer(
+                    InterfererKind::Wifi40,
+                    format!("Wi-Fi channels {}+{} (40 MHz)", channel, next),
+                    span,
+                    vec![*channel, *next],
+                    a,
+                    confidence(a),
+                ));
+                idx += 2;
+            } else {
+                let a = activity(frames);
+                let span = (center - WIFI_HALF_WIDTH_MHZ, center + WIFI_HALF_WIDTH_MHZ);
+                found.push(self.interferer(
+                    InterfererKind::Wifi20,
+                    format!("Wi-Fi channel {} (20 MHz)", channel),
+                    span,
+                    vec![*channel],
+                    a,
+                    confidence(a),
+                ));
+                idx += 1;
+            }
+        }
+
+        for interferer in &found {
+            self.claim(interferer.low_freq_mhz, interferer.high_freq_mhz);
+        }
+        found
+    }
+
+    /// Short narrow hits scattered across the band.
+    fn hopping(&mut self) -> Option<Interferer> {
+        let mut hits = vec![0usize; self.claimed.len()];
+        let mut hit_sweeps = 0;
+
+        for sweep in &self.recording.sweeps {
+            let mut any = false;
+            let mut idx = 0;
+            while idx < hits.len() {
+                if self.claimed[idx] || !self.active(sweep.rssi[idx]) {
+                    idx += 1;
+                    continue;
+                }
+                let start = idx;
+                while idx < hits.len() && !self.claimed[idx] && self.active(sweep.rssi[idx]) {
+                    idx += 1;
+                }
+                if idx - start <= 2 {
+                    hits[start] += 1;
+                    any = true;
+                }
+            }
+            if any {
+                hit_sweeps += 1;
+            }
+        }
+
+        let hit_bins: Vec<usize> = (0..hits.len()).filter(|i| hits[*i] > 0).collect();
+        let (first, last) = (*hit_bins.first()?, *hit_bins.last()?);
+        let activity = hit_sweeps as f64 / self.sweeps();
+        let max_bin = hits.iter().max().copied().unwrap_or(0) as f64 / self.sweeps();
+        if hit_bins.len() < HOPPING_MIN_CHANNELS || last - first < 20 || activity < MIN_ACTIVITY || max_bin >= 0.5 {
+            return None;
+        }
+
+        let (low, high) = (self.freq(first), self.freq(last));
+        Some(self.interferer(
+            InterfererKind::FrequencyHopping,
+            format!("Frequency hopping across {} channels", hit_bins.len()),
+            (low, high),
+            Vec::new(),
+            activity,
+            hit_bins.len() as f64 / (last - first + 1) as f64,
+        ))
+    }
+}
+
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(sweep: impl Fn(usize, u16) -> i8) -> SpectrumRecording {
+        let mut recording = SpectrumRecording::new(2402, 2480, 0, "test");
+        for i in 0..100 {
+            let rssi = recording.frequencies().into_iter().map(|f| sweep(i, f)).collect();
+            recording.push_sweep(i as u32 * 100, rssi).unwrap();
+        }
+        recording
+    }
+
+    #[test]
+    fn test_wifi40_carrier_and_microwave() {
+        let rec = recording(|i, f| {
+            let centre = 2450 + (i % 7) as u16;
+            if f == 2470 {
+                -50
+            } else if i % 3 == 0 && (centre - 6..=centre + 6).contains(&f) {
+                -45
+            } else if i % 2 == 0 && (2404..=2440).contains(&f) {
+                -60
+            } else {
+                -95
+            }
+        });
+
+        let report = classify(&rec, DEFAULT_THRESHOLD_DBM);
+        let kinds: Vec<_> = report.interferers.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![InterfererKind::Wifi40, InterfererKind::MicrowaveOven, InterfererKind::NarrowbandCarrier]
+        );
+
+        let wifi = &report.interferers[0];
+        assert_eq!(wifi.wifi_channels, vec![1, 5]);
+        assert_eq!(wifi.activity_percent, 50.0);
+        assert_eq!(report.interferers[2].low_freq_mhz, 2470);
+        assert!(report.affected_bt_channels.contains(&68));
+    }
+
+    #[test]
+    fn test_wifi20_hopping_and_afh() {
+        let rec = recording(|i, f| {
+            if (2454..=2470).contains(&f) || f == 2402 + ((i * 37) % 79) as u16 {
+                -60
+            } else {
+                -95
+            }
+        });
+
+        let report = classify(&rec, DEFAULT_THRESHOLD_DBM);
+        assert_eq!(report.interferers.len(), 2);
+        assert_eq!(report.interferers[0].kind, InterfererKind::FrequencyHopping);
+        assert_eq!(report.interferers[1].label, "Wi-Fi channel 11 (20 MHz)");
+        assert_eq!(report.affected_bt_channels, (52..=68).collect::<Vec<u8>>());
+
+        let mut avoided: Vec<u8> = (52..=68).collect();
+        avoided.push(5);
+        let xref = report.cross_reference(&avoided);
+        assert_eq!(xref.explained.len(), 17);
+        assert_eq!(xref.unexplained, vec![5]);
+        assert!(xref.interfered_in_use.is_empty());
+
+        let used = parse_afh_map("0xfeffffffffffffffff7f").unwrap();
+        assert_eq!(used.len(), 78);
+        assert!(!used.contains(&0) && used.contains(&78));
+        assert!(parse_afh_map("0x1234").is_none());
+    }
+}
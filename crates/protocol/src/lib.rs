@@ -0,0 +1,47 @@
+//! Pure-Rust Bluetooth protocol parsing shared by every backend.
+//!
+//! This crate has no USB or subprocess dependencies, so it can be used by the
+//! Python sidecar backend, the native Rust USB backend and the TUI alike.
+//!
+//! ## Modules
+//!
+//! - `advertising`: BLE advertising data (AD structure) parsing
+//! - `assigned_numbers`: Embedded Bluetooth SIG assigned-numbers database
+//! - `beacons`: Beacon and vendor-protocol decoders (iBeacon, Eddystone, Apple Continuity, ...)
+//! - `compat`: ubertooth-tools and firmware version detection and feature compatibility matrix
+//! - `compare`: Semantic comparison of two captures (devices, packets, timing)
+//! - `decode`: Capture packet decoding (BLE link layer, L2CAP, ATT, SMP, BR/EDR baseband)
+//! - `error`: Protocol parsing error types
+//! - `export`: Capture export to CSV and JSON Lines with selectable columns
+//! - `fingerprint`: Feature-based device fingerprinting against an extensible signature database
+//! - `interference`: Wi-Fi, carrier, microwave and frequency-hopping classification of spectrum data
+//! - `merge`: Time-ordered merging of captures with advertisement deduplication
+//! - `oui`: Offline IEEE OUI vendor lookup
+//! - `pcapng`: PCAPNG output with per-packet comments
+//! - `reader`: Format-independent PCAP/PCAPNG packet reading
+//! - `spectrum`: Spectrum waterfall recordings (sweep matrix format and summaries)
+//! - `tool_output`: Streaming parsers for ubertooth-* command-line output (packets, AFH maps, sweeps)
+//! - `trackers`: Unwanted-tracker classification (AirTag, SmartTag, Tile, ...)
+
+pub mod advertising;
+pub mod assigned_numbers;
+pub mod beacons;
+pub mod compat;
+pub mod compare;
+pub mod decode;
+pub mod error;
+pub mod export;
+pub mod fingerprint;
+pub mod interference;
+pub mod merge;
+pub mod oui;
+pub mod pcapng;
+pub mod reader;
+pub mod spectrum;
+pub mod tool_output;
+pub mod trackers;
+
+pub use advertising::{AddressType, AdvertisingData};
+pub use beacons::Beacon;
+pub use decode::{DecodedPacket, ProtocolNode};
+pub use error::{ProtocolError, Result};
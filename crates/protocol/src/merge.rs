@@ -0,0 +1,340 @@
+//! Time-ordered merging of captures.
+//!
+//! Sources may mix PCAP and PCAPNG and use different link types. When every
+//! packet shares one link type the result is a plain PCAP file; otherwise it
+//! is a PCAPNG file with one interface per link type. Timestamps are written
+//! with microsecond resolution, like the captures the Ubertooth tools write.
+//!
+//! Optionally, copies of the same advertising PDU received within a short
+//! window (one advertising event sent on channels 37, 38 and 39) are reduced
+//! to the first copy.
+
+use pcap_file::pcap::{PcapHeader, PcapPacket, PcapWriter};
+use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::PcapNgWriter;
+use pcap_file::DataLink;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::io::Write;
+use std::iter::Peekable;
+use std::time::Duration;
+
+use crate::decode::advertising_pdu;
+use crate::error::{ProtocolError, Result};
+use crate::reader::{malformed, PacketReader, RawPacket};
+
+/// Default window within which repeated advertising PDUs count as duplicates.
+pub const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_millis(10);
+
+/// Snapshot length written to merged files unless a packet is larger.
+const DEFAULT_SNAPLEN: u32 = 65535;
+
+/// How captures are merged.
+#[derive(Debug, Clone)]
+pub struct MergeOptions {
+    /// Interleave packets by timestamp; otherwise sources are concatenated in order
+    pub sort_by_timestamp: bool,
+
+    /// Drop copies of an advertising PDU seen again within this window
+    pub dedup_window: Option<Duration>,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        Self {
+            sort_by_timestamp: true,
+            dedup_window: None,
+        }
+    }
+}
+
+/// Packet counts of one merge source.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SourceCount {
+    pub packets_read: usize,
+    pub packets_written: usize,
+    pub duplicates_removed: usize,
+    pub linktypes: Vec<u32>,
+}
+
+/// Result of a merge.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeReport {
+    /// Per-source counts, in input order
+    pub sources: Vec<SourceCount>,
+
+    /// Packets written to the merged file
+    pub total_packets: usize,
+
+    /// Advertising copies dropped by deduplication
+    pub duplicates_removed: usize,
+
+    /// Link types in the merged file (PCAPNG interface order)
+    pub linktypes: Vec<u32>,
+
+    /// Whether the output is PCAPNG (several link types) rather than PCAP
+    pub pcapng: bool,
+}
+
+impl MergeReport {
+    /// File extension matching the output format.
+    pub fn extension(&self) -> &'static str {
+        if self.pcapng {
+            "pcapng"
+        } else {
+            "pcap"
+        }
+    }
+}
+
+enum Output<W: Write> {
+    Pcap(PcapWriter<W>),
+    PcapNg(PcapNgWriter<W>),
+}
+
+impl<W: Write> Output<W> {
+    fn write(&mut self, interface_id: u32, packet: &RawPacket) -> Result<()> {
+        match self {
+            Output::Pcap(writer) => writer
+                .write_packet(&PcapPacket::new(packet.timestamp, packet.original_len, &packet.data))
+                .map(|_| ()),
+            Output::PcapNg(writer) => {
+                // pcap-file writes the Duration's nanoseconds as the raw timestamp,
+                // so pass microseconds to match if_tsresol = 6
+                let micros = packet.timestamp.as_micros() as u64;
+                writer
+                    .write_pcapng_block(EnhancedPacketBlock {
+                        interface_id,
+                        timestamp: Duration::from_nanos(micros),
+                        original_len: packet.original_len,
+                        data: packet.data.clone(),
+                        options: Vec::new(),
+                    })
+                    .map(|_| ())
+            }
+        }
+        .map_err(malformed)
+    }
+}
+
+/// Merge `inputs` into `output`.
+///
+/// Fails before writing anything if an input cannot be read.
+pub fn merge<W: Write>(inputs: &[&[u8]], output: W, options: &MergeOptions) -> Result<MergeReport> {
+    if inputs.is_empty() {
+        return Err(ProtocolError::Malformed("No captures to merge".to_string()));
+    }
+
+    // First pass: validate every input and collect link types and sizes
+    let mut sources = vec![SourceCount::default(); inputs.len()];
+    let mut linktypes: Vec<u32> = Vec::new();
+    let mut snaplen = DEFAULT_SNAPLEN;
+    for (input, source) in inputs.iter().zip(sources.iter_mut()) {
+        for packet in PacketReader::new(input)? {
+            let packet = packet?;
+            if !source.linktypes.contains(&packet.linktype) {
+                source.linktypes.push(packet.linktype);
+            }
+            if !linktypes.contains(&packet.linktype) {
+                linktypes.push(packet.linktype);
+            }
+            snaplen = snaplen.max(packet.data.len() as u32);
+        }
+    }
+
+    let pcapng = linktypes.len() > 1;
+    let mut writer = if pcapng {
+        let mut writer = PcapNgWriter::new(output).map_err(malformed)?;
+        for linktype in &linktypes {
+            writer
+                .write_pcapng_block(InterfaceDescriptionBlock {
+                    linktype: DataLink::from(*linktype),
+                    snaplen,
+                    options: vec![InterfaceDescriptionOption::IfTsResol(6)],
+                })
+                .map_err(malformed)?;
+        }
+        Output::PcapNg(writer)
+    } else {
+        let header = PcapHeader {
+            datalink: DataLink::from(linktypes.first().copied().unwrap_or(256)),
+            snaplen,
+            ..Default::default()
+        };
+        Output::Pcap(PcapWriter::with_header(output, header).map_err(malformed)?)
+    };
+
+    let mut readers: Vec<Peekable<PacketReader>> = inputs
+        .iter()
+        .map(|input| PacketReader::new(input).map(Iterator::peekable))
+        .collect::<Result<_>>()?;
+
+    // Pending sources keyed by the timestamp of their next packet
+    let mut heap = BinaryHeap::new();
+    let mut concat_source = 0;
+    if options.sort_by_timestamp {
+        for (index, reader) in readers.iter_mut().enumerate() {
+            if let Some(Ok(packet)) = reader.peek() {
+                heap.push(Reverse((packet.timestamp, index)));
+            }
+        }
+    }
+
+    let mut seen: HashMap<Vec<u8>, Duration> = HashMap::new();
+    let mut report = MergeReport {
+        sources: Vec::new(),
+        total_packets: 0,
+        duplicates_removed: 0,
+        linktypes: linktypes.clone(),
+        pcapng,
+    };
+
+    loop {
+        let index = if options.sort_by_timestamp {
+            match heap.pop() {
+                Some(Reverse((_, index))) => index,
+                None => break,
+            }
+        } else {
+            while concat_source < readers.len() && readers[concat_source].peek().is_none() {
+                concat_source += 1;
+            }
+            if concat_source == readers.len() {
+                break;
+            }
+            concat_source
+        };
+
+        let packet = match readers[index].next() {
+            Some(packet) => packet?,
+            None => continue,
+        };
+        if options.sort_by_timestamp {
+            if let Some(Ok(next)) = readers[index].peek() {
+                heap.push(Reverse((next.timestamp, index)));
+            }
+        }
+
+        let source = &mut sources[index];
+        source.packets_read += 1;
+
+        if let Some(window) = options.dedup_window {
+            if let Some(pdu) = advertising_pdu(packet.linktype, &packet.data) {
+                let duplicate = seen
+                    .get(pdu)
+                    .is_some_and(|kept| packet.timestamp.abs_diff(*kept) <= window);
+                if duplicate {
+                    source.duplicates_removed += 1;
+                    report.duplicates_removed += 1;
+                    continue;
+                }
+                if seen.len() > 4096 && options.sort_by_timestamp {
+                    seen.retain(|_, kept| packet.timestamp.saturating_sub(*kept) <= window);
+                }
+                seen.insert(pdu.to_vec(), packet.timestamp);
+            }
+        }
+
+        let interface_id = linktypes.iter().position(|l| *l == packet.linktype).unwrap_or(0) as u32;
+        writer.write(interface_id, &packet)?;
+        source.packets_written += 1;
+        report.total_packets += 1;
+    }
+
+    report.sources = sources;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::linktype;
+    use pcap_file::pcap::PcapWriter;
+
+    fn adv(channel: u8, tag: u8) -> Vec<u8> {
+        // PHDR + ADV_NONCONN_IND with a 6-byte AdvA and one byte of data, then CRC
+        let mut data = vec![channel, 0xC4, 0, 0, 0, 0, 0, 0, 0x03, 0x00];
+        data.extend_from_slice(&[0xD6, 0xBE, 0x89, 0x8E, 0x02, 7, 1, 2, 3, 4, 5, 6, tag, 0xAA, 0xBB, 0xCC]);
+        data
+    }
+
+    fn pcap(linktype: DataLink, packets: &[(u64, Vec<u8>)]) -> Vec<u8> {
+        let header = PcapHeader {
+            datalink: linktype,
+            ..Default::default()
+        };
+        let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+        for (micros, data) in packets {
+            writer
+                .write_packet(&PcapPacket::new(Duration::from_micros(*micros), data.len() as u32, data))
+                .unwrap();
+        }
+        writer.into_writer()
+    }
+
+    fn read(merged: &[u8]) -> Vec<(Duration, u32)> {
+        PacketReader::new(merged)
+            .unwrap()
+            .map(|p| p.map(|p| (p.timestamp, p.linktype)))
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_merge_orders_and_deduplicates() {
+        // The same advertisement on channels 37 and 38, 1 ms apart, then a new one
+        let first = pcap(DataLink::BLUETOOTH_LE_LL_WITH_PHDR, &[(1_000, adv(0, 1)), (100_000, adv(0, 2))]);
+        let second = pcap(DataLink::BLUETOOTH_LE_LL_WITH_PHDR, &[(2_000, adv(12, 1)), (50_000, adv(12, 9))]);
+
+        let mut out = Vec::new();
+        let report = merge(&[&first, &second], &mut out, &MergeOptions::default()).unwrap();
+        assert!(!report.pcapng);
+        assert_eq!(report.total_packets, 4);
+        let times: Vec<u128> = read(&out).iter().map(|(t, _)| t.as_micros()).collect();
+        assert_eq!(times, [1_000, 2_000, 50_000, 100_000]);
+
+        let options = MergeOptions {
+            sort_by_timestamp: true,
+            dedup_window: Some(DEFAULT_DEDUP_WINDOW),
+        };
+        let mut out = Vec::new();
+        let report = merge(&[&first, &second], &mut out, &options).unwrap();
+        assert_eq!(report.total_packets, 3);
+        assert_eq!(report.duplicates_removed, 1);
+        assert_eq!((report.sources[1].packets_read, report.sources[1].packets_written), (2, 1));
+        assert_eq!(read(&out).len(), 3);
+    }
+
+    #[test]
+    fn test_merge_mixed_linktypes_as_pcapng() {
+        let ble = pcap(DataLink::BLUETOOTH_LE_LL_WITH_PHDR, &[(5, adv(0, 1)), (20, adv(0, 2))]);
+        let bredr = pcap(DataLink::BLUETOOTH_BREDR_BB, &[(10, vec![0; 22])]);
+        let mut ble_ng = Vec::new();
+        crate::pcapng::write_with_comments(&ble, &mut ble_ng, &Default::default()).unwrap();
+
+        let options = MergeOptions {
+            sort_by_timestamp: false,
+            dedup_window: None,
+        };
+        let mut out = Vec::new();
+        let report = merge(&[&ble_ng, &bredr], &mut out, &options).unwrap();
+        assert!(report.pcapng);
+        assert_eq!(report.extension(), "pcapng");
+        assert_eq!(report.linktypes, [linktype::BLUETOOTH_LE_LL_WITH_PHDR, linktype::BLUETOOTH_BREDR_BB]);
+
+        // Concatenated in source order, microsecond timestamps preserved
+        let packets = read(&out);
+        assert_eq!(
+            packets,
+            [
+                (Duration::from_micros(5), linktype::BLUETOOTH_LE_LL_WITH_PHDR),
+                (Duration::from_micros(20), linktype::BLUETOOTH_LE_LL_WITH_PHDR),
+                (Duration::from_micros(10), linktype::BLUETOOTH_BREDR_BB),
+            ]
+        );
+
+        assert!(merge(&[b"junk"], Vec::new(), &options).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! Offline IEEE OUI vendor lookup.
+//!
+//! A gzip-compressed copy of the IEEE MA-L, MA-M and MA-S registries is
+//! bundled with the crate (`data/oui.csv.gz`, regenerated with
+//! `scripts/update-oui.py`). Lookups use the longest matching prefix, so
+//! 36-bit MA-S and 28-bit MA-M blocks win over the 24-bit MA-L block they
+//! were carved from.
+//!
+//! A newer registry can be loaded from local IEEE CSV files (`oui.csv`,
+//! `mam.csv`, `oui36.csv`, optionally gzipped) and installed with
+//! [`set_database`].
+
+use crate::error::{ProtocolError, Result};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Bundled registry (Registry,Assignment,Organization Name).
+static BUNDLED_REGISTRY: &[u8] = include_bytes!("../data/oui.csv.gz");
+
+static DATABASE: OnceLock<RwLock<Arc<OuiDatabase>>> = OnceLock::new();
+
+/// IEEE registry an assignment comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Registry {
+    /// 24-bit MAC Address Block Large
+    MaL,
+    /// 28-bit MAC Address Block Medium
+    MaM,
+    /// 36-bit MAC Address Block Small
+    MaS,
+}
+
+impl Registry {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "MA-L" => Some(Registry::MaL),
+            "MA-M" => Some(Registry::MaM),
+            "MA-S" => Some(Registry::MaS),
+            _ => None,
+        }
+    }
+
+    /// Prefix length in bits.
+    pub fn prefix_bits(&self) -> u32 {
+        match self {
+            Registry::MaL => 24,
+            Registry::MaM => 28,
+            Registry::MaS => 36,
+        }
+    }
+
+    /// IEEE registry name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Registry::MaL => "MA-L",
+            Registry::MaM => "MA-M",
+            Registry::MaS => "MA-S",
+        }
+    }
+}
+
+/// Result of a vendor lookup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OuiMatch {
+    pub vendor: String,
+    pub registry: Registry,
+    /// Matched prefix as hex digits (6, 7 or 9 digits)
+    pub prefix: String,
+}
+
+/// In-memory OUI database with one sorted table per prefix length.
+#[derive(Debug, Default, Clone)]
+pub struct OuiDatabase {
+    names: Vec<String>,
+    ma_l: Vec<(u64, u32)>,
+    ma_m: Vec<(u64, u32)>,
+    ma_s: Vec<(u64, u32)>,
+}
+
+impl OuiDatabase {
+    /// Load the registry bundled with the crate.
+    pub fn bundled() -> Result<Self> {
+        let mut db = Self::default();
+        db.load_csv(GzDecoder::new(BUNDLED_REGISTRY))?;
+        Ok(db)
+    }
+
+    /// Load a local IEEE registry CSV file (plain or `.gz`).
+    pub fn load_file(&mut self, path: &Path) -> Result<usize> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| ProtocolError::Malformed(format!("{}: {}", path.display(), e)))?;
+
+        if path.extension().is_some_and(|ext| ext == "gz") {
+            self.load_csv(GzDecoder::new(file))
+        } else {
+            self.load_csv(file)
+        }
+    }
+
+    /// Load IEEE registry CSV rows (`Registry,Assignment,Organization Name,...`).
+    ///
+    /// Entries override existing assignments with the same prefix. Returns the
+    /// number of assignments read.
+    pub fn load_csv<R: Read>(&mut self, reader: R) -> Result<usize> {
+        let mut name_index: HashMap<String, u32> = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (name.clone(), idx as u32))
+            .collect();
+        let mut count = 0;
+
+        for (line_no, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|e| ProtocolError::Malformed(format!("line {}: {}", line_no + 1, e)))?;
+            let fields = split_csv_line(&line);
+            if fields.len() < 3 {
+                continue;
+            }
+
+            // Skips the header row and registries we don't index (CID, IAB)
+            let Some(registry) = Registry::parse(fields[0].trim()) else {
+                continue;
+            };
+
+            let assignment = fields[1].trim();
+            let digits = (registry.prefix_bits() / 4) as usize;
+            let prefix = match u64::from_str_radix(assignment, 16) {
+                Ok(prefix) if assignment.len() == digits => prefix,
+                _ => {
+                    return Err(ProtocolError::Malformed(format!(
+                        "line {}: invalid {} assignment '{}'",
+                        line_no + 1,
+                        registry.as_str(),
+                        assignment
+                    )))
+                }
+            };
+
+            let vendor = fields[2].trim();
+            let name_idx = match name_index.get(vendor) {
+                Some(idx) => *idx,
+                None => {
+                    self.names.push(vendor.to_string());
+                    let idx = (self.names.len() - 1) as u32;
+                    name_index.insert(vendor.to_string(), idx);
+                    idx
+                }
+            };
+
+            self.table_mut(registry).push((prefix, name_idx));
+            count += 1;
+        }
+
+        // Sort for binary search; for duplicate prefixes the latest entry wins
+        for table in [&mut self.ma_l, &mut self.ma_m, &mut self.ma_s] {
+            table.reverse();
+            table.sort_by_key(|(prefix, _)| *prefix);
+            table.dedup_by_key(|(prefix, _)| *prefix);
+        }
+
+        Ok(count)
+    }
+
+    fn table_mut(&mut self, registry: Registry) -> &mut Vec<(u64, u32)> {
+        match registry {
+            Registry::MaL => &mut self.ma_l,
+            Registry::MaM => &mut self.ma_m,
+            Registry::MaS => &mut self.ma_s,
+        }
+    }
+
+    fn table(&self, registry: Registry) -> &[(u64, u32)] {
+        match registry {
+            Registry::MaL => &self.ma_l,
+            Registry::MaM => &self.ma_m,
+            Registry::MaS => &self.ma_s,
+        }
+    }
+
+    /// Look up the vendor for an address in display order (MSB first).
+    pub fn lookup(&self, addr: &[u8; 6]) -> Option<OuiMatch> {
+        let value = addr.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+
+        // Longest prefix first
+        for registry in [Registry::MaS, Registry::MaM, Registry::MaL] {
+            let bits = registry.prefix_bits();
+            let prefix = value >> (48 - bits);
+            let table = self.table(registry);
+            if let Ok(idx) = table.binary_search_by_key(&prefix, |(p, _)| *p) {
+                return Some(OuiMatch {
+                    vendor: self.names[table[idx].1 as usize].clone(),
+                    registry,
+                    prefix: format!("{:0width$X}", prefix, width = (bits / 4) as usize),
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Look up the vendor for a "XX:XX:XX:XX:XX:XX" address string.
+    pub fn lookup_mac(&self, mac: &str) -> Option<OuiMatch> {
+        self.lookup(&parse_mac(mac)?)
+    }
+
+    /// Number of assignments per registry (MA-L, MA-M, MA-S).
+    pub fn counts(&self) -> (usize, usize, usize) {
+        (self.ma_l.len(), self.ma_m.len(), self.ma_s.len())
+    }
+
+    /// Total number of assignments.
+    pub fn len(&self) -> usize {
+        self.ma_l.len() + self.ma_m.len() + self.ma_s.len()
+    }
+
+    /// Check whether the database has no assignments.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn global() -> &'static RwLock<Arc<OuiDatabase>> {
+    DATABASE.get_or_init(|| {
+        let db = OuiDatabase::bundled().unwrap_or_else(|e| {
+            tracing::warn!("Failed to load bundled OUI registry: {}", e);
+            OuiDatabase::default()
+        });
+        RwLock::new(Arc::new(db))
+    })
+}
+
+/// Current process-wide OUI database (bundled unless replaced).
+pub fn database() -> Arc<OuiDatabase> {
+    global().read().map(|db| db.clone()).unwrap_or_default()
+}
+
+/// Replace the process-wide OUI database.
+pub fn set_database(db: OuiDatabase) {
+    if let Ok(mut current) = global().write() {
+        *current = Arc::new(db);
+    }
+}
+
+/// Vendor name for a public address string.
+///
+/// Randomized addresses carry no OUI, so callers pass `random = true` to
+/// skip the lookup.
+pub fn vendor_for(mac: &str, random: bool) -> Option<String> {
+    if random {
+        return None;
+    }
+    database().lookup_mac(mac).map(|m| m.vendor)
+}
+
+/// Parse "XX:XX:XX:XX:XX:XX" (or '-' separated) into display-order bytes.
+pub fn parse_mac(mac: &str) -> Option<[u8; 6]> {
+    let mut addr = [0u8; 6];
+    let mut parts = mac.trim().split([':', '-']);
+
+    for byte in addr.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(addr)
+}
+
+/// Split one CSV line, honouring double-quoted fields.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_lookup() {
+        let csv = "Registry,Assignment,Organization Name,Organization Address\n\
+                   MA-L,001122,\"Example, Inc.\",\"1 Road\"\n\
+                   MA-L,70B3D5,IEEE Registration Authority,\n\
+                   MA-M,70B3D51,Medium Block Co,\n\
+                   MA-S,70B3D5123,Small Block Co,\n";
+
+        let mut db = OuiDatabase::default();
+        assert_eq!(db.load_csv(csv.as_bytes()).unwrap(), 4);
+
+        assert_eq!(db.lookup_mac("00:11:22:33:44:55").unwrap().vendor, "Example, Inc.");
+
+        let small = db.lookup_mac("70:B3:D5:12:34:56").unwrap();
+        assert_eq!(small.vendor, "Small Block Co");
+        assert_eq!(small.registry, Registry::MaS);
+        assert_eq!(small.prefix, "70B3D5123");
+
+        assert_eq!(db.lookup_mac("70:B3:D5:1F:00:00").unwrap().registry, Registry::MaM);
+        assert_eq!(db.lookup_mac("70:B3:D5:F0:00:00").unwrap().registry, Registry::MaL);
+        assert!(db.lookup_mac("AA:BB:CC:DD:EE:FF").is_none());
+        assert!(db.load_csv("MA-L,XYZ,Bad,\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_bundled_registry() {
+        let db = OuiDatabase::bundled().unwrap();
+        let (ma_l, ma_m, ma_s) = db.counts();
+
+        assert!(ma_l > 30_000 && ma_m > 1_000 && ma_s > 1_000);
+        assert!(vendor_for("00:1B:63:00:00:00", false).unwrap().contains("Apple"));
+        assert_eq!(vendor_for("00:1B:63:00:00:00", true), None);
+    }
+}
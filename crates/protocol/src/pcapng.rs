@@ -0,0 +1,208 @@
+//! PCAPNG output with per-packet comments.
+//!
+//! Analyst notes are stored as `opt_comment` options on the Enhanced Packet
+//! Blocks they belong to, which Wireshark shows as packet comments
+//! (`frame.comment`). Packets are addressed by their 0-based position in
+//! the capture, the same index `bt_decode` reports.
+//!
+//! Input may be legacy PCAP or PCAPNG. PCAP input is converted with
+//! nanosecond timestamps (`if_tsresol = 9`); PCAPNG input keeps its
+//! section, interface and other blocks as they are.
+
+use pcap_file::pcap::PcapReader;
+use pcap_file::pcapng::blocks::enhanced_packet::{EnhancedPacketBlock, EnhancedPacketOption};
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::{Block, PcapNgReader, PcapNgWriter};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::error::Result;
+use crate::reader::malformed;
+
+/// First four bytes of a PCAPNG file (Section Header Block type).
+pub const PCAPNG_MAGIC: [u8; 4] = [0x0A, 0x0D, 0x0D, 0x0A];
+
+/// Whether `data` is a PCAPNG file rather than legacy PCAP.
+pub fn is_pcapng(data: &[u8]) -> bool {
+    data.starts_with(&PCAPNG_MAGIC)
+}
+
+/// Write `input` (PCAP or PCAPNG) to `output` as PCAPNG, attaching
+/// `comments` (packet index -> note) to the matching packets.
+///
+/// Returns the number of comments written; notes for packets beyond the
+/// end of the capture are dropped.
+pub fn write_with_comments<W: Write>(input: &[u8], output: W, comments: &BTreeMap<usize, String>) -> Result<usize> {
+    if is_pcapng(input) {
+        rewrite_pcapng(input, output, comments)
+    } else {
+        convert_pcap(input, output, comments)
+    }
+}
+
+/// Comments stored on the packets of a PCAPNG file, by packet index.
+///
+/// Several comments on one packet are joined with newlines. Legacy PCAP has
+/// no comments, so the result is empty.
+pub fn read_comments(input: &[u8]) -> Result<BTreeMap<usize, String>> {
+    let mut comments = BTreeMap::new();
+    if !is_pcapng(input) {
+        return Ok(comments);
+    }
+
+    let mut reader = PcapNgReader::new(input).map_err(malformed)?;
+    let mut index = 0;
+    while let Some(block) = reader.next_block() {
+        match block.map_err(malformed)? {
+            Block::EnhancedPacket(packet) => {
+                let notes: Vec<&str> = packet
+                    .options
+                    .iter()
+                    .filter_map(|o| match o {
+                        EnhancedPacketOption::Comment(c) => Some(c.as_ref()),
+                        _ => None,
+                    })
+                    .collect();
+                if !notes.is_empty() {
+                    comments.insert(index, notes.join("\n"));
+                }
+                index += 1;
+            }
+            Block::SimplePacket(_) | Block::Packet(_) => index += 1,
+            _ => {}
+        }
+    }
+
+    Ok(comments)
+}
+
+fn convert_pcap<W: Write>(input: &[u8], output: W, comments: &BTreeMap<usize, String>) -> Result<usize> {
+    let mut reader = PcapReader::new(input).map_err(malformed)?;
+    let header = reader.header();
+
+    let mut writer = PcapNgWriter::new(output).map_err(malformed)?;
+    writer
+        .write_pcapng_block(InterfaceDescriptionBlock {
+            linktype: header.datalink,
+            snaplen: header.snaplen,
+            // EPB timestamps are written in nanoseconds
+            options: vec![InterfaceDescriptionOption::IfTsResol(9)],
+        })
+        .map_err(malformed)?;
+
+    let mut written = 0;
+    let mut index = 0;
+    while let Some(packet) = reader.next_packet() {
+        let packet = packet.map_err(malformed)?;
+        let mut options = Vec::new();
+        if let Some(note) = comments.get(&index) {
+            options.push(EnhancedPacketOption::Comment(Cow::Borrowed(note.as_str())));
+            written += 1;
+        }
+        writer
+            .write_pcapng_block(EnhancedPacketBlock {
+                interface_id: 0,
+                timestamp: packet.timestamp,
+                original_len: packet.orig_len,
+                data: packet.data,
+                options,
+            })
+            .map_err(malformed)?;
+        index += 1;
+    }
+
+    Ok(written)
+}
+
+fn rewrite_pcapng<W: Write>(input: &[u8], output: W, comments: &BTreeMap<usize, String>) -> Result<usize> {
+    let mut reader = PcapNgReader::new(input).map_err(malformed)?;
+    let mut writer = PcapNgWriter::with_section_header(output, reader.section().clone()).map_err(malformed)?;
+
+    let mut written = 0;
+    let mut index = 0;
+    while let Some(block) = reader.next_block() {
+        match block.map_err(malformed)? {
+            Block::EnhancedPacket(mut packet) => {
+                if let Some(note) = comments.get(&index) {
+                    let present = packet
+                        .options
+                        .iter()
+                        .any(|o| matches!(o, EnhancedPacketOption::Comment(c) if c.as_ref() == note.as_str()));
+                    if !present {
+                        packet.options.push(EnhancedPacketOption::Comment(Cow::Borrowed(note.as_str())));
+                    }
+                    written += 1;
+                }
+                writer.write_block(&Block::EnhancedPacket(packet)).map_err(malformed)?;
+                index += 1;
+            }
+            other => {
+                if matches!(other, Block::SimplePacket(_) | Block::Packet(_)) {
+                    index += 1;
+                }
+                writer.write_block(&other).map_err(malformed)?;
+            }
+        }
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pcap_file::pcap::{PcapHeader, PcapPacket, PcapWriter};
+    use pcap_file::DataLink;
+    use std::time::Duration;
+
+    fn pcap(packets: usize) -> Vec<u8> {
+        let header = PcapHeader {
+            datalink: DataLink::BLUETOOTH_LE_LL_WITH_PHDR,
+            ..Default::default()
+        };
+        let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+        for i in 0..packets {
+            let data = [0xD6, 0xBE, 0x89, 0x8E, i as u8];
+            writer
+                .write_packet(&PcapPacket::new(Duration::from_micros(1_700_000_000_000_000 + i as u64), 5, &data))
+                .unwrap();
+        }
+        writer.into_writer()
+    }
+
+    #[test]
+    fn test_pcap_to_commented_pcapng() {
+        let comments = BTreeMap::from([(1, "CONNECT_IND from the badge reader".to_string()), (9, "past the end".to_string())]);
+
+        let mut out = Vec::new();
+        assert_eq!(write_with_comments(&pcap(3), &mut out, &comments).unwrap(), 1);
+        assert!(is_pcapng(&out));
+        assert_eq!(read_comments(&out).unwrap(), BTreeMap::from([(1, comments[&1].clone())]));
+
+        // Timestamps survive the conversion
+        let mut reader = PcapNgReader::new(&out[..]).unwrap();
+        let mut timestamps = Vec::new();
+        while let Some(block) = reader.next_block() {
+            if let Block::EnhancedPacket(p) = block.unwrap() {
+                timestamps.push(p.timestamp);
+            }
+        }
+        assert_eq!(timestamps[2], Duration::from_micros(1_700_000_000_000_002));
+    }
+
+    #[test]
+    fn test_pcapng_comments_merge() {
+        let mut first = Vec::new();
+        write_with_comments(&pcap(2), &mut first, &BTreeMap::from([(0, "adv".to_string())])).unwrap();
+
+        // Re-annotating keeps existing comments and doesn't duplicate them
+        let mut second = Vec::new();
+        let comments = BTreeMap::from([(0, "adv".to_string()), (1, "scan response".to_string())]);
+        assert_eq!(write_with_comments(&first, &mut second, &comments).unwrap(), 2);
+        assert_eq!(read_comments(&second).unwrap(), comments);
+
+        assert!(read_comments(&pcap(1)).unwrap().is_empty());
+        assert!(write_with_comments(b"junk", Vec::new(), &comments).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Format-independent packet reading for PCAP and PCAPNG captures.
+//!
+//! [`PacketReader`] walks a capture held in memory and yields every packet
+//! with its link type and an absolute timestamp, whatever the file format,
+//! byte order or PCAPNG timestamp resolution (`if_tsresol`). Packet data is
+//! borrowed from the input.
+
+use pcap_file::pcap::PcapParser;
+use pcap_file::pcapng::blocks::interface_description::{InterfaceDescriptionBlock, InterfaceDescriptionOption};
+use pcap_file::pcapng::{Block, PcapNgParser};
+use std::borrow::Cow;
+use std::time::Duration;
+
+use crate::error::{ProtocolError, Result};
+use crate::pcapng::is_pcapng;
+
+/// One packet of a capture.
+#[derive(Debug, Clone)]
+pub struct RawPacket<'a> {
+    /// Capture time since the Unix epoch (zero for PCAPNG simple packets)
+    pub timestamp: Duration,
+
+    /// PCAP link type of the packet's interface
+    pub linktype: u32,
+
+    /// Length of the packet on the wire
+    pub original_len: u32,
+
+    /// Captured bytes
+    pub data: Cow<'a, [u8]>,
+}
+
+enum Format {
+    Pcap { parser: PcapParser, linktype: u32 },
+    PcapNg(Box<PcapNgParser>),
+}
+
+/// Iterator over the packets of a PCAP or PCAPNG capture.
+///
+/// Stops after the first error, which is yielded once.
+pub struct PacketReader<'a> {
+    rest: &'a [u8],
+    format: Format,
+}
+
+impl<'a> PacketReader<'a> {
+    /// Read the file header of `input`.
+    pub fn new(input: &'a [u8]) -> Result<Self> {
+        let (rest, format) = if is_pcapng(input) {
+            let (rest, parser) = PcapNgParser::new(input).map_err(malformed)?;
+            (rest, Format::PcapNg(Box::new(parser)))
+        } else {
+            let (rest, parser) = PcapParser::new(input).map_err(malformed)?;
+            let linktype = u32::from(parser.header().datalink);
+            (rest, Format::Pcap { parser, linktype })
+        };
+        Ok(Self { rest, format })
+    }
+
+    /// Link type from the file header (PCAP only; PCAPNG declares it per interface).
+    pub fn linktype(&self) -> Option<u32> {
+        match &self.format {
+            Format::Pcap { linktype, .. } => Some(*linktype),
+            Format::PcapNg(_) => None,
+        }
+    }
+
+    fn next_packet(&mut self) -> Result<Option<RawPacket<'a>>> {
+        while !self.rest.is_empty() {
+            match &mut self.format {
+                Format::Pcap { parser, linktype } => {
+                    let (rest, packet) = parser.next_packet(self.rest).map_err(malformed)?;
+                    self.rest = rest;
+                    return Ok(Some(RawPacket {
+                        timestamp: packet.timestamp,
+                        linktype: *linktype,
+                        original_len: packet.orig_len,
+                        data: packet.data,
+                    }));
+                }
+                Format::PcapNg(parser) => {
+                    let (rest, block) = parser.next_block(self.rest).map_err(malformed)?;
+                    self.rest = rest;
+                    match block {
+                        Block::EnhancedPacket(epb) => {
+                            let interface = parser.packet_interface(&epb).ok_or_else(|| {
+                                ProtocolError::Malformed(format!("Packet on undeclared interface {}", epb.interface_id))
+                            })?;
+                            return Ok(Some(RawPacket {
+                                // pcap-file reads the raw EPB timestamp as nanoseconds
+                                timestamp: scale_timestamp(epb.timestamp.as_nanos(), ts_resolution(interface)),
+                                linktype: u32::from(interface.linktype),
+                                original_len: epb.original_len,
+                                data: epb.data,
+                            }));
+                        }
+                        Block::SimplePacket(spb) => {
+                            let interface = parser.interfaces().first().ok_or_else(|| {
+                                ProtocolError::Malformed("Packet before interface description".to_string())
+                            })?;
+                            return Ok(Some(RawPacket {
+                                timestamp: Duration::ZERO,
+                                linktype: u32::from(interface.linktype),
+                                original_len: spb.original_len,
+                                data: spb.data,
+                            }));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for PacketReader<'a> {
+    type Item = Result<RawPacket<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.next_packet();
+        if next.is_err() {
+            self.rest = &[];
+        }
+        next.transpose()
+    }
+}
+
+/// `if_tsresol` of an interface (default 6, microseconds).
+fn ts_resolution(interface: &InterfaceDescriptionBlock) -> u8 {
+    interface
+        .options
+        .iter()
+        .find_map(|o| match o {
+            InterfaceDescriptionOption::IfTsResol(r) => Some(*r),
+            _ => None,
+        })
+        .unwrap_or(6)
+}
+
+/// Convert a raw timestamp in `if_tsresol` units to a duration.
+fn scale_timestamp(raw: u128, resolution: u8) -> Duration {
+    let nanos = if resolution & 0x80 != 0 {
+        (raw * 1_000_000_000) >> (resolution & 0x7F)
+    } else if resolution <= 9 {
+        raw * 10u128.pow(9 - resolution as u32)
+    } else {
+        raw / 10u128.pow(resolution as u32 - 9)
+    };
+    Duration::from_nanos(nanos.min(u64::MAX as u128) as u64)
+}
+
+pub(crate) fn malformed(e: pcap_file::PcapError) -> ProtocolError {
+    ProtocolError::Malformed(format!("PCAP: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pcap_file::pcap::{PcapHeader, PcapPacket, PcapWriter};
+    use pcap_file::pcapng::blocks::enhanced_packet::EnhancedPacketBlock;
+    use pcap_file::pcapng::PcapNgWriter;
+    use pcap_file::DataLink;
+
+    #[test]
+    fn test_read_pcap() {
+        let header = PcapHeader {
+            datalink: DataLink::BLUETOOTH_LE_LL,
+            ..Default::default()
+        };
+        let mut writer = PcapWriter::with_header(Vec::new(), header).unwrap();
+        for i in 0..3u8 {
+            writer.write_packet(&PcapPacket::new(Duration::from_micros(10 + i as u64), 8, &[i; 4])).unwrap();
+        }
+        let pcap = writer.into_writer();
+
+        let packets: Vec<_> = PacketReader::new(&pcap).unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[2].timestamp, Duration::from_micros(12));
+        assert_eq!((packets[2].linktype, packets[2].original_len), (251, 8));
+        assert_eq!(&packets[2].data[..], &[2; 4]);
+
+        // A truncated file yields the packets before the damage, then one error
+        let results: Vec<_> = PacketReader::new(&pcap[..pcap.len() - 2]).unwrap().collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn test_read_pcapng_resolutions() {
+        let mut writer = PcapNgWriter::new(Vec::new()).unwrap();
+        for (linktype, resolution) in [(DataLink::BLUETOOTH_LE_LL_WITH_PHDR, None), (DataLink::BLUETOOTH_BREDR_BB, Some(9))] {
+            writer
+                .write_pcapng_block(InterfaceDescriptionBlock {
+                    linktype,
+                    snaplen: 0,
+                    options: resolution.map(InterfaceDescriptionOption::IfTsResol).into_iter().collect(),
+                })
+                .unwrap();
+        }
+        // Raw timestamp 1_500_000 is 1.5 s in microseconds and 1.5 ms in nanoseconds
+        for interface_id in 0..2 {
+            writer
+                .write_pcapng_block(EnhancedPacketBlock {
+                    interface_id,
+                    timestamp: Duration::from_nanos(1_500_000),
+                    original_len: 1,
+                    data: Cow::Borrowed(&[0]),
+                    options: vec![],
+                })
+                .unwrap();
+        }
+        let pcapng = writer.into_inner();
+
+        let packets: Vec<_> = PacketReader::new(&pcapng).unwrap().collect::<Result<_>>().unwrap();
+        assert_eq!(packets[0].timestamp, Duration::from_millis(1500));
+        assert_eq!(packets[0].linktype, 256);
+        assert_eq!(packets[1].timestamp, Duration::from_micros(1500));
+        assert_eq!(packets[1].linktype, 255);
+    }
+}
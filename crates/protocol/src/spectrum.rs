@@ -0,0 +1,448 @@
+//! Spectrum waterfall recordings.
+//!
+//! A recording is a time-indexed sweep matrix: one row per sweep, one RSSI
+//! column per MHz between `low_freq_mhz` and `high_freq_mhz` inclusive.
+//!
+//! ## File format (`.specan`)
+//!
+//! ```text
+//! magic        8 bytes  "UBSPEC\0\x01"
+//! header_len   u32 LE
+//! header       JSON (SpectrumHeader)
+//! rows         repeated: offset_ms u32 LE, then one i8 RSSI per channel
+//! ```
+//!
+//! Channels that were not sampled in a sweep hold [`NO_SAMPLE`].
+
+use crate::error::{ProtocolError, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// File magic and format version.
+pub const MAGIC: &[u8; 8] = b"UBSPEC\x00\x01";
+
+/// Current header version.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// RSSI value stored for channels without a sample.
+pub const NO_SAMPLE: i8 = i8::MIN;
+
+/// Recording metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpectrumHeader {
+    pub version: u32,
+    pub low_freq_mhz: u16,
+    pub high_freq_mhz: u16,
+    /// Recording start (Unix time, milliseconds)
+    pub start_unix_ms: u64,
+    /// Producer, e.g. "ubertooth-specan" or "rust-usb"
+    pub source: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// One sweep across the recorded band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep {
+    /// Time since recording start in milliseconds
+    pub offset_ms: u32,
+    /// One RSSI value (dBm) per channel, [`NO_SAMPLE`] if missing
+    pub rssi: Vec<i8>,
+}
+
+/// Per-minute average levels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MinuteAverage {
+    /// Minute index since recording start
+    pub minute: u32,
+    pub sweep_count: usize,
+    /// Average RSSI over the whole band
+    pub band_avg_dbm: Option<f64>,
+    /// Average RSSI per channel
+    pub channel_avg_dbm: Vec<Option<f64>>,
+}
+
+/// A spectrum waterfall recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpectrumRecording {
+    pub header: SpectrumHeader,
+    pub sweeps: Vec<Sweep>,
+}
+
+impl SpectrumRecording {
+    /// Create an empty recording for `low_freq_mhz..=high_freq_mhz`.
+    pub fn new(low_freq_mhz: u16, high_freq_mhz: u16, start_unix_ms: u64, source: &str) -> Self {
+        Self {
+            header: SpectrumHeader {
+                version: FORMAT_VERSION,
+                low_freq_mhz,
+                high_freq_mhz,
+                start_unix_ms,
+                source: source.to_string(),
+                description: String::new(),
+            },
+            sweeps: Vec::new(),
+        }
+    }
+
+    /// Number of channels (columns) per sweep.
+    pub fn channel_count(&self) -> usize {
+        (self.header.high_freq_mhz.saturating_sub(self.header.low_freq_mhz) + 1) as usize
+    }
+
+    /// Frequency in MHz of each column.
+    pub fn frequencies(&self) -> Vec<u16> {
+        (self.header.low_freq_mhz..=self.header.high_freq_mhz).collect()
+    }
+
+    /// Time covered by the recording in milliseconds.
+    pub fn duration_ms(&self) -> u32 {
+        self.sweeps.last().map(|s| s.offset_ms).unwrap_or(0)
+    }
+
+    /// Append a sweep; the row must have one value per channel.
+    pub fn push_sweep(&mut self, offset_ms: u32, rssi: Vec<i8>) -> Result<()> {
+        if rssi.len() != self.channel_count() {
+            return Err(ProtocolError::Malformed(format!(
+                "sweep has {} values, expected {}",
+                rssi.len(),
+                self.channel_count()
+            )));
+        }
+        self.sweeps.push(Sweep { offset_ms, rssi });
+        Ok(())
+    }
+
+    /// Serialize the recording.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<()> {
+        let header = serde_json::to_vec(&self.header).map_err(|e| ProtocolError::Malformed(e.to_string()))?;
+        let io_err = |e: std::io::Error| ProtocolError::Malformed(e.to_string());
+
+        writer.write_all(MAGIC).map_err(io_err)?;
+        writer.write_all(&(header.len() as u32).to_le_bytes()).map_err(io_err)?;
+        writer.write_all(&header).map_err(io_err)?;
+
+        for sweep in &self.sweeps {
+            writer.write_all(&sweep.offset_ms.to_le_bytes()).map_err(io_err)?;
+            let row: Vec<u8> = sweep.rssi.iter().map(|v| *v as u8).collect();
+            writer.write_all(&row).map_err(io_err)?;
+        }
+
+        writer.flush().map_err(io_err)
+    }
+
+    /// Deserialize a recording.
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self> {
+        let mut data = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .map_err(|e| ProtocolError::Malformed(e.to_string()))?;
+
+        if data.len() < 12 || &data[0..8] != MAGIC {
+            return Err(ProtocolError::Malformed("not a spectrum recording".to_string()));
+        }
+
+        let header_len = u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize;
+        let header_bytes = data
+            .get(12..12 + header_len)
+            .ok_or_else(|| ProtocolError::Truncated("spectrum recording header".to_string()))?;
+        let header: SpectrumHeader =
+            serde_json::from_slice(header_bytes).map_err(|e| ProtocolError::Malformed(e.to_string()))?;
+
+        if header.high_freq_mhz < header.low_freq_mhz {
+            return Err(ProtocolError::Malformed("invalid frequency range".to_string()));
+        }
+
+        let mut recording = Self { header, sweeps: Vec::new() };
+        let row_len = 4 + recording.channel_count();
+        let rows = &data[12 + header_len..];
+        if rows.len() % row_len != 0 {
+            return Err(ProtocolError::Truncated("spectrum recording rows".to_string()));
+        }
+
+        for row in rows.chunks_exact(row_len) {
+            recording.sweeps.push(Sweep {
+                offset_ms: u32::from_le_bytes([row[0], row[1], row[2], row[3]]),
+                rssi: row[4..].iter().map(|v| *v as i8).collect(),
+            });
+        }
+
+        Ok(recording)
+    }
+
+    /// Write the recording to a file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(|e| ProtocolError::Malformed(e.to_string()))?;
+        self.write_to(std::io::BufWriter::new(file))
+    }
+
+    /// Read a recording from a file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path).map_err(|e| ProtocolError::Malformed(e.to_string()))?;
+        Self::read_from(std::io::BufReader::new(file))
+    }
+
+    /// Sub-recording for a time window (ms from start) and frequency range.
+    ///
+    /// Offsets in the slice stay relative to the original start so slices
+    /// can be lined up against the full recording.
+    pub fn slice(
+        &self,
+        start_ms: Option<u32>,
+        end_ms: Option<u32>,
+        low_freq_mhz: Option<u16>,
+        high_freq_mhz: Option<u16>,
+    ) -> Result<Self> {
+        let low = low_freq_mhz.unwrap_or(self.header.low_freq_mhz).max(self.header.low_freq_mhz);
+        let high = high_freq_mhz.unwrap_or(self.header.high_freq_mhz).min(self.header.high_freq_mhz);
+        if low > high {
+            return Err(ProtocolError::Malformed(format!(
+                "frequency range {}-{} MHz is outside the recording",
+                low, high
+            )));
+        }
+
+        let first = (low - self.header.low_freq_mhz) as usize;
+        let last = (high - self.header.low_freq_mhz) as usize;
+        let start = start_ms.unwrap_or(0);
+        let end = end_ms.unwrap_or(u32::MAX);
+
+        let mut header = self.header.clone();
+        header.low_freq_mhz = low;
+        header.high_freq_mhz = high;
+
+        Ok(Self {
+            header,
+            sweeps: self
+                .sweeps
+                .iter()
+                .filter(|s| s.offset_ms >= start && s.offset_ms <= end)
+                .map(|s| Sweep { offset_ms: s.offset_ms, rssi: s.rssi[first..=last].to_vec() })
+                .collect(),
+        })
+    }
+
+    /// Maximum RSSI seen per channel.
+    pub fn peak_hold(&self) -> Vec<Option<i8>> {
+        let mut peak = vec![None; self.channel_count()];
+        for sweep in &self.sweeps {
+            for (slot, value) in peak.iter_mut().zip(&sweep.rssi) {
+                if *value != NO_SAMPLE && slot.is_none_or(|p| *value > p) {
+                    *slot = Some(*value);
+                }
+            }
+        }
+        peak
+    }
+
+    /// Average RSSI per channel.
+    pub fn average(&self) -> Vec<Option<f64>> {
+        channel_averages(self.channel_count(), self.sweeps.iter())
+    }
+
+    /// Noise floor per channel: the given percentile (0-100) of the samples.
+    ///
+    /// A low percentile skips the bursts of real traffic and leaves the
+    /// level the channel sits at when nothing is transmitting.
+    pub fn noise_floor(&self, percentile: f64) -> Vec<Option<i8>> {
+        (0..self.channel_count())
+            .map(|idx| {
+                let mut samples: Vec<i8> = self
+                    .sweeps
+                    .iter()
+                    .map(|s| s.rssi[idx])
+                    .filter(|v| *v != NO_SAMPLE)
+                    .collect();
+                if samples.is_empty() {
+                    return None;
+                }
+                samples.sort_unstable();
+                let rank = (percentile.clamp(0.0, 100.0) / 100.0 * (samples.len() - 1) as f64).round() as usize;
+                Some(samples[rank])
+            })
+            .collect()
+    }
+
+    /// Percentage of samples per channel at or above `threshold_dbm`.
+    pub fn occupancy(&self, threshold_dbm: i8) -> Vec<f64> {
+        let mut hits = vec![0usize; self.channel_count()];
+        let mut samples = vec![0usize; self.channel_count()];
+
+        for sweep in &self.sweeps {
+            for (idx, value) in sweep.rssi.iter().enumerate() {
+                if *value == NO_SAMPLE {
+                    continue;
+                }
+                samples[idx] += 1;
+                if *value >= threshold_dbm {
+                    hits[idx] += 1;
+                }
+            }
+        }
+
+        hits.iter()
+            .zip(&samples)
+            .map(|(h, s)| if *s == 0 { 0.0 } else { *h as f64 * 100.0 / *s as f64 })
+            .collect()
+    }
+
+    /// Band and per-channel averages for each minute of the recording.
+    pub fn per_minute_averages(&self) -> Vec<MinuteAverage> {
+        let mut minutes: Vec<MinuteAverage> = Vec::new();
+        let mut start = 0;
+
+        while start < self.sweeps.len() {
+            let minute = self.sweeps[start].offset_ms / 60_000;
+            let end = start
+                + self.sweeps[start..]
+                    .iter()
+                    .take_while(|s| s.offset_ms / 60_000 == minute)
+                    .count();
+            let window = &self.sweeps[start..end];
+
+            let channel_avg_dbm = channel_averages(self.channel_count(), window.iter());
+            let (sum, count) = window
+                .iter()
+                .flat_map(|s| s.rssi.iter())
+                .filter(|v| **v != NO_SAMPLE)
+                .fold((0i64, 0usize), |(sum, count), v| (sum + *v as i64, count + 1));
+
+            minutes.push(MinuteAverage {
+                minute,
+                sweep_count: window.len(),
+                band_avg_dbm: (count > 0).then(|| sum as f64 / count as f64),
+                channel_avg_dbm,
+            });
+            start = end;
+        }
+
+        minutes
+    }
+}
+
+fn channel_averages<'a>(channels: usize, sweeps: impl Iterator<Item = &'a Sweep>) -> Vec<Option<f64>> {
+    let mut sums = vec![0i64; channels];
+    let mut counts = vec![0usize; channels];
+
+    for sweep in sweeps {
+        for (idx, value) in sweep.rssi.iter().enumerate() {
+            if *value != NO_SAMPLE {
+                sums[idx] += *value as i64;
+                counts[idx] += 1;
+            }
+        }
+    }
+
+    sums.iter()
+        .zip(&counts)
+        .map(|(s, c)| (*c > 0).then(|| *s as f64 / *c as f64))
+        .collect()
+}
+
+/// Builds sweeps from a stream of (time, frequency, RSSI) samples.
+///
+/// A new sweep starts whenever the frequency wraps back to (or below) the
+/// previous sample, which is how both `ubertooth-specan` output and the
+/// firmware's specan packets step through the band.
+#[derive(Debug)]
+pub struct SpectrumRecorder {
+    recording: SpectrumRecording,
+    current: Vec<i8>,
+    current_offset_ms: u32,
+    last_freq: Option<u16>,
+}
+
+impl SpectrumRecorder {
+    /// Start recording `low_freq_mhz..=high_freq_mhz`.
+    pub fn new(low_freq_mhz: u16, high_freq_mhz: u16, start_unix_ms: u64, source: &str) -> Self {
+        let recording = SpectrumRecording::new(low_freq_mhz, high_freq_mhz, start_unix_ms, source);
+        let current = vec![NO_SAMPLE; recording.channel_count()];
+        Self { recording, current, current_offset_ms: 0, last_freq: None }
+    }
+
+    /// Add one sample; samples outside the band are ignored.
+    pub fn push_sample(&mut self, offset_ms: u32, frequency_mhz: u16, rssi: i8) {
+        let (low, high) = (self.recording.header.low_freq_mhz, self.recording.header.high_freq_mhz);
+        if frequency_mhz < low || frequency_mhz > high {
+            return;
+        }
+
+        if self.last_freq.is_some_and(|last| frequency_mhz <= last) {
+            self.flush();
+        }
+        if self.last_freq.is_none() {
+            self.current_offset_ms = offset_ms;
+        }
+
+        let idx = (frequency_mhz - low) as usize;
+        self.current[idx] = rssi;
+        self.last_freq = Some(frequency_mhz);
+    }
+
+    fn flush(&mut self) {
+        if self.last_freq.take().is_some() {
+            let row = std::mem::replace(&mut self.current, vec![NO_SAMPLE; self.recording.channel_count()]);
+            self.recording.sweeps.push(Sweep { offset_ms: self.current_offset_ms, rssi: row });
+        }
+    }
+
+    /// Number of completed sweeps so far.
+    pub fn sweep_count(&self) -> usize {
+        self.recording.sweeps.len()
+    }
+
+    /// Finish the partial sweep and return the recording.
+    pub fn finish(mut self) -> SpectrumRecording {
+        self.flush();
+        self.recording
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder_roundtrip() {
+        let mut recorder = SpectrumRecorder::new(2402, 2405, 1_700_000_000_000, "test");
+        for (t, base) in [(0u32, -90i8), (500, -60), (61_000, -70)] {
+            for f in 2400..=2405u16 {
+                recorder.push_sample(t, f, base + (f - 2400) as i8);
+            }
+        }
+        let recording = recorder.finish();
+        assert_eq!(recording.sweeps.len(), 3);
+        assert_eq!(recording.sweeps[1].rssi, vec![-58, -57, -56, -55]);
+
+        let mut bytes = Vec::new();
+        recording.write_to(&mut bytes).unwrap();
+        let parsed = SpectrumRecording::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(parsed, recording);
+        assert!(SpectrumRecording::read_from(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_summaries_and_slice() {
+        let mut recording = SpectrumRecording::new(2402, 2404, 0, "test");
+        recording.push_sweep(0, vec![-90, -50, NO_SAMPLE]).unwrap();
+        recording.push_sweep(1_000, vec![-80, -70, -60]).unwrap();
+        recording.push_sweep(60_000, vec![-70, -90, -60]).unwrap();
+
+        assert_eq!(recording.peak_hold(), vec![Some(-70), Some(-50), Some(-60)]);
+        assert_eq!(recording.average()[0], Some(-80.0));
+        assert_eq!(recording.occupancy(-65), vec![0.0, 100.0 / 3.0, 100.0]);
+        assert_eq!(recording.noise_floor(0.0), vec![Some(-90), Some(-90), Some(-60)]);
+        assert_eq!(recording.noise_floor(50.0)[0], Some(-80));
+
+        let minutes = recording.per_minute_averages();
+        assert_eq!(minutes.len(), 2);
+        assert_eq!(minutes[0].sweep_count, 2);
+        assert_eq!(minutes[0].channel_avg_dbm[2], Some(-60.0));
+
+        let slice = recording.slice(Some(500), None, Some(2403), None).unwrap();
+        assert_eq!(slice.frequencies(), vec![2403, 2404]);
+        assert_eq!(slice.sweeps.len(), 2);
+        assert!(recording.slice(None, None, Some(2410), None).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! `ubertooth-afh` output.
+//!
+//! The tool sniffs a piconet (printing `ubertooth-rx` packet lines) and
+//! prints the AFH channel map each time it changes, optionally with the
+//! used and avoided channel lists:
+//!
+//! ```text
+//! systime=1700000402 AFH map: 0xffffffff01fcffffff7f
+//! Used: 0-32, 42-78
+//! Avoided: 33-41
+//! ```
+
+use super::{channel_list, field, LineParser, OutputEvent, RxParser};
+use crate::interference;
+
+/// Parser for `ubertooth-afh` output.
+#[derive(Debug, Default)]
+pub struct AfhParser {
+    rx: RxParser,
+}
+
+impl AfhParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Parse a line containing `AFH map: 0x...` (or `Channel map: 0x...`).
+/// A map cut short of its 79 channels is ignored.
+fn afh_map(line: &str) -> Option<OutputEvent> {
+    let lower = line.to_ascii_lowercase();
+    let label = lower.find("afh map:").or_else(|| lower.find("channel map:"))?;
+    let map = lower[label..].split_once(':')?.1.split_whitespace().next()?.to_string();
+    Some(OutputEvent::AfhMap {
+        systime: field(line, "systime").and_then(|v| v.parse().ok()),
+        used_channels: interference::parse_afh_map(&map)?,
+        map,
+    })
+}
+
+impl LineParser for AfhParser {
+    fn feed_line(&mut self, line: &str) -> Vec<OutputEvent> {
+        if let Some(event) = afh_map(line) {
+            return vec![event];
+        }
+
+        let trimmed = line.trim();
+        if let Some((label, list)) = trimmed.split_once(':') {
+            let avoided = match label {
+                "Used" | "Active" => Some(false),
+                "Avoided" | "Disabled" => Some(true),
+                _ => None,
+            };
+            if let Some(avoided) = avoided {
+                return vec![OutputEvent::AfhChannels { avoided, channels: channel_list(list) }];
+            }
+        }
+
+        self.rx.feed_line(line)
+    }
+
+    fn finish(&mut self) -> Vec<OutputEvent> {
+        self.rx.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_afh_fixture() {
+        let events = AfhParser::new().parse_all(include_str!("fixtures/ubertooth-afh.txt"));
+
+        let maps: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                OutputEvent::AfhMap { systime, map, used_channels } => Some((*systime, map.as_str(), used_channels.len())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            maps,
+            vec![
+                (None, "0xffffffffffffffffff7f", 79),
+                (Some(1700000402), "0xffffffff01fcffffff7f", 70),
+            ]
+        );
+
+        assert!(events.contains(&OutputEvent::AfhChannels {
+            avoided: true,
+            channels: (33..=41).collect(),
+        }));
+        let used = events.iter().find_map(|e| match e {
+            OutputEvent::AfhChannels { avoided: false, channels } => Some(channels.len()),
+            _ => None,
+        });
+        assert_eq!(used, Some(70));
+        assert_eq!(events.iter().filter(|e| matches!(e, OutputEvent::BrPacket(_))).count(), 3);
+    }
+
+    #[test]
+    fn test_cut_off_maps_and_warnings() {
+        let output = "\
+libusb: warning [libusb_exit] application left some devices open
+AFH map: 0xffffffff01fc
+systime=1700000402 AFH map: 0xffffffff01fcffffff7f
+rx_xfer status: 1
+systime=1700000403 ch= 6 LAP=9e8b33 err=0 clkn=33718 clk_offset=2211 s=-44 n=-55 snr=11
+systime=1700000404 AFH map: 0xffff";
+        let events = AfhParser::new().parse_all(output);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], OutputEvent::AfhMap { systime: Some(1700000402), used_channels, .. } if used_channels.len() == 70));
+        assert!(matches!(&events[1], OutputEvent::BrPacket(p) if p.channel == Some(6)));
+    }
+}
@@ -0,0 +1,309 @@
+//! `ubertooth-btle` output.
+//!
+//! Every packet starts with a `systime=... freq=... addr=... delta_t=... ms
+//! rssi=...` header, followed by the raw bytes and libbtbb's packet dump,
+//! which ends with the `Data:` and `CRC:` lines:
+//!
+//! ```text
+//! systime=1700000000 freq=2402 addr=8e89bed6 delta_t=0.000 ms rssi=-52
+//! 40 14 8d 62 c2 54 4d 5e 02 01 1a ...
+//! Advertising / AA 8e89bed6 (valid)/ 20 bytes
+//!     Channel Index: 37
+//!     Type:  ADV_IND
+//!     AdvA:  5e:4d:54:c2:62:8d (random)
+//!     ...
+//!     Data:  8d 62 c2 54 4d 5e 02 01 1a ...
+//!     CRC:   3b 9c 11
+//! ```
+//!
+//! Promiscuous mode (`-p`) also prints the parameters it recovers for a
+//! connection (`Access address:`, `CRCInit:`, `Hop interval:`, `Hop increment:`)
+//! as unindented lines.
+
+use super::{field, BleConnection, BlePacket, LineParser, OutputEvent};
+
+/// Parser for `ubertooth-btle` output.
+#[derive(Debug, Default)]
+pub struct BtleParser {
+    packet: Option<BlePacket>,
+    connection: Option<BleConnection>,
+}
+
+impl BtleParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emit the pending packet (and its CONNECT_REQ parameters).
+    fn flush(&mut self) -> Vec<OutputEvent> {
+        let mut events = Vec::new();
+        if let Some(packet) = self.packet.take() {
+            events.push(OutputEvent::BlePacket(packet));
+        }
+        if let Some(connection) = self.connection.take() {
+            events.push(OutputEvent::BleConnection(connection));
+        }
+        events
+    }
+}
+
+/// Address and address type from `5e:4d:54:c2:62:8d (random)`.
+fn address(value: &str) -> (String, bool) {
+    let mut parts = value.split_whitespace();
+    let address = parts.next().unwrap_or_default().to_ascii_lowercase();
+    (address, parts.next() == Some("(random)"))
+}
+
+/// Number from `0024 (36)` (decimal in parentheses) or plain hex `0024`.
+fn number(value: &str) -> Option<u32> {
+    let decimal = value
+        .split_once('(')
+        .and_then(|(_, rest)| rest.trim_end_matches(')').trim().parse().ok());
+    decimal.or_else(|| u32::from_str_radix(value.split_whitespace().next()?, 16).ok())
+}
+
+/// Hex bytes as a contiguous lowercase string.
+fn hex(value: &str) -> String {
+    value.split_whitespace().collect::<String>().to_ascii_lowercase()
+}
+
+fn header_packet(line: &str) -> BlePacket {
+    BlePacket {
+        systime: field(line, "systime").and_then(|v| v.parse().ok()),
+        // A header cut off inside the frequency leaves a number outside the band
+        freq_mhz: field(line, "freq").and_then(|v| v.parse().ok()).filter(|f| (2400..2500).contains(f)),
+        access_address: field(line, "addr").unwrap_or_default().to_ascii_lowercase(),
+        delta_t_ms: field(line, "delta_t").and_then(|v| v.parse().ok()),
+        rssi: field(line, "rssi").and_then(|v| v.parse().ok()),
+        ..Default::default()
+    }
+}
+
+impl LineParser for BtleParser {
+    fn feed_line(&mut self, line: &str) -> Vec<OutputEvent> {
+        if line.starts_with("systime=") {
+            let events = self.flush();
+            self.packet = Some(header_packet(line));
+            return events;
+        }
+
+        // Unindented status lines from promiscuous mode
+        if !line.starts_with(char::is_whitespace) {
+            if let Some((name, value)) = line.split_once(':') {
+                let name = match name.trim() {
+                    "Access address" => Some("access_address"),
+                    "CRCInit" => Some("crc_init"),
+                    "Hop interval" => Some("hop_interval"),
+                    "Hop increment" => Some("hop_increment"),
+                    _ => None,
+                };
+                if let Some(name) = name {
+                    return vec![OutputEvent::ConnectionParameter {
+                        name: name.to_string(),
+                        value: value.trim().to_string(),
+                    }];
+                }
+            }
+        }
+
+        let Some(packet) = self.packet.as_mut() else {
+            return Vec::new();
+        };
+        let trimmed = line.trim();
+
+        // "Advertising / AA 8e89bed6 (valid)/ 20 bytes" or "Data / AA ... (valid) /  6 bytes"
+        if let Some(kind) = ["Advertising", "Data"].into_iter().find(|k| trimmed.starts_with(&format!("{} / AA", k))) {
+            packet.kind = kind.to_ascii_lowercase();
+            packet.aa_valid = trimmed.contains("(valid)");
+            packet.length = trimmed
+                .rsplit('/')
+                .next()
+                .and_then(|rest| rest.split_whitespace().next())
+                .and_then(|n| n.parse().ok());
+            return Vec::new();
+        }
+
+        let Some((key, value)) = trimmed.split_once(':') else {
+            return Vec::new();
+        };
+        let value = value.trim();
+        let connection = &mut self.connection;
+        match key {
+            "Channel Index" => packet.channel_index = value.parse().ok(),
+            "Type" => {
+                packet.pdu_type = Some(value.to_string());
+                if value == "CONNECT_REQ" || value == "CONNECT_IND" {
+                    *connection = Some(BleConnection::default());
+                }
+            }
+            "AdvA" => {
+                let (addr, random) = address(value);
+                if let Some(c) = connection.as_mut() {
+                    c.adv_address = Some(addr.clone());
+                }
+                packet.adv_address = Some(addr);
+                packet.adv_address_random = Some(random);
+            }
+            "InitA" => {
+                let (addr, _) = address(value);
+                if let Some(c) = connection.as_mut() {
+                    c.init_address = Some(addr.clone());
+                }
+                packet.init_address = Some(addr);
+            }
+            "ScanA" => packet.scan_address = Some(address(value).0),
+            "AdvData" | "ScanRspData" => packet.adv_data = Some(hex(value)),
+            "LLID" => packet.llid = value.split_whitespace().next().and_then(|v| v.parse().ok()),
+            "Opcode" => packet.ll_opcode = value.split_once('/').map(|(_, name)| name.trim().to_string()),
+            "Data" => packet.data = hex(value),
+            "CRC" => {
+                // Last line of the packet dump
+                packet.crc = hex(value);
+                return self.flush();
+            }
+            _ => {
+                if let Some(c) = connection.as_mut() {
+                    match key {
+                        "AA" => c.access_address = Some(value.to_ascii_lowercase()),
+                        "CRCInit" => c.crc_init = Some(value.to_ascii_lowercase()),
+                        "WinSize" => c.win_size = number(value),
+                        "WinOffset" => c.win_offset = number(value),
+                        "Interval" => c.interval = number(value),
+                        "Latency" => c.latency = number(value),
+                        "Timeout" => c.timeout = number(value),
+                        "ChM" => c.channel_map = Some(hex(value)),
+                        "Hop" => c.hop = value.parse().ok(),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Vec::new()
+    }
+
+    fn finish(&mut self) -> Vec<OutputEvent> {
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("fixtures/ubertooth-btle.txt");
+
+    fn packets(events: &[OutputEvent]) -> Vec<&BlePacket> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                OutputEvent::BlePacket(p) => Some(p),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_advertising_and_connect_req() {
+        let events = BtleParser::new().parse_all(FIXTURE);
+        let packets = packets(&events);
+        assert_eq!(packets.len(), 4);
+
+        let adv = packets[0];
+        assert_eq!(adv.systime, Some(1700000000));
+        assert_eq!((adv.freq_mhz, adv.rssi, adv.channel_index), (Some(2402), Some(-52), Some(37)));
+        assert_eq!(adv.access_address, "8e89bed6");
+        assert!(adv.aa_valid);
+        assert_eq!(adv.pdu_type.as_deref(), Some("ADV_IND"));
+        assert_eq!(adv.adv_address.as_deref(), Some("5e:4d:54:c2:62:8d"));
+        assert_eq!(adv.adv_address_random, Some(true));
+        assert_eq!(adv.adv_data.as_deref(), Some("02011a0aff4c001005011c102e5a"));
+        assert_eq!(adv.length, Some(20));
+        assert_eq!(adv.crc, "3b9c11");
+
+        assert_eq!(packets[1].scan_address.as_deref(), Some("76:55:44:33:22:11"));
+
+        let connection = events
+            .iter()
+            .find_map(|e| match e {
+                OutputEvent::BleConnection(c) => Some(c),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(connection.init_address.as_deref(), Some("11:22:33:44:55:66"));
+        assert_eq!(connection.access_address.as_deref(), Some("50654b2d"));
+        assert_eq!(connection.crc_init.as_deref(), Some("c4297a"));
+        assert_eq!((connection.interval, connection.timeout, connection.hop), (Some(36), Some(500), Some(9)));
+        assert_eq!(connection.channel_map.as_deref(), Some("ffffffff1f"));
+
+        let data = packets[3];
+        assert_eq!(data.kind, "data");
+        assert_eq!(data.llid, Some(3));
+        assert_eq!(data.ll_opcode.as_deref(), Some("LL_VERSION_IND"));
+        assert_eq!(data.access_address, "50654b2d");
+    }
+
+    #[test]
+    fn test_streaming_and_promiscuous_status() {
+        let mut parser = BtleParser::new();
+        let lines: Vec<&str> = FIXTURE.lines().collect();
+
+        // Nothing is emitted until the packet's CRC line arrives
+        let crc_line = lines.iter().position(|l| l.trim_start().starts_with("CRC:")).unwrap();
+        for line in &lines[..crc_line] {
+            assert!(parser.feed_line(line).is_empty());
+        }
+        assert_eq!(parser.feed_line(lines[crc_line]).len(), 1);
+
+        // A packet cut off by an interrupted run is flushed at the end
+        let mut parser = BtleParser::new();
+        parser.feed_line("systime=1700000005 freq=2480 addr=8e89bed6 delta_t=2.500 ms rssi=-70");
+        parser.feed_line("Advertising / AA 8e89bed6 (invalid)/ 12 bytes");
+        let events = parser.finish();
+        assert!(matches!(&events[..], [OutputEvent::BlePacket(p)] if !p.aa_valid && p.rssi == Some(-70)));
+
+        let events = BtleParser::new().parse_all(include_str!("fixtures/ubertooth-btle-promisc.txt"));
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0],
+            OutputEvent::ConnectionParameter { name: "access_address".to_string(), value: "50654b2d".to_string() }
+        );
+        assert!(matches!(&events[2], OutputEvent::ConnectionParameter { name, value } if name == "hop_interval" && value == "45.00 ms"));
+    }
+
+    #[test]
+    fn test_older_header_warnings_and_cut_off_lines() {
+        // Releases before 2018-06-R1 print no rssi; libusb and transfer warnings
+        // land between the lines of a packet; the run stops mid-header
+        let output = "\
+libusb: warning [libusb_detach_kernel_driver] detach failed
+systime=1441396212 freq=2426 addr=8e89bed6 delta_t=0.625 ms
+42 06 8d 62 c2 54 4d 5e 1f 2e 3d
+rx_xfer status: 1
+Advertising / AA 8e89bed6 (valid)/  6 bytes
+    Channel Index: 38
+    Type:  ADV_NONCONN_IND
+    AdvA:  5e:4d:54:c2:62:8d (random)
+
+    Data:  8d 62 c2 54 4d 5e
+    CRC:   1f 2e 3d
+
+systime=1441396213 freq=24";
+        let events = BtleParser::new().parse_all(output);
+        let packets = packets(&events);
+        assert_eq!(events.len(), 2);
+
+        let adv = packets[0];
+        assert_eq!((adv.systime, adv.freq_mhz, adv.rssi), (Some(1441396212), Some(2426), None));
+        assert_eq!((adv.length, adv.channel_index), (Some(6), Some(38)));
+        assert_eq!(adv.pdu_type.as_deref(), Some("ADV_NONCONN_IND"));
+        assert_eq!((adv.data.as_str(), adv.crc.as_str()), ("8d62c2544d5e", "1f2e3d"));
+
+        // The cut-off header is flushed without the truncated frequency
+        let cut = packets[1];
+        assert_eq!((cut.systime, cut.freq_mhz), (Some(1441396213), None));
+        assert!(cut.data.is_empty() && cut.crc.is_empty());
+
+        // A warning alone produces nothing, inside or outside a packet
+        assert!(BtleParser::new().parse_all("rx_xfer status: 1\nlibusb: error [op_reap] 0").is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+//! `ubertooth-rx` and `ubertooth-follow` output.
+//!
+//! Every BR/EDR packet is one line:
+//!
+//! ```text
+//! systime=1700000100 ch=12 LAP=9e8b33 err=0 clkn=58239 clk_offset=4580 s=-38 n=-55 snr=17
+//! ```
+//!
+//! followed, as libbtbb recovers the piconet, by `UAP = 0x4f found after 18
+//! total packets.` and `Acquired CLK1-27 = 0x...` lines. Survey mode (`-z`)
+//! ends with the piconets it saw, one address per line with `??` for bytes
+//! it could not recover.
+
+use super::{br_packet, is_address, is_hex_number, LineParser, OutputEvent};
+
+/// Parser for `ubertooth-rx` and `ubertooth-follow` output.
+#[derive(Debug, Default)]
+pub struct RxParser {
+    last_lap: Option<String>,
+}
+
+impl RxParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Parse `UAP = 0x4f found after 18 total packets.`
+fn uap_found(line: &str, lap: Option<String>) -> Option<OutputEvent> {
+    let rest = line.trim().strip_prefix("UAP = ")?;
+    let mut tokens = rest.split_whitespace();
+    let uap = tokens.next().filter(|uap| is_hex_number(uap))?.to_ascii_lowercase();
+    let packets = rest
+        .split_once("after ")
+        .and_then(|(_, count)| count.split_whitespace().next())
+        .and_then(|count| count.parse().ok());
+    Some(OutputEvent::UapFound { lap, uap, packets })
+}
+
+/// Parse `Acquired CLK1-27 = 0x0a3c41f` or `Got CLK1-6 0x2c`.
+fn clock_acquired(line: &str) -> Option<OutputEvent> {
+    let mut tokens = line.split_whitespace();
+    let bits = tokens.find(|t| t.starts_with("CLK1-"))?;
+    let value = tokens.find(|t| t.starts_with("0x"))?.trim_end_matches(['.', ',']);
+    if !is_hex_number(value) {
+        return None;
+    }
+    Some(OutputEvent::ClockAcquired {
+        bits: bits.to_string(),
+        value: value.to_ascii_lowercase(),
+    })
+}
+
+impl LineParser for RxParser {
+    fn feed_line(&mut self, line: &str) -> Vec<OutputEvent> {
+        if let Some(packet) = br_packet(line) {
+            self.last_lap = Some(packet.lap.clone());
+            return vec![OutputEvent::BrPacket(packet)];
+        }
+
+        let trimmed = line.trim();
+        let event = if trimmed.starts_with("UAP = ") {
+            uap_found(trimmed, self.last_lap.clone())
+        } else if trimmed.contains("CLK1-") {
+            clock_acquired(trimmed)
+        } else if is_address(trimmed) {
+            Some(OutputEvent::Device {
+                address: trimmed.to_ascii_lowercase(),
+                name: None,
+            })
+        } else {
+            None
+        };
+        event.into_iter().collect()
+    }
+
+    fn finish(&mut self) -> Vec<OutputEvent> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rx_fixture() {
+        let events = RxParser::new().parse_all(include_str!("fixtures/ubertooth-rx.txt"));
+
+        let packets: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                OutputEvent::BrPacket(p) => Some(p),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(packets.len(), 6);
+        assert_eq!(packets[0].lap, "9e8b33");
+        assert_eq!((packets[0].channel, packets[0].errors, packets[0].clkn), (Some(12), Some(0), Some(58239)));
+        assert_eq!((packets[0].signal, packets[0].noise, packets[0].snr), (Some(-38), Some(-55), Some(17)));
+
+        assert!(events.contains(&OutputEvent::UapFound {
+            lap: Some("9e8b33".to_string()),
+            uap: "0x4f".to_string(),
+            packets: Some(18),
+        }));
+        assert!(events.contains(&OutputEvent::ClockAcquired {
+            bits: "CLK1-27".to_string(),
+            value: "0x00e3a4f".to_string(),
+        }));
+
+        let devices: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                OutputEvent::Device { address, .. } => Some(address.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(devices, vec!["??:??:4f:9e:8b:33", "??:??:??:2a:96:ef"]);
+    }
+
+    #[test]
+    fn test_older_formats_warnings_and_cut_off_lines() {
+        // 2014-02 releases print clk100ns/clk1 instead of clkn/clk_offset; every
+        // release pads the channel to two columns
+        let output = "\
+Ubertooth 0 not found, using first available device
+systime=1391212015 ch= 3 LAP=9e8b33 err=0 clk100ns=245760000 clk1=8192 s=-40 n=-55 snr=15
+libusb: warning [libusb_exit] application left some devices open
+systime=1700000100 ch= 7 LAP=9E8B33 err=1 clkn=58431 clk_offset=4579 s=-41 n=-55 snr=14
+rx_xfer status: 1
+UAP = 0x
+Acquired CLK1-27 = 0x
+systime=1700000101 ch=70 LAP=2a96";
+        let events = RxParser::new().parse_all(output);
+        assert_eq!(events.len(), 2);
+
+        let OutputEvent::BrPacket(old) = &events[0] else { panic!("{:?}", events[0]) };
+        assert_eq!((old.systime, old.channel, old.lap.as_str()), (Some(1391212015), Some(3), "9e8b33"));
+        assert_eq!((old.clkn, old.clk_offset, old.snr), (None, None, Some(15)));
+
+        let OutputEvent::BrPacket(current) = &events[1] else { panic!("{:?}", events[1]) };
+        assert_eq!((current.channel, current.lap.as_str(), current.clkn), (Some(7), "9e8b33", Some(58431)));
+    }
+}
@@ -0,0 +1,144 @@
+//! `ubertooth-scan` output.
+//!
+//! The tool lists the devices found by HCI inquiry (`address<TAB>name`),
+//! sniffs with the Ubertooth (printing `ubertooth-rx` packet lines) and then
+//! prints each piconet it identified, followed by indented details such as
+//! the HCI/LMP version, the manufacturer and the AFH map:
+//!
+//! ```text
+//! 00:1a:7d:da:71:13    Living Room Speaker
+//! ??:??:4f:9e:8b:33
+//!     HCI version: 6 (Bluetooth 4.0)
+//!     AFH map: 0xffffffff01fcffffff7f
+//! ```
+
+use super::{is_address, LineParser, OutputEvent, RxParser};
+use crate::interference;
+
+/// Parser for `ubertooth-scan` output.
+#[derive(Debug, Default)]
+pub struct ScanParser {
+    rx: RxParser,
+    last_address: Option<String>,
+}
+
+impl ScanParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Parse `address<TAB>name`, `address - name` or a bare address.
+fn device(line: &str) -> Option<(String, Option<String>)> {
+    let line = line.trim();
+    let (address, name) = match line.split_once(char::is_whitespace) {
+        Some((address, name)) => (address, Some(name.trim().trim_start_matches('-').trim())),
+        None => (line, None),
+    };
+    if !is_address(address) {
+        return None;
+    }
+    let name = name.filter(|n| !n.is_empty()).map(str::to_string);
+    Some((address.to_ascii_lowercase(), name))
+}
+
+impl LineParser for ScanParser {
+    fn feed_line(&mut self, line: &str) -> Vec<OutputEvent> {
+        // Indented details belong to the device listed above them
+        if line.starts_with(char::is_whitespace) {
+            if let (Some(address), Some((key, value))) = (&self.last_address, line.trim().split_once(':')) {
+                let value = value.trim();
+                if key.eq_ignore_ascii_case("AFH map") {
+                    // A map cut short of its 79 channels is ignored
+                    return interference::parse_afh_map(value)
+                        .map(|used_channels| OutputEvent::AfhMap {
+                            systime: None,
+                            map: value.to_ascii_lowercase(),
+                            used_channels,
+                        })
+                        .into_iter()
+                        .collect();
+                }
+                return vec![OutputEvent::DeviceDetail {
+                    address: address.clone(),
+                    field: key.trim().to_string(),
+                    value: value.to_string(),
+                }];
+            }
+            return Vec::new();
+        }
+
+        if let Some((address, name)) = device(line) {
+            self.last_address = Some(address.clone());
+            return vec![OutputEvent::Device { address, name }];
+        }
+
+        self.rx.feed_line(line)
+    }
+
+    fn finish(&mut self) -> Vec<OutputEvent> {
+        self.rx.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_fixture() {
+        let events = ScanParser::new().parse_all(include_str!("fixtures/ubertooth-scan.txt"));
+
+        let devices: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                OutputEvent::Device { address, name } => Some((address.as_str(), name.as_deref())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            devices,
+            vec![
+                ("00:1a:7d:da:71:13", Some("Living Room Speaker")),
+                ("a4:c1:38:0b:5e:42", Some("Pixel 7")),
+                ("??:??:4f:9e:8b:33", None),
+                ("00:1a:7d:da:71:13", Some("Living Room Speaker")),
+            ]
+        );
+
+        assert!(events.contains(&OutputEvent::DeviceDetail {
+            address: "??:??:4f:9e:8b:33".to_string(),
+            field: "Manufacturer".to_string(),
+            value: "Broadcom Corporation (15)".to_string(),
+        }));
+        assert_eq!(events.iter().filter(|e| matches!(e, OutputEvent::BrPacket(_))).count(), 3);
+
+        let used = events
+            .iter()
+            .find_map(|e| match e {
+                OutputEvent::AfhMap { used_channels, .. } => Some(used_channels.len()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(used, 70);
+    }
+
+    #[test]
+    fn test_warnings_and_cut_off_details() {
+        let output = "\
+libusb: warning [libusb_detach_kernel_driver] detach failed
+HCI scan results:
+00:1a:7d:da:71:13\tLiving Room Speaker
+Inquiry failed: Connection timed out
+
+Scan results:
+??:??:4f:9e:8b:33
+\tAFH map: 0xffffffff01
+\tLMP subversion: 0x6119
+rx_xfer status: 1";
+        let events = ScanParser::new().parse_all(output);
+        let names: Vec<&str> = events.iter().map(OutputEvent::name).collect();
+        assert_eq!(names, ["device", "device", "device_detail"]);
+        assert!(matches!(&events[2], OutputEvent::DeviceDetail { address, field, .. } if address == "??:??:4f:9e:8b:33" && field == "LMP subversion"));
+    }
+}
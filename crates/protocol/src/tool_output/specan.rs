@@ -0,0 +1,122 @@
+//! `ubertooth-specan` output.
+//!
+//! One sample per line, either `time, freq, rssi` (the default) or
+//! `freq rssi` (gnuplot output, with a blank line after every sweep). A
+//! sweep is complete when the frequency wraps back to the start of the band
+//! or a blank line follows it. `ubertooth-specan-ui` plots the same sweeps.
+
+use super::{LineParser, OutputEvent, SpectrumSweep};
+
+/// Parser for `ubertooth-specan` output.
+#[derive(Debug, Default)]
+pub struct SpecanParser {
+    sweep: SpectrumSweep,
+}
+
+impl SpecanParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn flush(&mut self) -> Vec<OutputEvent> {
+        if self.sweep.samples.is_empty() {
+            return Vec::new();
+        }
+        vec![OutputEvent::Sweep(std::mem::take(&mut self.sweep))]
+    }
+}
+
+/// Parse a `time, freq, rssi` or `freq rssi` sample.
+fn sample(line: &str) -> Option<(Option<f64>, u16, i8)> {
+    let fields: Vec<&str> = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|f| !f.is_empty())
+        .collect();
+    match fields.as_slice() {
+        [time, freq, rssi] => Some((Some(time.parse().ok()?), freq.parse().ok()?, rssi.parse().ok()?)),
+        [freq, rssi] => Some((None, freq.parse().ok()?, rssi.parse().ok()?)),
+        _ => None,
+    }
+}
+
+impl LineParser for SpecanParser {
+    fn feed_line(&mut self, line: &str) -> Vec<OutputEvent> {
+        if line.trim().is_empty() {
+            return self.flush();
+        }
+        let Some((time, freq, rssi)) = sample(line) else {
+            return Vec::new();
+        };
+
+        // The frequency wrapping around starts the next sweep
+        let wrapped = self.sweep.samples.last().is_some_and(|(last, _)| freq <= *last);
+        let events = if wrapped { self.flush() } else { Vec::new() };
+        if self.sweep.samples.is_empty() {
+            self.sweep.time = time;
+        }
+        self.sweep.samples.push((freq, rssi));
+        events
+    }
+
+    fn finish(&mut self) -> Vec<OutputEvent> {
+        self.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sweeps(events: Vec<OutputEvent>) -> Vec<SpectrumSweep> {
+        events
+            .into_iter()
+            .filter_map(|e| match e {
+                OutputEvent::Sweep(s) => Some(s),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_specan_fixture() {
+        let mut parser = SpecanParser::new();
+        let mut events = Vec::new();
+        for line in include_str!("fixtures/ubertooth-specan.txt").lines() {
+            events.extend(parser.feed_line(line));
+        }
+
+        // The first two sweeps are complete once the third starts
+        let complete = sweeps(events);
+        assert_eq!(complete.len(), 2);
+        assert_eq!(complete[0].time, Some(1700000500.125));
+        assert_eq!(complete[0].samples.len(), 6);
+        assert_eq!(complete[0].peak(), Some((2437, -41)));
+
+        // The interrupted last sweep is flushed at the end
+        let last = sweeps(parser.finish());
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].samples, vec![(2402, -92), (2417, -85)]);
+
+        // Gnuplot output separates sweeps with blank lines
+        let gnuplot = sweeps(SpecanParser::new().parse_all("2402 -90\n2403 -88\n\n2402 -91\n2403 -60\n"));
+        assert_eq!(gnuplot.len(), 2);
+        assert_eq!(gnuplot[1].peak(), Some((2403, -60)));
+        assert_eq!(gnuplot[1].time, None);
+    }
+
+    #[test]
+    fn test_warnings_and_cut_off_samples() {
+        // Warnings and samples cut off by a stopped run are skipped without
+        // ending the sweep
+        let output = "\
+libusb: warning [libusb_detach_kernel_driver] detach failed
+1700000500.125, 2402, -91
+rx_xfer status: 1
+1700000500.126, 2417, -
+1700000500.127, 2432, -52
+1700000500.12";
+        let sweeps = sweeps(SpecanParser::new().parse_all(output));
+        assert_eq!(sweeps.len(), 1);
+        assert_eq!(sweeps[0].samples, vec![(2402, -91), (2432, -52)]);
+    }
+}
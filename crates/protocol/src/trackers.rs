@@ -0,0 +1,287 @@
+//! Unwanted-tracker classification.
+//!
+//! Recognises the advertisements of item trackers and Find My-style
+//! networks so captures can be checked for devices that may be following a
+//! person:
+//!
+//! - Apple Find My (AirTag and third-party Find My accessories)
+//! - Samsung SmartTag (service data 0xFD5A)
+//! - Tile (service UUIDs 0xFEED / 0xFEEC)
+//! - Chipolo (service UUID 0xFE33)
+//! - Google Find My Device network (Eddystone frame types 0x40 / 0x41)
+//!
+//! Where the protocol exposes it, the sighting records whether the tracker
+//! is separated from its owner, which is the state an unwanted tracker
+//! advertises while it travels with somebody else.
+//!
+//! Trackers rotate their random address, so sightings are also linked by
+//! their payload ([`same_tracker`]): the Find My public-key fragment, the
+//! SmartTag privacy ID and the Find My Device ephemeral ID change together
+//! with the address (at most daily while separated) and link sightings
+//! within one rotation period; the SmartTag aging counter rises every 15
+//! minutes and links sightings across rotations. Tile and Chipolo carry no
+//! such field.
+
+use crate::advertising::{hex, AdvertisingData};
+use crate::beacons::{Beacon, ContinuityMessage, UUID_EDDYSTONE};
+use serde::{Deserialize, Serialize};
+
+/// Samsung SmartTag offline-finding service UUID
+pub const UUID_SMARTTAG: u16 = 0xFD5A;
+
+/// Tile service UUIDs
+pub const UUIDS_TILE: [u16; 2] = [0xFEED, 0xFEEC];
+
+/// Chipolo service UUID
+pub const UUID_CHIPOLO: u16 = 0xFE33;
+
+/// Seconds between SmartTag aging counter increments
+pub const SMARTTAG_AGING_PERIOD_SECS: f64 = 15.0 * 60.0;
+
+/// Tracker family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackerKind {
+    AppleFindMy,
+    SamsungSmartTag,
+    Tile,
+    Chipolo,
+    GoogleFindMyDevice,
+}
+
+impl TrackerKind {
+    /// Display name.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            TrackerKind::AppleFindMy => "Apple Find My",
+            TrackerKind::SamsungSmartTag => "Samsung SmartTag",
+            TrackerKind::Tile => "Tile",
+            TrackerKind::Chipolo => "Chipolo",
+            TrackerKind::GoogleFindMyDevice => "Google Find My Device",
+        }
+    }
+
+    /// Machine-readable name.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrackerKind::AppleFindMy => "apple_find_my",
+            TrackerKind::SamsungSmartTag => "samsung_smarttag",
+            TrackerKind::Tile => "tile",
+            TrackerKind::Chipolo => "chipolo",
+            TrackerKind::GoogleFindMyDevice => "google_find_my_device",
+        }
+    }
+}
+
+/// A tracker advertisement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackerSighting {
+    pub kind: TrackerKind,
+    /// `Some(true)` when the tracker advertises a separated-from-owner
+    /// state, `None` if the protocol doesn't expose it
+    pub separated: Option<bool>,
+    /// Protocol state, e.g. "offline finding" or "overmature offline"
+    pub state: String,
+    /// Identifier carried in the payload (hex): the Find My key fragment,
+    /// SmartTag privacy ID or Find My Device ephemeral ID
+    pub payload_id: Option<String>,
+    /// SmartTag aging counter
+    pub aging_counter: Option<u32>,
+}
+
+/// Whether two sightings come from the same tracker judging by their
+/// payloads, `b` having been seen `seconds_apart` after `a`.
+///
+/// Equal payload IDs match; SmartTag aging counters match when they differ
+/// by the number of 15-minute periods between the sightings (give or take
+/// one). Sightings without either never match; compare addresses instead.
+pub fn same_tracker(a: &TrackerSighting, b: &TrackerSighting, seconds_apart: f64) -> bool {
+    if a.kind != b.kind {
+        return false;
+    }
+    if let (Some(a), Some(b)) = (&a.payload_id, &b.payload_id) {
+        if a == b {
+            return true;
+        }
+    }
+    match (a.aging_counter, b.aging_counter) {
+        (Some(a), Some(b)) => {
+            let expected = seconds_apart / SMARTTAG_AGING_PERIOD_SECS;
+            (b as f64 - a as f64 - expected).abs() <= 1.0
+        }
+        _ => false,
+    }
+}
+
+/// Classify an advertisement as a tracker, if it is one.
+pub fn classify(adv: &AdvertisingData) -> Option<TrackerSighting> {
+    for beacon in adv.beacons() {
+        if let Beacon::AppleContinuity(ContinuityMessage::FindMy { separated, key_fragment, .. }) = beacon {
+            return Some(TrackerSighting {
+                kind: TrackerKind::AppleFindMy,
+                separated: Some(separated),
+                state: if separated { "offline finding" } else { "owner nearby" }.to_string(),
+                payload_id: (!key_fragment.is_empty()).then(|| hex(&key_fragment)),
+                aging_counter: None,
+            });
+        }
+    }
+
+    for (uuid, data) in &adv.service_data {
+        match *uuid {
+            UUID_SMARTTAG if !data.is_empty() => {
+                // Bits 5-7 of the first byte carry the tag state, then come
+                // the aging counter (3 bytes, little-endian) and privacy ID (8)
+                let (state, separated) = match data[0] >> 5 {
+                    1 => ("premature offline", false),
+                    2 => ("offline", true),
+                    3 => ("overmature offline", true),
+                    4 => ("wait for connection", false),
+                    5 => ("connected", false),
+                    6 => ("lost", true),
+                    _ => ("unknown", false),
+                };
+                return Some(TrackerSighting {
+                    kind: TrackerKind::SamsungSmartTag,
+                    separated: Some(separated),
+                    state: state.to_string(),
+                    payload_id: data.get(4..12).map(hex),
+                    aging_counter: data.get(1..4).map(|c| u32::from_le_bytes([c[0], c[1], c[2], 0])),
+                });
+            }
+            // Find My Device network frames share the Eddystone UUID;
+            // 0x41 is the unwanted-tracking protection mode. The 20-byte
+            // ephemeral ID follows the frame type.
+            UUID_EDDYSTONE if matches!(data.first(), Some(0x40 | 0x41)) => {
+                let separated = data[0] == 0x41;
+                return Some(TrackerSighting {
+                    kind: TrackerKind::GoogleFindMyDevice,
+                    separated: Some(separated),
+                    state: if separated { "unwanted tracking protection" } else { "normal" }.to_string(),
+                    payload_id: data.get(1..21).map(hex),
+                    aging_counter: None,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let has_uuid = |wanted: &[u16]| {
+        adv.service_uuids_16.iter().any(|u| wanted.contains(u))
+            || adv.service_data.iter().any(|(u, _)| wanted.contains(u))
+    };
+
+    let kind = if has_uuid(&UUIDS_TILE) {
+        TrackerKind::Tile
+    } else if has_uuid(&[UUID_CHIPOLO]) {
+        TrackerKind::Chipolo
+    } else {
+        return None;
+    };
+
+    Some(TrackerSighting {
+        kind,
+        separated: None,
+        state: "advertising".to_string(),
+        payload_id: None,
+        aging_counter: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::advertising::AddressType;
+    use crate::beacons::COMPANY_APPLE;
+
+    #[test]
+    fn test_classify_trackers() {
+        let mut airtag = AdvertisingData::new([0; 6], AddressType::Random);
+        let mut find_my = vec![0x12, 0x19, 0x10];
+        find_my.extend_from_slice(&[0xAB; 24]);
+        airtag.manufacturer_data.push((COMPANY_APPLE, find_my));
+        let sighting = classify(&airtag).unwrap();
+        assert_eq!(sighting.kind, TrackerKind::AppleFindMy);
+        assert_eq!(sighting.separated, Some(true));
+
+        let mut smarttag = AdvertisingData::new([0; 6], AddressType::Random);
+        smarttag.service_data.push((UUID_SMARTTAG, vec![0x70, 0x01, 0x02]));
+        let sighting = classify(&smarttag).unwrap();
+        assert_eq!(sighting.kind, TrackerKind::SamsungSmartTag);
+        assert_eq!(sighting.state, "overmature offline");
+
+        let mut tile = AdvertisingData::new([0; 6], AddressType::Random);
+        tile.service_uuids_16.push(0xFEED);
+        assert_eq!(classify(&tile).unwrap().separated, None);
+    }
+
+    #[test]
+    fn test_non_trackers_ignored() {
+        let mut beacon = AdvertisingData::new([0; 6], AddressType::Random);
+        beacon.service_data.push((UUID_EDDYSTONE, vec![0x10, 0xEB, 0x03, b'a']));
+        beacon.service_uuids_16.push(0x180F);
+        assert!(classify(&beacon).is_none());
+
+        let mut fmdn = AdvertisingData::new([0; 6], AddressType::Random);
+        fmdn.service_data.push((UUID_EDDYSTONE, vec![0x41, 0x00]));
+        assert_eq!(classify(&fmdn).unwrap().kind, TrackerKind::GoogleFindMyDevice);
+    }
+
+    fn smarttag(aging_counter: u32, privacy_id: u8) -> TrackerSighting {
+        let mut adv = AdvertisingData::new([0; 6], AddressType::Random);
+        let mut data = vec![0x70];
+        data.extend_from_slice(&aging_counter.to_le_bytes()[..3]);
+        data.extend_from_slice(&[privacy_id; 8]);
+        data.extend_from_slice(&[0x00, 0xAA, 0xBB, 0xCC, 0xDD]);
+        adv.service_data.push((UUID_SMARTTAG, data));
+        classify(&adv).unwrap()
+    }
+
+    #[test]
+    fn test_payload_identity() {
+        // Find My: key fragment from the offline-finding payload
+        let mut airtag = AdvertisingData::new([0; 6], AddressType::Random);
+        let mut find_my = vec![0x12, 0x19, 0x10];
+        find_my.extend_from_slice(&[0xAB; 22]);
+        find_my.extend_from_slice(&[0x01, 0x00]);
+        airtag.manufacturer_data.push((COMPANY_APPLE, find_my));
+        let sighting = classify(&airtag).unwrap();
+        assert_eq!(sighting.payload_id, Some("ab".repeat(22)));
+        assert!(same_tracker(&sighting, &sighting.clone(), 3600.0));
+
+        // Owner-nearby frames are too short to carry the key
+        let mut nearby = AdvertisingData::new([0; 6], AddressType::Random);
+        nearby.manufacturer_data.push((COMPANY_APPLE, vec![0x12, 0x02, 0x00, 0x00]));
+        assert_eq!(classify(&nearby).unwrap().payload_id, None);
+
+        // Find My Device: 20-byte ephemeral ID after the frame type
+        let mut fmdn = AdvertisingData::new([0; 6], AddressType::Random);
+        let mut frame = vec![0x41];
+        frame.extend_from_slice(&[0x5A; 20]);
+        frame.push(0x80);
+        fmdn.service_data.push((UUID_EDDYSTONE, frame));
+        assert_eq!(classify(&fmdn).unwrap().payload_id, Some("5a".repeat(20)));
+
+        // SmartTag: privacy ID and aging counter
+        let tag = smarttag(0x012345, 0x11);
+        assert_eq!(tag.aging_counter, Some(0x012345));
+        assert_eq!(tag.payload_id, Some("11".repeat(8)));
+    }
+
+    #[test]
+    fn test_same_tracker_across_rotations() {
+        // Two hours later the privacy ID has rotated but the counter rose by 8
+        let morning = smarttag(1000, 0x11);
+        assert!(same_tracker(&morning, &smarttag(1008, 0x22), 7200.0));
+        assert!(same_tracker(&morning, &smarttag(1009, 0x22), 7200.0));
+        assert!(!same_tracker(&morning, &smarttag(1020, 0x22), 7200.0));
+        assert!(!same_tracker(&morning, &smarttag(500, 0x22), 7200.0));
+
+        // Different kinds or nothing to compare never match
+        let mut tile = AdvertisingData::new([0; 6], AddressType::Random);
+        tile.service_uuids_16.push(0xFEED);
+        let tile = classify(&tile).unwrap();
+        assert!(!same_tracker(&tile, &tile.clone(), 0.0));
+        assert!(!same_tracker(&morning, &tile, 0.0));
+    }
+}
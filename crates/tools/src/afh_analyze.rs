@@ -51,6 +51,10 @@ impl PentestTool for AfhAnalyzeTool {
                     "default": 30,
                     "minimum": 5,
                     "maximum": 300
+                },
+                "specan_capture_id": {
+                    "type": "string",
+                    "description": "Spectrum recording (from bt_specan) used to attribute avoided channels to Wi-Fi and other interferers"
                 }
             }
         })
@@ -70,6 +74,10 @@ impl PentestTool for AfhAnalyzeTool {
                     "type": "string",
                     "description": "79-bit channel map in hex"
                 },
+                "map_updates": {
+                    "type": "integer",
+                    "description": "Number of channel maps ubertooth-afh printed; afh_map is the latest"
+                },
                 "channels_used": {
                     "type": "array",
                     "items": { "type": "integer" }
@@ -84,6 +92,28 @@ impl PentestTool for AfhAnalyzeTool {
                 "avoided_count": {
                     "type": "integer"
                 },
+                "interferers": {
+                    "type": ["array", "null"],
+                    "description": "Interferers classified from specan_capture_id (see specan_interference)",
+                    "items": { "type": "object" }
+                },
+                "afh_cross_reference": {
+                    "type": ["object", "null"],
+                    "properties": {
+                        "explained": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "channel": { "type": "integer" },
+                                    "interferers": { "type": "array", "items": { "type": "string" } }
+                                }
+                            }
+                        },
+                        "unexplained": { "type": "array", "items": { "type": "integer" } },
+                        "interfered_in_use": { "type": "array", "items": { "type": "integer" } }
+                    }
+                },
                 "interpretation": {
                     "type": "string"
                 }
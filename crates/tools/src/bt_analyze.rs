@@ -69,6 +69,10 @@ impl PentestTool for BtAnalyzeTool {
                 "capture_id": {
                     "type": "string"
                 },
+                "rssi_calibration": {
+                    "type": ["object", "null"],
+                    "description": "RSSI calibration applied to the reported readings (serial, offset_db, reference); null when the dongle is uncalibrated"
+                },
                 "analysis": {
                     "type": "object",
                     "properties": {
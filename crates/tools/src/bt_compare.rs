@@ -9,8 +9,10 @@ use ubertooth_platform::UbertoothBackendProvider;
 
 /// Tool for comparing two captures to find differences.
 ///
-/// Useful for replay attack detection and protocol analysis.
-/// Compares packets, devices, and timing between two captures.
+/// Useful for replay attack detection and protocol analysis. Both captures
+/// are decoded and compared by content: devices that appeared, disappeared
+/// or changed, PDU-type and channel distributions, connection events and
+/// advertising timing.
 pub struct BtCompareTool {
     backend: Arc<dyn UbertoothBackendProvider>,
 }
@@ -33,7 +35,7 @@ impl PentestTool for BtCompareTool {
     }
 
     fn description(&self) -> &str {
-        "Compare two captures to find differences (useful for replay attacks)"
+        "Compare two captures semantically: devices appeared/disappeared/changed, PDU types, channels, connections and timing"
     }
 
     fn input_schema(&self) -> Value {
@@ -48,11 +50,11 @@ impl PentestTool for BtCompareTool {
                     "type": "string",
                     "description": "Second capture ID"
                 },
-                "compare_mode": {
+                "mode": {
                     "type": "string",
-                    "description": "Comparison mode",
-                    "enum": ["packets", "devices", "timing"],
-                    "default": "packets"
+                    "description": "Comparison view: devices (appeared, disappeared, changed name/RSSI band/advertising interval/payload), packets (content overlap, PDU types, channels, connections) or timing (duration, packet rate, advertising intervals, connection parameters)",
+                    "enum": ["devices", "packets", "timing"],
+                    "default": "devices"
                 }
             },
             "required": ["capture_id_a", "capture_id_b"]
@@ -76,9 +78,10 @@ impl PentestTool for BtCompareTool {
                             "items": {
                                 "type": "object",
                                 "properties": {
-                                    "type": { "type": "string" },
-                                    "packet_index_a": { "type": "integer" },
-                                    "packet_index_b": { "type": "integer" },
+                                    "type": {
+                                        "type": "string",
+                                        "enum": ["device_appeared", "device_disappeared", "device_changed", "pdu_type", "channel", "connection", "timing"]
+                                    },
                                     "field": { "type": "string" },
                                     "value_a": { "type": "string" },
                                     "value_b": { "type": "string" },
@@ -88,9 +91,23 @@ impl PentestTool for BtCompareTool {
                         },
                         "unique_to_a": { "type": "integer" },
                         "unique_to_b": { "type": "integer" },
-                        "common_packets": { "type": "integer" }
+                        "common_packets": { "type": "integer" },
+                        "devices": {
+                            "type": "object",
+                            "description": "Devices view: appeared, disappeared, changed (with per-field changes) and unchanged count"
+                        },
+                        "packets": {
+                            "type": "object",
+                            "description": "Packets view: pdu_types and channels (count_a/count_b/percent_a/percent_b), connections_a, connections_b"
+                        },
+                        "timing": {
+                            "type": "object",
+                            "description": "Timing view: duration_sec_a/b, packet_rate_a/b, adv_intervals, connections_a, connections_b"
+                        }
                     }
-                }
+                },
+                "capture_id_a": { "type": "string" },
+                "capture_id_b": { "type": "string" }
             },
             "required": ["success", "comparison"]
         })
@@ -157,7 +174,7 @@ mod tests {
         let result = tool.execute(json!({
             "capture_id_a": "cap-1",
             "capture_id_b": "cap-2",
-            "compare_mode": "packets"
+            "mode": "packets"
         })).await.unwrap();
 
         assert_eq!(result["success"], true);
@@ -9,8 +9,8 @@ use ubertooth_platform::UbertoothBackendProvider;
 
 /// Tool for decoding Bluetooth protocol layers.
 ///
-/// Parses BLE/BR packets and extracts structured protocol information
-/// (L2CAP, ATT, SMP, GATT, etc.).
+/// Decodes BLE and BR/EDR packets natively and returns a summary and
+/// protocol tree per packet (link layer, L2CAP, ATT, SMP, etc.).
 pub struct BtDecodeTool {
     backend: Arc<dyn UbertoothBackendProvider>,
 }
@@ -50,9 +50,14 @@ impl PentestTool for BtDecodeTool {
                 },
                 "protocol_layer": {
                     "type": "string",
-                    "description": "Protocol layer to decode",
+                    "description": "Only return packets carrying this protocol layer",
                     "enum": ["auto", "l2cap", "att", "smp", "gatt"],
                     "default": "auto"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of packets to return",
+                    "default": 100
                 }
             },
             "required": ["capture_id"]
@@ -73,8 +78,38 @@ impl PentestTool for BtDecodeTool {
                         "properties": {
                             "index": { "type": "integer" },
                             "timestamp": { "type": "string" },
-                            "layers": { "type": "object" },
-                            "interpretation": { "type": "string" }
+                            "channel": { "type": "string" },
+                            "rssi": { "type": "string" },
+                            "packet_type": { "type": "string" },
+                            "mac_address": { "type": "string" },
+                            "protocol": { "type": "string" },
+                            "summary": { "type": "string" },
+                            "protocol_tree": {
+                                "type": "object",
+                                "description": "Decoded layers: {layer, fields, children}, outermost first"
+                            },
+                            "error": {
+                                "type": ["string", "null"],
+                                "description": "Why the packet could not be fully decoded"
+                            }
+                        }
+                    }
+                },
+                "malformed_packets": {
+                    "type": "integer",
+                    "description": "Packets that could not be fully decoded"
+                },
+                "annotations": {
+                    "type": "array",
+                    "description": "Analyst notes and bookmarks saved on packets of this capture",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "packet_index": { "type": "integer" },
+                            "note": { "type": "string" },
+                            "bookmarked": { "type": "boolean" },
+                            "author": { "type": ["string", "null"] },
+                            "updated_at": { "type": "string" }
                         }
                     }
                 }
@@ -80,8 +80,9 @@ impl PentestTool for BtDiscoverTool {
                     "items": {
                         "type": "object",
                         "properties": {
+                            "lap": { "type": "string" },
                             "bd_addr": { "type": "string" },
-                            "uap": { "type": "integer" },
+                            "uap": { "type": ["integer", "null"] },
                             "packet_count": { "type": "integer" }
                         }
                     }
@@ -33,7 +33,7 @@ impl PentestTool for BtFingerprintTool {
     }
 
     fn description(&self) -> &str {
-        "Device fingerprinting based on protocol behavior"
+        "Identify device make, type and OS from advertising, connection and GATT features, ranked against a signature database"
     }
 
     fn input_schema(&self) -> Value {
@@ -46,11 +46,17 @@ impl PentestTool for BtFingerprintTool {
                 },
                 "target_mac": {
                     "type": "string",
-                    "description": "Target device MAC address",
+                    "description": "Target device MAC address (default: every advertising device)",
                     "pattern": "^([0-9A-Fa-f]{2}:){5}[0-9A-Fa-f]{2}$"
+                },
+                "max_matches": {
+                    "type": "integer",
+                    "description": "Ranked signature matches to return per device",
+                    "minimum": 1,
+                    "default": 3
                 }
             },
-            "required": ["capture_id", "target_mac"]
+            "required": ["capture_id"]
         })
     }
 
@@ -79,6 +85,49 @@ impl PentestTool for BtFingerprintTool {
                             "items": { "type": "string" }
                         }
                     }
+                },
+                "devices": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "address": { "type": "string" },
+                            "features": {
+                                "type": "object",
+                                "description": "ad_types, flags, tx_power, adv_interval_ms, adv_jitter_ms, manufacturer, service_uuids, ll_versions, ll_features, gatt, ..."
+                            },
+                            "matches": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "signature": { "type": "string" },
+                                        "manufacturer": { "type": "string" },
+                                        "device_type": { "type": "string" },
+                                        "os": { "type": ["string", "null"] },
+                                        "confidence": { "type": "number" },
+                                        "evidence": {
+                                            "type": "array",
+                                            "items": {
+                                                "type": "object",
+                                                "properties": {
+                                                    "feature": { "type": "string" },
+                                                    "expected": { "type": "string" },
+                                                    "observed": { "type": ["string", "null"] },
+                                                    "matched": { "type": ["boolean", "null"] },
+                                                    "weight": { "type": "number" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "signatures": {
+                    "type": "object",
+                    "description": "Signature count, directory for extra signature files and files loaded"
                 }
             },
             "required": ["success", "device"]
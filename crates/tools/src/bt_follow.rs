@@ -88,6 +88,11 @@ impl PentestTool for BtFollowTool {
                     "type": "array",
                     "items": { "type": "integer" }
                 },
+                "termination": {
+                    "type": "string",
+                    "enum": ["exited", "timed_out", "cancelled"],
+                    "description": "How ubertooth-follow ended; an interrupted follow still registers its partial PCAP"
+                },
                 "pcap_path": {
                     "type": "string"
                 }
@@ -72,6 +72,12 @@ impl PentestTool for BtScanTool {
                         "type": "object",
                         "properties": {
                             "bd_addr": { "type": "string" },
+                            "name": { "type": "string" },
+                            "vendor": { "type": ["string", "null"] },
+                            "details": {
+                                "type": "object",
+                                "description": "Details ubertooth-scan printed for the device (HCI/LMP version, manufacturer, AFH map)"
+                            },
                             "class_of_device": { "type": "string" },
                             "device_class": { "type": "string" },
                             "device_name": { "type": ["string", "null"] },
@@ -90,6 +90,22 @@ impl PentestTool for BtSpecanTool {
                     "type": "integer",
                     "description": "Scan duration"
                 },
+                "capture_type": {
+                    "type": "string",
+                    "description": "Always 'specan_recording'; use specan_replay/specan_summary with the capture_id"
+                },
+                "sweep_count": {
+                    "type": "integer",
+                    "description": "Number of sweeps recorded"
+                },
+                "recording_path": {
+                    "type": "string",
+                    "description": "Path of the stored waterfall recording"
+                },
+                "rssi_calibration": {
+                    "type": ["object", "null"],
+                    "description": "RSSI calibration applied to the reported readings (serial, offset_db, reference); null when the dongle is uncalibrated"
+                },
                 "scan_results": {
                     "type": "array",
                     "description": "RSSI data per frequency",
@@ -101,7 +117,10 @@ impl PentestTool for BtSpecanTool {
                             "rssi_avg": { "type": "integer" },
                             "rssi_max": { "type": "integer" },
                             "rssi_min": { "type": "integer" },
-                            "activity_percent": { "type": "number" }
+                            "activity_percent": {
+                                "type": "number",
+                                "description": "Percentage of sweeps at or above rssi_threshold"
+                            }
                         }
                     }
                 },
@@ -0,0 +1,223 @@
+//! Unwanted-tracker detection tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for detecting item trackers that may be following a person.
+///
+/// Classifies Apple Find My, Samsung SmartTag, Tile, Chipolo and Google Find
+/// My Device advertisements in stored captures, flags separated-from-owner
+/// states and reports trackers present across several captures, linking
+/// rotated addresses by payload where the protocol allows.
+pub struct BtTrackerDetectTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl BtTrackerDetectTool {
+    /// Create a new bt_tracker_detect tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for BtTrackerDetectTool {
+    fn name(&self) -> &str {
+        "bt_tracker_detect"
+    }
+
+    fn category(&self) -> &str {
+        "bt-analysis"
+    }
+
+    fn description(&self) -> &str {
+        "Detect AirTag, SmartTag, Tile and other item trackers following a person across captures"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "capture_ids": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "BLE captures to check, ideally taken at different times or places"
+                },
+                "min_captures": {
+                    "type": "integer",
+                    "description": "Number of captures a tracker must appear in to be reported as persistent",
+                    "default": 2,
+                    "minimum": 1
+                }
+            },
+            "required": ["capture_ids"]
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "captures_analyzed": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "capture_id": { "type": "string" },
+                            "timestamp": { "type": "string" },
+                            "tags": { "type": "array", "items": { "type": "string" } },
+                            "description": { "type": "string" },
+                            "trackers_found": { "type": "integer" }
+                        }
+                    }
+                },
+                "trackers": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "mac_address": { "type": "string" },
+                            "addresses": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "Every address the tracker was seen with (they rotate)"
+                            },
+                            "linked_by": {
+                                "type": "string",
+                                "enum": ["address", "payload"],
+                                "description": "Whether sightings under different addresses were linked by payload"
+                            },
+                            "payload_ids": {
+                                "type": "array",
+                                "items": { "type": "string" },
+                                "description": "Find My key fragments, SmartTag privacy IDs or Find My Device EIDs seen"
+                            },
+                            "tracker_type": {
+                                "type": "string",
+                                "enum": ["apple_find_my", "samsung_smarttag", "tile", "chipolo", "google_find_my_device"]
+                            },
+                            "tracker_name": { "type": "string" },
+                            "vendor": { "type": ["string", "null"] },
+                            "separated_from_owner": { "type": ["boolean", "null"] },
+                            "states": { "type": "array", "items": { "type": "string" } },
+                            "captures": { "type": "array", "items": { "type": "string" } },
+                            "capture_count": { "type": "integer" },
+                            "persistent": { "type": "boolean" },
+                            "packet_count": { "type": "integer" },
+                            "max_rssi": { "type": "integer" },
+                            "risk": { "type": "string", "enum": ["low", "medium", "high"] }
+                        }
+                    }
+                },
+                "summary": {
+                    "type": "object",
+                    "properties": {
+                        "total_trackers": { "type": "integer" },
+                        "separated": { "type": "integer" },
+                        "persistent": { "type": "integer" },
+                        "high_risk": { "type": "integer" }
+                    }
+                },
+                "recommendations": {
+                    "type": "array",
+                    "items": { "type": "string" }
+                },
+                "limitations": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Trackers the linking across captures can miss"
+                }
+            },
+            "required": ["success", "trackers", "summary"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing bt_tracker_detect");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("bt_tracker_detect", params).await?;
+
+        tracing::info!("bt_tracker_detect completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, _params: Value) -> Result<Value> {
+            if method == "bt_tracker_detect" {
+                Ok(json!({
+                    "success": true,
+                    "trackers": [{
+                        "mac_address": "C4:11:22:33:44:55",
+                        "tracker_type": "apple_find_my",
+                        "separated_from_owner": true,
+                        "capture_count": 2,
+                        "persistent": true,
+                        "risk": "high"
+                    }],
+                    "summary": {
+                        "total_trackers": 1,
+                        "separated": 1,
+                        "persistent": 1,
+                        "high_risk": 1
+                    }
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bt_tracker_detect() {
+        let backend = Arc::new(MockBackend);
+        let tool = BtTrackerDetectTool::new(backend);
+
+        let result = tool.execute(json!({
+            "capture_ids": ["cap-home", "cap-office"]
+        })).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["summary"]["high_risk"], 1);
+        assert_eq!(result["trackers"][0]["risk"], "high");
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = BtTrackerDetectTool::new(backend);
+
+        assert_eq!(tool.name(), "bt_tracker_detect");
+        assert_eq!(tool.category(), "bt-analysis");
+    }
+}
@@ -86,6 +86,15 @@ impl PentestTool for BtleScanTool {
                     "type": "integer",
                     "description": "Channel scanned"
                 },
+                "cancelled": {
+                    "type": "boolean",
+                    "description": "Whether the scan was cancelled; the capture holds what was scanned until then"
+                },
+                "compatibility_notes": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "How the scan was adapted to the installed ubertooth-tools (e.g. channel 37 only without ubertooth-btle -A)"
+                },
                 "devices_found": {
                     "type": "array",
                     "description": "List of discovered BLE devices",
@@ -108,6 +117,10 @@ impl PentestTool for BtleScanTool {
                     "type": "string",
                     "description": "Path to saved PCAP file"
                 },
+                "rssi_calibration": {
+                    "type": ["object", "null"],
+                    "description": "RSSI calibration applied to the reported readings (serial, offset_db, reference); null when the dongle is uncalibrated"
+                },
                 "preview": {
                     "type": "array",
                     "description": "Preview of captured data (first few packets)",
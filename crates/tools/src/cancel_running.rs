@@ -0,0 +1,119 @@
+//! Cancellation tool for running ubertooth-* commands.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for cancelling the ubertooth-* commands that are currently running.
+///
+/// Each command is interrupted with SIGINT, so a cancelled `btle_scan` or
+/// `bt_follow` still returns and registers what it captured so far.
+pub struct CancelRunningTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl CancelRunningTool {
+    /// Create a new cancel_running tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for CancelRunningTool {
+    fn name(&self) -> &str {
+        "cancel_running"
+    }
+
+    fn category(&self) -> &str {
+        "bt-device"
+    }
+
+    fn description(&self) -> &str {
+        "Cancel the ubertooth-* commands that are running; interrupted captures keep what they recorded"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean",
+                    "description": "Whether the cancellation was delivered"
+                },
+                "cancelled": {
+                    "type": "integer",
+                    "description": "Number of running commands that were cancelled"
+                }
+            },
+            "required": ["success", "cancelled"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing cancel_running");
+
+        let result = self.backend.call("cancel", params).await?;
+
+        tracing::info!("cancel_running cancelled {} command(s)", result["cancelled"]);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, _params: Value) -> Result<Value> {
+            if method == "cancel" {
+                Ok(json!({
+                    "success": true,
+                    "cancelled": 1
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running() {
+        let tool = CancelRunningTool::new(Arc::new(MockBackend));
+
+        let result = tool.execute(json!({})).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["cancelled"], 1);
+        assert_eq!(tool.name(), "cancel_running");
+        assert_eq!(tool.category(), "bt-device");
+    }
+}
@@ -0,0 +1,170 @@
+//! Capture bundle export tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for packaging captures to hand to another operator.
+///
+/// Writes one archive with the capture data, metadata (tags, description,
+/// provenance), the configuration presets used and a manifest of hashes.
+pub struct CaptureBundleExportTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl CaptureBundleExportTool {
+    /// Create a new capture_bundle_export tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for CaptureBundleExportTool {
+    fn name(&self) -> &str {
+        "capture_bundle_export"
+    }
+
+    fn category(&self) -> &str {
+        "bt-capture"
+    }
+
+    fn description(&self) -> &str {
+        "Package captures with their metadata, config presets and a hash manifest into one archive for another operator"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "capture_ids": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Captures to bundle"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Bundle every capture matching a catalog search instead (same syntax as capture_list), e.g. 'engagement:ACME-2026-03'"
+                },
+                "configs": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Extra configuration presets to include (presets the captures were taken with are always included)"
+                },
+                "output_path": {
+                    "type": "string",
+                    "description": "Bundle file (defaults to exports/bundle-<timestamp>.tar.gz under the storage root)"
+                }
+            }
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "bundle_path": {
+                    "type": "string"
+                },
+                "bundle_size_bytes": {
+                    "type": "integer"
+                },
+                "capture_count": {
+                    "type": "integer"
+                },
+                "captures": {
+                    "type": "array",
+                    "items": { "type": "string" }
+                },
+                "configs": {
+                    "type": "array",
+                    "items": { "type": "string" }
+                },
+                "message": {
+                    "type": "string"
+                }
+            },
+            "required": ["success", "bundle_path", "captures"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing capture_bundle_export");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("capture_bundle_export", params).await?;
+
+        tracing::info!("capture_bundle_export completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, params: Value) -> Result<Value> {
+            if method == "capture_bundle_export" {
+                Ok(json!({
+                    "success": true,
+                    "bundle_path": "/home/op/.ubertooth/exports/bundle-20260314-101500.tar.gz",
+                    "bundle_size_bytes": 48213,
+                    "capture_count": 2,
+                    "captures": params["capture_ids"],
+                    "configs": ["stealth"]
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capture_bundle_export() {
+        let backend = Arc::new(MockBackend);
+        let tool = CaptureBundleExportTool::new(backend);
+
+        let result = tool
+            .execute(json!({ "capture_ids": ["cap-btle-1", "cap-btle-2"] }))
+            .await
+            .unwrap();
+
+        assert_eq!(result["capture_count"], 2);
+        assert_eq!(result["captures"][1], "cap-btle-2");
+        assert_eq!(result["configs"][0], "stealth");
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = CaptureBundleExportTool::new(backend);
+
+        assert_eq!(tool.name(), "capture_bundle_export");
+        assert_eq!(tool.category(), "bt-capture");
+    }
+}
@@ -0,0 +1,182 @@
+//! Capture bundle import tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for importing a capture bundle from another operator.
+///
+/// Checks every file against the bundle manifest and the recorded capture
+/// hashes before anything is written, then registers the captures and
+/// presets, giving new IDs or names where they collide with local ones.
+pub struct CaptureBundleImportTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl CaptureBundleImportTool {
+    /// Create a new capture_bundle_import tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for CaptureBundleImportTool {
+    fn name(&self) -> &str {
+        "capture_bundle_import"
+    }
+
+    fn category(&self) -> &str {
+        "bt-capture"
+    }
+
+    fn description(&self) -> &str {
+        "Verify a capture bundle from another operator and add its captures and config presets to the local store"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "bundle_path": {
+                    "type": "string",
+                    "description": "Bundle file written by capture_bundle_export"
+                }
+            },
+            "required": ["bundle_path"]
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "bundle_sha256": {
+                    "type": "string"
+                },
+                "operator": {
+                    "type": ["string", "null"],
+                    "description": "Operator who created the bundle"
+                },
+                "created_at": {
+                    "type": "string"
+                },
+                "captures": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "original_id": { "type": "string" },
+                            "capture_id": { "type": "string", "description": "ID in the local store" },
+                            "status": {
+                                "type": "string",
+                                "enum": ["imported", "renamed", "duplicate"]
+                            }
+                        }
+                    }
+                },
+                "configs": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "imported_as": { "type": "string" },
+                            "status": {
+                                "type": "string",
+                                "enum": ["imported", "renamed", "identical"]
+                            }
+                        }
+                    }
+                },
+                "message": {
+                    "type": "string"
+                }
+            },
+            "required": ["success", "captures", "configs"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing capture_bundle_import");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("capture_bundle_import", params).await?;
+
+        tracing::info!("capture_bundle_import completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, _params: Value) -> Result<Value> {
+            if method == "capture_bundle_import" {
+                Ok(json!({
+                    "success": true,
+                    "operator": "j.doe",
+                    "captures": [
+                        { "original_id": "cap-btle-1", "capture_id": "cap-btle-1", "status": "imported" },
+                        { "original_id": "cap-btle-2", "capture_id": "cap-imported-7f3a", "status": "renamed" }
+                    ],
+                    "configs": [
+                        { "name": "stealth", "imported_as": "stealth", "status": "identical" }
+                    ]
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capture_bundle_import() {
+        let backend = Arc::new(MockBackend);
+        let tool = CaptureBundleImportTool::new(backend);
+
+        let result = tool
+            .execute(json!({ "bundle_path": "/tmp/bundle.tar.gz" }))
+            .await
+            .unwrap();
+
+        assert_eq!(result["captures"][1]["status"], "renamed");
+        assert_eq!(result["configs"][0]["status"], "identical");
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = CaptureBundleImportTool::new(backend);
+
+        assert_eq!(tool.name(), "capture_bundle_import");
+        assert_eq!(tool.category(), "bt-capture");
+        assert_eq!(tool.input_schema()["required"][0], "bundle_path");
+    }
+}
@@ -33,7 +33,7 @@ impl PentestTool for CaptureExportTool {
     }
 
     fn description(&self) -> &str {
-        "Export capture to standard formats (PCAP, JSON, CSV)"
+        "Export capture to standard formats (PCAP, PCAPNG, CSV, JSON, JSON Lines)"
     }
 
     fn input_schema(&self) -> Value {
@@ -46,13 +46,24 @@ impl PentestTool for CaptureExportTool {
                 },
                 "format": {
                     "type": "string",
-                    "description": "Export format",
-                    "enum": ["pcap", "pcapng", "json", "csv"],
+                    "description": "Export format (pcapng carries packet notes as packet comments; jsonl writes one packet per line for large captures)",
+                    "enum": ["pcap", "pcapng", "csv", "json", "jsonl"],
                     "default": "pcap"
                 },
+                "fields": {
+                    "type": "array",
+                    "description": "Columns for csv/json/jsonl, in order (default: timestamp, channel, rssi, access_address, pdu_type, address, peer_address, name, company_id, uuids, crc_valid)",
+                    "items": {
+                        "type": "string",
+                        "enum": [
+                            "index", "timestamp", "channel", "rssi", "access_address", "pdu_type", "address",
+                            "peer_address", "name", "company_id", "uuids", "crc_valid", "protocol", "length", "summary"
+                        ]
+                    }
+                },
                 "output_path": {
                     "type": ["string", "null"],
-                    "description": "Optional output path (defaults to captures dir)"
+                    "description": "Optional output path (defaults to the exports dir)"
                 }
             },
             "required": ["capture_id"]
@@ -75,8 +86,17 @@ impl PentestTool for CaptureExportTool {
                 "packet_count": {
                     "type": "integer"
                 },
+                "fields": {
+                    "type": ["array", "null"],
+                    "description": "Columns written (csv/json/jsonl)",
+                    "items": { "type": "string" }
+                },
                 "file_size_bytes": {
                     "type": "integer"
+                },
+                "comments_written": {
+                    "type": "integer",
+                    "description": "Packet notes written as PCAPNG packet comments"
                 }
             },
             "required": ["success", "export_path", "format"]
@@ -30,7 +30,7 @@ impl PentestTool for CaptureListTool {
     }
 
     fn description(&self) -> &str {
-        "List stored packet captures with filtering"
+        "List and search stored captures by type, tag, device, channel, engagement and time range"
     }
 
     fn input_schema(&self) -> Value {
@@ -41,6 +41,58 @@ impl PentestTool for CaptureListTool {
                     "type": ["string", "null"],
                     "description": "Filter by capture type (btle_sniff, specan, etc.)"
                 },
+                "query": {
+                    "type": "string",
+                    "description": "Search string, e.g. \"AA:BB:CC tag:lobby date:2026-03\". Keys: type, tag, mac, name, channel, engagement, date (YYYY[-MM[-DD]]), after, before; bare MACs match devices, other words match ID, description and tags"
+                },
+                "tag": {
+                    "type": "string",
+                    "description": "Only captures with this tag"
+                },
+                "mac": {
+                    "type": "string",
+                    "description": "Only captures in which this device was seen (full MAC or prefix such as an OUI)"
+                },
+                "device_name": {
+                    "type": "string",
+                    "description": "Only captures with a device whose name contains this text"
+                },
+                "channel": {
+                    "type": "integer",
+                    "description": "Only captures taken on this channel",
+                    "minimum": 0,
+                    "maximum": 78
+                },
+                "engagement": {
+                    "type": "string",
+                    "description": "Only captures belonging to this engagement"
+                },
+                "since": {
+                    "type": "string",
+                    "description": "Only captures overlapping this date or later (YYYY, YYYY-MM, YYYY-MM-DD or RFC 3339)"
+                },
+                "until": {
+                    "type": "string",
+                    "description": "Only captures overlapping this date or earlier (YYYY, YYYY-MM, YYYY-MM-DD or RFC 3339)"
+                },
+                "min_duration_sec": {
+                    "type": "integer",
+                    "minimum": 0
+                },
+                "max_duration_sec": {
+                    "type": "integer",
+                    "minimum": 0
+                },
+                "include_pruned": {
+                    "type": "boolean",
+                    "description": "Also list captures whose data was removed by retention",
+                    "default": false
+                },
+                "reindex": {
+                    "type": "boolean",
+                    "description": "Rebuild the capture index from scratch instead of updating changed captures only",
+                    "default": false
+                },
                 "limit": {
                     "type": "integer",
                     "description": "Maximum results",
@@ -90,13 +142,49 @@ impl PentestTool for CaptureListTool {
                             "file_size_bytes": { "type": "integer" },
                             "pcap_path": { "type": "string" },
                             "tags": { "type": "array", "items": { "type": "string" } },
-                            "description": { "type": "string" }
+                            "description": { "type": "string" },
+                            "channels": { "type": "array", "items": { "type": "integer" } },
+                            "engagement": { "type": ["string", "null"] },
+                            "pruned": {
+                                "type": ["object", "null"],
+                                "description": "When and why retention removed the capture data",
+                                "properties": {
+                                    "pruned_at": { "type": "string" },
+                                    "rule": { "type": "string" },
+                                    "reason": { "type": "string" },
+                                    "freed_bytes": { "type": "integer" }
+                                }
+                            },
+                            "devices": {
+                                "type": "array",
+                                "items": {
+                                    "type": "object",
+                                    "properties": {
+                                        "mac": { "type": "string" },
+                                        "name": { "type": "string" }
+                                    }
+                                }
+                            }
                         }
                     }
                 },
                 "total_count": {
+                    "type": "integer",
+                    "description": "Number of captures matching the search"
+                },
+                "indexed_count": {
                     "type": "integer"
                 },
+                "index": {
+                    "type": "object",
+                    "description": "Index refresh counts",
+                    "properties": {
+                        "added": { "type": "integer" },
+                        "updated": { "type": "integer" },
+                        "removed": { "type": "integer" },
+                        "unchanged": { "type": "integer" }
+                    }
+                },
                 "offset": {
                     "type": "integer"
                 },
@@ -0,0 +1,203 @@
+//! Capture retention (pruning) tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for applying the capture retention policy.
+///
+/// Previews by default: lists the captures the configured quota, maximum age
+/// and keep-tag rules would prune, and why. With `dry_run` off it removes
+/// their data and records the reason in their metadata.
+pub struct CapturePruneTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl CapturePruneTool {
+    /// Create a new capture_prune tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for CapturePruneTool {
+    fn name(&self) -> &str {
+        "capture_prune"
+    }
+
+    fn category(&self) -> &str {
+        "bt-capture"
+    }
+
+    fn description(&self) -> &str {
+        "Preview or apply the capture retention policy (storage quota, maximum age, keep tags)"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Only report what would be pruned",
+                    "default": true
+                },
+                "max_total_size_mb": {
+                    "type": "integer",
+                    "description": "Override the storage quota in MB (0 = no quota)",
+                    "minimum": 0
+                },
+                "max_age_days": {
+                    "type": "integer",
+                    "description": "Override the maximum capture age in days (0 = no limit)",
+                    "minimum": 0
+                },
+                "keep_tags": {
+                    "type": "array",
+                    "description": "Override the tags that protect a capture from pruning",
+                    "items": { "type": "string" }
+                }
+            }
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "dry_run": {
+                    "type": "boolean"
+                },
+                "policy": {
+                    "type": "object",
+                    "properties": {
+                        "max_total_size_mb": { "type": ["integer", "null"] },
+                        "max_age_days": { "type": ["integer", "null"] },
+                        "keep_tags": { "type": "array", "items": { "type": "string" } }
+                    }
+                },
+                "before": {
+                    "type": "object",
+                    "properties": {
+                        "capture_count": { "type": "integer" },
+                        "pruned_count": { "type": "integer" },
+                        "used_bytes": { "type": "integer" }
+                    }
+                },
+                "candidates": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "capture_id": { "type": "string" },
+                            "capture_type": { "type": "string" },
+                            "timestamp": { "type": "string" },
+                            "file_size_bytes": { "type": "integer" },
+                            "rule": { "type": "string", "enum": ["max_age", "max_total_size"] },
+                            "reason": { "type": "string" }
+                        }
+                    }
+                },
+                "freed_bytes": {
+                    "type": "integer"
+                },
+                "used_bytes_after": {
+                    "type": "integer"
+                },
+                "over_quota": {
+                    "type": "boolean",
+                    "description": "Still over quota because the remaining captures carry keep tags"
+                },
+                "message": {
+                    "type": "string"
+                }
+            },
+            "required": ["success", "dry_run", "candidates"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing capture_prune");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("capture_prune", params).await?;
+
+        tracing::info!("capture_prune completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, params: Value) -> Result<Value> {
+            if method == "capture_prune" {
+                Ok(json!({
+                    "success": true,
+                    "dry_run": params["dry_run"].as_bool().unwrap_or(true),
+                    "candidates": [
+                        {
+                            "capture_id": "cap-btle-old",
+                            "capture_type": "btle_sniff",
+                            "file_size_bytes": 1048576,
+                            "rule": "max_age",
+                            "reason": "Older than 30 days (captured 2026-01-02)"
+                        }
+                    ],
+                    "freed_bytes": 1048576,
+                    "over_quota": false
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capture_prune_preview() {
+        let backend = Arc::new(MockBackend);
+        let tool = CapturePruneTool::new(backend);
+
+        let result = tool.execute(json!({})).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["dry_run"], true);
+        assert_eq!(result["candidates"][0]["rule"], "max_age");
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = CapturePruneTool::new(backend);
+
+        assert_eq!(tool.name(), "capture_prune");
+        assert_eq!(tool.category(), "bt-capture");
+    }
+}
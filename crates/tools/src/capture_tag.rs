@@ -54,6 +54,10 @@ impl PentestTool for CaptureTagTool {
                     "type": "boolean",
                     "description": "Append tags vs replace",
                     "default": true
+                },
+                "engagement": {
+                    "type": "string",
+                    "description": "Engagement the capture belongs to (empty string clears it)"
                 }
             },
             "required": ["capture_id"]
@@ -76,6 +80,9 @@ impl PentestTool for CaptureTagTool {
                 },
                 "description": {
                     "type": "string"
+                },
+                "engagement": {
+                    "type": ["string", "null"]
                 }
             },
             "required": ["success", "capture_id"]
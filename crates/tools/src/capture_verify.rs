@@ -0,0 +1,202 @@
+//! Capture integrity verification tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for verifying that captures have not been altered.
+///
+/// Re-hashes capture data against the SHA-256 recorded when it was written
+/// and checks the hash chain of the custody log.
+pub struct CaptureVerifyTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl CaptureVerifyTool {
+    /// Create a new capture_verify tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for CaptureVerifyTool {
+    fn name(&self) -> &str {
+        "capture_verify"
+    }
+
+    fn category(&self) -> &str {
+        "bt-capture"
+    }
+
+    fn description(&self) -> &str {
+        "Re-hash captures against their recorded SHA-256, report tampering and check the chain-of-custody log"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "capture_id": {
+                    "type": "string",
+                    "description": "Capture to verify (all captures when omitted)"
+                },
+                "include_history": {
+                    "type": "boolean",
+                    "description": "Include the capture's custody log entries (default when capture_id is given)"
+                }
+            }
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "tampered": {
+                    "type": "boolean",
+                    "description": "A capture was modified or is missing, or the custody log was altered"
+                },
+                "summary": {
+                    "type": "object",
+                    "properties": {
+                        "intact": { "type": "integer" },
+                        "modified": { "type": "integer" },
+                        "missing": { "type": "integer" },
+                        "pruned": { "type": "integer" },
+                        "unhashed": { "type": "integer" }
+                    }
+                },
+                "checks": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "capture_id": { "type": "string" },
+                            "status": {
+                                "type": "string",
+                                "enum": ["intact", "modified", "missing", "pruned", "unhashed"]
+                            },
+                            "pcap_path": { "type": "string" },
+                            "expected_sha256": { "type": ["string", "null"] },
+                            "actual_sha256": { "type": ["string", "null"] },
+                            "expected_size": { "type": ["integer", "null"] },
+                            "actual_size": { "type": ["integer", "null"] }
+                        }
+                    }
+                },
+                "custody_log": {
+                    "type": "object",
+                    "properties": {
+                        "entries": { "type": "integer" },
+                        "intact": { "type": "boolean" },
+                        "first_broken_line": { "type": ["integer", "null"] },
+                        "problem": { "type": ["string", "null"] }
+                    }
+                },
+                "history": {
+                    "type": "array",
+                    "description": "Custody log entries of the capture (created, accessed, exported, tagged, verified, pruned, deleted)",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "seq": { "type": "integer" },
+                            "timestamp": { "type": "string" },
+                            "action": { "type": "string" },
+                            "operator": { "type": ["string", "null"] },
+                            "host": { "type": ["string", "null"] },
+                            "details": { "type": "object" },
+                            "hash": { "type": "string" }
+                        }
+                    }
+                },
+                "message": {
+                    "type": "string"
+                }
+            },
+            "required": ["success", "tampered", "checks", "custody_log"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing capture_verify");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("capture_verify", params).await?;
+
+        tracing::info!("capture_verify completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, params: Value) -> Result<Value> {
+            if method == "capture_verify" {
+                Ok(json!({
+                    "success": true,
+                    "tampered": true,
+                    "checks": [
+                        {
+                            "capture_id": params["capture_id"],
+                            "status": "modified",
+                            "expected_sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+                            "actual_sha256": "60303ae22b998861bce3b28f33eec1be758a213c86c93c076dbe9f558c11c752"
+                        }
+                    ],
+                    "custody_log": { "entries": 12, "intact": true }
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capture_verify() {
+        let backend = Arc::new(MockBackend);
+        let tool = CaptureVerifyTool::new(backend);
+
+        let result = tool.execute(json!({ "capture_id": "cap-btle-123" })).await.unwrap();
+
+        assert_eq!(result["tampered"], true);
+        assert_eq!(result["checks"][0]["capture_id"], "cap-btle-123");
+        assert_eq!(result["checks"][0]["status"], "modified");
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = CaptureVerifyTool::new(backend);
+
+        assert_eq!(tool.name(), "capture_verify");
+        assert_eq!(tool.category(), "bt-capture");
+    }
+}
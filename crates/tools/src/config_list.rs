@@ -70,6 +70,21 @@ impl PentestTool for ConfigListTool {
                 },
                 "count": {
                     "type": "integer"
+                },
+                "calibrations": {
+                    "type": "array",
+                    "description": "Per-dongle RSSI calibrations from squelch_calibrate",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "serial": { "type": "string" },
+                            "calibrated": { "type": "string", "format": "date-time" },
+                            "noise_floor_dbm": { "type": "integer" },
+                            "recommended_squelch_dbm": { "type": "integer" },
+                            "rssi_offset_db": { "type": "integer" },
+                            "reference": { "type": "string" }
+                        }
+                    }
                 }
             },
             "required": ["success", "configs", "count"]
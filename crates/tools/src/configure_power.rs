@@ -52,6 +52,10 @@ impl PentestTool for ConfigurePowerTool {
                     "type": "boolean",
                     "description": "High gain mode for RX",
                     "default": false
+                },
+                "rail_1v8": {
+                    "type": "boolean",
+                    "description": "Enable the 1.8V power rail (Rust backend only; unchanged if omitted)"
                 }
             },
             "required": ["power_level"]
@@ -78,6 +82,10 @@ impl PentestTool for ConfigurePowerTool {
                     "type": "boolean",
                     "description": "High gain mode enabled"
                 },
+                "rail_1v8": {
+                    "type": "boolean",
+                    "description": "1.8V power rail enabled (Rust backend only)"
+                },
                 "estimated_power_dbm": {
                     "type": "integer",
                     "description": "Approximate TX power in dBm"
@@ -64,6 +64,14 @@ impl PentestTool for DeviceConnectTool {
                     "type": "string",
                     "description": "API version"
                 },
+                "versions": {
+                    "type": "object",
+                    "description": "Detected versions: host_release, lib_version, lib_api (USB API libubertooth expects), firmware_release, firmware_api"
+                },
+                "compatibility": {
+                    "type": "object",
+                    "description": "Support per feature (e.g. btle_advertising_channel, afh): {status: supported|unknown|unsupported, reason}"
+                },
                 "board_id": {
                     "type": "integer",
                     "description": "Board ID (0=Zero, 1=One, 2=TC13Badge)"
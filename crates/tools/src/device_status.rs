@@ -64,6 +64,14 @@ impl PentestTool for DeviceStatusTool {
                     "type": "string",
                     "description": "Firmware version"
                 },
+                "versions": {
+                    "type": "object",
+                    "description": "Detected versions: host_release, lib_version, lib_api (USB API libubertooth expects), firmware_release, firmware_api"
+                },
+                "compatibility": {
+                    "type": "object",
+                    "description": "Support per feature (e.g. btle_advertising_channel, afh): {status: supported|unknown|unsupported, reason}"
+                },
                 "board_id": {
                     "type": "integer",
                     "description": "Board ID (0=Zero, 1=One, 2=TC13Badge)"
@@ -83,6 +91,34 @@ impl PentestTool for DeviceStatusTool {
                 "power_level": {
                     "type": "integer",
                     "description": "TX power level (0-7)"
+                },
+                "frequency_mhz": {
+                    "type": "integer",
+                    "description": "Current frequency in MHz"
+                },
+                "paen": {
+                    "type": "boolean",
+                    "description": "Power amplifier enabled"
+                },
+                "hgm": {
+                    "type": "boolean",
+                    "description": "High gain mode enabled"
+                },
+                "squelch_level": {
+                    "type": "integer",
+                    "description": "RSSI squelch threshold in dBm"
+                },
+                "leds": {
+                    "type": "object",
+                    "properties": {
+                        "usr": { "type": "boolean" },
+                        "rx": { "type": "boolean" },
+                        "tx": { "type": "boolean" }
+                    }
+                },
+                "rail_1v8": {
+                    "type": "boolean",
+                    "description": "1.8V power rail enabled"
                 }
             },
             "required": ["success", "connected"]
@@ -3,6 +3,7 @@
 mod device_connect;
 mod device_disconnect;
 mod device_status;
+mod cancel_running;
 mod btle_scan;
 mod bt_specan;
 mod configure_channel;
@@ -36,6 +37,18 @@ mod btle_slave;
 mod btle_mitm;
 mod bt_spoof;
 mod ubertooth_raw;
+mod oui_update;
+mod bt_tracker_detect;
+mod specan_list;
+mod specan_replay;
+mod specan_slice;
+mod specan_summary;
+mod specan_interference;
+mod squelch_calibrate;
+mod capture_prune;
+mod capture_verify;
+mod capture_bundle_export;
+mod capture_bundle_import;
 
 use std::sync::Arc;
 use ubertooth_core::tools::ToolRegistry;
@@ -44,6 +57,7 @@ use ubertooth_platform::UbertoothBackendProvider;
 pub use device_connect::DeviceConnectTool;
 pub use device_disconnect::DeviceDisconnectTool;
 pub use device_status::DeviceStatusTool;
+pub use cancel_running::CancelRunningTool;
 pub use btle_scan::BtleScanTool;
 pub use bt_specan::BtSpecanTool;
 pub use configure_channel::ConfigureChannelTool;
@@ -53,6 +67,10 @@ pub use capture_list::CaptureListTool;
 pub use capture_get::CaptureGetTool;
 pub use capture_delete::CaptureDeleteTool;
 pub use capture_tag::CaptureTagTool;
+pub use capture_prune::CapturePruneTool;
+pub use capture_verify::CaptureVerifyTool;
+pub use capture_bundle_export::CaptureBundleExportTool;
+pub use capture_bundle_import::CaptureBundleImportTool;
 pub use bt_analyze::BtAnalyzeTool;
 pub use session_context::SessionContextTool;
 pub use bt_scan::BtScanTool;
@@ -77,6 +95,14 @@ pub use btle_slave::BtleSlaveTool;
 pub use btle_mitm::BtleMitmTool;
 pub use bt_spoof::BtSpoofTool;
 pub use ubertooth_raw::UbertoothRawTool;
+pub use oui_update::OuiUpdateTool;
+pub use bt_tracker_detect::BtTrackerDetectTool;
+pub use specan_list::SpecanListTool;
+pub use specan_replay::SpecanReplayTool;
+pub use specan_slice::SpecanSliceTool;
+pub use specan_summary::SpecanSummaryTool;
+pub use specan_interference::SpecanInterferenceTool;
+pub use squelch_calibrate::SquelchCalibrateTool;
 
 /// Create and populate the tool registry with all available tools.
 ///
@@ -95,6 +121,9 @@ pub fn create_tool_registry(backend: Arc<dyn UbertoothBackendProvider>) -> ToolR
     registry.register(Arc::new(DeviceDisconnectTool::new(backend.clone())));
     registry.register(Arc::new(DeviceStatusTool::new(backend.clone())));
 
+    // bt-device (cancellation)
+    registry.register(Arc::new(CancelRunningTool::new(backend.clone())));
+
     // Phase 1 tools - bt-recon
     registry.register(Arc::new(BtleScanTool::new(backend.clone())));
     registry.register(Arc::new(BtSpecanTool::new(backend.clone())));
@@ -114,6 +143,7 @@ pub fn create_tool_registry(backend: Arc<dyn UbertoothBackendProvider>) -> ToolR
     // Phase 2 Week 3 - bt-config (advanced)
     registry.register(Arc::new(ConfigureSquelchTool::new(backend.clone())));
     registry.register(Arc::new(ConfigureLedsTool::new(backend.clone())));
+    registry.register(Arc::new(SquelchCalibrateTool::new(backend.clone())));
 
     // Phase 2 Week 4 - bt-config (presets)
     registry.register(Arc::new(BtSaveConfigTool::new(backend.clone())));
@@ -121,12 +151,30 @@ pub fn create_tool_registry(backend: Arc<dyn UbertoothBackendProvider>) -> ToolR
     registry.register(Arc::new(ConfigListTool::new(backend.clone())));
     registry.register(Arc::new(ConfigDeleteTool::new(backend.clone())));
 
+    // bt-config (vendor registry)
+    registry.register(Arc::new(OuiUpdateTool::new(backend.clone())));
+
     // Phase 1 tools - bt-capture
     registry.register(Arc::new(CaptureListTool::new(backend.clone())));
     registry.register(Arc::new(CaptureGetTool::new(backend.clone())));
     registry.register(Arc::new(CaptureDeleteTool::new(backend.clone())));
     registry.register(Arc::new(CaptureTagTool::new(backend.clone())));
 
+    // bt-capture (retention)
+    registry.register(Arc::new(CapturePruneTool::new(backend.clone())));
+
+    // bt-capture (evidence integrity)
+    registry.register(Arc::new(CaptureVerifyTool::new(backend.clone())));
+
+    // bt-capture (bundles)
+    registry.register(Arc::new(CaptureBundleExportTool::new(backend.clone())));
+    registry.register(Arc::new(CaptureBundleImportTool::new(backend.clone())));
+
+    // bt-capture (spectrum recordings)
+    registry.register(Arc::new(SpecanListTool::new(backend.clone())));
+    registry.register(Arc::new(SpecanReplayTool::new(backend.clone())));
+    registry.register(Arc::new(SpecanSliceTool::new(backend.clone())));
+
     // Phase 1 tools - bt-analysis
     registry.register(Arc::new(BtAnalyzeTool::new(backend.clone())));
 
@@ -136,6 +184,13 @@ pub fn create_tool_registry(backend: Arc<dyn UbertoothBackendProvider>) -> ToolR
     registry.register(Arc::new(BtFingerprintTool::new(backend.clone())));
     registry.register(Arc::new(PcapMergeTool::new(backend.clone())));
 
+    // bt-analysis (counter-surveillance)
+    registry.register(Arc::new(BtTrackerDetectTool::new(backend.clone())));
+
+    // bt-analysis (spectrum)
+    registry.register(Arc::new(SpecanSummaryTool::new(backend.clone())));
+    registry.register(Arc::new(SpecanInterferenceTool::new(backend.clone())));
+
     // Phase 2 Week 5 - capture export
     registry.register(Arc::new(CaptureExportTool::new(backend.clone())));
 
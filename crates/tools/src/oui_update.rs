@@ -0,0 +1,157 @@
+//! OUI registry update tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for updating the IEEE OUI vendor registry from local files.
+///
+/// Validates IEEE MA-L/MA-M/MA-S CSV exports, installs them into
+/// ~/.ubertooth/oui/ and reloads vendor lookups. No network access is needed.
+pub struct OuiUpdateTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl OuiUpdateTool {
+    /// Create a new oui_update tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for OuiUpdateTool {
+    fn name(&self) -> &str {
+        "oui_update"
+    }
+
+    fn category(&self) -> &str {
+        "bt-config"
+    }
+
+    fn description(&self) -> &str {
+        "Update the offline IEEE OUI vendor registry from local registry CSV files"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "registry_paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Paths to IEEE registry CSV files (oui.csv, mam.csv, oui36.csv; .gz accepted)"
+                }
+            },
+            "required": ["registry_paths"]
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "files_installed": {
+                    "type": "array",
+                    "items": { "type": "string" }
+                },
+                "registry_dir": {
+                    "type": "string"
+                },
+                "assignments": {
+                    "type": "object",
+                    "properties": {
+                        "ma_l": { "type": "integer" },
+                        "ma_m": { "type": "integer" },
+                        "ma_s": { "type": "integer" },
+                        "total": { "type": "integer" }
+                    }
+                }
+            },
+            "required": ["success", "files_installed", "assignments"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing oui_update");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("oui_update", params).await?;
+
+        tracing::info!("oui_update completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, params: Value) -> Result<Value> {
+            if method == "oui_update" {
+                let files = params["registry_paths"].as_array().cloned().unwrap_or_default();
+                Ok(json!({
+                    "success": true,
+                    "files_installed": files,
+                    "registry_dir": "/home/user/.ubertooth/oui",
+                    "assignments": {
+                        "ma_l": 39857,
+                        "ma_m": 6516,
+                        "ma_s": 7114,
+                        "total": 53487
+                    }
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oui_update() {
+        let backend = Arc::new(MockBackend);
+        let tool = OuiUpdateTool::new(backend);
+
+        let result = tool.execute(json!({
+            "registry_paths": ["/tmp/oui.csv"]
+        })).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["files_installed"].as_array().unwrap().len(), 1);
+        assert_eq!(result["assignments"]["total"], 53487);
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = OuiUpdateTool::new(backend);
+
+        assert_eq!(tool.name(), "oui_update");
+        assert_eq!(tool.category(), "bt-config");
+    }
+}
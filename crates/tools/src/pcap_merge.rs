@@ -9,8 +9,9 @@ use ubertooth_platform::UbertoothBackendProvider;
 
 /// Tool for merging multiple PCAP captures into one.
 ///
-/// Combines multiple capture files with optional timestamp sorting.
-/// Useful for consolidating related capture sessions.
+/// Combines multiple capture files in timestamp order, reconciling link
+/// types and optionally dropping duplicate advertisements. Useful for
+/// consolidating related capture sessions.
 pub struct PcapMergeTool {
     backend: Arc<dyn UbertoothBackendProvider>,
 }
@@ -53,8 +54,18 @@ impl PentestTool for PcapMergeTool {
                 },
                 "sort_by_timestamp": {
                     "type": "boolean",
-                    "description": "Sort packets by timestamp",
+                    "description": "Interleave packets by timestamp; false concatenates the captures in order",
                     "default": true
+                },
+                "deduplicate": {
+                    "type": "boolean",
+                    "description": "Keep one copy of an advertisement received on several channels",
+                    "default": false
+                },
+                "dedup_window_ms": {
+                    "type": "integer",
+                    "description": "Copies of the same advertising PDU within this many milliseconds are duplicates",
+                    "default": 10
                 }
             },
             "required": ["capture_ids"]
@@ -74,9 +85,30 @@ impl PentestTool for PcapMergeTool {
                 "source_captures": {
                     "type": "integer"
                 },
+                "sources": {
+                    "type": "array",
+                    "description": "Packet counts per source capture",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "capture_id": { "type": "string" },
+                            "packets_read": { "type": "integer" },
+                            "packets_written": { "type": "integer" },
+                            "duplicates_removed": { "type": "integer" },
+                            "linktypes": { "type": "array", "items": { "type": "integer" } }
+                        }
+                    }
+                },
                 "total_packets": {
                     "type": "integer"
                 },
+                "duplicates_removed": {
+                    "type": "integer"
+                },
+                "format": {
+                    "type": "string",
+                    "description": "pcap, or pcapng with one interface per link type when the sources differ"
+                },
                 "pcap_path": {
                     "type": "string"
                 }
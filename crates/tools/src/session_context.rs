@@ -113,7 +113,17 @@ impl PentestTool for SessionContextTool {
                     "properties": {
                         "captures_dir": { "type": "string" },
                         "captures_count": { "type": "integer" },
-                        "total_size_mb": { "type": "number" }
+                        "pruned_count": { "type": "integer" },
+                        "total_size_mb": { "type": "number" },
+                        "retention": {
+                            "type": "object",
+                            "properties": {
+                                "max_total_size_mb": { "type": ["integer", "null"] },
+                                "max_age_days": { "type": ["integer", "null"] },
+                                "keep_tags": { "type": "array", "items": { "type": "string" } },
+                                "quota_used_percent": { "type": ["number", "null"] }
+                            }
+                        }
                     }
                 }
             },
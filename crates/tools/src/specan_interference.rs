@@ -0,0 +1,215 @@
+//! Spectrum interference classification tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for classifying what occupies the band in a spectrum recording.
+///
+/// Detects 20/40 MHz Wi-Fi channels, narrowband carriers, microwave ovens and
+/// frequency-hopping activity, and lines the result up against an AFH map
+/// when one is given.
+pub struct SpecanInterferenceTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl SpecanInterferenceTool {
+    /// Create a new specan_interference tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for SpecanInterferenceTool {
+    fn name(&self) -> &str {
+        "specan_interference"
+    }
+
+    fn category(&self) -> &str {
+        "bt-analysis"
+    }
+
+    fn description(&self) -> &str {
+        "Classify Wi-Fi channels, carriers, microwave ovens and hoppers in a spectrum recording and cross-reference with AFH maps"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "capture_id": {
+                    "type": "string",
+                    "description": "Spectrum recording to classify"
+                },
+                "threshold_dbm": {
+                    "type": "integer",
+                    "description": "RSSI at or above which a sample counts as activity",
+                    "default": -80,
+                    "minimum": -128,
+                    "maximum": 0
+                },
+                "afh_map": {
+                    "type": "string",
+                    "description": "AFH channel map from afh_analyze (hex, e.g. 0x7fffffffffffffffff7f)"
+                },
+                "channels_avoided": {
+                    "type": "array",
+                    "items": { "type": "integer", "minimum": 0, "maximum": 78 },
+                    "description": "Avoided BR/EDR channels, used when afh_map is not given"
+                }
+            },
+            "required": ["capture_id"]
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "capture_id": {
+                    "type": "string"
+                },
+                "threshold_dbm": {
+                    "type": "integer"
+                },
+                "sweep_count": {
+                    "type": "integer"
+                },
+                "interferers": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "kind": {
+                                "type": "string",
+                                "enum": ["wifi_20", "wifi_40", "narrowband_carrier", "microwave_oven", "frequency_hopping"]
+                            },
+                            "label": { "type": "string" },
+                            "low_freq_mhz": { "type": "integer" },
+                            "high_freq_mhz": { "type": "integer" },
+                            "center_freq_mhz": { "type": "integer" },
+                            "wifi_channels": { "type": "array", "items": { "type": "integer" } },
+                            "activity_percent": { "type": "number" },
+                            "peak_dbm": { "type": ["integer", "null"] },
+                            "confidence": { "type": "number" },
+                            "bt_channels": { "type": "array", "items": { "type": "integer" } }
+                        }
+                    }
+                },
+                "affected_bt_channels": {
+                    "type": "array",
+                    "items": { "type": "integer" },
+                    "description": "BR/EDR channels covered by static (non-hopping) interferers"
+                },
+                "afh_cross_reference": {
+                    "type": ["object", "null"],
+                    "properties": {
+                        "explained": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "channel": { "type": "integer" },
+                                    "interferers": { "type": "array", "items": { "type": "string" } }
+                                }
+                            }
+                        },
+                        "unexplained": { "type": "array", "items": { "type": "integer" } },
+                        "interfered_in_use": { "type": "array", "items": { "type": "integer" } }
+                    }
+                }
+            },
+            "required": ["success", "interferers", "affected_bt_channels"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing specan_interference");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("specan_interference", params).await?;
+
+        tracing::info!("specan_interference completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, _params: Value) -> Result<Value> {
+            if method == "specan_interference" {
+                Ok(json!({
+                    "success": true,
+                    "capture_id": "specan-20260101-120000-abcd",
+                    "interferers": [{
+                        "kind": "wifi_20",
+                        "label": "Wi-Fi channel 6 (20 MHz)",
+                        "low_freq_mhz": 2429,
+                        "high_freq_mhz": 2445,
+                        "wifi_channels": [6]
+                    }],
+                    "affected_bt_channels": [27, 28, 29],
+                    "afh_cross_reference": {
+                        "explained": [{ "channel": 27, "interferers": ["Wi-Fi channel 6 (20 MHz)"] }],
+                        "unexplained": [],
+                        "interfered_in_use": [28, 29]
+                    }
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_specan_interference() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanInterferenceTool::new(backend);
+
+        let result = tool.execute(json!({
+            "capture_id": "specan-20260101-120000-abcd",
+            "channels_avoided": [27]
+        })).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["interferers"][0]["wifi_channels"][0], 6);
+        assert_eq!(result["afh_cross_reference"]["explained"][0]["channel"], 27);
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanInterferenceTool::new(backend);
+
+        assert_eq!(tool.name(), "specan_interference");
+        assert_eq!(tool.category(), "bt-analysis");
+    }
+}
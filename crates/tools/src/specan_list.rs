@@ -0,0 +1,160 @@
+//! Spectrum recording listing tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for listing stored spectrum waterfall recordings.
+pub struct SpecanListTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl SpecanListTool {
+    /// Create a new specan_list tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for SpecanListTool {
+    fn name(&self) -> &str {
+        "specan_list"
+    }
+
+    fn category(&self) -> &str {
+        "bt-capture"
+    }
+
+    fn description(&self) -> &str {
+        "List stored spectrum waterfall recordings from bt_specan"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of recordings to return",
+                    "default": 50,
+                    "minimum": 1
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Number of recordings to skip",
+                    "default": 0,
+                    "minimum": 0
+                }
+            }
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "recordings": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "capture_id": { "type": "string" },
+                            "timestamp": { "type": "string" },
+                            "sweep_count": { "type": "integer" },
+                            "duration_sec": { "type": ["integer", "null"] },
+                            "low_freq_mhz": { "type": ["integer", "null"] },
+                            "high_freq_mhz": { "type": ["integer", "null"] },
+                            "file_size_bytes": { "type": "integer" },
+                            "tags": { "type": "array", "items": { "type": "string" } },
+                            "description": { "type": "string" }
+                        }
+                    }
+                },
+                "total_count": {
+                    "type": "integer"
+                }
+            },
+            "required": ["success", "recordings", "total_count"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing specan_list");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("specan_list", params).await?;
+
+        tracing::info!("specan_list completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, _params: Value) -> Result<Value> {
+            if method == "specan_list" {
+                Ok(json!({
+                    "success": true,
+                    "recordings": [{
+                        "capture_id": "specan-20260101-120000-abcd",
+                        "sweep_count": 120,
+                        "low_freq_mhz": 2402,
+                        "high_freq_mhz": 2480
+                    }],
+                    "total_count": 1
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_specan_list() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanListTool::new(backend);
+
+        let result = tool.execute(json!({})).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["total_count"], 1);
+        assert_eq!(result["recordings"][0]["sweep_count"], 120);
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanListTool::new(backend);
+
+        assert_eq!(tool.name(), "specan_list");
+        assert_eq!(tool.category(), "bt-capture");
+    }
+}
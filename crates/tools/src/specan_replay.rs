@@ -0,0 +1,183 @@
+//! Spectrum recording replay tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for replaying the sweeps of a spectrum recording.
+///
+/// Returns sweeps in recorded order with their time offsets so a waterfall
+/// can be redrawn or processed after the scan.
+pub struct SpecanReplayTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl SpecanReplayTool {
+    /// Create a new specan_replay tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for SpecanReplayTool {
+    fn name(&self) -> &str {
+        "specan_replay"
+    }
+
+    fn category(&self) -> &str {
+        "bt-capture"
+    }
+
+    fn description(&self) -> &str {
+        "Replay the sweep-by-sweep waterfall of a stored spectrum recording"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "capture_id": {
+                    "type": "string",
+                    "description": "Spectrum recording to replay"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Index of the first sweep to return",
+                    "default": 0,
+                    "minimum": 0
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of sweeps to return",
+                    "default": 100,
+                    "minimum": 1
+                }
+            },
+            "required": ["capture_id"]
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "capture_id": {
+                    "type": "string"
+                },
+                "start_time": {
+                    "type": ["string", "null"]
+                },
+                "frequencies_mhz": {
+                    "type": "array",
+                    "items": { "type": "integer" },
+                    "description": "Frequency of each RSSI column"
+                },
+                "sweep_count": {
+                    "type": "integer"
+                },
+                "sweeps": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "offset_ms": { "type": "integer" },
+                            "rssi": {
+                                "type": "array",
+                                "items": { "type": ["integer", "null"] },
+                                "description": "RSSI in dBm per frequency, null if not sampled"
+                            }
+                        }
+                    }
+                },
+                "has_more": {
+                    "type": "boolean"
+                }
+            },
+            "required": ["success", "frequencies_mhz", "sweeps"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing specan_replay");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("specan_replay", params).await?;
+
+        tracing::info!("specan_replay completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, _params: Value) -> Result<Value> {
+            if method == "specan_replay" {
+                Ok(json!({
+                    "success": true,
+                    "capture_id": "specan-20260101-120000-abcd",
+                    "frequencies_mhz": [2402, 2403, 2404],
+                    "sweep_count": 2,
+                    "sweeps": [
+                        { "offset_ms": 0, "rssi": [-90, -45, null] },
+                        { "offset_ms": 100, "rssi": [-88, -50, -92] }
+                    ],
+                    "has_more": false
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_specan_replay() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanReplayTool::new(backend);
+
+        let result = tool.execute(json!({
+            "capture_id": "specan-20260101-120000-abcd",
+            "limit": 10
+        })).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["sweeps"].as_array().unwrap().len(), 2);
+        assert_eq!(result["sweeps"][0]["rssi"][1], -45);
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanReplayTool::new(backend);
+
+        assert_eq!(tool.name(), "specan_replay");
+        assert_eq!(tool.category(), "bt-capture");
+    }
+}
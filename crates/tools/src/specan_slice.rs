@@ -0,0 +1,179 @@
+//! Spectrum recording slicing tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for cutting a time window or frequency range out of a spectrum
+/// recording. The slice is stored as a new recording.
+pub struct SpecanSliceTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl SpecanSliceTool {
+    /// Create a new specan_slice tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for SpecanSliceTool {
+    fn name(&self) -> &str {
+        "specan_slice"
+    }
+
+    fn category(&self) -> &str {
+        "bt-capture"
+    }
+
+    fn description(&self) -> &str {
+        "Slice a spectrum recording by time window and frequency range into a new recording"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "capture_id": {
+                    "type": "string",
+                    "description": "Spectrum recording to slice"
+                },
+                "start_ms": {
+                    "type": "integer",
+                    "description": "Start of the window in milliseconds from the recording start",
+                    "minimum": 0
+                },
+                "end_ms": {
+                    "type": "integer",
+                    "description": "End of the window in milliseconds from the recording start",
+                    "minimum": 0
+                },
+                "low_freq": {
+                    "type": "integer",
+                    "description": "Lowest frequency to keep in MHz",
+                    "minimum": 2400,
+                    "maximum": 2500
+                },
+                "high_freq": {
+                    "type": "integer",
+                    "description": "Highest frequency to keep in MHz",
+                    "minimum": 2400,
+                    "maximum": 2500
+                }
+            },
+            "required": ["capture_id"]
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "capture_id": {
+                    "type": "string",
+                    "description": "ID of the new recording"
+                },
+                "source_capture_id": {
+                    "type": "string"
+                },
+                "low_freq_mhz": {
+                    "type": "integer"
+                },
+                "high_freq_mhz": {
+                    "type": "integer"
+                },
+                "sweep_count": {
+                    "type": "integer"
+                },
+                "recording_path": {
+                    "type": "string"
+                }
+            },
+            "required": ["success", "capture_id"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing specan_slice");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("specan_slice", params).await?;
+
+        tracing::info!("specan_slice completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, _params: Value) -> Result<Value> {
+            if method == "specan_slice" {
+                Ok(json!({
+                    "success": true,
+                    "capture_id": "specan-20260101-120500-ef01",
+                    "source_capture_id": "specan-20260101-120000-abcd",
+                    "low_freq_mhz": 2426,
+                    "high_freq_mhz": 2440,
+                    "sweep_count": 30
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_specan_slice() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanSliceTool::new(backend);
+
+        let result = tool.execute(json!({
+            "capture_id": "specan-20260101-120000-abcd",
+            "start_ms": 10000,
+            "end_ms": 40000,
+            "low_freq": 2426,
+            "high_freq": 2440
+        })).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["source_capture_id"], "specan-20260101-120000-abcd");
+        assert_eq!(result["low_freq_mhz"], 2426);
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanSliceTool::new(backend);
+
+        assert_eq!(tool.name(), "specan_slice");
+        assert_eq!(tool.category(), "bt-capture");
+    }
+}
@@ -0,0 +1,191 @@
+//! Spectrum recording summary tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for summarising a spectrum recording.
+///
+/// Reports peak hold, average level and occupancy above a threshold per
+/// frequency, plus per-minute averages to show how the band changed over
+/// the recording.
+pub struct SpecanSummaryTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl SpecanSummaryTool {
+    /// Create a new specan_summary tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for SpecanSummaryTool {
+    fn name(&self) -> &str {
+        "specan_summary"
+    }
+
+    fn category(&self) -> &str {
+        "bt-analysis"
+    }
+
+    fn description(&self) -> &str {
+        "Summarise a spectrum recording: peak hold, occupancy over threshold and per-minute averages"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "capture_id": {
+                    "type": "string",
+                    "description": "Spectrum recording to summarise"
+                },
+                "threshold_dbm": {
+                    "type": "integer",
+                    "description": "RSSI at or above which a sample counts as occupied",
+                    "default": -90,
+                    "minimum": -128,
+                    "maximum": 0
+                }
+            },
+            "required": ["capture_id"]
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "capture_id": {
+                    "type": "string"
+                },
+                "sweep_count": {
+                    "type": "integer"
+                },
+                "duration_ms": {
+                    "type": "integer"
+                },
+                "threshold_dbm": {
+                    "type": "integer"
+                },
+                "busiest_frequency_mhz": {
+                    "type": ["integer", "null"]
+                },
+                "channels": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "frequency_mhz": { "type": "integer" },
+                            "peak_dbm": { "type": ["integer", "null"] },
+                            "average_dbm": { "type": ["number", "null"] },
+                            "occupancy_percent": { "type": "number" }
+                        }
+                    }
+                },
+                "per_minute": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "minute": { "type": "integer" },
+                            "sweep_count": { "type": "integer" },
+                            "band_avg_dbm": { "type": ["number", "null"] },
+                            "channel_avg_dbm": {
+                                "type": "array",
+                                "items": { "type": ["number", "null"] }
+                            }
+                        }
+                    }
+                }
+            },
+            "required": ["success", "channels", "per_minute"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing specan_summary");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("specan_summary", params).await?;
+
+        tracing::info!("specan_summary completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, _params: Value) -> Result<Value> {
+            if method == "specan_summary" {
+                Ok(json!({
+                    "success": true,
+                    "capture_id": "specan-20260101-120000-abcd",
+                    "threshold_dbm": -90,
+                    "busiest_frequency_mhz": 2437,
+                    "channels": [
+                        { "frequency_mhz": 2437, "peak_dbm": -40, "average_dbm": -62.5, "occupancy_percent": 97.0 }
+                    ],
+                    "per_minute": [
+                        { "minute": 0, "sweep_count": 600, "band_avg_dbm": -85.1 }
+                    ]
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_specan_summary() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanSummaryTool::new(backend);
+
+        let result = tool.execute(json!({
+            "capture_id": "specan-20260101-120000-abcd",
+            "threshold_dbm": -80
+        })).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["busiest_frequency_mhz"], 2437);
+        assert_eq!(result["channels"][0]["peak_dbm"], -40);
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = SpecanSummaryTool::new(backend);
+
+        assert_eq!(tool.name(), "specan_summary");
+        assert_eq!(tool.category(), "bt-analysis");
+    }
+}
@@ -0,0 +1,224 @@
+//! Noise-floor calibration and automatic squelch tool.
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use ubertooth_core::error::Result;
+use ubertooth_core::tools::PentestTool;
+use ubertooth_platform::UbertoothBackendProvider;
+
+/// Tool for calibrating the noise floor and tuning squelch automatically.
+///
+/// Samples the noise floor per channel in spectrum mode, sets squelch a
+/// margin above it and stores a per-dongle RSSI offset so readings from
+/// several Ubertooths can be compared.
+pub struct SquelchCalibrateTool {
+    backend: Arc<dyn UbertoothBackendProvider>,
+}
+
+impl SquelchCalibrateTool {
+    /// Create a new squelch_calibrate tool.
+    pub fn new(backend: Arc<dyn UbertoothBackendProvider>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl PentestTool for SquelchCalibrateTool {
+    fn name(&self) -> &str {
+        "squelch_calibrate"
+    }
+
+    fn category(&self) -> &str {
+        "bt-config"
+    }
+
+    fn description(&self) -> &str {
+        "Measure the noise floor per channel, set squelch a margin above it and store the dongle's RSSI calibration"
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "duration_sec": {
+                    "type": "integer",
+                    "description": "How long to sample the band",
+                    "default": 5,
+                    "minimum": 1,
+                    "maximum": 60
+                },
+                "low_freq": {
+                    "type": "integer",
+                    "description": "Lowest frequency to sample in MHz",
+                    "default": 2402,
+                    "minimum": 2400,
+                    "maximum": 2500
+                },
+                "high_freq": {
+                    "type": "integer",
+                    "description": "Highest frequency to sample in MHz",
+                    "default": 2480,
+                    "minimum": 2400,
+                    "maximum": 2500
+                },
+                "margin_db": {
+                    "type": "integer",
+                    "description": "Squelch margin above the noise floor in dB",
+                    "default": 10,
+                    "minimum": 0,
+                    "maximum": 40
+                },
+                "apply": {
+                    "type": "boolean",
+                    "description": "Set the recommended squelch on the device",
+                    "default": true
+                },
+                "reference_serial": {
+                    "type": "string",
+                    "description": "Serial of an already calibrated dongle to align RSSI offsets with"
+                },
+                "reference_noise_floor_dbm": {
+                    "type": "integer",
+                    "description": "Absolute noise floor to align RSSI offsets with, when no reference dongle is given",
+                    "minimum": -128,
+                    "maximum": 0
+                }
+            }
+        })
+    }
+
+    fn output_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "success": {
+                    "type": "boolean"
+                },
+                "serial": {
+                    "type": "string"
+                },
+                "sweep_count": {
+                    "type": "integer"
+                },
+                "noise_floor_dbm": {
+                    "type": "integer",
+                    "description": "Band noise floor (median of the per-channel floors)"
+                },
+                "channels": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "frequency_mhz": { "type": "integer" },
+                            "noise_floor_dbm": { "type": ["integer", "null"] }
+                        }
+                    }
+                },
+                "margin_db": {
+                    "type": "integer"
+                },
+                "recommended_squelch_dbm": {
+                    "type": "integer"
+                },
+                "applied": {
+                    "type": "boolean"
+                },
+                "squelch_level": {
+                    "type": ["integer", "null"],
+                    "description": "Squelch read back from the device when applied"
+                },
+                "rssi_offset_db": {
+                    "type": "integer",
+                    "description": "Offset added to this dongle's RSSI readings to align them with the reference"
+                },
+                "reference": {
+                    "type": "string",
+                    "description": "none, absolute or serial:<serial>"
+                },
+                "calibrated": {
+                    "type": "string"
+                },
+                "message": {
+                    "type": "string"
+                }
+            },
+            "required": ["success", "noise_floor_dbm", "recommended_squelch_dbm", "applied"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        tracing::info!("Executing squelch_calibrate");
+        tracing::debug!("Parameters: {}", params);
+
+        let result = self.backend.call("squelch_calibrate", params).await?;
+
+        tracing::info!("squelch_calibrate completed successfully");
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ubertooth_core::error::{Result, UbertoothError};
+    use ubertooth_platform::UbertoothBackendProvider;
+
+    struct MockBackend;
+
+    #[async_trait]
+    impl UbertoothBackendProvider for MockBackend {
+        async fn call(&self, method: &str, params: Value) -> Result<Value> {
+            if method == "squelch_calibrate" {
+                let margin = params["margin_db"].as_i64().unwrap_or(10);
+                Ok(json!({
+                    "success": true,
+                    "serial": "0000000000000000a1b2c3d4",
+                    "noise_floor_dbm": -96,
+                    "margin_db": margin,
+                    "recommended_squelch_dbm": -96 + margin,
+                    "applied": true,
+                    "squelch_level": -96 + margin,
+                    "rssi_offset_db": 0,
+                    "reference": "none"
+                }))
+            } else {
+                Err(UbertoothError::BackendError("Unexpected method".to_string()))
+            }
+        }
+
+        async fn is_alive(&self) -> bool {
+            true
+        }
+
+        async fn restart(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn backend_type(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_squelch_calibrate() {
+        let backend = Arc::new(MockBackend);
+        let tool = SquelchCalibrateTool::new(backend);
+
+        let result = tool.execute(json!({ "margin_db": 6 })).await.unwrap();
+
+        assert_eq!(result["success"], true);
+        assert_eq!(result["recommended_squelch_dbm"], -90);
+        assert_eq!(result["squelch_level"], -90);
+    }
+
+    #[test]
+    fn test_tool_metadata() {
+        let backend = Arc::new(MockBackend);
+        let tool = SquelchCalibrateTool::new(backend);
+
+        assert_eq!(tool.name(), "squelch_calibrate");
+        assert_eq!(tool.category(), "bt-config");
+    }
+}
@@ -3,7 +3,7 @@
 use crate::constants::*;
 use crate::device_libusb::UbertoothDeviceLibusb;
 use crate::error::UsbError;
-use crate::protocol::{BlePacket, UsbPacket};
+use crate::protocol::{AdvertisingData, BlePacket, UsbPacket};
 use crate::async_reader::flush_usb_buffer_libusb;
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -12,6 +12,9 @@ use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::{info, warn, debug};
 use ubertooth_core::error::Result;
+use ubertooth_core::storage;
+use ubertooth_protocol::oui;
+use ubertooth_protocol::spectrum::{SpectrumRecorder, SpectrumRecording};
 
 /// High-level command executor for Ubertooth operations.
 pub struct UbertoothCommands {
@@ -92,12 +95,36 @@ impl UbertoothCommands {
 
         let info = device.device_info().unwrap();
 
+        // Report the live configuration read back from the radio
+        let radio = usb_result!(device.radio_config())?;
+        let channel = if radio.modulation == MOD_BT_LOW_ENERGY {
+            radio.ble_channel().map(|c| c as u16)
+        } else {
+            radio.frequency_mhz.checked_sub(2402)
+        };
+
         Ok(json!({
+            "success": true,
             "connected": true,
             "device_id": format!("ubertooth-{}", info.serial_number),
             "serial": info.serial_number,
+            "firmware": info.firmware_version,
             "firmware_version": info.firmware_version,
+            "board_id": info.board_id,
             "board_name": info.board_name(),
+            "channel": channel,
+            "frequency_mhz": radio.frequency_mhz,
+            "modulation": radio.modulation_name(),
+            "power_level": radio.power_level,
+            "paen": radio.paen,
+            "hgm": radio.hgm,
+            "squelch_level": radio.squelch,
+            "leds": {
+                "usr": radio.usr_led,
+                "rx": radio.rx_led,
+                "tx": radio.tx_led
+            },
+            "rail_1v8": radio.rail_1v8,
             "message": "Device connected and responsive"
         }))
     }
@@ -127,22 +154,33 @@ impl UbertoothCommands {
             .as_u64()
             .ok_or_else(|| ubertooth_core::error::UbertoothError::InvalidParameter("channel required".to_string()))? as u8;
 
-        // Validate channel
-        if channel > BLE_CHANNEL_MAX {
+        // Validate channel (BR/EDR channels 0-78, 1 MHz apart from 2402 MHz)
+        if channel > 78 {
             return usb_result!(Err(UsbError::InvalidParameter(format!(
-                "Invalid channel: {} (max: {})",
-                channel, BLE_CHANNEL_MAX
+                "Invalid channel: {} (max: 78)",
+                channel
             ))));
         }
 
+        // The firmware takes the frequency in MHz, not the channel number
+        let frequency = 2402 + channel as u16;
+
         let device = self.device.lock().await;
-        usb_result!(device.set_channel(channel))?;
+        usb_result!(device.control_transfer(CMD_SET_CHANNEL, frequency, 0, &[], USB_TIMEOUT_SHORT_MS))?;
+        let frequency_mhz = usb_result!(device.get_channel())?;
+
+        if frequency_mhz != frequency {
+            return usb_result!(Err(UsbError::InvalidPacket(format!(
+                "Channel read-back mismatch: requested {} MHz, device reports {} MHz",
+                frequency, frequency_mhz
+            ))));
+        }
 
         Ok(json!({
             "success": true,
             "channel": channel,
-            "frequency_mhz": 2402 + channel as u16,
-            "message": format!("Channel set to {} ({}MHz)", channel, 2402 + channel as u16)
+            "frequency_mhz": frequency_mhz,
+            "message": format!("Channel set to {} ({}MHz)", channel, frequency_mhz)
         }))
     }
 
@@ -153,9 +191,9 @@ impl UbertoothCommands {
             .ok_or_else(|| ubertooth_core::error::UbertoothError::InvalidParameter("modulation required".to_string()))?;
 
         let modulation = match modulation_str {
-            "bt_basic_rate" | "BR" => MOD_BT_BASIC_RATE,
-            "bt_low_energy" | "BLE" => MOD_BT_LOW_ENERGY,
-            "802.15.4" | "FHSS" => MOD_80211_FHSS,
+            "bt_basic_rate" | "BR" | "BT_BASIC_RATE" => MOD_BT_BASIC_RATE,
+            "bt_low_energy" | "BLE" | "BT_LOW_ENERGY" => MOD_BT_LOW_ENERGY,
+            "802.15.4" | "FHSS" | "80211_FHSS" => MOD_80211_FHSS,
             _ => {
                 return usb_result!(Err(UsbError::InvalidParameter(format!(
                     "Invalid modulation: {}",
@@ -167,6 +205,14 @@ impl UbertoothCommands {
         let device = self.device.lock().await;
         usb_result!(device.set_modulation(modulation))?;
 
+        let current = usb_result!(device.get_modulation())?;
+        if current != modulation {
+            return usb_result!(Err(UsbError::InvalidPacket(format!(
+                "Modulation read-back mismatch: requested {}, device reports {}",
+                modulation, current
+            ))));
+        }
+
         Ok(json!({
             "success": true,
             "modulation": modulation_str,
@@ -176,18 +222,117 @@ impl UbertoothCommands {
 
     /// Execute configure_power command.
     pub async fn configure_power(&self, params: Value) -> Result<Value> {
-        let power_dbm = params["power_dbm"]
+        let power_level = params["power_level"]
+            .as_u64()
+            .ok_or_else(|| ubertooth_core::error::UbertoothError::InvalidParameter("power_level required".to_string()))?;
+
+        if power_level > 7 {
+            return usb_result!(Err(UsbError::InvalidParameter(format!(
+                "Invalid power level: {} (must be 0-7)",
+                power_level
+            ))));
+        }
+
+        let paen = params["paen"].as_bool().unwrap_or(true);
+        let hgm = params["hgm"].as_bool().unwrap_or(false);
+        let rail_1v8 = params["rail_1v8"].as_bool();
+
+        let device = self.device.lock().await;
+        usb_result!(device.set_palevel(power_level as u8))?;
+        usb_result!(device.set_paen(paen))?;
+        usb_result!(device.set_hgm(hgm))?;
+        if let Some(on) = rail_1v8 {
+            usb_result!(device.set_1v8(on))?;
+        }
+
+        // Report what the device actually applied
+        let power_level = usb_result!(device.get_palevel())?;
+        let paen = usb_result!(device.get_paen())?;
+        let hgm = usb_result!(device.get_hgm())?;
+        let rail_1v8 = usb_result!(device.get_1v8())?;
+
+        // Rough estimate: level 0-7 spans ~0-14 dBm without PA, ~10-24 dBm with PA
+        let estimated_power_dbm = if paen {
+            10 + power_level as i64 * 2
+        } else {
+            power_level as i64 * 2
+        };
+
+        Ok(json!({
+            "success": true,
+            "power_level": power_level,
+            "paen": paen,
+            "hgm": hgm,
+            "rail_1v8": rail_1v8,
+            "estimated_power_dbm": estimated_power_dbm,
+            "message": format!(
+                "Power configured: Level {} with PA {} (~{} dBm)",
+                power_level,
+                if paen { "enabled" } else { "disabled" },
+                estimated_power_dbm
+            )
+        }))
+    }
+
+    /// Serial number of the connected device.
+    pub async fn device_serial(&self) -> Result<String> {
+        let device = self.device.lock().await;
+        let info = usb_result!(device
+            .device_info()
+            .ok_or_else(|| UsbError::InvalidPacket("No device connected".to_string())))?;
+        Ok(info.serial_number.clone())
+    }
+
+    /// Execute configure_squelch command.
+    pub async fn configure_squelch(&self, params: Value) -> Result<Value> {
+        let squelch_level = params["squelch_level"]
             .as_i64()
-            .ok_or_else(|| ubertooth_core::error::UbertoothError::InvalidParameter("power_dbm required".to_string()))?
-            as i8;
+            .ok_or_else(|| ubertooth_core::error::UbertoothError::InvalidParameter("squelch_level required".to_string()))?;
+
+        if !(-128..=0).contains(&squelch_level) {
+            return usb_result!(Err(UsbError::InvalidParameter(format!(
+                "Invalid squelch level: {} (must be -128 to 0 dBm)",
+                squelch_level
+            ))));
+        }
+
+        let device = self.device.lock().await;
+        usb_result!(device.set_squelch(squelch_level as i8))?;
+        let squelch_level = usb_result!(device.get_squelch())?;
+
+        Ok(json!({
+            "success": true,
+            "squelch_level": squelch_level,
+            "message": format!("Squelch set to {} dBm", squelch_level)
+        }))
+    }
+
+    /// Execute configure_leds command.
+    pub async fn configure_leds(&self, params: Value) -> Result<Value> {
+        let usr_led = params["usr_led"].as_bool().unwrap_or(true);
+        let rx_led = params["rx_led"].as_bool().unwrap_or(false);
+        let tx_led = params["tx_led"].as_bool().unwrap_or(false);
 
         let device = self.device.lock().await;
-        usb_result!(device.set_power(power_dbm))?;
+        usb_result!(device.set_usrled(usr_led))?;
+        usb_result!(device.set_rxled(rx_led))?;
+        usb_result!(device.set_txled(tx_led))?;
+
+        let leds = json!({
+            "usr": usb_result!(device.get_usrled())?,
+            "rx": usb_result!(device.get_rxled())?,
+            "tx": usb_result!(device.get_txled())?
+        });
 
         Ok(json!({
             "success": true,
-            "power_dbm": power_dbm,
-            "message": format!("Transmit power set to {} dBm", power_dbm)
+            "leds": leds,
+            "message": format!(
+                "LEDs set: USR {}, RX {}, TX {}",
+                if usr_led { "on" } else { "off" },
+                if rx_led { "on" } else { "off" },
+                if tx_led { "on" } else { "off" }
+            )
         }))
     }
 
@@ -268,16 +413,10 @@ impl UbertoothCommands {
 
         // Save PCAP if requested
         let pcap_path = if save_pcap {
-            let path = format!(
-                "/home/{}/.ubertooth/captures/{}.pcap",
-                std::env::var("USER").unwrap_or_else(|_| "user".to_string()),
-                capture_id
-            );
-
-            // Create captures directory
-            if let Some(parent) = std::path::Path::new(&path).parent() {
-                std::fs::create_dir_all(parent)?;
-            }
+            let path = storage::captures_dir()?
+                .join(format!("{}.pcap", capture_id))
+                .to_string_lossy()
+                .to_string();
 
             // TODO: Write PCAP file (will be implemented in pcap.rs)
             // For now, return the intended path
@@ -291,12 +430,16 @@ impl UbertoothCommands {
             .devices
             .into_iter()
             .map(|(mac, dev)| {
+                // Random addresses carry no OUI
+                let vendor = oui::vendor_for(&mac, dev.address_type == "random");
                 json!({
                     "mac_address": mac,
                     "address_type": dev.address_type,
+                    "vendor": vendor,
                     "device_name": dev.name.unwrap_or_else(|| "Unknown".to_string()),
                     "rssi_avg": dev.rssi_avg,
-                    "packet_count": dev.packet_count
+                    "packet_count": dev.packet_count,
+                    "advertising_data": dev.advertising.as_ref().map(AdvertisingData::to_json_summary)
                 })
             })
             .collect();
@@ -366,12 +509,22 @@ impl UbertoothCommands {
                                                     rssi_sum: 0,
                                                     packet_count: 0,
                                                     rssi_avg: 0,
+                                                    advertising: None,
                                                 });
 
                                                 stats.packet_count += 1;
                                                 stats.rssi_sum += ble_pkt.rssi as i32;
                                                 stats.rssi_avg = stats.rssi_sum / stats.packet_count as i32;
 
+                                                // Accumulate AD structures across ADV_* and SCAN_RSP
+                                                if let Ok(ad_data) = ble_pkt.parse_advertising_data() {
+                                                    stats.address_type = ad_data.address_type.as_str().to_string();
+                                                    match stats.advertising.as_mut() {
+                                                        Some(existing) => existing.merge(&ad_data),
+                                                        None => stats.advertising = Some(ad_data),
+                                                    }
+                                                }
+
                                                 // Try to extract device name
                                                 if stats.name.is_none() {
                                                     if let Some(name) = ble_pkt.device_name() {
@@ -522,16 +675,10 @@ impl UbertoothCommands {
 
         // Save PCAP if requested
         let pcap_path = if save_pcap {
-            let path = format!(
-                "/home/{}/.ubertooth/captures/{}.pcap",
-                std::env::var("USER").unwrap_or_else(|_| "user".to_string()),
-                capture_id
-            );
-
-            // Ensure parent directory exists
-            if let Some(parent) = std::path::Path::new(&path).parent() {
-                let _ = std::fs::create_dir_all(parent);
-            }
+            let path = storage::captures_dir()?
+                .join(format!("{}.pcap", capture_id))
+                .to_string_lossy()
+                .to_string();
 
             // TODO: Write PCAP file (will be implemented in pcap.rs)
             Some(path)
@@ -544,12 +691,16 @@ impl UbertoothCommands {
             .devices
             .into_iter()
             .map(|(mac, dev)| {
+                // Random addresses carry no OUI
+                let vendor = oui::vendor_for(&mac, dev.address_type == "random");
                 json!({
                     "mac_address": mac,
                     "address_type": dev.address_type,
+                    "vendor": vendor,
                     "device_name": dev.name.unwrap_or_else(|| "Unknown".to_string()),
                     "rssi_avg": dev.rssi_avg,
-                    "packet_count": dev.packet_count
+                    "packet_count": dev.packet_count,
+                    "advertising_data": dev.advertising.as_ref().map(AdvertisingData::to_json_summary)
                 })
             })
             .collect();
@@ -570,9 +721,20 @@ impl UbertoothCommands {
 
     /// Execute bt_specan command (spectrum analysis).
     pub async fn bt_specan(&self, params: Value) -> Result<Value> {
+        self.bt_specan_recording(params).await.map(|(result, _)| result)
+    }
+
+    /// Execute bt_specan and also return the sweep-by-sweep waterfall recording.
+    pub async fn bt_specan_recording(&self, params: Value) -> Result<(Value, SpectrumRecording)> {
         let duration_sec = params["duration_sec"].as_u64().unwrap_or(10);
-        let low_freq = params["low_frequency_mhz"].as_u64().unwrap_or(2402) as u16;
-        let high_freq = params["high_frequency_mhz"].as_u64().unwrap_or(2480) as u16;
+        let low_freq = params["low_freq"]
+            .as_u64()
+            .or_else(|| params["low_frequency_mhz"].as_u64())
+            .unwrap_or(2402) as u16;
+        let high_freq = params["high_freq"]
+            .as_u64()
+            .or_else(|| params["high_frequency_mhz"].as_u64())
+            .unwrap_or(2480) as u16;
 
         info!(
             "Starting spectrum analysis: duration={}s, range={}-{} MHz",
@@ -606,7 +768,7 @@ impl UbertoothCommands {
         tokio::time::sleep(Duration::from_millis(200)).await;
 
         // Collect spectrum data
-        let spectrum_result = self.scan_spectrum_data(duration_sec).await?;
+        let spectrum_result = self.scan_spectrum_data(duration_sec, low_freq, high_freq).await?;
 
         // Stop scanning
         let device = self.device.lock().await;
@@ -634,7 +796,7 @@ impl UbertoothCommands {
             })
             .collect();
 
-        Ok(json!({
+        let result = json!({
             "success": true,
             "duration_sec": duration_sec,
             "low_frequency_mhz": low_freq,
@@ -643,15 +805,23 @@ impl UbertoothCommands {
             "total_samples": spectrum_result.total_samples,
             "spectrum_data": channel_data,
             "message": format!("Analyzed {} channels over {} sweeps", channel_data.len(), spectrum_result.sweep_count)
-        }))
+        });
+
+        Ok((result, spectrum_result.recording))
     }
 
     /// Scan and collect spectrum data.
-    async fn scan_spectrum_data(&self, duration_sec: u64) -> Result<SpectrumScanResult> {
+    async fn scan_spectrum_data(&self, duration_sec: u64, low_freq: u16, high_freq: u16) -> Result<SpectrumScanResult> {
         let mut channel_stats: HashMap<u8, ChannelStats> = HashMap::new();
         let mut sweep_count = 0;
         let mut total_samples = 0;
 
+        let start_unix_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let mut recorder = SpectrumRecorder::new(low_freq, high_freq, start_unix_ms, "rust-usb");
+
         info!("Collecting spectrum data for {}s using CMD_POLL...", duration_sec);
 
         let start = tokio::time::Instant::now();
@@ -674,8 +844,10 @@ impl UbertoothCommands {
                                     sweep_count += 1;
                                     let point_count = points.len();
 
+                                    let offset_ms = start.elapsed().as_millis() as u32;
                                     for point in points {
                                         total_samples += 1;
+                                        recorder.push_sample(offset_ms, point.frequency_mhz, point.rssi);
 
                                         let stats = channel_stats
                                             .entry(point.channel)
@@ -725,6 +897,7 @@ impl UbertoothCommands {
             channel_stats,
             sweep_count,
             total_samples,
+            recording: recorder.finish(),
         })
     }
 }
@@ -737,6 +910,7 @@ struct DeviceStats {
     rssi_sum: i32,
     packet_count: usize,
     rssi_avg: i32,
+    advertising: Option<AdvertisingData>,
 }
 
 /// Scan result structure.
@@ -753,6 +927,7 @@ struct SpectrumScanResult {
     channel_stats: HashMap<u8, ChannelStats>,
     sweep_count: usize,
     total_samples: usize,
+    recording: SpectrumRecording,
 }
 
 /// Channel statistics for spectrum analysis.
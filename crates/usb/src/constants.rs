@@ -100,6 +100,7 @@ pub const CMD_EGO: u8 = 60;
 pub const CMD_AFH: u8 = 61;
 pub const CMD_HOP: u8 = 62;
 pub const CMD_TRIM_CLOCK: u8 = 63;
+pub const CMD_GET_API_VERSION: u8 = 64;
 pub const CMD_WRITE_REGISTERS: u8 = 65;
 pub const CMD_READ_ALL_REGISTERS: u8 = 66;
 pub const CMD_RX_GENERIC: u8 = 67;
@@ -112,7 +113,6 @@ pub const CMD_XMAS: u8 = 73;
 
 // Legacy/Aliased Commands (may not exist in firmware, kept for compatibility)
 pub const CMD_SET_POWER: u8 = CMD_SET_PALEVEL; // Alias for PA level
-pub const CMD_GET_API_VERSION: u8 = CMD_HOP; // TODO: Verify this mapping
 
 // Jam Modes
 pub const JAM_NONE: u8 = 0;
@@ -7,6 +7,7 @@ use rusb::{Context, Device, DeviceHandle, UsbContext};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tracing::{debug, info, warn};
+use ubertooth_protocol::compat::ApiVersion;
 
 /// Ubertooth USB device handle with connection management.
 pub struct UbertoothDevice {
@@ -410,14 +411,10 @@ impl UbertoothDevice {
             USB_TIMEOUT_SHORT_MS,
         )?;
 
-        if len >= 4 {
-            Ok(format!(
-                "{}.{}.{}",
-                buffer[0], buffer[1], buffer[2]
-            ))
-        } else {
-            Ok("unknown".to_string())
-        }
+        // Little-endian `major << 8 | minor`, printed like the tools do (e.g. 1.07)
+        Ok(ApiVersion::from_le_bytes(&buffer[..len])
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "unknown".to_string()))
     }
 
     /// Get serial number.
@@ -6,10 +6,11 @@
 use crate::constants::*;
 use crate::error::{Result, UsbError};
 use crate::libusb_ffi::*;
-use crate::protocol::DeviceInfo;
+use crate::protocol::{DeviceInfo, RadioConfig};
 use std::ffi::c_void;
 use std::ptr;
 use tracing::{debug, info, warn};
+use ubertooth_protocol::compat::ApiVersion;
 
 /// Ubertooth device using pure libusb-1.0
 pub struct UbertoothDeviceLibusb {
@@ -321,6 +322,143 @@ impl UbertoothDeviceLibusb {
         Ok(())
     }
 
+    /// Read a single-byte value (CMD_GET_*)
+    fn read_u8(&self, request: u8) -> Result<u8> {
+        let mut buffer = [0u8; 1];
+        let len = self.control_transfer_in(request, 0, 0, &mut buffer, USB_TIMEOUT_SHORT_MS)?;
+        if len < 1 {
+            return Err(UsbError::InvalidPacket(format!("Empty response to command {}", request)));
+        }
+        Ok(buffer[0])
+    }
+
+    /// Send an on/off value (CMD_SET_*)
+    fn set_flag(&self, request: u8, on: bool) -> Result<()> {
+        self.control_transfer(request, on as u16, 0, &[], USB_TIMEOUT_SHORT_MS)?;
+        Ok(())
+    }
+
+    /// Get current frequency in MHz
+    pub fn get_channel(&self) -> Result<u16> {
+        let mut buffer = [0u8; 2];
+        let len = self.control_transfer_in(CMD_GET_CHANNEL, 0, 0, &mut buffer, USB_TIMEOUT_SHORT_MS)?;
+        if len < 2 {
+            return Err(UsbError::InvalidPacket("Short GET_CHANNEL response".to_string()));
+        }
+        Ok(u16::from_le_bytes(buffer))
+    }
+
+    /// Get modulation mode
+    pub fn get_modulation(&self) -> Result<u8> {
+        self.read_u8(CMD_GET_MOD)
+    }
+
+    /// Set CC2400 PA level (0-7)
+    pub fn set_palevel(&self, level: u8) -> Result<()> {
+        if level > 7 {
+            return Err(UsbError::InvalidParameter(format!("PA level {} out of range (0-7)", level)));
+        }
+        debug!("Setting PA level to {}", level);
+        self.control_transfer(CMD_SET_PALEVEL, level as u16, 0, &[], USB_TIMEOUT_SHORT_MS)?;
+        Ok(())
+    }
+
+    /// Get CC2400 PA level
+    pub fn get_palevel(&self) -> Result<u8> {
+        self.read_u8(CMD_GET_PALEVEL)
+    }
+
+    /// Enable or disable the power amplifier
+    pub fn set_paen(&self, on: bool) -> Result<()> {
+        debug!("Setting PA enable to {}", on);
+        self.set_flag(CMD_SET_PAEN, on)
+    }
+
+    /// Get power amplifier state
+    pub fn get_paen(&self) -> Result<bool> {
+        Ok(self.read_u8(CMD_GET_PAEN)? != 0)
+    }
+
+    /// Enable or disable high gain mode
+    pub fn set_hgm(&self, on: bool) -> Result<()> {
+        debug!("Setting high gain mode to {}", on);
+        self.set_flag(CMD_SET_HGM, on)
+    }
+
+    /// Get high gain mode state
+    pub fn get_hgm(&self) -> Result<bool> {
+        Ok(self.read_u8(CMD_GET_HGM)? != 0)
+    }
+
+    /// Set RSSI squelch threshold in dBm
+    pub fn set_squelch(&self, squelch: i8) -> Result<()> {
+        debug!("Setting squelch to {}", squelch);
+        self.control_transfer(CMD_SET_SQUELCH, squelch as u8 as u16, 0, &[], USB_TIMEOUT_SHORT_MS)?;
+        Ok(())
+    }
+
+    /// Get RSSI squelch threshold in dBm
+    pub fn get_squelch(&self) -> Result<i8> {
+        Ok(self.read_u8(CMD_GET_SQUELCH)? as i8)
+    }
+
+    /// Set user LED
+    pub fn set_usrled(&self, on: bool) -> Result<()> {
+        self.set_flag(CMD_SET_USRLED, on)
+    }
+
+    /// Get user LED state
+    pub fn get_usrled(&self) -> Result<bool> {
+        Ok(self.read_u8(CMD_GET_USRLED)? != 0)
+    }
+
+    /// Set RX LED
+    pub fn set_rxled(&self, on: bool) -> Result<()> {
+        self.set_flag(CMD_SET_RXLED, on)
+    }
+
+    /// Get RX LED state
+    pub fn get_rxled(&self) -> Result<bool> {
+        Ok(self.read_u8(CMD_GET_RXLED)? != 0)
+    }
+
+    /// Set TX LED
+    pub fn set_txled(&self, on: bool) -> Result<()> {
+        self.set_flag(CMD_SET_TXLED, on)
+    }
+
+    /// Get TX LED state
+    pub fn get_txled(&self) -> Result<bool> {
+        Ok(self.read_u8(CMD_GET_TXLED)? != 0)
+    }
+
+    /// Enable or disable the 1.8V rail
+    pub fn set_1v8(&self, on: bool) -> Result<()> {
+        debug!("Setting 1.8V rail to {}", on);
+        self.set_flag(CMD_SET_1V8, on)
+    }
+
+    /// Get 1.8V rail state
+    pub fn get_1v8(&self) -> Result<bool> {
+        Ok(self.read_u8(CMD_GET_1V8)? != 0)
+    }
+
+    /// Read back the complete live radio configuration
+    pub fn radio_config(&self) -> Result<RadioConfig> {
+        Ok(RadioConfig {
+            frequency_mhz: self.get_channel()?,
+            modulation: self.get_modulation()?,
+            power_level: self.get_palevel()?,
+            paen: self.get_paen()?,
+            hgm: self.get_hgm()?,
+            squelch: self.get_squelch()?,
+            usr_led: self.get_usrled()?,
+            rx_led: self.get_rxled()?,
+            tx_led: self.get_txled()?,
+            rail_1v8: self.get_1v8()?,
+        })
+    }
+
     /// Get raw device handle for async operations
     pub(crate) fn raw_handle(&self) -> Option<*mut c_void> {
         self.handle
@@ -394,9 +532,26 @@ impl UbertoothDeviceLibusb {
             _ => "unknown".to_string(),
         };
 
+        // Get USB API version
+        let api_version = match self.control_transfer_in(
+            CMD_GET_API_VERSION,
+            0,
+            0,
+            &mut buffer[..4],
+            USB_TIMEOUT_SHORT_MS,
+        ) {
+            Ok(len) => ApiVersion::from_le_bytes(&buffer[..len])
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            Err(_) => {
+                debug!("Failed to get API version, using default");
+                "unknown".to_string()
+            }
+        };
+
         self.device_info = Some(DeviceInfo {
             firmware_version: firmware_version.clone(),
-            api_version: "1.07".to_string(), // Current Ubertooth API version
+            api_version,
             board_id,
             serial_number,
             compile_info: firmware_version.clone(),
@@ -7,6 +7,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
 use tracing::{debug, info, warn};
+use ubertooth_protocol::compat::ApiVersion;
 
 /// Ubertooth USB device handle with async support.
 pub struct UbertoothDevice {
@@ -484,11 +485,10 @@ impl UbertoothDevice {
             )
             .await?;
 
-        if len >= 4 {
-            Ok(format!("{}.{}.{}", buffer[0], buffer[1], buffer[2]))
-        } else {
-            Ok("unknown".to_string())
-        }
+        // Little-endian `major << 8 | minor`, printed like the tools do (e.g. 1.07)
+        Ok(ApiVersion::from_le_bytes(&buffer[..len])
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "unknown".to_string()))
     }
 
     /// Get serial number.
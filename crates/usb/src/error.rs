@@ -93,6 +93,13 @@ impl UsbError {
     }
 }
 
+/// Protocol parsing errors surface as invalid packets.
+impl From<ubertooth_protocol::ProtocolError> for UsbError {
+    fn from(err: ubertooth_protocol::ProtocolError) -> Self {
+        UsbError::InvalidPacket(err.to_string())
+    }
+}
+
 /// Convert USB errors to core Ubertooth errors.
 impl From<UsbError> for ubertooth_core::error::UbertoothError {
     fn from(err: UsbError) -> Self {
@@ -53,7 +53,7 @@ pub mod libusb_async;
 pub use constants::*;
 pub use device::UbertoothDevice;
 pub use error::{Result, UsbError};
-pub use protocol::{BlePacket, DeviceInfo, UsbPacket};
+pub use protocol::{BlePacket, DeviceInfo, RadioConfig, UsbPacket};
 pub use commands::UbertoothCommands;
 pub use async_reader::{AsyncPacketReader, flush_usb_buffer};
 pub use libusb_async::LibusbStreamReader;
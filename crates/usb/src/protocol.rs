@@ -4,6 +4,8 @@ use crate::constants::*;
 use crate::error::{Result, UsbError};
 use serde::{Deserialize, Serialize};
 
+pub use ubertooth_protocol::advertising::{AddressType, AdvertisingData};
+
 /// USB packet header structure (14 bytes).
 ///
 /// This matches the usb_pkt_rx structure from the Ubertooth firmware.
@@ -286,178 +288,11 @@ impl BlePacket {
             return Err(UsbError::InvalidPacket("Not an advertising packet".to_string()));
         }
 
-        AdvertisingData::parse(&self.payload)
-    }
-}
-
-/// Parsed BLE advertising data structures
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AdvertisingData {
-    /// Advertiser address (first 6 bytes)
-    pub address: [u8; 6],
-
-    /// Address type (public/random from PDU header)
-    pub address_type: AddressType,
-
-    /// Complete or shortened local name
-    pub name: Option<String>,
-
-    /// Flags
-    pub flags: Option<u8>,
-
-    /// TX power level (dBm)
-    pub tx_power: Option<i8>,
-
-    /// Service UUIDs (16-bit)
-    pub service_uuids_16: Vec<u16>,
-
-    /// Service UUIDs (128-bit)
-    pub service_uuids_128: Vec<u128>,
-
-    /// Manufacturer specific data (company ID + data)
-    pub manufacturer_data: Option<(u16, Vec<u8>)>,
-
-    /// Service data
-    pub service_data: Vec<(u16, Vec<u8>)>,
-
-    /// Raw AD structures for unparsed types
-    pub raw_ad_structures: Vec<(u8, Vec<u8>)>,
-}
-
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub enum AddressType {
-    Public,
-    Random,
-}
-
-impl AdvertisingData {
-    /// Parse advertising data from BLE payload
-    pub fn parse(payload: &[u8]) -> Result<Self> {
-        if payload.len() < 6 {
-            return Err(UsbError::InvalidPacket("Payload too short for address".to_string()));
-        }
-
-        // Extract address (first 6 bytes)
-        let mut address = [0u8; 6];
-        address.copy_from_slice(&payload[0..6]);
-
-        let mut ad_data = Self {
-            address,
-            address_type: AddressType::Public, // Will be set from PDU header
-            name: None,
-            flags: None,
-            tx_power: None,
-            service_uuids_16: Vec::new(),
-            service_uuids_128: Vec::new(),
-            manufacturer_data: None,
-            service_data: Vec::new(),
-            raw_ad_structures: Vec::new(),
-        };
-
-        // Parse AD structures starting after address
-        if payload.len() <= 6 {
-            return Ok(ad_data);
-        }
-
-        let ad_bytes = &payload[6..];
-        let mut offset = 0;
-
-        while offset < ad_bytes.len() {
-            if offset + 1 >= ad_bytes.len() {
-                break;
-            }
-
-            let length = ad_bytes[offset] as usize;
-            if length == 0 {
-                break;
-            }
-
-            if offset + 1 + length > ad_bytes.len() {
-                break; // Incomplete structure
-            }
-
-            let ad_type = ad_bytes[offset + 1];
-            let data = &ad_bytes[offset + 2..offset + 1 + length];
-
-            match ad_type {
-                0x01 => {
-                    // Flags
-                    if !data.is_empty() {
-                        ad_data.flags = Some(data[0]);
-                    }
-                }
-                0x08 | 0x09 => {
-                    // Shortened (0x08) or Complete (0x09) Local Name
-                    if let Ok(name) = String::from_utf8(data.to_vec()) {
-                        ad_data.name = Some(name);
-                    }
-                }
-                0x0A => {
-                    // TX Power Level
-                    if !data.is_empty() {
-                        ad_data.tx_power = Some(data[0] as i8);
-                    }
-                }
-                0x02 | 0x03 => {
-                    // 16-bit Service UUIDs (incomplete/complete)
-                    for chunk in data.chunks(2) {
-                        if chunk.len() == 2 {
-                            let uuid = u16::from_le_bytes([chunk[0], chunk[1]]);
-                            ad_data.service_uuids_16.push(uuid);
-                        }
-                    }
-                }
-                0x06 | 0x07 => {
-                    // 128-bit Service UUIDs (incomplete/complete)
-                    for chunk in data.chunks(16) {
-                        if chunk.len() == 16 {
-                            let mut bytes = [0u8; 16];
-                            bytes.copy_from_slice(chunk);
-                            let uuid = u128::from_le_bytes(bytes);
-                            ad_data.service_uuids_128.push(uuid);
-                        }
-                    }
-                }
-                0xFF => {
-                    // Manufacturer Specific Data
-                    if data.len() >= 2 {
-                        let company_id = u16::from_le_bytes([data[0], data[1]]);
-                        let mfg_data = data[2..].to_vec();
-                        ad_data.manufacturer_data = Some((company_id, mfg_data));
-                    }
-                }
-                0x16 => {
-                    // Service Data - 16-bit UUID
-                    if data.len() >= 2 {
-                        let uuid = u16::from_le_bytes([data[0], data[1]]);
-                        let service_data = data[2..].to_vec();
-                        ad_data.service_data.push((uuid, service_data));
-                    }
-                }
-                _ => {
-                    // Store unknown AD types
-                    ad_data.raw_ad_structures.push((ad_type, data.to_vec()));
-                }
-            }
-
-            offset += 1 + length;
-        }
-
+        let mut ad_data = AdvertisingData::parse(&self.payload)?;
+        // TxAdd (bit 6) selects public or random advertiser address
+        ad_data.address_type = AddressType::from_bit(self.pdu_header & 0x40 != 0);
         Ok(ad_data)
     }
-
-    /// Format address as MAC string (XX:XX:XX:XX:XX:XX)
-    pub fn address_string(&self) -> String {
-        format!(
-            "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
-            self.address[5],
-            self.address[4],
-            self.address[3],
-            self.address[2],
-            self.address[1],
-            self.address[0]
-        )
-    }
 }
 
 /// Spectrum analysis data point.
@@ -562,6 +397,63 @@ impl DeviceInfo {
     }
 }
 
+/// Live radio configuration read back from the device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RadioConfig {
+    /// Current frequency in MHz (2402-2480)
+    pub frequency_mhz: u16,
+
+    /// Modulation (MOD_* constant)
+    pub modulation: u8,
+
+    /// CC2400 PA level (0-7)
+    pub power_level: u8,
+
+    /// Power amplifier enabled
+    pub paen: bool,
+
+    /// High gain mode enabled
+    pub hgm: bool,
+
+    /// RSSI squelch threshold in dBm
+    pub squelch: i8,
+
+    /// LED states
+    pub usr_led: bool,
+    pub rx_led: bool,
+    pub tx_led: bool,
+
+    /// 1.8V rail enabled
+    pub rail_1v8: bool,
+}
+
+impl RadioConfig {
+    /// Modulation name as accepted by configure_modulation.
+    pub fn modulation_name(&self) -> &'static str {
+        match self.modulation {
+            MOD_BT_BASIC_RATE => "bt_basic_rate",
+            MOD_BT_LOW_ENERGY => "bt_low_energy",
+            MOD_80211_FHSS => "802.15.4",
+            _ => "unknown",
+        }
+    }
+
+    /// BLE channel index (0-39) for the current frequency, if it is on one.
+    pub fn ble_channel(&self) -> Option<u8> {
+        match self.frequency_mhz {
+            2402 => Some(37),
+            2426 => Some(38),
+            2480 => Some(39),
+            f if (2404..=2478).contains(&f) && f % 2 == 0 => {
+                let index = ((f - 2404) / 2) as u8;
+                // Data channels skip the advertising channel at 2426 MHz
+                Some(if index < 11 { index } else { index - 1 })
+            }
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -616,4 +508,49 @@ mod tests {
         assert_eq!(packet.header.channel, 37);
         assert_eq!(packet.payload.len(), 4);
     }
+
+    #[test]
+    fn test_radio_config_channel() {
+        let mut config = RadioConfig {
+            frequency_mhz: 2402,
+            modulation: MOD_BT_LOW_ENERGY,
+            power_level: 7,
+            paen: true,
+            hgm: false,
+            squelch: -90,
+            usr_led: true,
+            rx_led: false,
+            tx_led: false,
+            rail_1v8: true,
+        };
+
+        assert_eq!(config.ble_channel(), Some(37));
+        assert_eq!(config.modulation_name(), "bt_low_energy");
+        config.frequency_mhz = 2428;
+        assert_eq!(config.ble_channel(), Some(11));
+        config.frequency_mhz = 2441;
+        assert_eq!(config.ble_channel(), None);
+    }
+
+    #[test]
+    fn test_parse_advertising_data_address_type() {
+        let mut payload = vec![0x66, 0x55, 0x44, 0x33, 0x22, 0x11];
+        payload.extend_from_slice(&[0x02, 0x01, 0x06, 0x03, 0x19, 0xC1, 0x03]);
+        let packet = BlePacket {
+            access_address: 0x8E89BED6,
+            pdu_header: 0x40, // ADV_IND, TxAdd = random
+            length: payload.len() as u8,
+            payload,
+            crc: [0; 3],
+            rssi: -60,
+            channel: 37,
+            timestamp: 0,
+        };
+
+        let ad = packet.parse_advertising_data().unwrap();
+
+        assert!(matches!(ad.address_type, AddressType::Random));
+        assert_eq!(ad.flags, Some(0x06));
+        assert_eq!(ad.appearance, Some(0x03C1));
+    }
 }
@@ -106,7 +106,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                                 println!("   Services (128-bit): {} UUIDs", ad_data.service_uuids_128.len());
                                             }
 
-                                            if let Some((company_id, data)) = &ad_data.manufacturer_data {
+                                            for (company_id, data) in &ad_data.manufacturer_data {
                                                 println!("   Manufacturer: 0x{:04X} ({} bytes)",
                                                     company_id, data.len());
                                             }
@@ -0,0 +1,127 @@
+#!/usr/bin/env python3
+"""Regenerate the embedded Bluetooth SIG assigned-numbers tables.
+
+Reads the YAML files from a local checkout of the Bluetooth SIG public
+repository and writes crates/protocol/src/assigned_numbers/data.rs.
+
+Usage:
+    git clone https://bitbucket.org/bluetooth-SIG/public.git /tmp/bluetooth-sig
+    python3 scripts/update-assigned-numbers.py /tmp/bluetooth-sig
+
+Requires PyYAML (pip install pyyaml). The connector itself never needs
+network access: the generated tables are compiled into the binary.
+"""
+
+import argparse
+import sys
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parent.parent
+OUTPUT = REPO_ROOT / "crates/protocol/src/assigned_numbers/data.rs"
+
+# Source files inside the SIG repository
+COMPANY_IDS = "assigned_numbers/company_identifiers/company_identifiers.yaml"
+SERVICE_UUIDS = "assigned_numbers/uuids/service_uuids.yaml"
+CHARACTERISTIC_UUIDS = "assigned_numbers/uuids/characteristic_uuids.yaml"
+DESCRIPTOR_UUIDS = "assigned_numbers/uuids/descriptors.yaml"
+MEMBER_UUIDS = "assigned_numbers/uuids/member_uuids.yaml"
+APPEARANCE = "assigned_numbers/core/appearance_values.yaml"
+AD_TYPES = "assigned_numbers/core/ad_types.yaml"
+
+
+def load_yaml(root, relative):
+    import yaml
+
+    with open(root / relative, encoding="utf-8") as fh:
+        return yaml.safe_load(fh)
+
+
+def load_sig_tables(root):
+    """Build the table dictionary from the SIG YAML files."""
+    tables = {}
+
+    tables["company_ids"] = [
+        (int(e["value"]), e["name"])
+        for e in load_yaml(root, COMPANY_IDS)["company_identifiers"]
+    ]
+
+    for key, path in (
+        ("service_uuids", SERVICE_UUIDS),
+        ("characteristic_uuids", CHARACTERISTIC_UUIDS),
+        ("descriptor_uuids", DESCRIPTOR_UUIDS),
+        ("member_uuids", MEMBER_UUIDS),
+    ):
+        tables[key] = [(int(e["uuid"]), e["name"]) for e in load_yaml(root, path)["uuids"]]
+
+    categories = []
+    subcategories = []
+    for category in load_yaml(root, APPEARANCE)["appearance_values"]:
+        cat = int(category["category"])
+        categories.append((cat, category["name"]))
+        for sub in category.get("subcategory", []) or []:
+            subcategories.append(((cat << 6) | int(sub["value"]), sub["name"]))
+    tables["appearance_categories"] = categories
+    tables["appearance_subcategories"] = subcategories
+
+    tables["ad_types"] = [(int(e["value"]), e["name"]) for e in load_yaml(root, AD_TYPES)["ad_types"]]
+
+    return tables
+
+
+def rust_str(value):
+    return '"' + value.replace("\\", "\\\\").replace('"', '\\"') + '"'
+
+
+def emit_table(out, name, key_type, width, entries, doc):
+    # Tables are sorted and de-duplicated so lookups can binary search
+    seen = {}
+    for key, value in entries:
+        seen.setdefault(key, value.strip())
+
+    out.append(f"/// {doc}")
+    out.append(f"pub(crate) static {name}: &[({key_type}, &str)] = &[")
+    for key in sorted(seen):
+        out.append(f"    (0x{key:0{width}X}, {rust_str(seen[key])}),")
+    out.append("];")
+    out.append("")
+
+
+def emit(tables, source):
+    out = [
+        "//! Bluetooth SIG assigned-numbers tables.",
+        "//!",
+        "//! @generated by scripts/update-assigned-numbers.py - do not edit by hand.",
+        f"//! Source: {source}",
+        "",
+    ]
+    emit_table(out, "COMPANY_IDS", "u16", 4, tables["company_ids"], "Company identifiers (manufacturer specific data).")
+    emit_table(out, "SERVICE_UUIDS", "u16", 4, tables["service_uuids"], "16-bit GATT service UUIDs.")
+    emit_table(out, "MEMBER_UUIDS", "u16", 4, tables["member_uuids"], "16-bit UUIDs assigned to SIG member companies.")
+    emit_table(out, "CHARACTERISTIC_UUIDS", "u16", 4, tables["characteristic_uuids"], "16-bit GATT characteristic UUIDs.")
+    emit_table(out, "DESCRIPTOR_UUIDS", "u16", 4, tables["descriptor_uuids"], "16-bit GATT descriptor UUIDs.")
+    emit_table(out, "APPEARANCE_CATEGORIES", "u16", 3, tables["appearance_categories"], "Appearance categories (upper 10 bits of the appearance value).")
+    emit_table(out, "APPEARANCE_SUBCATEGORIES", "u16", 4, tables["appearance_subcategories"], "Full appearance values with a subcategory.")
+    emit_table(out, "AD_TYPES", "u8", 2, tables["ad_types"], "GAP advertising data types.")
+    return "\n".join(out)
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__, formatter_class=argparse.RawDescriptionHelpFormatter)
+    parser.add_argument("sig_repo", type=Path, help="Path to a checkout of bitbucket.org/bluetooth-SIG/public")
+    parser.add_argument("-o", "--output", type=Path, default=OUTPUT, help="Output Rust file")
+    args = parser.parse_args()
+
+    try:
+        tables = load_sig_tables(args.sig_repo)
+    except ImportError:
+        sys.exit("PyYAML is required: pip install pyyaml")
+    except (OSError, KeyError) as err:
+        sys.exit(f"Failed to read SIG assigned numbers: {err}")
+
+    args.output.write_text(emit(tables, "bitbucket.org/bluetooth-SIG/public"), encoding="utf-8")
+    counts = ", ".join(f"{k}={len(v)}" for k, v in tables.items())
+    print(f"Wrote {args.output} ({counts})")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,61 @@
+#!/usr/bin/env python3
+"""Regenerate the bundled IEEE OUI registry (crates/protocol/data/oui.csv.gz).
+
+Takes the IEEE MA-L, MA-M and MA-S CSV exports and writes a compact,
+gzip-compressed "Registry,Assignment,Organization Name" file (addresses are
+dropped to keep the binary small).
+
+Usage:
+    curl -LO https://standards-oui.ieee.org/oui/oui.csv
+    curl -LO https://standards-oui.ieee.org/oui28/mam.csv
+    curl -LO https://standards-oui.ieee.org/oui36/oui36.csv
+    python3 scripts/update-oui.py oui.csv mam.csv oui36.csv
+
+To refresh vendor names at runtime without rebuilding, pass the same CSV
+files to the `oui_update` tool instead.
+"""
+
+import argparse
+import csv
+import gzip
+import io
+from pathlib import Path
+
+REPO_ROOT = Path(__file__).resolve().parent.parent
+OUTPUT = REPO_ROOT / "crates/protocol/data/oui.csv.gz"
+REGISTRIES = ("MA-L", "MA-M", "MA-S")
+
+
+def read_registry(path):
+    with open(path, newline="", encoding="utf-8") as fh:
+        for row in csv.reader(fh):
+            if len(row) >= 3 and row[0] in REGISTRIES:
+                yield row[0], row[1].strip().upper(), row[2].strip()
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__, formatter_class=argparse.RawDescriptionHelpFormatter)
+    parser.add_argument("csv_files", nargs="+", type=Path, help="IEEE registry CSV exports")
+    parser.add_argument("-o", "--output", type=Path, default=OUTPUT, help="Output .csv.gz file")
+    args = parser.parse_args()
+
+    entries = {}
+    for path in args.csv_files:
+        for registry, assignment, name in read_registry(path):
+            entries[(registry, assignment)] = name
+
+    buf = io.StringIO()
+    writer = csv.writer(buf, lineterminator="\n")
+    writer.writerow(["Registry", "Assignment", "Organization Name"])
+    for (registry, assignment), name in sorted(entries.items()):
+        writer.writerow([registry, assignment, name])
+
+    # mtime=0 keeps the output reproducible
+    args.output.write_bytes(gzip.compress(buf.getvalue().encode("utf-8"), 9, mtime=0))
+
+    counts = {r: sum(1 for k in entries if k[0] == r) for r in REGISTRIES}
+    print(f"Wrote {args.output} ({', '.join(f'{r}={n}' for r, n in counts.items())})")
+
+
+if __name__ == "__main__":
+    main()